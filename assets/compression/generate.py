@@ -0,0 +1,492 @@
+#!/usr/bin/env python3
+"""Generates the tile-compressed FITS fixtures used by the compression tests.
+
+The files are written by this standalone encoder, which follows the tiled image
+compression convention (FITS standard, section 10) and the published RICE_1,
+GZIP_1, and HCOMPRESS_1 algorithms. It shares no code with the decoder under
+test, but it is not CFITSIO; the files exercise the bit-level formats,
+quantization, and subtractive dithering independently of the crate's own
+compressor.
+
+Each file contains an empty primary HDU, the compressed image, and an
+uncompressed IMAGE extension named EXPECTED holding the pixels that a
+conforming reader produces when decompressing the image.
+
+Usage: python3 generate.py  (writes the files next to this script)
+"""
+
+import gzip
+import math
+import os
+import struct
+
+N_RANDOM = 10000
+ZERO_VALUE = -2147483646
+
+
+def random_values():
+    a, m, seed = 16807.0, 2147483647.0, 1.0
+    values = []
+    for _ in range(N_RANDOM):
+        temp = a * seed
+        seed = temp - m * int(temp / m)
+        values.append(struct.unpack(">f", struct.pack(">f", seed / m))[0])
+    assert seed == 1043618065.0
+    return values
+
+
+RANDOM = random_values()
+
+
+class Dither:
+    """The sequence of random offsets used to dither the pixels of one tile."""
+
+    def __init__(self, tile_index, zdither0):
+        self.iseed = (tile_index + zdither0 - 1) % N_RANDOM
+        self.next = int(RANDOM[self.iseed] * 500.0)
+
+    def value(self):
+        r = RANDOM[self.next]
+        self.next += 1
+        if self.next == N_RANDOM:
+            self.iseed = (self.iseed + 1) % N_RANDOM
+            self.next = int(RANDOM[self.iseed] * 500.0)
+        return r
+
+
+def nint(x):
+    return int(math.floor(x + 0.5))
+
+
+def f32(x):
+    return struct.unpack(">f", struct.pack(">f", x))[0]
+
+
+def quantize(values, tile_index, zdither0, zscale, zzero, method):
+    dither = Dither(tile_index, zdither0)
+    ints, restored = [], []
+    for value in values:
+        r = dither.value()
+        if method == "SUBTRACTIVE_DITHER_2" and value == 0.0:
+            ints.append(ZERO_VALUE)
+            restored.append(0.0)
+            continue
+        q = nint((value - zzero) / zscale + r - 0.5)
+        ints.append(q)
+        restored.append(f32((q - r + 0.5) * zscale + zzero))
+    return ints, restored
+
+
+class BitWriter:
+    def __init__(self):
+        self.out = bytearray()
+        self.buffer = 0
+        self.bits = 0
+
+    def write(self, value, n):
+        for shift in range(n - 1, -1, -1):
+            self.buffer = (self.buffer << 1) | ((value >> shift) & 1)
+            self.bits += 1
+            if self.bits == 8:
+                self.out.append(self.buffer)
+                self.buffer = 0
+                self.bits = 0
+
+    def finish(self):
+        if self.bits:
+            self.out.append((self.buffer << (8 - self.bits)) & 0xFF)
+            self.buffer = 0
+            self.bits = 0
+        return bytes(self.out)
+
+
+def rice_encode(values, bytepix, block_size):
+    fsbits, fsmax = {1: (3, 6), 2: (4, 14), 4: (5, 25)}[bytepix]
+    bbits = 8 * bytepix
+    mask = (1 << bbits) - 1
+    w = BitWriter()
+    w.write(values[0] & mask, bbits)
+    last = values[0]
+    for start in range(0, len(values), block_size):
+        block = values[start:start + block_size]
+        diffs = []
+        for value in block:
+            d = (value - last) & mask
+            if d >> (bbits - 1):
+                d -= 1 << bbits
+            diffs.append((~(d << 1) if d < 0 else d << 1) & mask)
+            last = value
+        total = sum(diffs)
+        dpsum = max((total - len(block) // 2 - 1) / len(block), 0.0)
+        psum = int(dpsum) >> 1
+        fs = 0
+        while psum > 0:
+            psum >>= 1
+            fs += 1
+        if fs >= fsmax:
+            w.write(fsmax + 1, fsbits)
+            for d in diffs:
+                w.write(d, bbits)
+        elif fs == 0 and total == 0:
+            w.write(0, fsbits)
+        else:
+            w.write(fs + 1, fsbits)
+            for d in diffs:
+                top = d >> fs
+                for _ in range(top):
+                    w.write(0, 1)
+                w.write(1, 1)
+                if fs:
+                    w.write(d & ((1 << fs) - 1), fs)
+    return w.finish()
+
+
+# HCOMPRESS_1, lossless (SCALE = 0)
+
+def shuffle(a, start, n, stride):
+    items = [a[start + i * stride] for i in range(n)]
+    reordered = items[0::2] + items[1::2]
+    for i, value in enumerate(reordered):
+        a[start + i * stride] = value
+
+
+def htrans(a, nx, ny):
+    nmax = max(nx, ny)
+    log2n = int(math.log(nmax) / math.log(2.0) + 0.5)
+    if nmax > (1 << log2n):
+        log2n += 1
+    shift, mask, prnd = 0, -2, 1
+    mask2, prnd2 = mask << 1, prnd << 1
+    nrnd2 = prnd2 - 1
+    nxtop, nytop = nx, ny
+
+    def rnd(h):
+        return (h + prnd if h >= 0 else h) & mask
+
+    def rnd0(h):
+        return (h + prnd2 if h >= 0 else h + nrnd2) & mask2
+
+    for _ in range(log2n):
+        oddx, oddy = nxtop % 2, nytop % 2
+        i = 0
+        while i < nxtop - oddx:
+            s00 = i * ny
+            s10 = s00 + ny
+            j = 0
+            while j < nytop - oddy:
+                h0 = (a[s10 + 1] + a[s10] + a[s00 + 1] + a[s00]) >> shift
+                hx = (a[s10 + 1] + a[s10] - a[s00 + 1] - a[s00]) >> shift
+                hy = (a[s10 + 1] - a[s10] + a[s00 + 1] - a[s00]) >> shift
+                hc = (a[s10 + 1] - a[s10] - a[s00 + 1] + a[s00]) >> shift
+                a[s10 + 1] = hc
+                a[s10] = rnd(hx)
+                a[s00 + 1] = rnd(hy)
+                a[s00] = rnd0(h0)
+                s00 += 2
+                s10 += 2
+                j += 2
+            if oddy:
+                h0 = (a[s10] + a[s00]) << (1 - shift)
+                hx = (a[s10] - a[s00]) << (1 - shift)
+                a[s10] = rnd(hx)
+                a[s00] = rnd0(h0)
+            i += 2
+        if oddx:
+            s00 = i * ny
+            j = 0
+            while j < nytop - oddy:
+                h0 = (a[s00 + 1] + a[s00]) << (1 - shift)
+                hy = (a[s00 + 1] - a[s00]) << (1 - shift)
+                a[s00 + 1] = rnd(hy)
+                a[s00] = rnd0(h0)
+                s00 += 2
+                j += 2
+            if oddy:
+                a[s00] = rnd0(a[s00] << (2 - shift))
+        for i in range(nxtop):
+            shuffle(a, ny * i, nytop, 1)
+        for j in range(nytop):
+            shuffle(a, j, nxtop, ny)
+        nxtop = (nxtop + 1) >> 1
+        nytop = (nytop + 1) >> 1
+        shift = 1
+        mask = mask2
+        prnd = prnd2
+        mask2 <<= 1
+        prnd2 <<= 1
+        nrnd2 = prnd2 - 1
+
+
+HUFFMAN_CODE = [0x3E, 0x00, 0x01, 0x08, 0x02, 0x09, 0x1A, 0x1B,
+                0x03, 0x1C, 0x0A, 0x1D, 0x0B, 0x1E, 0x3F, 0x0C]
+HUFFMAN_LEN = [6, 3, 3, 4, 3, 4, 5, 5, 3, 5, 4, 5, 4, 5, 6, 4]
+
+
+def qtree_onebit(a, offset, n, nx, ny, bit):
+    b = []
+    for i in range(0, nx, 2):
+        for j in range(0, ny, 2):
+            s00 = offset + n * i + j
+            code = ((a[s00] >> bit) & 1) << 3
+            if j + 1 < ny:
+                code |= ((a[s00 + 1] >> bit) & 1) << 2
+            if i + 1 < nx:
+                code |= ((a[s00 + n] >> bit) & 1) << 1
+                if j + 1 < ny:
+                    code |= (a[s00 + n + 1] >> bit) & 1
+            b.append(code)
+    return b
+
+
+def qtree_reduce(a, nx, ny):
+    b = []
+    for i in range(0, nx, 2):
+        for j in range(0, ny, 2):
+            s00 = ny * i + j
+            code = (a[s00] != 0) << 3
+            if j + 1 < ny:
+                code |= (a[s00 + 1] != 0) << 2
+            if i + 1 < nx:
+                code |= (a[s00 + ny] != 0) << 1
+                if j + 1 < ny:
+                    code |= a[s00 + ny + 1] != 0
+            b.append(code)
+    return b
+
+
+def qtree_encode(w, a, offset, n, nqx, nqy, nbitplanes):
+    nqmax = max(nqx, nqy)
+    log2n = int(math.log(nqmax) / math.log(2.0) + 0.5)
+    if nqmax > (1 << log2n):
+        log2n += 1
+    nqx2, nqy2 = (nqx + 1) // 2, (nqy + 1) // 2
+    bmax = (nqx2 * nqy2 + 1) // 2
+    for bit in range(nbitplanes - 1, -1, -1):
+        # the codes of each level are gathered from the bottom up, then written in reverse,
+        # so that a reader meets the top of the quadtree first
+        buffer, bitbuffer, bits = [], 0, 0
+        full = False
+        scratch = qtree_onebit(a, offset, n, nqx, nqy, bit)
+        nx, ny = nqx2, nqy2
+        for level in range(max(log2n, 1)):
+            if level > 0:
+                scratch = qtree_reduce(scratch, nx, ny)
+                nx, ny = (nx + 1) >> 1, (ny + 1) >> 1
+            for value in scratch:
+                if value:
+                    bitbuffer |= HUFFMAN_CODE[value] << bits
+                    bits += HUFFMAN_LEN[value]
+                    if bits >= 8:
+                        buffer.append(bitbuffer & 0xFF)
+                        bitbuffer >>= 8
+                        bits -= 8
+                        if len(buffer) >= bmax:
+                            full = True
+                            break
+            if full:
+                break
+        if full:
+            # the quadtree is no smaller than the bit plane, which is written directly
+            w.write(0x0, 4)
+            for value in qtree_onebit(a, offset, n, nqx, nqy, bit):
+                w.write(value, 4)
+            continue
+        w.write(0xF, 4)
+        if not buffer:
+            if bits > 0:
+                w.write(bitbuffer & ((1 << bits) - 1), bits)
+            else:
+                w.write(HUFFMAN_CODE[0], HUFFMAN_LEN[0])
+        else:
+            if bits > 0:
+                w.write(bitbuffer & ((1 << bits) - 1), bits)
+            for byte in reversed(buffer):
+                w.write(byte, 8)
+
+
+def hcompress_encode(values, nx, ny):
+    """Encodes a tile of nx rows of ny pixels."""
+    a = list(values)
+    htrans(a, nx, ny)
+    out = bytearray(b"\xdd\x99")
+    out += struct.pack(">iii", nx, ny, 0)
+    out += struct.pack(">q", a[0])
+    a[0] = 0
+    signs = BitWriter()
+    for i, value in enumerate(a):
+        if value:
+            signs.write(1 if value < 0 else 0, 1)
+            a[i] = abs(value)
+    nx2, ny2 = (nx + 1) // 2, (ny + 1) // 2
+    vmax = [0, 0, 0]
+    for i, value in enumerate(a):
+        k, j = divmod(i, ny)
+        q = (j >= ny2) + (k >= nx2)
+        vmax[q] = max(vmax[q], value)
+    nbitplanes = [v.bit_length() for v in vmax]
+    out += bytes(nbitplanes)
+    w = BitWriter()
+    qtree_encode(w, a, 0, ny, nx2, ny2, nbitplanes[0])
+    qtree_encode(w, a, ny2, ny, nx2, ny // 2, nbitplanes[1])
+    qtree_encode(w, a, ny * nx2, ny, nx // 2, ny2, nbitplanes[1])
+    qtree_encode(w, a, ny * nx2 + ny2, ny, nx // 2, ny // 2, nbitplanes[2])
+    w.write(0, 4)
+    out += w.finish()
+    out += signs.finish()
+    return bytes(out)
+
+
+# FITS output
+
+def card(keyword, value=None, comment=None):
+    if value is None:
+        text = keyword.ljust(80)
+    else:
+        if isinstance(value, bool):
+            field = ("T" if value else "F").rjust(20)
+        elif isinstance(value, int):
+            field = str(value).rjust(20)
+        elif isinstance(value, float):
+            field = repr(value).upper().rjust(20)
+        else:
+            field = ("'" + value.replace("'", "''").ljust(8) + "'").ljust(20)
+        text = keyword.ljust(8) + "= " + field
+        if comment:
+            text += " / " + comment
+    assert len(text) <= 80, text
+    return text.ljust(80).encode("ascii")
+
+
+def header(cards):
+    raw = b"".join(cards) + card("END")
+    return raw + b" " * (-len(raw) % 2880)
+
+
+def data(raw):
+    return raw + b"\0" * (-len(raw) % 2880)
+
+
+def primary():
+    return header([card("SIMPLE", True), card("BITPIX", 8), card("NAXIS", 0),
+                   card("EXTEND", True)])
+
+
+def tiles(image, width, height, tile_width, tile_height):
+    for ty in range(0, height, tile_height):
+        for tx in range(0, width, tile_width):
+            w = min(tile_width, width - tx)
+            h = min(tile_height, height - ty)
+            yield w, h, [image[(ty + y) * width + tx + x] for y in range(h) for x in range(w)]
+
+
+def compressed_image(image, width, height, tile, zbitpix, algorithm, parameters,
+                     quantization=None):
+    rows, heap, restored = [], bytearray(), [0.0] * (width * height)
+    tile_width, tile_height = tile
+    for index, (w, h, values) in enumerate(tiles(image, width, height, tile_width, tile_height)):
+        if quantization:
+            method, zdither0 = quantization
+            lo, hi = min(values), max(values)
+            zscale = (hi - lo) / 60000.0 if hi > lo else 1.0
+            zzero = (lo + hi) / 2.0
+            ints, tile_restored = quantize(values, index, zdither0, zscale, zzero, method)
+        else:
+            ints, tile_restored, zscale, zzero = values, values, None, None
+        if algorithm == "RICE_1":
+            compressed = rice_encode(ints, parameters["BYTEPIX"], parameters["BLOCKSIZE"])
+        elif algorithm == "GZIP_1":
+            fmt = ">%di" % len(ints) if quantization else ">%dh" % len(ints)
+            compressed = gzip.compress(struct.pack(fmt, *ints), mtime=0)
+        elif algorithm == "HCOMPRESS_1":
+            compressed = hcompress_encode(ints, h, w)
+        else:
+            raise ValueError(algorithm)
+        rows.append((len(compressed), len(heap), zscale, zzero))
+        heap += compressed
+        # scatter the restored tile back into the image
+        ty = (index // -(-width // tile_width)) * tile_height
+        tx = (index % -(-width // tile_width)) * tile_width
+        for y in range(h):
+            for x in range(w):
+                restored[(ty + y) * width + tx + x] = tile_restored[y * w + x]
+
+    max_len = max(length for length, _, _, _ in rows)
+    row_len = 8 + (16 if quantization else 0)
+    table = bytearray()
+    for length, offset, zscale, zzero in rows:
+        table += struct.pack(">ii", length, offset)
+        if quantization:
+            table += struct.pack(">dd", zscale, zzero)
+    cards = [
+        card("XTENSION", "BINTABLE"), card("BITPIX", 8), card("NAXIS", 2),
+        card("NAXIS1", row_len), card("NAXIS2", len(rows)),
+        card("PCOUNT", len(heap)), card("GCOUNT", 1),
+        card("TFIELDS", 3 if quantization else 1),
+        card("TTYPE1", "COMPRESSED_DATA"), card("TFORM1", "1PB(%d)" % max_len),
+    ]
+    if quantization:
+        cards += [card("TTYPE2", "ZSCALE"), card("TFORM2", "1D"),
+                  card("TTYPE3", "ZZERO"), card("TFORM3", "1D")]
+    cards += [
+        card("ZIMAGE", True), card("ZTENSION", "IMAGE"), card("ZBITPIX", zbitpix),
+        card("ZNAXIS", 2), card("ZNAXIS1", width), card("ZNAXIS2", height),
+        card("ZPCOUNT", 0), card("ZGCOUNT", 1),
+        card("ZTILE1", tile_width), card("ZTILE2", tile_height),
+        card("ZCMPTYPE", algorithm),
+    ]
+    for n, (name, value) in enumerate(parameters.items(), start=1):
+        cards += [card("ZNAME%d" % n, name), card("ZVAL%d" % n, value)]
+    if quantization:
+        cards += [card("ZQUANTIZ", quantization[0]), card("ZDITHER0", quantization[1])]
+    cards.append(card("EXTNAME", "COMPRESSED_IMAGE"))
+    return header(cards) + data(bytes(table) + bytes(heap)), restored
+
+
+def image_extension(values, width, height, bitpix):
+    fmt = {-32: ">%df", 16: ">%dh"}[bitpix]
+    return header([
+        card("XTENSION", "IMAGE"), card("BITPIX", bitpix), card("NAXIS", 2),
+        card("NAXIS1", width), card("NAXIS2", height), card("PCOUNT", 0),
+        card("GCOUNT", 1), card("EXTNAME", "EXPECTED"),
+    ]) + data(struct.pack(fmt % len(values), *values))
+
+
+def float_image(width, height):
+    # a smooth background with a few sources and deterministic noise, with exact zeros in one corner
+    image = []
+    state = 12345
+    for y in range(height):
+        for x in range(width):
+            state = (1103515245 * state + 12345) % 2 ** 31
+            noise = (state / 2 ** 31 - 0.5) * 4.0
+            value = 100.0 + 0.5 * x - 0.25 * y + noise
+            for cx, cy, flux in ((10, 8, 2000.0), (31, 20, 500.0)):
+                value += flux * math.exp(-((x - cx) ** 2 + (y - cy) ** 2) / 8.0)
+            image.append(0.0 if x < 3 and y < 2 else f32(value))
+    return image
+
+
+def main():
+    here = os.path.dirname(os.path.abspath(__file__))
+    width, height = 45, 31
+    image = float_image(width, height)
+    int_image = [((x * 37 + y * 101) % 2000) - 1000 for y in range(height) for x in range(width)]
+
+    fixtures = [
+        ("rice_dither1.fits", image, -32, "RICE_1", {"BLOCKSIZE": 32, "BYTEPIX": 4},
+         ("SUBTRACTIVE_DITHER_1", 7)),
+        ("gzip_dither2.fits", image, -32, "GZIP_1", {}, ("SUBTRACTIVE_DITHER_2", 42)),
+        ("hcompress_dither1.fits", image, -32, "HCOMPRESS_1", {"SCALE": 0, "SMOOTH": 0},
+         ("SUBTRACTIVE_DITHER_1", 9999)),
+        ("rice_i16.fits", int_image, 16, "RICE_1", {"BLOCKSIZE": 16, "BYTEPIX": 2}, None),
+    ]
+    for name, values, zbitpix, algorithm, parameters, quantization in fixtures:
+        compressed, restored = compressed_image(
+            values, width, height, (20, 16), zbitpix, algorithm, parameters, quantization)
+        raw = primary() + compressed + image_extension(restored, width, height, zbitpix)
+        with open(os.path.join(here, name), "wb") as f:
+            f.write(raw)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,129 @@
+#!/usr/bin/env python3
+"""Computes the reference coordinates used by the WCS tests.
+
+The transforms are written directly from the formulas of Greisen & Calabretta
+(2002, A&A 395, 1061) and Calabretta & Greisen (2002, A&A 395, 1077), the SIP
+convention of Shupe et al. (2005), and the TPV convention as registered at
+<https://fits.gsfc.nasa.gov/registry/tpvwcs.html>. This script shares no code
+with the crate, but it is not WCSLIB or astropy; the values check the crate
+against an independent implementation of the published formulas, away from the
+reference point where errors in the projections and the spherical rotation
+would cancel out.
+
+Usage: python3 reference.py  (prints the rows of the tables in tests/fits.rs)
+"""
+
+import math
+
+CRPIX = (100.5, 50.5)
+PIXELS = [(1.0, 1.0), (200.0, 1.0), (37.0, 88.0), (200.0, 100.0)]
+
+
+def arg(x, y):
+    return math.degrees(math.atan2(y, x))
+
+
+def sind(a):
+    return math.sin(math.radians(a))
+
+
+def cosd(a):
+    return math.cos(math.radians(a))
+
+
+def tan_native(x, y):
+    r = math.hypot(x, y)
+    return arg(-y, x), math.degrees(math.atan2(180.0 / math.pi, r))
+
+
+def sin_native(x, y):
+    r = math.hypot(x, y)
+    return arg(-y, x), math.degrees(math.acos(math.pi * r / 180.0))
+
+
+def ait_native(x, y):
+    z = math.sqrt(1.0 - (math.pi * x / 720.0) ** 2 - (math.pi * y / 360.0) ** 2)
+    phi = 2.0 * arg(2.0 * z * z - 1.0, math.pi * z * x / 360.0)
+    theta = math.degrees(math.asin(math.pi * y * z / 180.0))
+    return phi, theta
+
+
+# the native coordinates of the reference point of each projection
+THETA0 = {"TAN": 90.0, "SIN": 90.0, "AIT": 0.0}
+NATIVE = {"TAN": tan_native, "SIN": sin_native, "AIT": ait_native}
+
+
+def celestial_pole(alpha0, delta0, theta0):
+    """Returns the celestial coordinates of the native pole and LONPOLE, for phi0 = 0 and LATPOLE = +90."""
+    phi_p = 180.0 if delta0 < theta0 else 0.0
+    if theta0 == 90.0:
+        return alpha0, delta0, phi_p
+    # Paper II, equation 8, choosing the solution nearest to LATPOLE
+    base = arg(cosd(theta0) * cosd(phi_p), sind(theta0))
+    spread = math.degrees(
+        math.acos(sind(delta0) / math.sqrt(1.0 - (cosd(theta0) * sind(phi_p)) ** 2))
+    )
+    candidates = [d for d in (base + spread, base - spread) if -90.0 <= d <= 90.0]
+    delta_p = max(candidates)
+    # the reference point must map to alpha0
+    alpha_p = alpha0 - native_to_celestial(0.0, theta0, 0.0, delta_p, phi_p)[0]
+    return alpha_p, delta_p, phi_p
+
+
+def native_to_celestial(phi, theta, alpha_p, delta_p, phi_p):
+    """Paper II, equation 2."""
+    alpha = alpha_p + arg(
+        sind(theta) * cosd(delta_p) - cosd(theta) * sind(delta_p) * cosd(phi - phi_p),
+        -cosd(theta) * sind(phi - phi_p),
+    )
+    delta = math.degrees(
+        math.asin(
+            sind(theta) * sind(delta_p) + cosd(theta) * cosd(delta_p) * cosd(phi - phi_p)
+        )
+    )
+    return alpha % 360.0, delta
+
+
+def world(code, crval, x, y):
+    phi, theta = NATIVE[code](x, y)
+    pole = celestial_pole(crval[0], crval[1], THETA0[code])
+    return native_to_celestial(phi, theta, *pole)
+
+
+def projections():
+    for code, cdelt in [("TAN", 0.01), ("SIN", 0.01), ("AIT", 1.0)]:
+        for px, py in PIXELS:
+            x = -cdelt * (px - CRPIX[0])
+            y = cdelt * (py - CRPIX[1])
+            ra, dec = world(code, (45.0, 30.0), x, y)
+            print(f'("{code}", {px!r}, {py!r}, {ra!r}, {dec!r}),')
+
+
+def sip():
+    # A_2_0 = 2e-4, A_1_1 = -1e-4, B_0_2 = 3e-4, applied to pixel offsets before CDELT
+    for px, py in PIXELS:
+        u = px - CRPIX[0]
+        v = py - CRPIX[1]
+        x = -0.001 * (u + 2e-4 * u * u - 1e-4 * u * v)
+        y = 0.001 * (v + 3e-4 * v * v)
+        ra, dec = world("TAN", (150.0, 2.0), x, y)
+        print(f'("SIP", {px!r}, {py!r}, {ra!r}, {dec!r}),')
+
+
+def tpv():
+    # PV1_0 = 1e-4, PV1_4 = 0.5, PV2_1 = 0.99, PV2_11 = 2, with PVi_1 = 1 when not given;
+    # the terms of the second axis exchange the roles of x and y
+    for px, py in PIXELS:
+        x = -0.001 * (px - CRPIX[0])
+        y = 0.001 * (py - CRPIX[1])
+        r = math.hypot(x, y)
+        xi = 1e-4 + x + 0.5 * x * x
+        eta = 0.99 * y + 2.0 * r ** 3
+        ra, dec = world("TAN", (150.0, 2.0), xi, eta)
+        print(f'("TPV", {px!r}, {py!r}, {ra!r}, {dec!r}),')
+
+
+if __name__ == "__main__":
+    projections()
+    sip()
+    tpv()
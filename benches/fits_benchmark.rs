@@ -37,5 +37,27 @@ fn bench_get_header_20(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, bench_get_header, bench_get_header_20);
+#[allow(unused_variables)]
+fn bench_get_header_20_lazy(c: &mut Criterion) {
+    #[cfg(feature = "fits")]
+    {
+        use astro_rs::fits::*;
+        c.bench_function("get 20th header from fits file lazily", |b| {
+            b.iter(|| {
+                let fits_file = black_box(File::open("assets/benchmarks/many_hdu.fits").unwrap());
+                let fits_file_reader = BufReader::new(fits_file);
+
+                let mut hdu_list = HduList::new_lazy(fits_file_reader);
+                hdu_list.get_by_index(20).unwrap().header.clone()
+            })
+        });
+    }
+}
+
+criterion_group!(
+    benches,
+    bench_get_header,
+    bench_get_header_20,
+    bench_get_header_20_lazy
+);
 criterion_main!(benches);
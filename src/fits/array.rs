@@ -0,0 +1,112 @@
+//! Convert the data sections of images to and from n-dimensional arrays.
+
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn};
+use thiserror::Error;
+
+use super::*;
+
+/// An enumeration of errors that could occur when creating an array from the data section of an image.
+#[derive(Debug, Error)]
+pub enum ArrayError {
+    /// Indicates the header of the image could not be processed, or does not describe the data section.
+    #[error(transparent)]
+    Header(#[from] FitsHeaderError),
+    /// Indicates the deferred data section of the image could not be read.
+    #[error(transparent)]
+    Read(#[from] FitsError),
+}
+
+impl Hdu {
+    /// Creates an array from the data section of the HDU, with the shape of the NAXISn cards in reverse order,
+    /// so that the array is in row-major (C) order and indexed as `[z, y, x]`.
+    /// Returns an error if BITPIX does not represent the element type, if the data section is too short,
+    /// or if a deferred data section cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 3u32)?;
+    /// hdu.header.set_value(*b"NAXIS2  ", 2u32)?;
+    /// hdu.set_data(&vec![0i16, 1, 2, 10, 11, 12]);
+    ///
+    /// let array = hdu.get_array::<i16>()?;
+    /// assert_eq!(array.shape(), [2, 3]);
+    /// assert_eq!(array[[1, 2]], 12);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn get_array<T: FitsDataElement>(&mut self) -> Result<ArrayD<T>, ArrayError> {
+        let bitpix = *self
+            .header
+            .get_card(BITPIX_KEYWORD)
+            .ok_or(FitsHeaderError::MissingKeyword {
+                keyword: BITPIX_KEYWORD.into(),
+            })?
+            .get_value::<Bitpix>()?;
+        if bitpix != T::BITPIX {
+            return Err(FitsHeaderError::InvalidType.into());
+        }
+        let mut shape = self.get_dimensions();
+        shape.reverse();
+        let len = shape.iter().product::<usize>() * bitpix.value() / 8;
+        let data_raw = self.try_data_raw()?;
+        let raw = data_raw
+            .get(..len)
+            .ok_or_else(|| FitsHeaderError::InvalidLength {
+                expected: len,
+                found: data_raw.len(),
+                intent: String::from("image array"),
+            })?;
+        let values = raw
+            .chunks_exact(std::mem::size_of::<T>())
+            .map(T::from_be_slice)
+            .collect();
+        ArrayD::from_shape_vec(IxDyn(&shape), values).map_err(|_| {
+            FitsHeaderError::InvalidLength {
+                expected: len,
+                found: raw.len(),
+                intent: String::from("image array"),
+            }
+            .into()
+        })
+    }
+
+    /// Sets the data section of the HDU to the given array, which is interpreted in row-major (C) order
+    /// regardless of its memory layout. The BITPIX, NAXIS, and NAXISn cards are updated to describe the array,
+    /// with NAXIS1 being the length of the last axis of the array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use ndarray::Array3;
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.set_array(&Array3::<f32>::zeros((4, 3, 2)))?;
+    /// assert_eq!(hdu.get_dimensions(), vec![2, 3, 4]);
+    /// assert_eq!(*hdu.header.get_card(BITPIX_KEYWORD).unwrap().get_value::<Bitpix>()?, Bitpix::F32);
+    /// assert!(hdu.header.verify(1).is_empty());
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn set_array<T, S, D>(&mut self, array: &ArrayBase<S, D>) -> Result<(), FitsHeaderError>
+    where
+        T: FitsDataElement,
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        self.header.set_bitpix(T::BITPIX)?;
+        let dimensions: Vec<usize> = array.shape().iter().rev().copied().collect();
+        self.header.set_dimensions(&dimensions)?;
+
+        let mut data_raw = Vec::with_capacity(array.len() * std::mem::size_of::<T>());
+        for value in array.iter() {
+            value.extend_be_bytes(&mut data_raw);
+        }
+        self.set_data_raw(data_raw);
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+//! Compute and verify the CHECKSUM and DATASUM keywords.
+//! See <https://fits.gsfc.nasa.gov/registry/checksum.html> for the checksum convention.
+
+use super::*;
+
+/// The keyword for the checksum of an entire HDU.
+pub const CHECKSUM_KEYWORD: [u8; 8] = *b"CHECKSUM";
+/// The keyword for the checksum of the data section of an HDU.
+pub const DATASUM_KEYWORD: [u8; 8] = *b"DATASUM ";
+
+const CHECKSUM_PLACEHOLDER: &str = "0000000000000000";
+const CHECKSUM_OFFSET: u8 = 0x30;
+const CHECKSUM_EXCLUDED: [u8; 13] = [
+    0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f, 0x40, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f, 0x60,
+];
+
+impl Hdu {
+    /// Computes the 32-bit ones' complement checksum of the data section.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// assert_eq!(hdu.datasum(), 0);
+    ///
+    /// hdu.set_data_raw(vec![0, 0, 0, 1, 0, 0, 0, 2]);
+    /// assert_eq!(hdu.datasum(), 3);
+    /// ```
+    pub fn datasum(&self) -> u32 {
+        ones_complement_sum(self.data_raw())
+    }
+
+    /// Computes the 32-bit ones' complement checksum of the serialized HDU.
+    /// The checksum of an HDU with a correct CHECKSUM card is `0xFFFFFFFF`.
+    pub fn checksum(&self) -> u32 {
+        let header_sum = ones_complement_sum(&self.header.clone().to_bytes());
+        ones_complement_add(header_sum, self.datasum())
+    }
+
+    /// Sets the DATASUM and CHECKSUM cards to match the current contents of the HDU.
+    /// The cards are created if they do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 4u32)?;
+    /// hdu.set_data(&vec![1u8, 2, 3, 4]);
+    /// assert_eq!(hdu.verify_checksum(), None);
+    ///
+    /// hdu.update_checksum()?;
+    /// assert_eq!(hdu.verify_datasum(), Some(true));
+    /// assert_eq!(hdu.verify_checksum(), Some(true));
+    /// assert_eq!(hdu.checksum(), 0xFFFFFFFF);
+    ///
+    /// hdu.set_data(&vec![1u8, 2, 3, 5]);
+    /// assert_eq!(hdu.verify_datasum(), Some(false));
+    /// assert_eq!(hdu.verify_checksum(), Some(false));
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn update_checksum(&mut self) -> Result<(), FitsHeaderError> {
+        let datasum = self.datasum();
+        update_header_checksum(&mut self.header, datasum)
+    }
+
+    /// Determines if the DATASUM card matches the data section, or None if the card does not exist.
+    pub fn verify_datasum(&mut self) -> Option<bool> {
+        let datasum = self
+            .header
+            .get_card(DATASUM_KEYWORD)?
+            .get_value::<String>()
+            .ok()
+            .and_then(|datasum| datasum.trim().parse::<u32>().ok());
+        Some(datasum == Some(self.datasum()))
+    }
+
+    /// Determines if the CHECKSUM card matches the serialized HDU, or None if the card does not exist.
+    pub fn verify_checksum(&mut self) -> Option<bool> {
+        self.header.get_card(CHECKSUM_KEYWORD)?;
+        Some(self.checksum() == u32::MAX)
+    }
+}
+
+impl<R: Read> HduList<R> {
+    /// Sets whether the CHECKSUM and DATASUM cards of HDUs that have not yet been read are verified against the raw bytes.
+    /// Mismatches are reported by [`HduList::verify`], and cause reading to fail in [`ParseMode::Strict`].
+    /// Verifying a deferred data section requires it to be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// hdu_list.set_update_checksums(true);
+    /// let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+    /// hdu_list.write(&mut writer)?;
+    /// let mut bytes = writer.into_inner()?.into_inner();
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes.clone())));
+    /// hdu_list.set_verify_checksums(true);
+    /// assert!(hdu_list.verify()?.is_valid());
+    ///
+    /// // modify the SIMPLE value
+    /// bytes[29] = b'F';
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes)));
+    /// hdu_list.set_verify_checksums(true);
+    /// let report = hdu_list.verify()?;
+    /// assert_eq!(report.errors().next().unwrap().message, "CHECKSUM does not match the HDU");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn set_verify_checksums(&mut self, verify_checksums: bool) {
+        self.verify_checksums = verify_checksums;
+    }
+
+    /// Sets whether the CHECKSUM and DATASUM cards of each HDU are updated by [`HduList::write`].
+    /// When enabled, all HDUs are deserialized before writing.
+    pub fn set_update_checksums(&mut self, update_checksums: bool) {
+        self.update_checksums = update_checksums;
+    }
+}
+
+/// Sets the DATASUM and CHECKSUM cards of the given header to match the given sum of the data section.
+/// The cards are created if they do not exist.
+pub(crate) fn update_header_checksum(
+    header: &mut FitsHeader,
+    datasum: u32,
+) -> Result<(), FitsHeaderError> {
+    // the value is padded to 8 characters, as the CHECKSUM keyword convention writes it
+    header.set_value(DATASUM_KEYWORD, format!("{:<8}", datasum))?;
+    header.set_value(CHECKSUM_KEYWORD, String::from(CHECKSUM_PLACEHOLDER))?;
+    let checksum = ones_complement_add(ones_complement_sum(&header.clone().to_bytes()), datasum);
+    header.set_value(CHECKSUM_KEYWORD, encode_checksum(!checksum))
+}
+
+/// Verifies the CHECKSUM and DATASUM cards of an HDU as it was read, given the sums of the raw header and data bytes.
+pub(crate) fn verify_checksums(
+    header: &mut FitsHeader,
+    header_sum: u32,
+    datasum: u32,
+    hdu_index: usize,
+) -> Vec<VerificationIssue> {
+    let mut issues = Vec::new();
+    if let Some(card) = header.get_card(DATASUM_KEYWORD) {
+        let expected = card
+            .get_value::<String>()
+            .ok()
+            .and_then(|value| value.trim().parse::<u32>().ok());
+        if expected != Some(datasum) {
+            issues.push(VerificationIssue::error(
+                hdu_index,
+                None,
+                format!(
+                    "DATASUM does not match the data section, computed {}",
+                    datasum
+                ),
+            ));
+        }
+    }
+    if header.get_card(CHECKSUM_KEYWORD).is_some()
+        && ones_complement_add(header_sum, datasum) != u32::MAX
+    {
+        issues.push(VerificationIssue::error(
+            hdu_index,
+            None,
+            String::from("CHECKSUM does not match the HDU"),
+        ));
+    }
+    issues
+}
+
+/// Computes the 32-bit ones' complement sum of the given bytes, interpreted as big-endian words.
+/// A partial word at the end is filled with zeros.
+pub(crate) fn ones_complement_sum(bytes: &[u8]) -> u32 {
+    let mut chunks = bytes.chunks_exact(4);
+    let mut sum: u64 = 0;
+    for chunk in &mut chunks {
+        sum += u32::from_be_bytes(chunk.try_into().unwrap()) as u64;
+    }
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        let mut word = [0; 4];
+        word[..remainder.len()].copy_from_slice(remainder);
+        sum += u32::from_be_bytes(word) as u64;
+    }
+    fold_carries(sum)
+}
+
+fn ones_complement_add(a: u32, b: u32) -> u32 {
+    fold_carries(a as u64 + b as u64)
+}
+
+fn fold_carries(mut sum: u64) -> u32 {
+    while sum >> 32 != 0 {
+        sum = (sum & 0xFFFFFFFF) + (sum >> 32);
+    }
+    sum as u32
+}
+
+/// Encodes the given value as 16 ASCII characters, such that the characters contribute the value
+/// to the ones' complement sum when they start at the 12th column of a card.
+fn encode_checksum(value: u32) -> String {
+    let mut encoded = [0u8; 16];
+    for (i, byte) in value.to_be_bytes().into_iter().enumerate() {
+        let quotient = byte / 4 + CHECKSUM_OFFSET;
+        let remainder = byte % 4;
+        let mut ch = [quotient; 4];
+        ch[0] += remainder;
+        let mut check = true;
+        while check {
+            check = false;
+            for excluded in CHECKSUM_EXCLUDED {
+                for j in [0, 2] {
+                    if ch[j] == excluded || ch[j + 1] == excluded {
+                        ch[j] += 1;
+                        ch[j + 1] -= 1;
+                        check = true;
+                    }
+                }
+            }
+        }
+        for (j, c) in ch.into_iter().enumerate() {
+            encoded[4 * j + i] = c;
+        }
+    }
+    // the value starts one byte past a word boundary, so the characters are rotated right by one
+    encoded.rotate_right(1);
+    String::from_utf8_lossy(&encoded).into_owned()
+}
@@ -0,0 +1,354 @@
+//! Decoding of the HCOMPRESS_1 algorithm, which codes the bit planes of an H-transform with quadtrees.
+
+use super::BitReader;
+
+const MAGIC: [u8; 2] = [0xdd, 0x99];
+
+/// Decodes a tile from the given bytes, returning the pixels along with the tile dimensions
+/// in the order they are stored, slowest varying first.
+pub(crate) fn decode(input: &[u8]) -> Result<(Vec<i64>, usize, usize), String> {
+    if input.get(0..2) != Some(&MAGIC) {
+        return Err(String::from("missing HCOMPRESS magic bytes"));
+    }
+    let read_int = |start: usize, len: usize| {
+        input
+            .get(start..start + len)
+            .map(|bytes| bytes.iter().fold(0i64, |acc, b| (acc << 8) | *b as i64))
+            .ok_or_else(|| String::from("HCOMPRESS header ended unexpectedly"))
+    };
+    let nx = read_int(2, 4)? as i32;
+    let ny = read_int(6, 4)? as i32;
+    let scale = read_int(10, 4)? as i32;
+    let sumall = read_int(14, 8)?;
+    let nbitplanes = input
+        .get(22..25)
+        .ok_or_else(|| String::from("HCOMPRESS header ended unexpectedly"))?;
+    if nx <= 0 || ny <= 0 {
+        return Err(format!("invalid HCOMPRESS dimensions {} x {}", nx, ny));
+    }
+    let (nx, ny) = (nx as usize, ny as usize);
+
+    let mut a = vec![0i64; nx * ny];
+    let mut reader = BitReader::new(&input[25..]);
+    let nx2 = nx.div_ceil(2);
+    let ny2 = ny.div_ceil(2);
+    qtree_decode(&mut reader, &mut a, 0, ny, nx2, ny2, nbitplanes[0])?;
+    qtree_decode(&mut reader, &mut a, ny2, ny, nx2, ny / 2, nbitplanes[1])?;
+    qtree_decode(
+        &mut reader,
+        &mut a,
+        ny * nx2,
+        ny,
+        nx / 2,
+        ny2,
+        nbitplanes[1],
+    )?;
+    qtree_decode(
+        &mut reader,
+        &mut a,
+        ny * nx2 + ny2,
+        ny,
+        nx / 2,
+        ny / 2,
+        nbitplanes[2],
+    )?;
+    if reader.read_bits(4)? != 0 {
+        return Err(String::from("invalid HCOMPRESS bit plane values"));
+    }
+    // the sign bits follow, starting at a byte boundary
+    reader.align();
+    for value in a.iter_mut() {
+        if *value != 0 && reader.read_bits(1)? != 0 {
+            *value = -*value;
+        }
+    }
+    a[0] = sumall;
+
+    if scale > 1 {
+        for value in a.iter_mut() {
+            *value *= scale as i64;
+        }
+    }
+    hinv(&mut a, nx, ny);
+    Ok((a, nx, ny))
+}
+
+/// Gets the base 2 logarithm of the given value, rounded up.
+fn log2_ceil(n: usize) -> u32 {
+    let mut log2n = ((n as f32).ln() / 2f32.ln() + 0.5) as u32;
+    if n > (1 << log2n) {
+        log2n += 1;
+    }
+    log2n
+}
+
+fn qtree_decode(
+    reader: &mut BitReader,
+    a: &mut [i64],
+    start: usize,
+    n: usize,
+    nqx: usize,
+    nqy: usize,
+    nbitplanes: u8,
+) -> Result<(), String> {
+    let log2n = log2_ceil(nqx.max(nqy));
+    let mut scratch = vec![0u8; (nqx.div_ceil(2) * nqy.div_ceil(2)).max(1)];
+    for bit in (0..nbitplanes as u32).rev() {
+        match reader.read_bits(4)? {
+            0 => {
+                // the bit plane was written directly, 4 pixels per nybble
+                for value in scratch.iter_mut() {
+                    *value = reader.read_bits(4)? as u8;
+                }
+            }
+            0xf => {
+                scratch[0] = input_huffman(reader)?;
+                let mut nx = 1;
+                let mut ny = 1;
+                let mut nfx = nqx;
+                let mut nfy = nqy;
+                let mut c = 1 << log2n;
+                for _ in 1..log2n {
+                    c >>= 1;
+                    nx <<= 1;
+                    ny <<= 1;
+                    if nfx <= c {
+                        nx -= 1;
+                    } else {
+                        nfx -= c;
+                    }
+                    if nfy <= c {
+                        ny -= 1;
+                    } else {
+                        nfy -= c;
+                    }
+                    qtree_expand(reader, &mut scratch, nx, ny)?;
+                }
+            }
+            code => return Err(format!("invalid HCOMPRESS quadtree format code {}", code)),
+        }
+        qtree_bitins(&scratch, nqx, nqy, &mut a[start..], n, bit);
+    }
+    Ok(())
+}
+
+/// Expands each 4-bit value of `a[(nx+1)/2, (ny+1)/2]` into a 2x2 block of `a[nx, ny]`,
+/// then reads a new 4-bit value for each non-zero element.
+fn qtree_expand(reader: &mut BitReader, a: &mut [u8], nx: usize, ny: usize) -> Result<(), String> {
+    let nx2 = nx.div_ceil(2);
+    let ny2 = ny.div_ceil(2);
+    // iterate backwards, as the expanded values may overwrite the compact values
+    for i in (0..nx2).rev() {
+        for j in (0..ny2).rev() {
+            let value = a[ny2 * i + j];
+            for (di, dj, shift) in [(1, 1, 0), (1, 0, 1), (0, 1, 2), (0, 0, 3)] {
+                let (x, y) = (2 * i + di, 2 * j + dj);
+                if x < nx && y < ny {
+                    a[ny * x + y] = (value >> shift) & 1;
+                }
+            }
+        }
+    }
+    for i in (0..nx * ny).rev() {
+        if a[i] != 0 {
+            a[i] = input_huffman(reader)?;
+        }
+    }
+    Ok(())
+}
+
+/// Expands each 4-bit value of `a[(nx+1)/2, (ny+1)/2]` into a 2x2 block,
+/// setting the given bit plane of `b[nx, ny]`, which has a row length of `n`.
+fn qtree_bitins(a: &[u8], nx: usize, ny: usize, b: &mut [i64], n: usize, bit: u32) {
+    let plane_value = 1i64 << bit;
+    let ny2 = ny.div_ceil(2);
+    for i in 0..nx.div_ceil(2) {
+        for j in 0..ny2 {
+            let value = a[ny2 * i + j];
+            for (di, dj, shift) in [(1, 1, 0), (1, 0, 1), (0, 1, 2), (0, 0, 3)] {
+                let (x, y) = (2 * i + di, 2 * j + dj);
+                if x < nx && y < ny && (value >> shift) & 1 != 0 {
+                    b[n * x + y] |= plane_value;
+                }
+            }
+        }
+    }
+}
+
+fn input_huffman(reader: &mut BitReader) -> Result<u8, String> {
+    let mut c = reader.read_bits(3)?;
+    if c < 4 {
+        return Ok(1 << c);
+    }
+    c = (c << 1) | reader.read_bits(1)?;
+    match c {
+        8 => return Ok(3),
+        9 => return Ok(5),
+        10 => return Ok(10),
+        11 => return Ok(12),
+        12 => return Ok(15),
+        _ => {}
+    }
+    c = (c << 1) | reader.read_bits(1)?;
+    match c {
+        26 => return Ok(6),
+        27 => return Ok(7),
+        28 => return Ok(9),
+        29 => return Ok(11),
+        30 => return Ok(13),
+        _ => {}
+    }
+    c = (c << 1) | reader.read_bits(1)?;
+    Ok(if c == 62 { 0 } else { 14 })
+}
+
+/// Inverts the H-transform of `a[nx, ny]` in place.
+fn hinv(a: &mut [i64], nx: usize, ny: usize) {
+    let nmax = nx.max(ny);
+    let log2n = log2_ceil(nmax);
+    if log2n == 0 {
+        return;
+    }
+    let mut tmp = vec![0i64; nmax.div_ceil(2)];
+
+    let mut shift = 1;
+    let mut bit0: i64 = 1 << (log2n - 1);
+    let mut bit1 = bit0 << 1;
+    let bit2 = bit0 << 2;
+    let mut mask0 = -bit0;
+    let mut mask1 = mask0 << 1;
+    let mask2 = mask0 << 2;
+    let mut prnd0 = bit0 >> 1;
+    let mut prnd1 = bit1 >> 1;
+    let prnd2 = bit2 >> 1;
+    let mut nrnd0 = prnd0 - 1;
+    let mut nrnd1 = prnd1 - 1;
+    let nrnd2 = prnd2 - 1;
+
+    // round h0 to a multiple of bit2
+    a[0] = (a[0] + if a[0] < 0 { nrnd2 } else { prnd2 }) & mask2;
+
+    let mut nxtop = 1;
+    let mut nytop = 1;
+    let mut nxf = nx;
+    let mut nyf = ny;
+    let mut c = 1 << log2n;
+    for k in (0..log2n).rev() {
+        c >>= 1;
+        nxtop <<= 1;
+        nytop <<= 1;
+        if nxf <= c {
+            nxtop -= 1;
+        } else {
+            nxf -= c;
+        }
+        if nyf <= c {
+            nytop -= 1;
+        } else {
+            nyf -= c;
+        }
+        // double the shift and fix nrnd0 on the last pass
+        if k == 0 {
+            nrnd0 = 0;
+            shift = 2;
+        }
+        for i in 0..nxtop {
+            unshuffle(a, ny * i, nytop, 1, &mut tmp);
+        }
+        for j in 0..nytop {
+            unshuffle(a, j, nxtop, ny, &mut tmp);
+        }
+
+        let oddx = nxtop % 2;
+        let oddy = nytop % 2;
+        let mut i = 0;
+        while i < nxtop - oddx {
+            let mut s00 = ny * i;
+            let mut s10 = s00 + ny;
+            let mut j = 0;
+            while j < nytop - oddy {
+                let mut h0 = a[s00];
+                let mut hx = a[s10];
+                let mut hy = a[s00 + 1];
+                let mut hc = a[s10 + 1];
+                // round hx and hy to a multiple of bit1, and hc to a multiple of bit0
+                hx = (hx + if hx < 0 { nrnd1 } else { prnd1 }) & mask1;
+                hy = (hy + if hy < 0 { nrnd1 } else { prnd1 }) & mask1;
+                hc = (hc + if hc < 0 { nrnd0 } else { prnd0 }) & mask0;
+                // propagate bit0 of hc to hx and hy
+                let lowbit0 = hc & bit0;
+                hx = if hx >= 0 { hx - lowbit0 } else { hx + lowbit0 };
+                hy = if hy >= 0 { hy - lowbit0 } else { hy + lowbit0 };
+                // propagate bits 0 and 1 of hc, hx, and hy to h0
+                let lowbit1 = (hc ^ hx ^ hy) & bit1;
+                h0 = if h0 >= 0 {
+                    h0 + lowbit0 - lowbit1
+                } else if lowbit0 == 0 {
+                    h0 + lowbit1
+                } else {
+                    h0 + lowbit0 - lowbit1
+                };
+                a[s10 + 1] = (h0 + hx + hy + hc) >> shift;
+                a[s10] = (h0 + hx - hy - hc) >> shift;
+                a[s00 + 1] = (h0 - hx + hy - hc) >> shift;
+                a[s00] = (h0 - hx - hy + hc) >> shift;
+                s00 += 2;
+                s10 += 2;
+                j += 2;
+            }
+            if oddy == 1 {
+                let mut h0 = a[s00];
+                let mut hx = a[s10];
+                hx = (hx + if hx < 0 { nrnd1 } else { prnd1 }) & mask1;
+                let lowbit1 = hx & bit1;
+                h0 = if h0 >= 0 { h0 - lowbit1 } else { h0 + lowbit1 };
+                a[s10] = (h0 + hx) >> shift;
+                a[s00] = (h0 - hx) >> shift;
+            }
+            i += 2;
+        }
+        if oddx == 1 {
+            let mut s00 = ny * i;
+            let mut j = 0;
+            while j < nytop - oddy {
+                let mut h0 = a[s00];
+                let mut hy = a[s00 + 1];
+                hy = (hy + if hy < 0 { nrnd1 } else { prnd1 }) & mask1;
+                let lowbit1 = hy & bit1;
+                h0 = if h0 >= 0 { h0 - lowbit1 } else { h0 + lowbit1 };
+                a[s00 + 1] = (h0 + hy) >> shift;
+                a[s00] = (h0 - hy) >> shift;
+                s00 += 2;
+                j += 2;
+            }
+            if oddy == 1 {
+                a[s00] >>= shift;
+            }
+        }
+
+        // divide the masks and rounding values by 2
+        bit1 = bit0;
+        bit0 >>= 1;
+        mask1 = mask0;
+        mask0 >>= 1;
+        prnd1 = prnd0;
+        prnd0 >>= 1;
+        nrnd1 = nrnd0;
+        nrnd0 = prnd0 - 1;
+    }
+}
+
+/// Moves the first half of the `n` elements of `a` starting at `start` with stride `n2` to the even positions,
+/// and the second half to the odd positions.
+fn unshuffle(a: &mut [i64], start: usize, n: usize, n2: usize, tmp: &mut [i64]) {
+    let nhalf = n.div_ceil(2);
+    for (t, i) in (nhalf..n).enumerate() {
+        tmp[t] = a[start + n2 * i];
+    }
+    for i in (0..nhalf).rev() {
+        a[start + 2 * n2 * i] = a[start + n2 * i];
+    }
+    for (t, i) in (1..n).step_by(2).enumerate() {
+        a[start + n2 * i] = tmp[t];
+    }
+}
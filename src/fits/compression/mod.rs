@@ -0,0 +1,1436 @@
+//! Read and write tile-compressed images.
+//! See <https://fits.gsfc.nasa.gov/registry/tilecompression.html> for the tiled image compression convention.
+
+mod hcompress;
+mod plio;
+mod rice;
+
+use std::io::{Read, Write};
+use std::sync::OnceLock;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use thiserror::Error;
+
+use super::*;
+
+const ZBITPIX_KEYWORD: [u8; 8] = *b"ZBITPIX ";
+const ZTILE_KEYWORD: [u8; 8] = *b"ZTILE   ";
+const ZNAME_KEYWORD: [u8; 8] = *b"ZNAME   ";
+const ZVAL_KEYWORD: [u8; 8] = *b"ZVAL    ";
+const ZQUANTIZ_KEYWORD: [u8; 8] = *b"ZQUANTIZ";
+const ZDITHER0_KEYWORD: [u8; 8] = *b"ZDITHER0";
+const ZSIMPLE_KEYWORD: [u8; 8] = *b"ZSIMPLE ";
+const ZTENSION_KEYWORD: [u8; 8] = *b"ZTENSION";
+const ZPCOUNT_KEYWORD: [u8; 8] = *b"ZPCOUNT ";
+const ZGCOUNT_KEYWORD: [u8; 8] = *b"ZGCOUNT ";
+const ZBLANK_KEYWORD: [u8; 8] = *b"ZBLANK  ";
+const ZSCALE_KEYWORD: [u8; 8] = *b"ZSCALE  ";
+const ZZERO_KEYWORD: [u8; 8] = *b"ZZERO   ";
+
+const COMPRESSED_DATA_COLUMN: &str = "COMPRESSED_DATA";
+const GZIP_COMPRESSED_DATA_COLUMN: &str = "GZIP_COMPRESSED_DATA";
+const UNCOMPRESSED_DATA_COLUMN: &str = "UNCOMPRESSED_DATA";
+const ZSCALE_COLUMN: &str = "ZSCALE";
+const ZZERO_COLUMN: &str = "ZZERO";
+const ZBLANK_COLUMN: &str = "ZBLANK";
+
+/// The quantized value representing an undefined floating point pixel.
+const NULL_VALUE: i32 = -2147483647;
+/// The quantized value representing a floating point pixel of exactly zero with SUBTRACTIVE_DITHER_2.
+const ZERO_VALUE: i32 = -2147483646;
+/// The number of values in the random sequence used for dithering.
+const N_RANDOM: usize = 10000;
+const DEFAULT_BLOCK_SIZE: usize = 32;
+
+/// An enumeration of errors that could occur when compressing or decompressing an image.
+#[derive(Debug, Error)]
+pub enum CompressionError {
+    /// Indicates a header card could not be processed.
+    #[error(transparent)]
+    Header(#[from] FitsHeaderError),
+    /// Indicates the HDU does not contain a tile-compressed image.
+    #[error("HDU does not contain a tile-compressed image")]
+    NotCompressed,
+    /// Indicates the image was compressed with an algorithm that is not supported.
+    #[error("unsupported compression algorithm {0}")]
+    UnsupportedAlgorithm(String),
+    /// Indicates a tile could not be decompressed.
+    #[error("failed to decompress tile {tile}: {message}")]
+    Decode {
+        /// The index of the affected tile.
+        tile: usize,
+        /// The reason decompression failed.
+        message: String,
+    },
+    /// Indicates a tile could not be compressed.
+    #[error("failed to compress tile {tile}: {message}")]
+    Encode {
+        /// The index of the affected tile.
+        tile: usize,
+        /// The reason compression failed.
+        message: String,
+    },
+    /// Indicates the compression options cannot be applied to the image.
+    #[error("invalid compression options: {0}")]
+    InvalidOptions(String),
+    /// Indicates an I/O error occurred while compressing or decompressing GZIP data.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl CompressionError {
+    /// Creates an equivalent error, recreating an I/O error from its kind and message.
+    pub(crate) fn replay(&self) -> Self {
+        match self {
+            CompressionError::Header(source) => CompressionError::Header(source.clone()),
+            CompressionError::NotCompressed => CompressionError::NotCompressed,
+            CompressionError::UnsupportedAlgorithm(algorithm) => {
+                CompressionError::UnsupportedAlgorithm(algorithm.clone())
+            }
+            CompressionError::Decode { tile, message } => CompressionError::Decode {
+                tile: *tile,
+                message: message.clone(),
+            },
+            CompressionError::Encode { tile, message } => CompressionError::Encode {
+                tile: *tile,
+                message: message.clone(),
+            },
+            CompressionError::InvalidOptions(message) => {
+                CompressionError::InvalidOptions(message.clone())
+            }
+            CompressionError::Io(source) => {
+                CompressionError::Io(std::io::Error::new(source.kind(), source.to_string()))
+            }
+        }
+    }
+}
+
+/// An enumeration of the algorithms with which images can be compressed.
+/// HCOMPRESS_1 images can be decompressed, but not compressed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    /// The Rice algorithm, which is fast and effective for integer data.
+    #[default]
+    Rice1,
+    /// The GZIP algorithm.
+    Gzip1,
+    /// The GZIP algorithm, applied after shuffling the bytes of each pixel so that bytes of equal significance are adjacent.
+    Gzip2,
+    /// The IRAF pixel list algorithm, which is effective for masks of non-negative integers below 2^24.
+    Plio1,
+}
+
+impl CompressionAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            CompressionAlgorithm::Rice1 => "RICE_1",
+            CompressionAlgorithm::Gzip1 => "GZIP_1",
+            CompressionAlgorithm::Gzip2 => "GZIP_2",
+            CompressionAlgorithm::Plio1 => "PLIO_1",
+        }
+    }
+
+    fn is_gzip(&self) -> bool {
+        matches!(
+            self,
+            CompressionAlgorithm::Gzip1 | CompressionAlgorithm::Gzip2
+        )
+    }
+}
+
+/// An enumeration of the methods with which random noise is added while quantizing floating point pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DitherMethod {
+    /// Pixels are rounded to the nearest quantized level.
+    NoDither,
+    /// A random offset is added to each pixel before rounding, and subtracted when the image is decompressed.
+    #[default]
+    SubtractiveDither1,
+    /// Like [`DitherMethod::SubtractiveDither1`], but pixels of exactly zero are preserved.
+    SubtractiveDither2,
+}
+
+impl DitherMethod {
+    fn name(&self) -> &'static str {
+        match self {
+            DitherMethod::NoDither => "NO_DITHER",
+            DitherMethod::SubtractiveDither1 => "SUBTRACTIVE_DITHER_1",
+            DitherMethod::SubtractiveDither2 => "SUBTRACTIVE_DITHER_2",
+        }
+    }
+}
+
+/// The parameters with which floating point pixels are quantized to integers before compression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantization {
+    /// The number of quantized levels per unit of the estimated noise in each tile.
+    /// A negative value is instead used as the spacing between quantized levels.
+    pub level: f32,
+    /// The method with which random noise is added.
+    pub method: DitherMethod,
+    /// The seed of the random sequence used for dithering, between 1 and 10000.
+    pub seed: u16,
+}
+
+impl Default for Quantization {
+    fn default() -> Self {
+        Quantization {
+            level: 4.0,
+            method: DitherMethod::default(),
+            seed: 1,
+        }
+    }
+}
+
+/// The options with which an image is compressed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionOptions {
+    /// The compression algorithm.
+    pub algorithm: CompressionAlgorithm,
+    /// The dimensions of each tile, or None to compress each row of the image as a tile.
+    pub tile: Option<Vec<usize>>,
+    /// The quantization of floating point pixels, or None to compress them losslessly,
+    /// which is only supported by the GZIP algorithms.
+    pub quantization: Option<Quantization>,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        CompressionOptions {
+            algorithm: CompressionAlgorithm::default(),
+            tile: None,
+            quantization: Some(Quantization::default()),
+        }
+    }
+}
+
+impl Hdu {
+    /// Determines if the HDU contains a tile-compressed image, as indicated by the ZIMAGE card.
+    pub fn is_compressed_image(&mut self) -> bool {
+        self.header
+            .get_card(ZIMAGE_KEYWORD)
+            .and_then(|card| card.get_value::<bool>().ok())
+            .is_some_and(|zimage| *zimage)
+    }
+
+    /// Decompresses the tile-compressed image contained in the binary table of this HDU,
+    /// returning an image HDU with the original header and data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 4u32)?;
+    /// hdu.header.set_value(*b"NAXIS2  ", 3u32)?;
+    /// hdu.set_data(&(0..12).collect::<Vec<i16>>());
+    ///
+    /// let mut compressed = hdu.compress(&CompressionOptions::default())?;
+    /// assert!(compressed.is_compressed_image());
+    /// assert_eq!(compressed.get_dimensions(), vec![8, 3]);
+    ///
+    /// let mut decompressed = compressed.decompress()?;
+    /// assert_eq!(decompressed.get_dimensions(), vec![4, 3]);
+    /// assert_eq!(decompressed.get_data::<Vec<i16>>()?, (0..12).collect::<Vec<i16>>());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn decompress(&mut self) -> Result<Hdu, CompressionError> {
+        if !self.is_compressed_image() {
+            return Err(CompressionError::NotCompressed);
+        }
+        let header = &mut self.header;
+        let zbitpix = *required_value::<Bitpix>(header, ZBITPIX_KEYWORD)?;
+        let znaxis = *required_value::<u16>(header, ZNAXIS_KEYWORD)?;
+        let mut dims = Vec::with_capacity(znaxis as usize);
+        let mut tile = Vec::with_capacity(znaxis as usize);
+        for n in 1..=znaxis {
+            let dim = *required_value::<u32>(header, numbered(ZNAXIS_KEYWORD, n))? as usize;
+            let tile_dim = match header.get_card(numbered(ZTILE_KEYWORD, n)) {
+                Some(card) => *card.get_value::<u32>()? as usize,
+                None if n == 1 => dim,
+                None => 1,
+            };
+            dims.push(dim);
+            tile.push(tile_dim.clamp(1, dim.max(1)));
+        }
+        let algorithm = header
+            .get_card(ZCMPTYPE_KEYWORD)
+            .map(|card| card.get_value::<String>())
+            .transpose()?
+            .map(|algorithm| algorithm.to_string())
+            .unwrap_or_default();
+        let mut parameters = Vec::new();
+        for n in 1.. {
+            let Some(card) = header.get_card(numbered(ZNAME_KEYWORD, n)) else {
+                break;
+            };
+            let name = card.get_value::<String>()?.to_uppercase();
+            let value = header
+                .get_card(numbered(ZVAL_KEYWORD, n))
+                .and_then(numeric_value);
+            parameters.push((name, value));
+        }
+        let parameter = |name: &str, default: usize| {
+            parameters
+                .iter()
+                .find(|(parameter, _)| parameter == name)
+                .and_then(|(_, value)| *value)
+                .map_or(default, |value| value as usize)
+        };
+        let block_size = parameter("BLOCKSIZE", DEFAULT_BLOCK_SIZE);
+        let bytepix = parameter("BYTEPIX", 4);
+        let method = match header
+            .get_card(ZQUANTIZ_KEYWORD)
+            .map(|card| card.get_value::<String>())
+            .transpose()?
+            .as_deref()
+            .map(String::as_str)
+        {
+            Some("SUBTRACTIVE_DITHER_1") => Some(DitherMethod::SubtractiveDither1),
+            Some("SUBTRACTIVE_DITHER_2") => Some(DitherMethod::SubtractiveDither2),
+            Some("NONE") => None,
+            _ => Some(DitherMethod::NoDither),
+        };
+        let zdither0 = header
+            .get_card(ZDITHER0_KEYWORD)
+            .and_then(numeric_value)
+            .unwrap_or(1.0) as i64;
+        let zblank = header.get_card(ZBLANK_KEYWORD).and_then(numeric_value);
+        let zscale = header.get_card(ZSCALE_KEYWORD).and_then(numeric_value);
+        let zzero = header.get_card(ZZERO_KEYWORD).and_then(numeric_value);
+        let layout = TableLayout::new(header)?;
+        let num_tiles = tile_count(&dims, &tile);
+        if layout.num_rows != num_tiles {
+            return Err(CompressionError::InvalidOptions(format!(
+                "expected {} tiles, found {} rows",
+                num_tiles, layout.num_rows
+            )));
+        }
+
+        let is_float = matches!(zbitpix, Bitpix::F32 | Bitpix::F64);
+        let elem_len = zbitpix.value() / 8;
+        let image_len = dims.iter().product::<usize>();
+        let mut image = vec![0u8; image_len * elem_len];
+        let data = self.data_raw();
+        let compressed_column = layout.column(COMPRESSED_DATA_COLUMN);
+        let gzip_column = layout.column(GZIP_COMPRESSED_DATA_COLUMN);
+        let uncompressed_column = layout.column(UNCOMPRESSED_DATA_COLUMN);
+        let zscale_column = layout.column(ZSCALE_COLUMN);
+        let zzero_column = layout.column(ZZERO_COLUMN);
+        let zblank_column = layout.column(ZBLANK_COLUMN);
+        // floating point pixels are only quantized if there is a scale to restore them
+        let quantized =
+            is_float && method.is_some() && (zscale.is_some() || zscale_column.is_some());
+
+        for t in 0..num_tiles {
+            let decode_error = |message: String| CompressionError::Decode { tile: t, message };
+            let (start, size) = tile_geometry(&dims, &tile, t);
+            let tile_len = size.iter().product::<usize>();
+            let compressed = match compressed_column {
+                Some(column) => layout.heap_slice(data, column, t).map_err(decode_error)?,
+                None => &[],
+            };
+            let values = if !compressed.is_empty() {
+                let int_len = if quantized { 4 } else { elem_len };
+                match algorithm.as_str() {
+                    "RICE_1" | "RICE_ONE" => TileValues::Ints(
+                        rice::decode(compressed, bytepix, block_size, tile_len)
+                            .map_err(decode_error)?
+                            .into_iter()
+                            .map(|value| value as i64)
+                            .collect(),
+                    ),
+                    "GZIP_1" | "GZIP_2" => {
+                        let mut raw = gunzip(compressed)?;
+                        if algorithm == "GZIP_2" {
+                            raw = unshuffle_bytes(&raw, int_len);
+                        }
+                        if quantized {
+                            TileValues::Ints(
+                                raw.chunks_exact(4)
+                                    .map(|chunk| {
+                                        i32::from_be_bytes(chunk.try_into().unwrap()) as i64
+                                    })
+                                    .collect(),
+                            )
+                        } else {
+                            TileValues::Raw(raw)
+                        }
+                    }
+                    "PLIO_1" => {
+                        let list = compressed
+                            .chunks_exact(2)
+                            .map(|chunk| i16::from_be_bytes([chunk[0], chunk[1]]))
+                            .collect::<Vec<_>>();
+                        TileValues::Ints(
+                            plio::decode(&list, tile_len)
+                                .map_err(decode_error)?
+                                .into_iter()
+                                .map(|value| value as i64)
+                                .collect(),
+                        )
+                    }
+                    "HCOMPRESS_1" => {
+                        let (values, nx, ny) =
+                            hcompress::decode(compressed).map_err(decode_error)?;
+                        if nx * ny != tile_len {
+                            return Err(decode_error(format!(
+                                "expected {} pixels, found {} x {}",
+                                tile_len, nx, ny
+                            )));
+                        }
+                        TileValues::Ints(values)
+                    }
+                    "NOCOMPRESS" => TileValues::Raw(compressed.to_vec()),
+                    _ => return Err(CompressionError::UnsupportedAlgorithm(algorithm)),
+                }
+            } else if let Some(column) = gzip_column
+                .map(|column| layout.heap_slice(data, column, t))
+                .transpose()
+                .map_err(decode_error)?
+                .filter(|slice| !slice.is_empty())
+            {
+                // tiles that could not be quantized are compressed losslessly
+                TileValues::Raw(gunzip(column)?)
+            } else if let Some(column) = uncompressed_column {
+                TileValues::Raw(
+                    layout
+                        .heap_slice(data, column, t)
+                        .map_err(decode_error)?
+                        .to_vec(),
+                )
+            } else {
+                return Err(decode_error(String::from("tile contains no data")));
+            };
+
+            let mut pixel = 0;
+            match values {
+                TileValues::Raw(raw) => {
+                    if raw.len() < tile_len * elem_len {
+                        return Err(decode_error(format!(
+                            "expected {} bytes, found {}",
+                            tile_len * elem_len,
+                            raw.len()
+                        )));
+                    }
+                    for_each_tile_pixel(&dims, &start, &size, |index| {
+                        image[index * elem_len..(index + 1) * elem_len]
+                            .copy_from_slice(&raw[pixel * elem_len..(pixel + 1) * elem_len]);
+                        pixel += 1;
+                    });
+                }
+                TileValues::Ints(ints) => {
+                    if ints.len() < tile_len {
+                        return Err(decode_error(format!(
+                            "expected {} pixels, found {}",
+                            tile_len,
+                            ints.len()
+                        )));
+                    }
+                    let scale = zscale_column
+                        .and_then(|column| layout.f64_cell(data, column, t))
+                        .or(zscale)
+                        .unwrap_or(1.0);
+                    let zero = zzero_column
+                        .and_then(|column| layout.f64_cell(data, column, t))
+                        .or(zzero)
+                        .unwrap_or(0.0);
+                    let blank = zblank_column
+                        .and_then(|column| layout.f64_cell(data, column, t))
+                        .or(zblank)
+                        .map(|blank| blank as i64);
+                    let method = method.unwrap_or(DitherMethod::NoDither);
+                    let mut dither =
+                        (method != DitherMethod::NoDither).then(|| Dither::new(t, zdither0));
+                    for_each_tile_pixel(&dims, &start, &size, |index| {
+                        let value = ints[pixel];
+                        pixel += 1;
+                        let bytes = &mut image[index * elem_len..(index + 1) * elem_len];
+                        if !is_float {
+                            write_int(bytes, value, zbitpix);
+                            return;
+                        }
+                        let r = dither.as_mut().map(|dither| dither.next_value());
+                        let float = if Some(value) == blank || value == NULL_VALUE as i64 {
+                            f64::NAN
+                        } else if method == DitherMethod::SubtractiveDither2
+                            && value == ZERO_VALUE as i64
+                        {
+                            0.0
+                        } else if let Some(r) = r {
+                            (value as f64 - r as f64 + 0.5) * scale + zero
+                        } else {
+                            value as f64 * scale + zero
+                        };
+                        write_float(bytes, float, zbitpix);
+                    });
+                }
+            }
+        }
+
+        let mut result = Hdu::new();
+        let header = &mut self.header;
+        let out = &mut result.header;
+        let is_primary = header
+            .get_card(ZSIMPLE_KEYWORD)
+            .and_then(|card| card.get_value::<bool>().ok())
+            .is_some_and(|simple| *simple);
+        if is_primary {
+            out.set_value(SIMPLE_KEYWORD, true)?;
+        } else {
+            let xtension = header
+                .get_card(ZTENSION_KEYWORD)
+                .and_then(|card| card.get_value::<String>().ok())
+                .map_or_else(|| String::from("IMAGE"), |xtension| xtension.to_string());
+            // the closing quote of XTENSION is placed no earlier than the 20th column of the card
+            out.set_value(XTENSION_KEYWORD, format!("{:<8}", xtension))?;
+        }
+        out.set_value(BITPIX_KEYWORD, zbitpix)?;
+        out.set_value(NAXIS_KEYWORD, znaxis)?;
+        for (n, dim) in (1..=znaxis).zip(&dims) {
+            out.set_value(numbered(NAXIS_KEYWORD, n), *dim as u32)?;
+        }
+        if !is_primary {
+            let pcount = header
+                .get_card(ZPCOUNT_KEYWORD)
+                .and_then(numeric_value)
+                .unwrap_or(0.0);
+            let gcount = header
+                .get_card(ZGCOUNT_KEYWORD)
+                .and_then(numeric_value)
+                .unwrap_or(1.0);
+            out.set_value(PCOUNT_KEYWORD, pcount as u32)?;
+            out.set_value(GCOUNT_KEYWORD, gcount as u32)?;
+        }
+        if let (false, Some(zblank), None) = (is_float, zblank, header.get_card(BLANK_KEYWORD)) {
+            out.set_value(BLANK_KEYWORD, zblank as i32)?;
+        }
+        for card in &header.cards {
+            if !is_compression_keyword(&card.keyword().to_string()) {
+                out.cards.push(card.clone());
+            }
+        }
+        out.cards.push(FitsHeaderCard::from(DEFAULT_END_BYTES));
+        result.set_data_raw(image);
+        Ok(result)
+    }
+
+    /// Compresses the image contained in this HDU with the given options,
+    /// returning a binary table HDU in which each row contains a compressed tile.
+    /// Keywords other than the mandatory image keywords, CHECKSUM, and DATASUM are copied to the new header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F32)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 100u32)?;
+    /// let data = (0..100).map(|i| (i as f32 * 0.7).sin() * 100.0).collect::<Vec<f32>>();
+    /// hdu.set_data(&data);
+    ///
+    /// // floating point pixels must be quantized by algorithms other than GZIP
+    /// let options = CompressionOptions {
+    ///     quantization: None,
+    ///     ..Default::default()
+    /// };
+    /// assert!(hdu.compress(&options).is_err());
+    ///
+    /// let options = CompressionOptions {
+    ///     algorithm: CompressionAlgorithm::Gzip2,
+    ///     quantization: None,
+    ///     ..Default::default()
+    /// };
+    /// let mut compressed = hdu.compress(&options)?;
+    /// assert_eq!(compressed.decompress()?.get_data::<Vec<f32>>()?, data);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn compress(&mut self, options: &CompressionOptions) -> Result<Hdu, CompressionError> {
+        let bitpix = *required_value::<Bitpix>(&mut self.header, BITPIX_KEYWORD)?;
+        let dims = self.get_dimensions();
+        if dims.is_empty() || dims.contains(&0) {
+            return Err(CompressionError::InvalidOptions(String::from(
+                "the HDU does not contain an image",
+            )));
+        }
+        let tile = match &options.tile {
+            Some(tile) if tile.len() != dims.len() => {
+                return Err(CompressionError::InvalidOptions(format!(
+                    "expected {} tile dimensions, found {}",
+                    dims.len(),
+                    tile.len()
+                )));
+            }
+            Some(tile) => tile
+                .iter()
+                .zip(&dims)
+                .map(|(tile_dim, dim)| (*tile_dim).clamp(1, *dim))
+                .collect::<Vec<_>>(),
+            None => dims
+                .iter()
+                .enumerate()
+                .map(|(k, dim)| if k == 0 { *dim } else { 1 })
+                .collect(),
+        };
+        let is_float = matches!(bitpix, Bitpix::F32 | Bitpix::F64);
+        let quantization = options.quantization.filter(|_| is_float);
+        if is_float && quantization.is_none() && !options.algorithm.is_gzip() {
+            return Err(CompressionError::InvalidOptions(String::from(
+                "floating point pixels must be quantized unless compressed with GZIP",
+            )));
+        }
+        if let Some(quantization) = quantization {
+            if !(1..=N_RANDOM as u16).contains(&quantization.seed) {
+                return Err(CompressionError::InvalidOptions(format!(
+                    "dither seed {} is not between 1 and {}",
+                    quantization.seed, N_RANDOM
+                )));
+            }
+        }
+        let elem_len = bitpix.value() / 8;
+        let data = self.data_raw();
+        let image_len = dims.iter().product::<usize>() * elem_len;
+        if data.len() < image_len {
+            return Err(CompressionError::InvalidOptions(format!(
+                "expected {} bytes of image data, found {}",
+                image_len,
+                data.len()
+            )));
+        }
+
+        let num_tiles = tile_count(&dims, &tile);
+        let mut heap = Vec::new();
+        let mut tiles = Vec::with_capacity(num_tiles);
+        for t in 0..num_tiles {
+            let encode_error = |message: String| CompressionError::Encode { tile: t, message };
+            let (start, size) = tile_geometry(&dims, &tile, t);
+            let mut raw = Vec::with_capacity(size.iter().product::<usize>() * elem_len);
+            for_each_tile_pixel(&dims, &start, &size, |index| {
+                raw.extend_from_slice(&data[index * elem_len..(index + 1) * elem_len]);
+            });
+
+            let mut row = TileRow::default();
+            if let Some(quantization) = quantization {
+                let values = raw
+                    .chunks_exact(elem_len)
+                    .map(|bytes| read_float(bytes, bitpix))
+                    .collect::<Vec<_>>();
+                let encoded =
+                    quantize_tile(&values, size[0], &quantization, t).and_then(|quantized| {
+                        encode_ints(&quantized.ints, 4, options.algorithm)
+                            .ok()
+                            .map(|encoded| (encoded, quantized))
+                    });
+                match encoded {
+                    Some((encoded, quantized)) => {
+                        row.compressed = append_heap(&mut heap, &encoded, options.algorithm);
+                        row.scale = quantized.scale;
+                        row.zero = quantized.zero;
+                    }
+                    None => {
+                        row.gzip = Some(append_heap(
+                            &mut heap,
+                            &gzip(&raw)?,
+                            CompressionAlgorithm::Gzip1,
+                        ))
+                    }
+                }
+            } else if is_float {
+                let encoded = encode_raw(&raw, elem_len, options.algorithm)?;
+                row.compressed = append_heap(&mut heap, &encoded, options.algorithm);
+            } else {
+                let ints = raw
+                    .chunks_exact(elem_len)
+                    .map(|bytes| read_int(bytes, bitpix))
+                    .collect::<Vec<_>>();
+                let encoded =
+                    encode_ints(&ints, elem_len, options.algorithm).map_err(encode_error)?;
+                row.compressed = append_heap(&mut heap, &encoded, options.algorithm);
+            }
+            tiles.push(row);
+        }
+
+        let has_gzip_column = tiles.iter().any(|row| row.gzip.is_some());
+        let max_len = |descriptor: fn(&TileRow) -> Option<(usize, usize)>| {
+            tiles
+                .iter()
+                .filter_map(descriptor)
+                .map(|(count, _)| count)
+                .max()
+                .unwrap_or_default()
+        };
+        let element_type = if options.algorithm == CompressionAlgorithm::Plio1 {
+            "I"
+        } else {
+            "B"
+        };
+        let mut columns = vec![(
+            COMPRESSED_DATA_COLUMN,
+            TForm {
+                r: 1,
+                t: TFormType::ArrayDescriptor,
+                a: format!("{}({})", element_type, max_len(|row| Some(row.compressed))),
+            },
+        )];
+        if has_gzip_column {
+            columns.push((
+                GZIP_COMPRESSED_DATA_COLUMN,
+                TForm {
+                    r: 1,
+                    t: TFormType::ArrayDescriptor,
+                    a: format!("B({})", max_len(|row| row.gzip)),
+                },
+            ));
+        }
+        if quantization.is_some() {
+            for name in [ZSCALE_COLUMN, ZZERO_COLUMN] {
+                columns.push((
+                    name,
+                    TForm {
+                        r: 1,
+                        t: TFormType::F64,
+                        a: String::new(),
+                    },
+                ));
+            }
+        }
+        let row_len = columns
+            .iter()
+            .map(|(_, tform)| tform.value())
+            .sum::<usize>();
+        let mut table = Vec::with_capacity(row_len * num_tiles + heap.len());
+        for row in &tiles {
+            for descriptor in [Some(row.compressed), row.gzip]
+                .into_iter()
+                .take(1 + has_gzip_column as usize)
+            {
+                let (count, offset) = descriptor.unwrap_or_default();
+                table.extend_from_slice(&(count as i32).to_be_bytes());
+                table.extend_from_slice(&(offset as i32).to_be_bytes());
+            }
+            if quantization.is_some() {
+                table.extend_from_slice(&row.scale.to_be_bytes());
+                table.extend_from_slice(&row.zero.to_be_bytes());
+            }
+        }
+        table.append(&mut heap);
+
+        let mut result = Hdu::new();
+        let header = &mut self.header;
+        let out = &mut result.header;
+        out.set_value(XTENSION_KEYWORD, String::from("BINTABLE"))?;
+        out.set_value(BITPIX_KEYWORD, Bitpix::U8)?;
+        out.set_value(NAXIS_KEYWORD, 2u16)?;
+        out.set_value(numbered(NAXIS_KEYWORD, 1), row_len as u32)?;
+        out.set_value(numbered(NAXIS_KEYWORD, 2), num_tiles as u32)?;
+        out.set_value(PCOUNT_KEYWORD, (table.len() - row_len * num_tiles) as u32)?;
+        out.set_value(GCOUNT_KEYWORD, 1u32)?;
+        out.set_value(TFIELDS_KEYWORD, columns.len() as u16)?;
+        for (n, (name, tform)) in (1..).zip(columns) {
+            out.set_value(numbered(TTYPE_KEYWORD, n), String::from(name))?;
+            out.set_value(numbered(TFORM_KEYWORD, n), tform)?;
+        }
+        out.set_value(ZIMAGE_KEYWORD, true)?;
+        out.set_value(ZBITPIX_KEYWORD, bitpix)?;
+        out.set_value(ZNAXIS_KEYWORD, dims.len() as u16)?;
+        for (n, (dim, tile_dim)) in (1..).zip(dims.iter().zip(&tile)) {
+            out.set_value(numbered(ZNAXIS_KEYWORD, n), *dim as u32)?;
+            out.set_value(numbered(ZTILE_KEYWORD, n), *tile_dim as u32)?;
+        }
+        out.set_value(ZCMPTYPE_KEYWORD, String::from(options.algorithm.name()))?;
+        if options.algorithm == CompressionAlgorithm::Rice1 {
+            let bytepix = if quantization.is_some() { 4 } else { elem_len };
+            out.set_value(numbered(ZNAME_KEYWORD, 1), String::from("BLOCKSIZE"))?;
+            out.set_value(numbered(ZVAL_KEYWORD, 1), DEFAULT_BLOCK_SIZE as i32)?;
+            out.set_value(numbered(ZNAME_KEYWORD, 2), String::from("BYTEPIX"))?;
+            out.set_value(numbered(ZVAL_KEYWORD, 2), bytepix as i32)?;
+        }
+        if let Some(quantization) = quantization {
+            out.set_value(ZQUANTIZ_KEYWORD, String::from(quantization.method.name()))?;
+            if quantization.method != DitherMethod::NoDither {
+                out.set_value(ZDITHER0_KEYWORD, quantization.seed as i32)?;
+            }
+        } else if is_float {
+            out.set_value(ZQUANTIZ_KEYWORD, String::from("NONE"))?;
+        }
+        let is_primary = header.get_card(SIMPLE_KEYWORD).is_some();
+        if is_primary {
+            out.set_value(ZSIMPLE_KEYWORD, true)?;
+        } else {
+            let xtension = header
+                .get_card(XTENSION_KEYWORD)
+                .and_then(|card| card.get_value::<String>().ok())
+                .map_or_else(|| String::from("IMAGE"), |xtension| xtension.to_string());
+            out.set_value(ZTENSION_KEYWORD, xtension)?;
+            out.set_value(ZPCOUNT_KEYWORD, 0u32)?;
+            out.set_value(ZGCOUNT_KEYWORD, 1u32)?;
+        }
+        for card in &header.cards {
+            if !is_image_keyword(&card.keyword().to_string()) {
+                out.cards.push(card.clone());
+            }
+        }
+        out.cards.push(FitsHeaderCard::from(DEFAULT_END_BYTES));
+        result.set_data_raw(table);
+        Ok(result)
+    }
+}
+
+impl<R: Read> HduList<R> {
+    /// Sets whether tile-compressed images that have not yet been read are decompressed as they are read,
+    /// so that they are presented as image HDUs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 5u32)?;
+    /// hdu.set_data(&vec![1u8, 1, 2, 3, 5]);
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// hdu_list.push(hdu);
+    /// hdu_list.set_write_compression(Some(CompressionOptions::default()));
+    /// let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+    /// hdu_list.write(&mut writer)?;
+    /// let bytes = writer.into_inner()?.into_inner();
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes.clone())));
+    /// assert!(hdu_list.get_by_index(1).unwrap().is_compressed_image());
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes)));
+    /// hdu_list.set_decompress_images(true);
+    /// let hdu = hdu_list.try_get_by_index(1)?.unwrap();
+    /// assert_eq!(hdu.get_data::<Vec<u8>>()?[0..5], [1, 1, 2, 3, 5]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn set_decompress_images(&mut self, decompress_images: bool) {
+        self.decompress_images = decompress_images;
+    }
+
+    /// Sets the options with which image extensions are compressed by [`HduList::write`], or None to write them as they are.
+    /// The primary HDU and images without data are not compressed.
+    /// When enabled, all HDUs are deserialized before writing.
+    pub fn set_write_compression(&mut self, options: Option<CompressionOptions>) {
+        self.write_compression = options;
+    }
+}
+
+/// The pixels of a decoded tile.
+enum TileValues {
+    /// Pixels in the big-endian representation of the image.
+    Raw(Vec<u8>),
+    /// Integer pixels, which are quantized if the image is floating point.
+    Ints(Vec<i64>),
+}
+
+/// The heap descriptors and quantization parameters of a compressed tile.
+#[derive(Default)]
+struct TileRow {
+    compressed: (usize, usize),
+    gzip: Option<(usize, usize)>,
+    scale: f64,
+    zero: f64,
+}
+
+/// The quantized pixels of a floating point tile.
+struct QuantizedTile {
+    ints: Vec<i64>,
+    scale: f64,
+    zero: f64,
+}
+
+/// The location of a column within a binary table.
+struct TableColumn {
+    name: String,
+    offset: usize,
+    form: TForm,
+}
+
+/// The layout of a binary table.
+struct TableLayout {
+    row_len: usize,
+    num_rows: usize,
+    heap_offset: usize,
+    columns: Vec<TableColumn>,
+}
+
+impl TableLayout {
+    fn new(header: &mut FitsHeader) -> Result<Self, CompressionError> {
+        let row_len = *required_value::<u32>(header, numbered(NAXIS_KEYWORD, 1))? as usize;
+        let num_rows = *required_value::<u32>(header, numbered(NAXIS_KEYWORD, 2))? as usize;
+        let tfields = *required_value::<u16>(header, TFIELDS_KEYWORD)?;
+        let heap_offset = header
+            .get_card(THEAP_KEYWORD)
+            .and_then(numeric_value)
+            .map_or(row_len * num_rows, |theap| theap as usize);
+        let mut columns = Vec::with_capacity(tfields as usize);
+        let mut offset = 0;
+        for n in 1..=tfields {
+            let form = (*required_value::<TForm>(header, numbered(TFORM_KEYWORD, n))?).clone();
+            let name = header
+                .get_card(numbered(TTYPE_KEYWORD, n))
+                .and_then(|card| card.get_value::<String>().ok())
+                .map(|name| name.to_string())
+                .unwrap_or_default();
+            let len = form.value();
+            columns.push(TableColumn { name, offset, form });
+            offset += len;
+        }
+        Ok(TableLayout {
+            row_len,
+            num_rows,
+            heap_offset,
+            columns,
+        })
+    }
+
+    fn column(&self, name: &str) -> Option<&TableColumn> {
+        self.columns
+            .iter()
+            .find(|column| column.name.eq_ignore_ascii_case(name))
+    }
+
+    fn cell<'a>(
+        &self,
+        data: &'a [u8],
+        column: &TableColumn,
+        row: usize,
+    ) -> Result<&'a [u8], String> {
+        let start = row * self.row_len + column.offset;
+        data.get(start..start + column.form.value())
+            .ok_or_else(|| format!("row {} of column {} is out of range", row, column.name))
+    }
+
+    /// Gets the heap array referenced by the descriptor in the given cell.
+    fn heap_slice<'a>(
+        &self,
+        data: &'a [u8],
+        column: &TableColumn,
+        row: usize,
+    ) -> Result<&'a [u8], String> {
+        let cell = self.cell(data, column, row)?;
+        let (count, offset) = match column.form.t {
+            TFormType::ArrayDescriptor => (
+                u32::from_be_bytes(cell[0..4].try_into().unwrap()) as usize,
+                u32::from_be_bytes(cell[4..8].try_into().unwrap()) as usize,
+            ),
+            TFormType::LongArrayDescriptor => (
+                u64::from_be_bytes(cell[0..8].try_into().unwrap()) as usize,
+                u64::from_be_bytes(cell[8..16].try_into().unwrap()) as usize,
+            ),
+            // fixed-length columns are stored in the row itself
+            _ => return Ok(cell),
+        };
+        let elem_len = match column.form.a.chars().next() {
+            Some('I') => 2,
+            Some('J') | Some('E') => 4,
+            Some('K') | Some('D') => 8,
+            _ => 1,
+        };
+        let start = self.heap_offset + offset;
+        data.get(start..start + count * elem_len)
+            .ok_or_else(|| format!("heap array of column {} is out of range", column.name))
+    }
+
+    /// Gets the numeric value in the given cell, or None if it cannot be read.
+    fn f64_cell(&self, data: &[u8], column: &TableColumn, row: usize) -> Option<f64> {
+        let cell = self.cell(data, column, row).ok()?;
+        match column.form.t {
+            TFormType::F64 => Some(f64::from_be_bytes(cell[0..8].try_into().unwrap())),
+            TFormType::F32 => Some(f32::from_be_bytes(cell[0..4].try_into().unwrap()) as f64),
+            TFormType::I32 => Some(i32::from_be_bytes(cell[0..4].try_into().unwrap()) as f64),
+            TFormType::I16 => Some(i16::from_be_bytes(cell[0..2].try_into().unwrap()) as f64),
+            TFormType::UnsignedByte => Some(cell[0] as f64),
+            _ => None,
+        }
+    }
+}
+
+/// The random sequence used to dither quantized pixels, starting at an offset determined by the tile.
+struct Dither {
+    values: &'static [f32],
+    iseed: usize,
+    next: usize,
+}
+
+impl Dither {
+    fn new(tile_index: usize, zdither0: i64) -> Self {
+        let values = random_values();
+        let iseed = (tile_index as i64 + zdither0 - 1).rem_euclid(N_RANDOM as i64) as usize;
+        Dither {
+            values,
+            iseed,
+            next: (values[iseed] as f64 * 500.0) as usize,
+        }
+    }
+
+    fn next_value(&mut self) -> f32 {
+        let value = self.values[self.next];
+        self.next += 1;
+        if self.next == N_RANDOM {
+            self.iseed = (self.iseed + 1) % N_RANDOM;
+            self.next = (self.values[self.iseed] as f64 * 500.0) as usize;
+        }
+        value
+    }
+}
+
+/// Gets the sequence of random values in the range 0 to 1 defined by the tiled image compression convention.
+fn random_values() -> &'static [f32] {
+    static VALUES: OnceLock<Vec<f32>> = OnceLock::new();
+    VALUES.get_or_init(|| {
+        let a = 16807.0;
+        let m = 2147483647.0;
+        let mut seed: f64 = 1.0;
+        (0..N_RANDOM)
+            .map(|_| {
+                let temp = a * seed;
+                seed = temp - m * (temp / m).trunc();
+                (seed / m) as f32
+            })
+            .collect()
+    })
+}
+
+/// Quantizes the given tile, or returns None if the noise in the tile cannot be estimated
+/// or the range of the tile is too large.
+fn quantize_tile(
+    values: &[f64],
+    row_len: usize,
+    quantization: &Quantization,
+    tile_index: usize,
+) -> Option<QuantizedTile> {
+    let finite = values
+        .iter()
+        .copied()
+        .filter(|value| value.is_finite())
+        .collect::<Vec<_>>();
+    let min = finite.iter().copied().reduce(f64::min)?;
+    let max = finite.iter().copied().reduce(f64::max)?;
+    let delta = if quantization.level < 0.0 {
+        -quantization.level as f64
+    } else {
+        noise3(values, row_len)? / quantization.level as f64
+    };
+    if !delta.is_finite() || delta <= 0.0 || (max - min) / delta > (i32::MAX - 10) as f64 {
+        return None;
+    }
+
+    let mut dither = (quantization.method != DitherMethod::NoDither)
+        .then(|| Dither::new(tile_index, quantization.seed as i64));
+    let ints = values
+        .iter()
+        .map(|value| {
+            let r = dither.as_mut().map(|dither| dither.next_value());
+            if !value.is_finite() {
+                NULL_VALUE as i64
+            } else if quantization.method == DitherMethod::SubtractiveDither2 && *value == 0.0 {
+                ZERO_VALUE as i64
+            } else {
+                let scaled = (value - min) / delta;
+                nint(r.map_or(scaled, |r| scaled + r as f64 - 0.5))
+            }
+        })
+        .collect();
+    Some(QuantizedTile {
+        ints,
+        scale: delta,
+        zero: min,
+    })
+}
+
+/// Estimates the noise in the given values from the median of the second order differences along each row.
+/// Rows shorter than 5 pixels are combined.
+fn noise3(values: &[f64], row_len: usize) -> Option<f64> {
+    let row_len = if row_len < 5 { values.len() } else { row_len };
+    let mut differences = Vec::with_capacity(values.len());
+    for row in values.chunks(row_len) {
+        let finite = row
+            .iter()
+            .copied()
+            .filter(|value| value.is_finite())
+            .collect::<Vec<_>>();
+        differences.extend(
+            finite
+                .windows(5)
+                .map(|window| (2.0 * window[2] - window[0] - window[4]).abs()),
+        );
+    }
+    if differences.is_empty() {
+        return None;
+    }
+    let middle = differences.len() / 2;
+    let (_, median, _) = differences.select_nth_unstable_by(middle, f64::total_cmp);
+    Some(0.6052697 * *median)
+}
+
+/// Rounds the given value to the nearest integer, with halves rounded away from zero.
+fn nint(value: f64) -> i64 {
+    if value >= 0.0 {
+        (value + 0.5) as i64
+    } else {
+        (value - 0.5) as i64
+    }
+}
+
+/// Encodes integer pixels of `bytepix` bytes with the given algorithm.
+fn encode_ints(
+    ints: &[i64],
+    bytepix: usize,
+    algorithm: CompressionAlgorithm,
+) -> Result<Vec<u8>, String> {
+    match algorithm {
+        CompressionAlgorithm::Rice1 => {
+            let pixels = ints.iter().map(|value| *value as i32).collect::<Vec<_>>();
+            rice::encode(&pixels, bytepix, DEFAULT_BLOCK_SIZE)
+        }
+        CompressionAlgorithm::Gzip1 | CompressionAlgorithm::Gzip2 => {
+            let mut raw = Vec::with_capacity(ints.len() * bytepix);
+            for value in ints {
+                raw.extend_from_slice(&value.to_be_bytes()[8 - bytepix..]);
+            }
+            encode_raw(&raw, bytepix, algorithm).map_err(|e| e.to_string())
+        }
+        CompressionAlgorithm::Plio1 => {
+            let pixels = ints
+                .iter()
+                .map(|value| i32::try_from(*value).unwrap_or(-1))
+                .collect::<Vec<_>>();
+            let list = plio::encode(&pixels)?;
+            Ok(list.iter().flat_map(|word| word.to_be_bytes()).collect())
+        }
+    }
+}
+
+/// Encodes big-endian pixels of `elem_len` bytes with a GZIP algorithm.
+fn encode_raw(
+    raw: &[u8],
+    elem_len: usize,
+    algorithm: CompressionAlgorithm,
+) -> Result<Vec<u8>, CompressionError> {
+    match algorithm {
+        CompressionAlgorithm::Gzip1 => gzip(raw),
+        CompressionAlgorithm::Gzip2 => gzip(&shuffle_bytes(raw, elem_len)),
+        _ => Err(CompressionError::InvalidOptions(format!(
+            "{} cannot compress floating point pixels losslessly",
+            algorithm.name()
+        ))),
+    }
+}
+
+/// Appends the given bytes to the heap, returning the element count and byte offset of the array.
+fn append_heap(
+    heap: &mut Vec<u8>,
+    bytes: &[u8],
+    algorithm: CompressionAlgorithm,
+) -> (usize, usize) {
+    let offset = heap.len();
+    heap.extend_from_slice(bytes);
+    let elem_len = if algorithm == CompressionAlgorithm::Plio1 {
+        2
+    } else {
+        1
+    };
+    (bytes.len() / elem_len, offset)
+}
+
+fn gzip(bytes: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    let mut result = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut result)?;
+    Ok(result)
+}
+
+/// Groups the bytes of each pixel by significance, with the most significant bytes first.
+fn shuffle_bytes(raw: &[u8], elem_len: usize) -> Vec<u8> {
+    let count = raw.len() / elem_len;
+    let mut result = vec![0; raw.len()];
+    for (i, bytes) in raw.chunks_exact(elem_len).enumerate() {
+        for (j, byte) in bytes.iter().enumerate() {
+            result[j * count + i] = *byte;
+        }
+    }
+    result
+}
+
+/// Reverses [`shuffle_bytes`].
+fn unshuffle_bytes(raw: &[u8], elem_len: usize) -> Vec<u8> {
+    let count = raw.len() / elem_len;
+    let mut result = vec![0; raw.len()];
+    for (i, bytes) in result.chunks_exact_mut(elem_len).enumerate() {
+        for (j, byte) in bytes.iter_mut().enumerate() {
+            *byte = raw[j * count + i];
+        }
+    }
+    result
+}
+
+fn read_int(bytes: &[u8], bitpix: Bitpix) -> i64 {
+    match bitpix {
+        Bitpix::U8 => bytes[0] as i64,
+        Bitpix::I16 => i16::from_be_bytes([bytes[0], bytes[1]]) as i64,
+        Bitpix::I64 => i64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+        _ => i32::from_be_bytes(bytes[0..4].try_into().unwrap()) as i64,
+    }
+}
+
+fn write_int(bytes: &mut [u8], value: i64, bitpix: Bitpix) {
+    match bitpix {
+        Bitpix::U8 => bytes[0] = value as u8,
+        Bitpix::I16 => bytes.copy_from_slice(&(value as i16).to_be_bytes()),
+        Bitpix::I64 => bytes.copy_from_slice(&value.to_be_bytes()),
+        _ => bytes.copy_from_slice(&(value as i32).to_be_bytes()),
+    }
+}
+
+fn read_float(bytes: &[u8], bitpix: Bitpix) -> f64 {
+    match bitpix {
+        Bitpix::F64 => f64::from_be_bytes(bytes[0..8].try_into().unwrap()),
+        _ => f32::from_be_bytes(bytes[0..4].try_into().unwrap()) as f64,
+    }
+}
+
+fn write_float(bytes: &mut [u8], value: f64, bitpix: Bitpix) {
+    match bitpix {
+        Bitpix::F64 => bytes.copy_from_slice(&value.to_be_bytes()),
+        _ => bytes.copy_from_slice(&(value as f32).to_be_bytes()),
+    }
+}
+
+/// Gets the number of tiles that cover an image.
+fn tile_count(dims: &[usize], tile: &[usize]) -> usize {
+    dims.iter()
+        .zip(tile)
+        .map(|(dim, tile_dim)| dim.div_ceil(*tile_dim))
+        .product()
+}
+
+/// Gets the starting pixel and dimensions of the tile at the given index, in which the first axis varies fastest.
+fn tile_geometry(dims: &[usize], tile: &[usize], tile_index: usize) -> (Vec<usize>, Vec<usize>) {
+    let mut remaining = tile_index;
+    let mut start = Vec::with_capacity(dims.len());
+    let mut size = Vec::with_capacity(dims.len());
+    for (dim, tile_dim) in dims.iter().zip(tile) {
+        let num_tiles = dim.div_ceil(*tile_dim);
+        let axis_start = (remaining % num_tiles) * tile_dim;
+        remaining /= num_tiles;
+        start.push(axis_start);
+        size.push((*tile_dim).min(dim - axis_start));
+    }
+    (start, size)
+}
+
+/// Calls the given function with the index within the image of each pixel of a tile, in the order they are stored in the tile.
+fn for_each_tile_pixel<F: FnMut(usize)>(dims: &[usize], start: &[usize], size: &[usize], mut f: F) {
+    let count = size.iter().product::<usize>();
+    for pixel in 0..count {
+        let mut remaining = pixel;
+        let mut index = 0;
+        let mut stride = 1;
+        for k in 0..dims.len() {
+            index += (start[k] + remaining % size[k]) * stride;
+            remaining /= size[k];
+            stride *= dims[k];
+        }
+        f(index);
+    }
+}
+
+/// Determines if the given keyword describes the binary table or compression of a compressed image.
+fn is_compression_keyword(keyword: &str) -> bool {
+    let base = keyword.trim_end_matches(|c: char| c.is_ascii_digit());
+    matches!(
+        keyword,
+        "XTENSION"
+            | "BITPIX"
+            | "PCOUNT"
+            | "GCOUNT"
+            | "TFIELDS"
+            | "THEAP"
+            | "END"
+            | "CHECKSUM"
+            | "DATASUM"
+    ) || matches!(
+        base,
+        "NAXIS"
+            | "TTYPE"
+            | "TFORM"
+            | "TUNIT"
+            | "TDIM"
+            | "TNULL"
+            | "TSCAL"
+            | "TZERO"
+            | "TDISP"
+            | "ZIMAGE"
+            | "ZBITPIX"
+            | "ZNAXIS"
+            | "ZTILE"
+            | "ZCMPTYPE"
+            | "ZNAME"
+            | "ZVAL"
+            | "ZQUANTIZ"
+            | "ZDITHER"
+            | "ZSIMPLE"
+            | "ZTENSION"
+            | "ZEXTEND"
+            | "ZBLOCKED"
+            | "ZPCOUNT"
+            | "ZGCOUNT"
+            | "ZHECKSUM"
+            | "ZDATASUM"
+            | "ZBLANK"
+            | "ZSCALE"
+            | "ZZERO"
+            | "ZMASKCMP"
+    )
+}
+
+/// Determines if the given keyword is a mandatory image keyword, or is otherwise invalidated by compression.
+fn is_image_keyword(keyword: &str) -> bool {
+    let base = keyword.trim_end_matches(|c: char| c.is_ascii_digit());
+    matches!(
+        keyword,
+        "SIMPLE"
+            | "XTENSION"
+            | "BITPIX"
+            | "PCOUNT"
+            | "GCOUNT"
+            | "EXTEND"
+            | "END"
+            | "CHECKSUM"
+            | "DATASUM"
+    ) || base == "NAXIS"
+}
+
+fn numbered(keyword: [u8; 8], number: u16) -> FitsHeaderKeyword {
+    let mut keyword = FitsHeaderKeyword::from(keyword);
+    keyword.append_number(number);
+    keyword
+}
+
+fn required_value<T: FitsHeaderValue + 'static>(
+    header: &mut FitsHeader,
+    keyword: impl Into<FitsHeaderKeyword>,
+) -> Result<Rc<T>, FitsHeaderError> {
+    let keyword = keyword.into();
+    header
+        .get_card(keyword)
+        .ok_or(FitsHeaderError::MissingKeyword { keyword })?
+        .get_value::<T>()
+}
+
+/// Reads big-endian bit sequences from a byte slice.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, position: 0 }
+    }
+
+    /// Reads the next `n` bits, up to 32, as an unsigned value.
+    fn read_bits(&mut self, n: usize) -> Result<u32, String> {
+        if self.position + n > self.data.len() * 8 {
+            return Err(String::from("compressed data ended unexpectedly"));
+        }
+        let mut value: u64 = 0;
+        let mut remaining = n;
+        while remaining > 0 {
+            let byte = self.data[self.position / 8] as u64;
+            let available = 8 - self.position % 8;
+            let take = available.min(remaining);
+            let bits = (byte >> (available - take)) & ((1 << take) - 1);
+            value = (value << take) | bits;
+            self.position += take;
+            remaining -= take;
+        }
+        Ok(value as u32)
+    }
+
+    /// Counts and consumes the zero bits before the next one bit, which is also consumed.
+    fn count_zeros(&mut self) -> Result<u32, String> {
+        let mut count = 0;
+        while self.position < self.data.len() * 8 {
+            let byte = self.data[self.position / 8];
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            self.position += 1;
+            if bit == 1 {
+                return Ok(count);
+            }
+            count += 1;
+        }
+        Err(String::from("compressed data ended unexpectedly"))
+    }
+
+    /// Advances to the next byte boundary.
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+/// Writes big-endian bit sequences to a byte vector.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    len: usize,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            len: 0,
+        }
+    }
+
+    /// Writes the lowest `n` bits, up to 32, of the given value.
+    fn write_bits(&mut self, value: u32, n: usize) {
+        if n == 0 {
+            return;
+        }
+        self.buffer = (self.buffer << n) | (value as u64 & ((1 << n) - 1));
+        self.len += n;
+        while self.len >= 8 {
+            self.len -= 8;
+            self.bytes.push((self.buffer >> self.len) as u8);
+        }
+        self.buffer &= (1 << self.len) - 1;
+    }
+
+    /// Writes the given number of zero bits.
+    fn write_zeros(&mut self, mut count: usize) {
+        while count > 0 {
+            let n = count.min(32);
+            self.write_bits(0, n);
+            count -= n;
+        }
+    }
+
+    /// Pads the final byte with zero bits, and returns the written bytes.
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push((self.buffer << (8 - self.len)) as u8);
+        }
+        self.bytes
+    }
+}
@@ -0,0 +1,94 @@
+//! The PLIO_1 algorithm, which encodes non-negative integers as an IRAF pixel list.
+
+/// The length of the line list header in words.
+const HEADER_LEN: usize = 7;
+/// The largest value that can be encoded.
+pub(crate) const MAX_VALUE: i32 = (1 << 24) - 1;
+
+/// Decodes `len` pixels from the given line list.
+pub(crate) fn decode(list: &[i16], len: usize) -> Result<Vec<i32>, String> {
+    let word = |index: usize| {
+        list.get(index)
+            .map(|word| *word as i32)
+            .ok_or_else(|| String::from("line list ended unexpectedly"))
+    };
+    let (list_len, first) = if word(2)? > 0 {
+        (word(2)? as usize, 3)
+    } else {
+        (((word(4)? << 15) + word(3)?) as usize, word(1)? as usize)
+    };
+
+    let mut result = vec![0; len];
+    let mut op = 0;
+    let mut x = 0;
+    let mut pv = 1;
+    let mut index = first;
+    while index < list_len.min(list.len()) && x < len {
+        let instruction = word(index)? as u16;
+        let opcode = instruction >> 12;
+        let data = (instruction & 0xfff) as i32;
+        match opcode {
+            0 | 4 | 5 => {
+                let run_end = (x + data as usize).min(len);
+                let fill = if opcode == 4 { pv } else { 0 };
+                result[op..run_end].fill(fill);
+                if opcode == 5 && run_end > op && x + data as usize <= len {
+                    result[run_end - 1] = pv;
+                }
+                op = run_end;
+                x += data as usize;
+            }
+            1 => {
+                pv = (word(index + 1)? << 12) + data;
+                index += 1;
+            }
+            2 => pv += data,
+            3 => pv -= data,
+            6 | 7 => {
+                pv += if opcode == 6 { data } else { -data };
+                result[op] = pv;
+                op += 1;
+                x += 1;
+            }
+            _ => return Err(format!("invalid line list opcode {}", opcode)),
+        }
+        index += 1;
+    }
+    Ok(result)
+}
+
+/// Encodes the given pixels as a line list. Returns an error if a pixel is negative or exceeds 2^24 - 1.
+pub(crate) fn encode(pixels: &[i32]) -> Result<Vec<i16>, String> {
+    let mut list = vec![0i16; HEADER_LEN];
+    let mut pv = 1;
+    let mut run_start = 0;
+    while run_start < pixels.len() {
+        let value = pixels[run_start];
+        if !(0..=MAX_VALUE).contains(&value) {
+            return Err(format!("pixel value {} cannot be encoded", value));
+        }
+        let run_len = pixels[run_start..]
+            .iter()
+            .take_while(|pixel| **pixel == value)
+            .count();
+        if value != 0 && value != pv {
+            list.push((0x1000 | (value & 0xfff)) as i16);
+            list.push((value >> 12) as i16);
+            pv = value;
+        }
+        let opcode = if value == 0 { 0 } else { 0x4000 };
+        let mut remaining = run_len;
+        while remaining > 0 {
+            let count = remaining.min(0xfff);
+            list.push((opcode | count) as i16);
+            remaining -= count;
+        }
+        run_start += run_len;
+    }
+    let list_len = list.len();
+    list[1] = HEADER_LEN as i16;
+    list[2] = -100;
+    list[3] = (list_len % 32768) as i16;
+    list[4] = (list_len / 32768) as i16;
+    Ok(list)
+}
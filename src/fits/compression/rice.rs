@@ -0,0 +1,126 @@
+//! The RICE_1 algorithm, which encodes the differences between consecutive pixels with adaptive Rice codes.
+
+use super::{BitReader, BitWriter};
+
+/// Gets the number of bits in the split position code, the maximum split position,
+/// and the number of bits in a pixel for the given number of bytes per pixel.
+fn parameters(bytepix: usize) -> Result<(usize, u32, usize), String> {
+    match bytepix {
+        1 => Ok((3, 6, 8)),
+        2 => Ok((4, 14, 16)),
+        4 => Ok((5, 25, 32)),
+        _ => Err(format!("unsupported BYTEPIX value {}", bytepix)),
+    }
+}
+
+fn mask(bits: usize) -> u32 {
+    if bits >= 32 {
+        u32::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// Decodes `len` pixels of `bytepix` bytes each from the given bytes.
+/// Pixels of 1 byte are unsigned, while wider pixels are sign-extended.
+pub(crate) fn decode(
+    input: &[u8],
+    bytepix: usize,
+    block_size: usize,
+    len: usize,
+) -> Result<Vec<i32>, String> {
+    let (fsbits, fsmax, bbits) = parameters(bytepix)?;
+    let pixel_mask = mask(bbits);
+    if len == 0 {
+        return Ok(Vec::new());
+    }
+    let mut reader = BitReader::new(input);
+    let mut result = Vec::with_capacity(len);
+    let mut lastpix = reader.read_bits(bbits)?;
+    while result.len() < len {
+        let block_len = block_size.min(len - result.len());
+        let fs = reader.read_bits(fsbits)? as i64 - 1;
+        for _ in 0..block_len {
+            let diff = if fs < 0 {
+                0
+            } else if fs == fsmax as i64 {
+                reader.read_bits(bbits)?
+            } else {
+                let nzero = reader.count_zeros()?;
+                let low = reader.read_bits(fs as usize)?;
+                (nzero << fs) | low
+            };
+            // undo the mapping of signed differences to unsigned values
+            let diff = if diff & 1 == 0 {
+                diff >> 1
+            } else {
+                !(diff >> 1)
+            };
+            lastpix = lastpix.wrapping_add(diff) & pixel_mask;
+            result.push(lastpix);
+        }
+    }
+    Ok(result
+        .into_iter()
+        .map(|pixel| match bytepix {
+            2 => pixel as u16 as i16 as i32,
+            4 => pixel as i32,
+            _ => pixel as i32,
+        })
+        .collect())
+}
+
+/// Encodes the given pixels of `bytepix` bytes each.
+pub(crate) fn encode(pixels: &[i32], bytepix: usize, block_size: usize) -> Result<Vec<u8>, String> {
+    let (fsbits, fsmax, bbits) = parameters(bytepix)?;
+    let pixel_mask = mask(bbits);
+    let mut writer = BitWriter::new();
+    let Some(first) = pixels.first() else {
+        return Ok(Vec::new());
+    };
+    let mut lastpix = *first as u32 & pixel_mask;
+    writer.write_bits(lastpix, bbits);
+    let mut diffs = Vec::with_capacity(block_size);
+    for block in pixels.chunks(block_size) {
+        diffs.clear();
+        let mut pixelsum = 0.0;
+        for pixel in block {
+            let pixel = *pixel as u32 & pixel_mask;
+            // interpret the wrapped difference as a signed value of the pixel width
+            let shift = 64 - bbits;
+            let pdiff = ((pixel.wrapping_sub(lastpix) as u64) << shift) as i64 >> shift;
+            let mapped = if pdiff < 0 { !(pdiff << 1) } else { pdiff << 1 } as u32;
+            pixelsum += mapped as f64;
+            diffs.push(mapped);
+            lastpix = pixel;
+        }
+
+        let block_len = block.len() as f64;
+        let dpsum = ((pixelsum - (block.len() / 2) as f64 - 1.0) / block_len).max(0.0);
+        let mut psum = (dpsum as u64) >> 1;
+        let mut fs = 0;
+        while psum > 0 {
+            psum >>= 1;
+            fs += 1;
+        }
+
+        if fs >= fsmax {
+            // high entropy, so the differences are written directly
+            writer.write_bits(fsmax + 1, fsbits);
+            for diff in &diffs {
+                writer.write_bits(*diff, bbits);
+            }
+        } else if fs == 0 && pixelsum == 0.0 {
+            // low entropy, so every difference is zero
+            writer.write_bits(0, fsbits);
+        } else {
+            writer.write_bits(fs + 1, fsbits);
+            for diff in &diffs {
+                writer.write_zeros((diff >> fs) as usize);
+                writer.write_bits(1, 1);
+                writer.write_bits(diff & mask(fs as usize), fs as usize);
+            }
+        }
+    }
+    Ok(writer.finish())
+}
@@ -0,0 +1,966 @@
+//! Compare headers and data sections of HDUs, similar to `fitsdiff`.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use super::text::split_string;
+use super::*;
+
+const COMMENTARY_KEYWORDS: [&str; 3] = ["COMMENT", "HISTORY", ""];
+
+/// The options with which headers and data sections are compared.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffOptions {
+    /// The keywords of cards that are not compared, such as DATE and CHECKSUM.
+    /// Keywords are matched case-insensitively, and a trailing `*` matches any keyword with the given prefix.
+    pub ignore_keywords: Vec<String>,
+    /// Whether the comments of cards are not compared.
+    pub ignore_comments: bool,
+    /// The names of table columns that are not compared, matched in the same way as `ignore_keywords`.
+    pub ignore_columns: Vec<String>,
+    /// The relative tolerance of numeric values.
+    pub rtol: f64,
+    /// The absolute tolerance of numeric values.
+    /// Values `a` and `b` are equal if `|a - b| <= atol + rtol * |b|`, or if both are NaN.
+    pub atol: f64,
+    /// The maximum number of differing values recorded for each data section or column.
+    /// All differing values are counted.
+    pub max_differences: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            ignore_keywords: Vec::new(),
+            ignore_comments: false,
+            ignore_columns: Vec::new(),
+            rtol: 0.0,
+            atol: 0.0,
+            max_differences: 10,
+        }
+    }
+}
+
+impl DiffOptions {
+    fn is_close(&self, first: f64, second: f64) -> bool {
+        first == second
+            || (first.is_nan() && second.is_nan())
+            || (first - second).abs() <= self.atol + self.rtol * second.abs()
+    }
+}
+
+/// A header card, as reported in a [`HeaderDiff`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardText {
+    /// The keyword of the card.
+    pub keyword: String,
+    /// The number of cards with the same keyword preceding this card, such as 1 for the second HISTORY card.
+    pub occurrence: usize,
+    /// The value of the card as written, with the padding of strings removed, or the text of a commentary card.
+    pub value: String,
+    /// The comment of the card, or an empty string if it has none.
+    pub comment: String,
+}
+
+impl CardText {
+    fn from_card(card: &FitsHeaderCard, occurrence: usize) -> Self {
+        let raw: [u8; HEADER_CARD_LEN] = card.clone().into();
+        let text = String::from_utf8_lossy(&raw);
+        let keyword = card.keyword().to_string();
+        let (value, comment) = if &raw[HEADER_KEYWORD_LEN..HEADER_KEYWORD_LEN + 2] == b"= "
+            && !COMMENTARY_KEYWORDS.contains(&keyword.as_str())
+        {
+            let value = text[HEADER_KEYWORD_LEN + 2..].trim();
+            match value
+                .strip_prefix('\'')
+                .and_then(|quoted| split_string(quoted, 0).ok())
+            {
+                Some((string, rest)) => (
+                    format!("'{}'", string.trim_end().replace('\'', "''")),
+                    rest.trim().strip_prefix('/').unwrap_or_default().trim(),
+                ),
+                None => match value.split_once('/') {
+                    Some((value, comment)) => (value.trim().to_owned(), comment.trim()),
+                    None => (value.to_owned(), ""),
+                },
+            }
+        } else {
+            (text[HEADER_KEYWORD_LEN..].trim_end().to_owned(), "")
+        };
+        CardText {
+            keyword,
+            occurrence,
+            value,
+            comment: comment.to_owned(),
+        }
+    }
+
+    fn is_commentary(&self) -> bool {
+        COMMENTARY_KEYWORDS.contains(&self.keyword.as_str())
+    }
+
+    fn name(&self) -> String {
+        if self.occurrence == 0 {
+            self.keyword.clone()
+        } else {
+            format!("{}[{}]", self.keyword, self.occurrence)
+        }
+    }
+}
+
+impl Display for CardText {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_commentary() {
+            write!(f, "{} {}", self.name(), self.value)
+        } else if self.comment.is_empty() {
+            write!(f, "{} = {}", self.name(), self.value)
+        } else {
+            write!(f, "{} = {} / {}", self.name(), self.value, self.comment)
+        }
+    }
+}
+
+/// The differences between two headers.
+/// Cards with the same keyword are paired in the order in which they appear.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HeaderDiff {
+    /// The cards found only in the first header.
+    pub removed: Vec<CardText>,
+    /// The cards found only in the second header.
+    pub added: Vec<CardText>,
+    /// The cards whose values or comments differ, from the first and second header.
+    pub changed: Vec<(CardText, CardText)>,
+}
+
+impl HeaderDiff {
+    /// Determines if the headers are identical, apart from the ignored cards.
+    pub fn is_identical(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for HeaderDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for card in &self.removed {
+            writeln!(f, "- {}", card)?;
+        }
+        for card in &self.added {
+            writeln!(f, "+ {}", card)?;
+        }
+        for (first, second) in &self.changed {
+            write!(f, "! {} -> {}", first, second.value)?;
+            if second.comment.is_empty() {
+                writeln!(f)?;
+            } else {
+                writeln!(f, " / {}", second.comment)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A value that differs between two data sections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueDifference<T> {
+    /// The index of the value in the data section, or the row of the table.
+    pub index: usize,
+    /// The value in the first data section.
+    pub first: T,
+    /// The value in the second data section.
+    pub second: T,
+}
+
+/// The value of a table field, as reported in a [`ColumnDiff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    /// The physical values of a numeric field, with TSCALn and TZEROn applied.
+    /// Complex values are given as pairs of real and imaginary parts.
+    Numbers(Vec<f64>),
+    /// The text of a character or logical field, with trailing spaces removed.
+    Text(String),
+    /// The bytes of a bit field, or of the heap array of an array descriptor field.
+    Bytes(Vec<u8>),
+}
+
+impl FieldValue {
+    fn is_close(&self, other: &FieldValue, options: &DiffOptions) -> bool {
+        match (self, other) {
+            (FieldValue::Numbers(first), FieldValue::Numbers(second)) => {
+                first.len() == second.len()
+                    && first
+                        .iter()
+                        .zip(second)
+                        .all(|(first, second)| options.is_close(*first, *second))
+            }
+            _ => self == other,
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Numbers(values) if values.len() == 1 => write!(f, "{}", values[0]),
+            FieldValue::Numbers(values) => write!(f, "{:?}", values),
+            FieldValue::Text(text) => write!(f, "{:?}", text),
+            FieldValue::Bytes(bytes) => write!(f, "{:?}", bytes),
+        }
+    }
+}
+
+/// The differences between the data sections of two images.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageDiff {
+    /// The dimensions of the first and second image, if they differ; the pixels are then not compared.
+    pub dimensions: Option<(Vec<usize>, Vec<usize>)>,
+    /// The number of pixels whose physical values differ, with BSCALE, BZERO, and BLANK applied.
+    pub num_differences: usize,
+    /// The first pixels that differ, up to the maximum number of differences, indexed in the order of the data section.
+    pub differences: Vec<ValueDifference<f64>>,
+    /// The largest absolute difference between pixels.
+    pub max_absolute: f64,
+    /// The largest difference between pixels relative to the pixel of the second image.
+    pub max_relative: f64,
+}
+
+impl ImageDiff {
+    /// Determines if the images are identical within the tolerances.
+    pub fn is_identical(&self) -> bool {
+        self.dimensions.is_none() && self.num_differences == 0
+    }
+}
+
+impl Display for ImageDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some((first, second)) = &self.dimensions {
+            return writeln!(f, "dimensions differ: {:?} != {:?}", first, second);
+        }
+        if self.num_differences > 0 {
+            writeln!(
+                f,
+                "{} pixels differ, max absolute difference {}, max relative difference {}",
+                self.num_differences, self.max_absolute, self.max_relative
+            )?;
+        }
+        for difference in &self.differences {
+            writeln!(
+                f,
+                "  pixel {}: {} != {}",
+                difference.index, difference.first, difference.second
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The differences between a column of two tables.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnDiff {
+    /// The name of the column, given by TTYPEn, or the number of the column if it is unnamed.
+    pub name: String,
+    /// The TFORMn values of the first and second column, if they differ.
+    pub formats: Option<(String, String)>,
+    /// The number of rows whose values differ.
+    pub num_differences: usize,
+    /// The first rows whose values differ, up to the maximum number of differences.
+    pub differences: Vec<ValueDifference<FieldValue>>,
+}
+
+/// The differences between the data sections of two tables, compared column by column.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableDiff {
+    /// The number of rows of the first and second table, if they differ; the common rows are compared.
+    pub num_rows: Option<(usize, usize)>,
+    /// The names of the columns found only in the first table.
+    pub removed_columns: Vec<String>,
+    /// The names of the columns found only in the second table.
+    pub added_columns: Vec<String>,
+    /// The columns whose formats or values differ.
+    pub columns: Vec<ColumnDiff>,
+}
+
+impl TableDiff {
+    /// Determines if the tables are identical within the tolerances, apart from the ignored columns.
+    pub fn is_identical(&self) -> bool {
+        self.num_rows.is_none()
+            && self.removed_columns.is_empty()
+            && self.added_columns.is_empty()
+            && self.columns.is_empty()
+    }
+}
+
+impl Display for TableDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some((first, second)) = self.num_rows {
+            writeln!(f, "row counts differ: {} != {}", first, second)?;
+        }
+        for name in &self.removed_columns {
+            writeln!(f, "- column {}", name)?;
+        }
+        for name in &self.added_columns {
+            writeln!(f, "+ column {}", name)?;
+        }
+        for column in &self.columns {
+            if let Some((first, second)) = &column.formats {
+                writeln!(f, "! column {} format {} -> {}", column.name, first, second)?;
+            }
+            if column.num_differences > 0 {
+                writeln!(
+                    f,
+                    "! column {}: {} rows differ",
+                    column.name, column.num_differences
+                )?;
+            }
+            for difference in &column.differences {
+                writeln!(
+                    f,
+                    "  row {}: {} != {}",
+                    difference.index, difference.first, difference.second
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The differences between two data sections compared byte by byte.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RawDiff {
+    /// The lengths of the first and second data section, if they differ; the common bytes are compared.
+    pub lengths: Option<(usize, usize)>,
+    /// The number of bytes that differ.
+    pub num_differences: usize,
+    /// The first bytes that differ, up to the maximum number of differences.
+    pub differences: Vec<ValueDifference<u8>>,
+}
+
+impl RawDiff {
+    /// Determines if the data sections are identical.
+    pub fn is_identical(&self) -> bool {
+        self.lengths.is_none() && self.num_differences == 0
+    }
+}
+
+impl Display for RawDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some((first, second)) = self.lengths {
+            writeln!(f, "data lengths differ: {} != {}", first, second)?;
+        }
+        if self.num_differences > 0 {
+            writeln!(f, "{} bytes differ", self.num_differences)?;
+        }
+        for difference in &self.differences {
+            writeln!(
+                f,
+                "  byte {}: {} != {}",
+                difference.index, difference.first, difference.second
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The differences between the data sections of two HDUs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataDiff {
+    /// The differences between two images, or between the data sections of two primary HDUs.
+    Image(ImageDiff),
+    /// The differences between two ASCII or binary tables.
+    Table(TableDiff),
+    /// The differences between data sections of other kinds, of different kinds,
+    /// or that cannot be read as described by their headers.
+    Raw(RawDiff),
+}
+
+impl DataDiff {
+    /// Determines if the data sections are identical within the tolerances.
+    pub fn is_identical(&self) -> bool {
+        match self {
+            DataDiff::Image(diff) => diff.is_identical(),
+            DataDiff::Table(diff) => diff.is_identical(),
+            DataDiff::Raw(diff) => diff.is_identical(),
+        }
+    }
+}
+
+impl Display for DataDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataDiff::Image(diff) => write!(f, "{}", diff),
+            DataDiff::Table(diff) => write!(f, "{}", diff),
+            DataDiff::Raw(diff) => write!(f, "{}", diff),
+        }
+    }
+}
+
+/// The differences between two HDUs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HduDiff {
+    /// The kinds of the first and second HDU, if they differ.
+    pub kinds: Option<(HduKind, HduKind)>,
+    /// The differences between the headers.
+    pub header: HeaderDiff,
+    /// The differences between the data sections.
+    pub data: DataDiff,
+}
+
+impl HduDiff {
+    /// Determines if the HDUs are identical within the tolerances, apart from the ignored cards and columns.
+    pub fn is_identical(&self) -> bool {
+        self.kinds.is_none() && self.header.is_identical() && self.data.is_identical()
+    }
+}
+
+impl Display for HduDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some((first, second)) = &self.kinds {
+            writeln!(f, "kinds differ: {} != {}", first, second)?;
+        }
+        write!(f, "{}{}", self.header, self.data)
+    }
+}
+
+/// The differences between two HDU lists.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HduListDiff {
+    /// The number of HDUs in the first and second list, if they differ; the common HDUs are compared.
+    pub num_hdus: Option<(usize, usize)>,
+    /// The positions and differences of the HDUs that differ.
+    pub hdus: Vec<(usize, HduDiff)>,
+}
+
+impl HduListDiff {
+    /// Determines if the HDU lists are identical within the tolerances, apart from the ignored cards and columns.
+    pub fn is_identical(&self) -> bool {
+        self.num_hdus.is_none() && self.hdus.is_empty()
+    }
+}
+
+impl Display for HduListDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_identical() {
+            return writeln!(f, "no differences");
+        }
+        if let Some((first, second)) = self.num_hdus {
+            writeln!(f, "HDU counts differ: {} != {}", first, second)?;
+        }
+        for (index, diff) in &self.hdus {
+            writeln!(f, "HDU {}:", index)?;
+            for line in diff.to_string().lines() {
+                writeln!(f, "  {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FitsHeader {
+    /// Compares the cards of this header with those of the given header.
+    /// Blank cards and the END card are not compared, and numeric values are compared within the tolerances.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut first = primary_hdu::default().header;
+    /// first.set_exptime(30.0)?;
+    /// first.set_date(String::from("2024-01-01"))?;
+    /// let mut second = primary_hdu::default().header;
+    /// second.set_exptime(30.5)?;
+    /// second.set_date(String::from("2024-01-02"))?;
+    /// second.set_object(String::from("M31"))?;
+    ///
+    /// let options = DiffOptions {
+    ///     ignore_keywords: vec![String::from("DATE*")],
+    ///     ..Default::default()
+    /// };
+    /// let diff = first.diff(&second, &options);
+    /// assert!(diff.removed.is_empty());
+    /// assert_eq!(diff.added[0].keyword, "OBJECT");
+    /// assert_eq!(diff.added[0].value, "'M31'");
+    /// assert_eq!(diff.changed[0].0.keyword, "EXPTIME");
+    ///
+    /// let options = DiffOptions {
+    ///     atol: 1.0,
+    ///     ..options
+    /// };
+    /// assert_eq!(first.diff(&second, &options).changed.len(), 0);
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn diff(&self, other: &FitsHeader, options: &DiffOptions) -> HeaderDiff {
+        let first = header_cards(self, options);
+        let second = header_cards(other, options);
+        let second_index: HashMap<(&str, usize), &CardText> = second
+            .iter()
+            .map(|card| ((card.keyword.as_str(), card.occurrence), card))
+            .collect();
+        let first_index: HashMap<(&str, usize), &CardText> = first
+            .iter()
+            .map(|card| ((card.keyword.as_str(), card.occurrence), card))
+            .collect();
+
+        let mut diff = HeaderDiff::default();
+        for card in &first {
+            match second_index.get(&(card.keyword.as_str(), card.occurrence)) {
+                Some(other_card) => {
+                    let values_differ = !values_equal(&card.value, &other_card.value, options);
+                    let comments_differ =
+                        !options.ignore_comments && card.comment != other_card.comment;
+                    if values_differ || comments_differ {
+                        diff.changed.push((card.clone(), (*other_card).clone()));
+                    }
+                }
+                None => diff.removed.push(card.clone()),
+            }
+        }
+        diff.added = second
+            .iter()
+            .filter(|card| !first_index.contains_key(&(card.keyword.as_str(), card.occurrence)))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+impl Hdu {
+    /// Compares the header and data section of this HDU with those of the given HDU.
+    /// Images are compared pixel by pixel and tables column by column, with numeric values compared within the tolerances.
+    /// With the `compression` feature, tile-compressed images are decompressed and compared pixel by pixel;
+    /// otherwise, or if either cannot be decompressed, their binary tables are compared.
+    /// Data sections of other kinds, of different kinds, or that cannot be read as described by their headers
+    /// are compared byte by byte.
+    /// Data sections that cannot be read from their source are compared as empty; see [`HduList::diff`] to handle the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut first = ImageHdu::default();
+    /// first.set_pixels(&[3], &[1.0f32, 2.0, 3.0])?;
+    /// let mut second = ImageHdu::default();
+    /// second.set_pixels(&[3], &[1.0f32, 2.001, 3.5])?;
+    ///
+    /// let options = DiffOptions {
+    ///     rtol: 1e-3,
+    ///     ..Default::default()
+    /// };
+    /// let diff = Hdu::from(first).diff(&mut second.into(), &options);
+    /// assert!(diff.header.is_identical());
+    /// let DataDiff::Image(image_diff) = &diff.data else {
+    ///     panic!("expected an image diff");
+    /// };
+    /// assert_eq!(image_diff.num_differences, 1);
+    /// assert_eq!(image_diff.differences[0].index, 2);
+    /// assert_eq!(image_diff.max_absolute, 0.5);
+    /// assert_eq!(diff.to_string(), "1 pixels differ, max absolute difference 0.5, max relative difference 0.14285714285714285\n  pixel 2: 3 != 3.5\n");
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn diff(&mut self, other: &mut Hdu, options: &DiffOptions) -> HduDiff {
+        let header = self.header.diff(&other.header, options);
+        let first_kind = self.kind();
+        let second_kind = other.kind();
+        let kinds = (first_kind != second_kind).then(|| (first_kind.clone(), second_kind));
+
+        let data = match first_kind {
+            _ if kinds.is_some() => None,
+            HduKind::Primary | HduKind::Image => {
+                image_diff(self, other, options).map(DataDiff::Image)
+            }
+            HduKind::AsciiTable | HduKind::BinTable => {
+                table_diff(self, other, options).map(DataDiff::Table)
+            }
+            HduKind::CompressedImage => compressed_image_diff(self, other, options),
+            _ => None,
+        }
+        .unwrap_or_else(|| DataDiff::Raw(raw_diff(self.data_raw(), other.data_raw(), options)));
+
+        HduDiff {
+            kinds,
+            header,
+            data,
+        }
+    }
+}
+
+impl<R: Read> HduList<R> {
+    /// Deserializes the remaining HDUs of both lists and compares the HDUs at each position.
+    /// An error is returned if either list cannot be read, including deferred data sections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut first = HduList::default();
+    /// first.push(primary_hdu::default());
+    /// let mut second = HduList::default();
+    /// second.push(primary_hdu::default());
+    /// assert!(first.diff(&mut second, &DiffOptions::default())?.is_identical());
+    ///
+    /// let mut table = binary_table_hdu::default();
+    /// table.header.set_extname(String::from("EVENTS"))?;
+    /// second.push(table);
+    /// let diff = first.diff(&mut second, &DiffOptions::default())?;
+    /// assert_eq!(diff.num_hdus, Some((1, 2)));
+    /// assert_eq!(diff.to_string(), "HDU counts differ: 1 != 2\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn diff<S: Read>(
+        &mut self,
+        other: &mut HduList<S>,
+        options: &DiffOptions,
+    ) -> Result<HduListDiff, FitsError> {
+        let first_len = self.try_iter_mut()?.len();
+        let second_len = other.try_iter_mut()?.len();
+        let mut diff = HduListDiff {
+            num_hdus: (first_len != second_len).then_some((first_len, second_len)),
+            hdus: Vec::new(),
+        };
+        for (index, (first, second)) in self.hdus.iter_mut().zip(&mut other.hdus).enumerate() {
+            first.try_data_raw()?;
+            second.try_data_raw()?;
+            let hdu_diff = first.diff(second, options);
+            if !hdu_diff.is_identical() {
+                diff.hdus.push((index, hdu_diff));
+            }
+        }
+        Ok(diff)
+    }
+}
+
+fn matches_pattern(name: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim();
+        match pattern.strip_suffix('*') {
+            Some(prefix) => name
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix)),
+            None => name.eq_ignore_ascii_case(pattern),
+        }
+    })
+}
+
+/// Collects the compared cards of the header, numbering the occurrences of each keyword.
+fn header_cards(header: &FitsHeader, options: &DiffOptions) -> Vec<CardText> {
+    let mut occurrences: HashMap<FitsHeaderKeyword, usize> = HashMap::new();
+    let mut cards = Vec::new();
+    for card in &header.cards {
+        if *card.keyword() == END_KEYWORD {
+            break;
+        }
+        let occurrence = occurrences.entry(*card.keyword()).or_default();
+        let card_text = CardText::from_card(card, *occurrence);
+        *occurrence += 1;
+        if (card_text.keyword.is_empty() && card_text.value.is_empty())
+            || matches_pattern(&card_text.keyword, &options.ignore_keywords)
+        {
+            continue;
+        }
+        cards.push(card_text);
+    }
+    cards
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.to_ascii_uppercase().replace('D', "E").parse().ok()
+}
+
+fn values_equal(first: &str, second: &str, options: &DiffOptions) -> bool {
+    match (parse_number(first), parse_number(second)) {
+        (Some(first), Some(second)) => options.is_close(first, second),
+        _ => first == second,
+    }
+}
+
+fn image_diff(first: &mut Hdu, second: &mut Hdu, options: &DiffOptions) -> Option<ImageDiff> {
+    let first_dimensions = first.get_dimensions();
+    let second_dimensions = second.get_dimensions();
+    if first_dimensions != second_dimensions {
+        return Some(ImageDiff {
+            dimensions: Some((first_dimensions, second_dimensions)),
+            ..Default::default()
+        });
+    }
+
+    let mut diff = ImageDiff::default();
+    let first_values = image_hdu::physical_pixels(first).ok()?;
+    let second_values = image_hdu::physical_pixels(second).ok()?;
+    for (index, (first, second)) in first_values.into_iter().zip(second_values).enumerate() {
+        if options.is_close(first, second) {
+            continue;
+        }
+        let absolute = (first - second).abs();
+        diff.max_absolute = diff.max_absolute.max(absolute);
+        if second != 0.0 {
+            diff.max_relative = diff.max_relative.max(absolute / second.abs());
+        }
+        diff.num_differences += 1;
+        if diff.differences.len() < options.max_differences {
+            diff.differences.push(ValueDifference {
+                index,
+                first,
+                second,
+            });
+        }
+    }
+    Some(diff)
+}
+
+/// Compares the decompressed pixels of tile-compressed images, falling back to their binary tables.
+fn compressed_image_diff(
+    first: &mut Hdu,
+    second: &mut Hdu,
+    options: &DiffOptions,
+) -> Option<DataDiff> {
+    #[cfg(feature = "compression")]
+    if let (Ok(mut first), Ok(mut second)) = (first.decompress(), second.decompress()) {
+        return image_diff(&mut first, &mut second, options).map(DataDiff::Image);
+    }
+    table_diff(first, second, options).map(DataDiff::Table)
+}
+
+/// A column of a table, with the value of each row.
+struct Column {
+    name: String,
+    format: String,
+    values: Vec<FieldValue>,
+}
+
+/// Reads the columns of an ASCII or binary table, or None if the data section cannot be read as described.
+fn table_columns(hdu: &mut Hdu) -> Option<(usize, Vec<Column>)> {
+    let is_ascii = hdu.kind() == HduKind::AsciiTable;
+    let tfields = hdu.header.tfields().ok()?;
+    let row_len = hdu.header.naxisn(1).ok()? as usize;
+    let num_rows = hdu.header.naxisn(2).ok()? as usize;
+    let rows_len = row_len.checked_mul(num_rows)?;
+    let theap = hdu
+        .header
+        .theap()
+        .ok()?
+        .map_or(rows_len, |theap| theap as usize);
+
+    let mut fields = Vec::with_capacity(tfields as usize);
+    for n in 1..=tfields {
+        let name = hdu
+            .header
+            .ttype(n)
+            .ok()?
+            .map(|name| name.trim().to_owned())
+            .unwrap_or_else(|| n.to_string());
+        let format = card_string(&hdu.header, numbered_keyword(TFORM_KEYWORD, n))?;
+        let tscal = hdu.header.tscal(n).ok()?;
+        let tzero = hdu.header.tzero(n).ok()?;
+        let layout = if is_ascii {
+            let start = hdu.header.tbcol(n).ok()??.checked_sub(1)? as usize;
+            let width: usize = format.get(1..)?.split('.').next()?.parse().ok()?;
+            FieldLayout::Ascii {
+                start,
+                width,
+                numeric: matches!(format.as_bytes()[0], b'I' | b'F' | b'E' | b'D'),
+            }
+        } else {
+            FieldLayout::Binary(hdu.header.tform(n).ok()?)
+        };
+        fields.push((name, format, layout, tscal, tzero));
+    }
+
+    let data_raw = hdu.data_raw();
+    let rows = data_raw.get(..rows_len)?;
+    let heap = data_raw
+        .get(theap.min(data_raw.len())..)
+        .unwrap_or_default();
+    let mut columns = Vec::with_capacity(fields.len());
+    let mut column_start = 0;
+    for (name, format, layout, tscal, tzero) in fields {
+        let (start, len) = match &layout {
+            FieldLayout::Ascii { start, width, .. } => (*start, *width),
+            FieldLayout::Binary(tform) => (column_start, tform.value()),
+        };
+        column_start = column_start.checked_add(len)?;
+        let end = start.checked_add(len).filter(|end| *end <= row_len)?;
+        let values = rows
+            .chunks_exact(row_len.max(1))
+            .take(num_rows)
+            .map(|row| layout.read(&row[start..end], heap, tscal, tzero))
+            .collect::<Option<Vec<_>>>()?;
+        columns.push(Column {
+            name,
+            format,
+            values,
+        });
+    }
+    Some((num_rows, columns))
+}
+
+/// Gets the unquoted string value of the card with the given keyword.
+fn card_string(header: &FitsHeader, keyword: FitsHeaderKeyword) -> Option<String> {
+    let card = header
+        .cards
+        .iter()
+        .find(|card| *card.keyword() == keyword)?;
+    let value = CardText::from_card(card, 0).value;
+    let (string, _) = split_string(value.strip_prefix('\'')?, 0).ok()?;
+    Some(string.trim().to_owned())
+}
+
+/// The location and type of a field within the rows of a table.
+enum FieldLayout {
+    Ascii {
+        start: usize,
+        width: usize,
+        numeric: bool,
+    },
+    Binary(TForm),
+}
+
+impl FieldLayout {
+    fn read(&self, field: &[u8], heap: &[u8], tscal: f64, tzero: f64) -> Option<FieldValue> {
+        let text = || {
+            String::from_utf8_lossy(field)
+                .trim_end_matches([' ', '\0'])
+                .to_owned()
+        };
+        let numbers = |element_len: usize, read: fn(&[u8]) -> f64| {
+            FieldValue::Numbers(
+                field
+                    .chunks_exact(element_len)
+                    .map(|raw| tzero + tscal * read(raw))
+                    .collect(),
+            )
+        };
+        let tform = match self {
+            FieldLayout::Ascii { numeric, .. } => {
+                let text = text();
+                return Some(match parse_number(text.trim()) {
+                    Some(value) if *numeric => FieldValue::Numbers(vec![tzero + tscal * value]),
+                    _ => FieldValue::Text(text.trim().to_owned()),
+                });
+            }
+            FieldLayout::Binary(tform) => tform,
+        };
+        Some(match tform.t {
+            TFormType::Logical => FieldValue::Text(
+                field
+                    .iter()
+                    .map(|value| match value {
+                        b'T' | b'F' => *value as char,
+                        _ => ' ',
+                    })
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned(),
+            ),
+            TFormType::Bit => FieldValue::Bytes(field.to_vec()),
+            TFormType::UnsignedByte => numbers(1, |raw| f64::from(raw[0])),
+            TFormType::I16 => numbers(2, |raw| f64::from(i16::from_be_slice(raw))),
+            TFormType::I32 => numbers(4, |raw| f64::from(i32::from_be_slice(raw))),
+            TFormType::Character => FieldValue::Text(text()),
+            TFormType::F32 => numbers(4, |raw| f64::from(f32::from_be_slice(raw))),
+            TFormType::F64 => numbers(8, f64::from_be_slice),
+            TFormType::C64 => FieldValue::Numbers(stored_values(field, Bitpix::F32)),
+            TFormType::C128 => FieldValue::Numbers(stored_values(field, Bitpix::F64)),
+            TFormType::ArrayDescriptor | TFormType::LongArrayDescriptor => {
+                // negative counts and offsets are invalid, and are not read
+                let (count, offset) = match tform.t {
+                    TFormType::ArrayDescriptor => (
+                        usize::try_from(i32::from_be_slice(&field[..4])).ok()?,
+                        usize::try_from(i32::from_be_slice(&field[4..8])).ok()?,
+                    ),
+                    _ => (
+                        usize::try_from(i64::from_be_bytes(field[..8].try_into().ok()?)).ok()?,
+                        usize::try_from(i64::from_be_bytes(field[8..16].try_into().ok()?)).ok()?,
+                    ),
+                };
+                let element = TForm {
+                    r: 1,
+                    t: TFormType::try_from(tform.a.chars().next()?).ok()?,
+                    a: String::new(),
+                };
+                let len = match element.t {
+                    TFormType::Bit => count.div_ceil(8),
+                    _ => count.checked_mul(element.value())?,
+                };
+                FieldValue::Bytes(heap.get(offset..offset.checked_add(len)?)?.to_vec())
+            }
+        })
+    }
+}
+
+fn table_diff(first: &mut Hdu, second: &mut Hdu, options: &DiffOptions) -> Option<TableDiff> {
+    let (first_rows, first_columns) = table_columns(first)?;
+    let (second_rows, second_columns) = table_columns(second)?;
+    let mut diff = TableDiff {
+        num_rows: (first_rows != second_rows).then_some((first_rows, second_rows)),
+        ..Default::default()
+    };
+    let find_column = |columns: &[Column], name: &str| {
+        columns
+            .iter()
+            .position(|column| column.name.eq_ignore_ascii_case(name))
+    };
+
+    for column in &first_columns {
+        if matches_pattern(&column.name, &options.ignore_columns) {
+            continue;
+        }
+        let Some(other) = find_column(&second_columns, &column.name).map(|i| &second_columns[i])
+        else {
+            diff.removed_columns.push(column.name.clone());
+            continue;
+        };
+        let mut column_diff = ColumnDiff {
+            name: column.name.clone(),
+            formats: (column.format != other.format)
+                .then(|| (column.format.clone(), other.format.clone())),
+            ..Default::default()
+        };
+        for (index, (first, second)) in column.values.iter().zip(&other.values).enumerate() {
+            if first.is_close(second, options) {
+                continue;
+            }
+            column_diff.num_differences += 1;
+            if column_diff.differences.len() < options.max_differences {
+                column_diff.differences.push(ValueDifference {
+                    index,
+                    first: first.clone(),
+                    second: second.clone(),
+                });
+            }
+        }
+        if column_diff.formats.is_some() || column_diff.num_differences > 0 {
+            diff.columns.push(column_diff);
+        }
+    }
+    diff.added_columns = second_columns
+        .iter()
+        .filter(|column| {
+            !matches_pattern(&column.name, &options.ignore_columns)
+                && find_column(&first_columns, &column.name).is_none()
+        })
+        .map(|column| column.name.clone())
+        .collect();
+    Some(diff)
+}
+
+fn raw_diff(first: &[u8], second: &[u8], options: &DiffOptions) -> RawDiff {
+    let mut diff = RawDiff {
+        lengths: (first.len() != second.len()).then_some((first.len(), second.len())),
+        ..Default::default()
+    };
+    for (index, (first, second)) in first.iter().zip(second).enumerate() {
+        if first == second {
+            continue;
+        }
+        diff.num_differences += 1;
+        if diff.differences.len() < options.max_differences {
+            diff.differences.push(ValueDifference {
+                index,
+                first: *first,
+                second: *second,
+            });
+        }
+    }
+    diff
+}
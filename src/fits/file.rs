@@ -0,0 +1,379 @@
+//! Open and create FITS files, which may be compressed as a whole with gzip, bzip2, or zstd.
+
+use std::fs::File;
+use std::path::Path;
+
+use super::*;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const BZIP2_MAGIC: [u8; 3] = *b"BZh";
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// An enumeration of the formats with which an entire FITS file may be compressed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FileCompression {
+    /// The file is not compressed.
+    #[default]
+    None,
+    /// The file is compressed with gzip, typically with the `.gz` extension.
+    Gzip,
+    /// The file is compressed with bzip2, typically with the `.bz2` extension.
+    Bzip2,
+    /// The file is compressed with zstd, typically with the `.zst` extension.
+    Zstd,
+}
+
+impl FileCompression {
+    /// Detects the compression of a file from its leading bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// assert_eq!(FileCompression::from_magic(&[0x1f, 0x8b, 0x08]), FileCompression::Gzip);
+    /// assert_eq!(FileCompression::from_magic(b"BZh9"), FileCompression::Bzip2);
+    /// assert_eq!(FileCompression::from_magic(&SIMPLE_KEYWORD), FileCompression::None);
+    /// ```
+    pub fn from_magic(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            FileCompression::Gzip
+        } else if bytes.starts_with(&BZIP2_MAGIC) {
+            FileCompression::Bzip2
+        } else if bytes.starts_with(&ZSTD_MAGIC) {
+            FileCompression::Zstd
+        } else {
+            FileCompression::None
+        }
+    }
+
+    /// Determines the compression implied by the extension of the given path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// assert_eq!(FileCompression::from_path("image.fits.gz"), FileCompression::Gzip);
+    /// assert_eq!(FileCompression::from_path("image.fits.zst"), FileCompression::Zstd);
+    /// assert_eq!(FileCompression::from_path("image.fits"), FileCompression::None);
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("gz") | Some("gzip") => FileCompression::Gzip,
+            Some("bz2") => FileCompression::Bzip2,
+            Some("zst") | Some("zstd") => FileCompression::Zstd,
+            _ => FileCompression::None,
+        }
+    }
+
+    fn unsupported(&self) -> std::io::Error {
+        let name = match self {
+            FileCompression::Gzip => "gzip",
+            FileCompression::Bzip2 => "bzip2",
+            _ => "zstd",
+        };
+        std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("{0} compression requires the `{0}` feature", name),
+        )
+    }
+}
+
+impl HduList<FitsFileReader> {
+    /// Opens the FITS file at the given path.
+    /// Files compressed with gzip, bzip2, or zstd are detected from their leading bytes,
+    /// and decompressed as they are read if the corresponding feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let path = std::env::temp_dir().join("astro_rs_doc_open.fits");
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// let mut writer = HduList::create(&path)?;
+    /// hdu_list.write(&mut writer)?;
+    /// writer.into_inner()?.finish()?;
+    ///
+    /// let mut hdu_list = HduList::open(&path)?;
+    /// assert!(hdu_list.is_header_valid()?);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, FitsError> {
+        Ok(HduList::new(BufReader::new(FitsFileReader::open(path)?)))
+    }
+
+    /// Opens the FITS file at the given path, deferring reading data sections until they are accessed as with [`HduList::new_lazy`].
+    /// Compressed files cannot be seeked, so their data sections are read along with the headers as with [`HduList::open`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let path = std::env::temp_dir().join("astro_rs_doc_open_lazy.fits");
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 4u32)?;
+    /// hdu.set_data(&vec![1u8, 2, 3, 4]);
+    /// std::fs::write(&path, hdu.to_bytes())?;
+    ///
+    /// let mut hdu_list = HduList::open_lazy(&path)?;
+    /// let hdu = hdu_list.try_first_mut()?.unwrap();
+    /// assert!(!hdu.is_data_loaded());
+    /// assert_eq!(hdu.get_data::<Vec<u8>>()?[0..4], [1, 2, 3, 4]);
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open_lazy<P: AsRef<Path>>(path: P) -> Result<Self, FitsError> {
+        let reader = FitsFileReader::open(path)?;
+        Ok(match reader.compression() {
+            FileCompression::None => HduList::new_lazy(BufReader::new(reader)),
+            _ => HduList::new(BufReader::new(reader)),
+        })
+    }
+
+    /// Creates a FITS file at the given path, returning a writer to pass to [`HduList::write`].
+    /// The file is compressed according to its extension, as determined by [`FileCompression::from_path`].
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<BufWriter<FitsFileWriter>, std::io::Error> {
+        let compression = FileCompression::from_path(&path);
+        Self::create_with_compression(path, compression)
+    }
+
+    /// Creates a FITS file at the given path that is compressed with the given format,
+    /// returning a writer to pass to [`HduList::write`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let path = std::env::temp_dir().join("astro_rs_doc_create.fits");
+    /// let result = HduList::create_with_compression(&path, FileCompression::Bzip2);
+    /// if cfg!(feature = "bzip2") {
+    ///     assert!(result.is_ok());
+    /// } else {
+    ///     assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+    /// }
+    /// # let _ = std::fs::remove_file(&path);
+    /// ```
+    pub fn create_with_compression<P: AsRef<Path>>(
+        path: P,
+        compression: FileCompression,
+    ) -> Result<BufWriter<FitsFileWriter>, std::io::Error> {
+        let encoder = match compression {
+            FileCompression::None => Encoder::None(File::create(path)?),
+            #[cfg(feature = "gzip")]
+            FileCompression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                File::create(path)?,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "bzip2")]
+            FileCompression::Bzip2 => Encoder::Bzip2(bzip2::write::BzEncoder::new(
+                File::create(path)?,
+                bzip2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            FileCompression::Zstd => {
+                Encoder::Zstd(zstd::stream::write::Encoder::new(File::create(path)?, 0)?)
+            }
+            #[allow(unreachable_patterns)]
+            compression => return Err(compression.unsupported()),
+        };
+        Ok(BufWriter::new(FitsFileWriter {
+            encoder: Some(encoder),
+        }))
+    }
+}
+
+/// A reader for a FITS file, which may be compressed as a whole.
+/// An uncompressed file is read directly from disk and can be seeked, while a compressed file is decompressed as it is read
+/// and seeking it fails with [`std::io::ErrorKind::Unsupported`].
+#[derive(Debug)]
+pub struct FitsFileReader {
+    decoder: Decoder,
+    compression: FileCompression,
+}
+
+impl FitsFileReader {
+    /// Opens the file at the given path, detecting its compression from the leading bytes.
+    fn open<P: AsRef<Path>>(path: P) -> Result<Self, FitsError> {
+        let io_error = |source| FitsError::Io {
+            source,
+            hdu_index: 0,
+            offset: 0,
+        };
+        let mut file = File::open(path).map_err(io_error)?;
+        let mut magic = Vec::with_capacity(ZSTD_MAGIC.len());
+        (&mut file)
+            .take(ZSTD_MAGIC.len() as u64)
+            .read_to_end(&mut magic)
+            .map_err(io_error)?;
+        file.rewind().map_err(io_error)?;
+        let compression = FileCompression::from_magic(&magic);
+        let decoder = match compression {
+            FileCompression::None => Decoder::None(file),
+            #[cfg(feature = "gzip")]
+            FileCompression::Gzip => Decoder::Stream(Box::new(
+                flate2::bufread::MultiGzDecoder::new(BufReader::new(file)),
+            )),
+            #[cfg(feature = "bzip2")]
+            FileCompression::Bzip2 => Decoder::Stream(Box::new(
+                bzip2::bufread::MultiBzDecoder::new(BufReader::new(file)),
+            )),
+            #[cfg(feature = "zstd")]
+            FileCompression::Zstd => Decoder::Stream(Box::new(
+                zstd::stream::read::Decoder::new(file).map_err(io_error)?,
+            )),
+            #[allow(unreachable_patterns)]
+            compression => return Err(io_error(compression.unsupported())),
+        };
+        Ok(FitsFileReader {
+            decoder,
+            compression,
+        })
+    }
+
+    /// Gets the compression of the file, as detected from its leading bytes.
+    pub fn compression(&self) -> FileCompression {
+        self.compression
+    }
+}
+
+impl Read for FitsFileReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match &mut self.decoder {
+            Decoder::None(file) => file.read(buf),
+            #[cfg(any(feature = "gzip", feature = "bzip2", feature = "zstd"))]
+            Decoder::Stream(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Seek for FitsFileReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match &mut self.decoder {
+            Decoder::None(file) => file.seek(pos),
+            #[cfg(any(feature = "gzip", feature = "bzip2", feature = "zstd"))]
+            Decoder::Stream(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "a compressed file cannot be seeked",
+            )),
+        }
+    }
+}
+
+/// The stream by which a file is decompressed.
+enum Decoder {
+    None(File),
+    #[cfg(any(feature = "gzip", feature = "bzip2", feature = "zstd"))]
+    Stream(Box<dyn Read>),
+}
+
+impl Debug for Decoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Decoder::None(_) => "None",
+            #[cfg(any(feature = "gzip", feature = "bzip2", feature = "zstd"))]
+            Decoder::Stream(_) => "Stream",
+        })
+    }
+}
+
+/// A writer for a FITS file, which may be compressed as a whole.
+/// The compressed stream is completed by [`FitsFileWriter::finish`], or when the writer is dropped,
+/// in which case errors are ignored.
+#[derive(Debug)]
+pub struct FitsFileWriter {
+    encoder: Option<Encoder>,
+}
+
+impl FitsFileWriter {
+    /// Completes the compressed stream and flushes it to the file.
+    pub fn finish(mut self) -> Result<(), std::io::Error> {
+        match self.encoder.take() {
+            Some(encoder) => encoder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    fn encoder(&mut self) -> &mut dyn Write {
+        match self.encoder.as_mut() {
+            Some(Encoder::None(file)) => file,
+            #[cfg(feature = "gzip")]
+            Some(Encoder::Gzip(encoder)) => encoder,
+            #[cfg(feature = "bzip2")]
+            Some(Encoder::Bzip2(encoder)) => encoder,
+            #[cfg(feature = "zstd")]
+            Some(Encoder::Zstd(encoder)) => encoder,
+            None => unreachable!("the encoder is only taken when the writer is consumed"),
+        }
+    }
+}
+
+impl Write for FitsFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.encoder().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.encoder().flush()
+    }
+}
+
+impl Drop for FitsFileWriter {
+    fn drop(&mut self) {
+        if let Some(encoder) = self.encoder.take() {
+            let _ = encoder.finish();
+        }
+    }
+}
+
+/// The stream by which a file is compressed.
+enum Encoder {
+    None(File),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<File>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(bzip2::write::BzEncoder<File>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, File>),
+}
+
+impl Encoder {
+    // the match is irrefutable when no compression features are enabled
+    #[allow(clippy::infallible_destructuring_match)]
+    fn finish(self) -> Result<(), std::io::Error> {
+        let mut file = match self {
+            Encoder::None(file) => file,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "bzip2")]
+            Encoder::Bzip2(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(encoder) => encoder.finish()?,
+        };
+        file.flush()
+    }
+}
+
+impl Debug for Encoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Encoder::None(_) => "None",
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(_) => "Gzip",
+            #[cfg(feature = "bzip2")]
+            Encoder::Bzip2(_) => "Bzip2",
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(_) => "Zstd",
+        })
+    }
+}
@@ -0,0 +1,155 @@
+//! Read primary HDUs in the random groups format.
+//! See section 6 of <https://fits.gsfc.nasa.gov/standard40/fits_standard40aa-le.pdf> for the format.
+
+use super::*;
+
+/// The description of a parameter of each group, given by the PTYPEn, PSCALn, and PZEROn cards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupParameter {
+    /// The name of the parameter, or None if PTYPEn is absent.
+    pub name: Option<String>,
+    /// The factor applied to the stored value of the parameter, defaulting to 1.
+    pub scale: f64,
+    /// The offset added to the scaled value of the parameter, defaulting to 0.
+    pub zero: f64,
+}
+
+/// A group of a random groups HDU, with its parameters and array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    /// The physical values of the parameters, with PSCALn and PZEROn applied.
+    pub parameters: Vec<f64>,
+    /// The physical values of the array, with BSCALE and BZERO applied, in the order of the data section.
+    pub array: Vec<f64>,
+}
+
+/// The contents of a random groups HDU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomGroups {
+    /// The descriptions of the parameters of each group.
+    pub parameters: Vec<GroupParameter>,
+    /// The dimensions of the array of each group, given by NAXIS2 to NAXISn.
+    pub shape: Vec<usize>,
+    /// The groups, in the order of the data section.
+    pub groups: Vec<Group>,
+}
+
+impl RandomGroups {
+    /// Gets the value of the parameter with the given name in the group at the given index,
+    /// or None if the group or parameter doesn't exist.
+    /// As is conventional, the values of parameters that share a name are added together,
+    /// which allows a value such as a date to be stored with more precision than a single parameter allows.
+    pub fn parameter(&self, group: usize, name: &str) -> Option<f64> {
+        let group = self.groups.get(group)?;
+        let mut values = self
+            .parameters
+            .iter()
+            .zip(&group.parameters)
+            .filter(|(parameter, _)| parameter.name.as_deref() == Some(name))
+            .map(|(_, value)| *value)
+            .peekable();
+        values.peek()?;
+        Some(values.sum())
+    }
+}
+
+impl Hdu {
+    /// Determines if the HDU is in the random groups format, indicated by GROUPS = T and NAXIS1 = 0.
+    pub fn is_random_groups(&mut self) -> bool {
+        is_random_groups(&mut self.header)
+    }
+
+    /// Reads the descriptions of the PCOUNT parameters of each group of a random groups HDU.
+    /// Returns an error if a PTYPEn, PSCALn, or PZEROn card is invalid.
+    pub fn group_parameters(&mut self) -> Result<Vec<GroupParameter>, FitsHeaderError> {
+        let pcount = self.header.pcount()?;
+        (1..=pcount)
+            .map(|x| {
+                let x = u16::try_from(x).map_err(|_| FitsHeaderError::InvalidType)?;
+                Ok(GroupParameter {
+                    name: self.header.ptype(x)?.map(|name| name.trim().to_owned()),
+                    scale: self.header.pscal(x)?,
+                    zero: self.header.pzero(x)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the groups of a random groups HDU.
+    /// Returns an error if the HDU is not in the random groups format, or if its data section is too short.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = random_groups_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+    /// hdu.header.set_value(*b"NAXIS2  ", 3u32)?;
+    /// hdu.header.set_value(PCOUNT_KEYWORD, 1u32)?;
+    /// hdu.header.set_value(GCOUNT_KEYWORD, 2u32)?;
+    /// hdu.header.set_value(*b"PTYPE1  ", String::from("UU"))?;
+    /// hdu.header.set_value(*b"PSCAL1  ", 0.5)?;
+    /// hdu.set_data(&vec![10i16, 1, 2, 3, 20, 4, 5, 6]);
+    ///
+    /// let groups = hdu.get_random_groups()?;
+    /// assert_eq!(groups.shape, [3]);
+    /// assert_eq!(groups.groups[1].array, [4.0, 5.0, 6.0]);
+    /// assert_eq!(groups.parameter(1, "UU"), Some(10.0));
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn get_random_groups(&mut self) -> Result<RandomGroups, FitsHeaderError> {
+        if !self.is_random_groups() {
+            return Err(FitsHeaderError::MissingKeyword {
+                keyword: GROUPS_KEYWORD.into(),
+            });
+        }
+        let parameters = self.group_parameters()?;
+        let shape = self.get_dimensions().split_off(1);
+        let gcount = self.header.gcount()? as usize;
+        let bitpix = self.header.bitpix()?;
+        let bscale = self.header.bscale()?;
+        let bzero = self.header.bzero()?;
+
+        let array_len = shape.iter().product::<usize>();
+        let group_len = parameters.len() + array_len;
+        let len = group_len * gcount * bitpix.value() / 8;
+        let data_raw = self.data_raw();
+        let raw = data_raw
+            .get(..len)
+            .ok_or_else(|| FitsHeaderError::InvalidLength {
+                expected: len,
+                found: data_raw.len(),
+                intent: String::from("random groups"),
+            })?;
+        let values = stored_values(raw, bitpix);
+
+        let groups = values
+            .chunks(group_len.max(1))
+            .take(gcount)
+            .map(|group| {
+                let (stored_parameters, array) = group.split_at(parameters.len());
+                Group {
+                    parameters: stored_parameters
+                        .iter()
+                        .zip(&parameters)
+                        .map(|(value, parameter)| parameter.zero + parameter.scale * value)
+                        .collect(),
+                    array: array.iter().map(|value| bzero + bscale * value).collect(),
+                }
+            })
+            .collect();
+        Ok(RandomGroups {
+            parameters,
+            shape,
+            groups,
+        })
+    }
+}
+
+/// Determines if the given header describes data in the random groups format, indicated by GROUPS = T and NAXIS1 = 0.
+pub(crate) fn is_random_groups(header: &mut FitsHeader) -> bool {
+    header.groups().is_ok_and(|groups| groups == Some(true))
+        && header.naxisn(1).is_ok_and(|naxis1| naxis1 == 0)
+}
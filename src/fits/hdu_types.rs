@@ -7,6 +7,10 @@ pub const TTYPE_KEYWORD: [u8; 8] = *b"TTYPE   ";
 /// The header keyword indicating the size in bytes of a table column.
 pub const TFORM_KEYWORD: [u8; 8] = *b"TFORM   ";
 
+pub(crate) const DEFAULT_SIMPLE_BYTES: [u8; 80] =
+    *b"SIMPLE  =                    T                                                  ";
+pub(crate) const DEFAULT_IMAGE_XTENSION_BYTES: [u8; 80] =
+    *b"XTENSION= 'IMAGE   '                                                            ";
 pub(crate) const DEFAULT_BITPIX_BYTES: [u8; 80] =
     *b"BITPIX  =                    8                                                  ";
 pub(crate) const DEFAULT_NAXIS_BYTES: [u8; 80] =
@@ -26,9 +30,7 @@ pub mod primary_hdu {
 
     /// Constructs an HDU pre-populated with the required cards to be a Primary HDU.
     pub fn default() -> Hdu {
-        let simple_card = FitsHeaderCard::from(
-            *b"SIMPLE  =                    T                                                  ",
-        );
+        let simple_card = FitsHeaderCard::from(DEFAULT_SIMPLE_BYTES);
         let bitpix_card = FitsHeaderCard::from(DEFAULT_BITPIX_BYTES);
         let naxis_card = FitsHeaderCard::from(DEFAULT_NAXIS_BYTES);
         let end_card = FitsHeaderCard::from(DEFAULT_END_BYTES);
@@ -43,6 +45,46 @@ pub mod primary_hdu {
     }
 }
 
+/// Functions related to a Primary type HDU in the random groups format.
+pub mod random_groups_hdu {
+    use super::*;
+
+    /// Constructs an HDU pre-populated with the required cards to be a Primary HDU in the random groups format.
+    pub fn default() -> Hdu {
+        let simple_card = FitsHeaderCard::from(DEFAULT_SIMPLE_BYTES);
+        let bitpix_card = FitsHeaderCard::from(DEFAULT_BITPIX_BYTES);
+        let naxis_card = FitsHeaderCard::from(
+            *b"NAXIS   =                    1                                                  ",
+        );
+        let naxis1_card = FitsHeaderCard::from(
+            *b"NAXIS1  =                    0                                                  ",
+        );
+        let groups_card = FitsHeaderCard::from(
+            *b"GROUPS  =                    T                                                  ",
+        );
+        let pcount_card = FitsHeaderCard::from(DEFAULT_PCOUNT_BYTES);
+        let gcount_card = FitsHeaderCard::from(DEFAULT_GCOUNT_BYTES);
+        let end_card = FitsHeaderCard::from(DEFAULT_END_BYTES);
+        let header = FitsHeader {
+            cards: vec![
+                simple_card,
+                bitpix_card,
+                naxis_card,
+                naxis1_card,
+                groups_card,
+                pcount_card,
+                gcount_card,
+                end_card,
+            ],
+        };
+
+        Hdu {
+            header,
+            ..Default::default()
+        }
+    }
+}
+
 /// Functions related to an ASCII Table type HDU.
 pub mod ascii_table_hdu {
     use super::*;
@@ -93,9 +135,7 @@ pub mod image_hdu {
 
     /// Constructs an HDU pre-populated with the required cards to be an Image HDU.
     pub fn default() -> Hdu {
-        let xtension_card = FitsHeaderCard::from(
-            *b"XTENSION= 'IMAGE   '                                                            ",
-        );
+        let xtension_card = FitsHeaderCard::from(DEFAULT_IMAGE_XTENSION_BYTES);
         let bitpix_card = FitsHeaderCard::from(DEFAULT_BITPIX_BYTES);
         let naxis_card = FitsHeaderCard::from(DEFAULT_NAXIS_BYTES);
         let pcount_card = FitsHeaderCard::from(DEFAULT_PCOUNT_BYTES);
@@ -117,12 +157,50 @@ pub mod image_hdu {
             ..Default::default()
         }
     }
+
+    /// Reads the physical values of the pixels of an image, with BSCALE and BZERO applied
+    /// and pixels equal to BLANK read as NaN.
+    /// Returns an error if the data section is shorter than described by the header.
+    pub(crate) fn physical_pixels(hdu: &mut Hdu) -> Result<Vec<f64>, FitsHeaderError> {
+        let bitpix = hdu.header.bitpix()?;
+        let bscale = hdu.header.bscale()?;
+        let bzero = hdu.header.bzero()?;
+        let blank = match bitpix {
+            Bitpix::F32 | Bitpix::F64 => None,
+            _ => hdu.header.blank()?,
+        };
+        let dimensions = hdu.get_dimensions();
+        let len = if dimensions.is_empty() {
+            0
+        } else {
+            dimensions.iter().product::<usize>()
+        };
+        let element_len = bitpix.value() / 8;
+        let data_raw = hdu.data_raw();
+        let raw =
+            data_raw
+                .get(..len * element_len)
+                .ok_or_else(|| FitsHeaderError::InvalidLength {
+                    expected: len * element_len,
+                    found: data_raw.len(),
+                    intent: String::from("image pixels"),
+                })?;
+        Ok(stored_values(raw, bitpix)
+            .into_iter()
+            .map(|stored| {
+                if blank.is_some_and(|blank| blank as f64 == stored) {
+                    f64::NAN
+                } else {
+                    bzero + bscale * stored
+                }
+            })
+            .collect())
+    }
 }
 
 /// Functions related to a Binary Table type HDU.
 pub mod binary_table_hdu {
     use super::*;
-    use crate::fits::header_value::TForm;
 
     /// Constructs an HDU pre-populated with the required cards to be a Binary Table HDU.
     pub fn default() -> Hdu {
@@ -165,94 +243,96 @@ pub mod binary_table_hdu {
 
     /// Obtains the data in the column of the given name, or None if a column with the given name cannot be found.
     pub fn column_by_name<T>(hdu: &mut Hdu, name: &str) -> Option<Vec<T>> {
-        let mut n = 1;
-        let mut column_start = 0;
-        let mut tform = None;
-        let mut naxis_keyword = NAXIS_KEYWORD;
-        naxis_keyword[5] = b'2';
-        let num_rows = *hdu
-            .header
-            .get_card(naxis_keyword)
-            .and_then(|card| card.get_value::<u32>().ok())
-            .unwrap_or_default() as usize;
-        naxis_keyword[5] = b'1';
-        let row_len = *hdu
-            .header
-            .get_card(naxis_keyword)
-            .and_then(|card| card.get_value::<u32>().ok())
-            .unwrap_or_default() as usize;
-        let mut tform_keyword = FitsHeaderKeyword::from(TFORM_KEYWORD);
-        let mut ttype_keyword = FitsHeaderKeyword::from(TTYPE_KEYWORD);
-        while n as usize <= num_rows {
-            tform_keyword.append_number(n);
-            if let Some(card) = hdu.header.get_card(tform_keyword) {
-                if let Ok(tform_value) = card.get_value::<TForm>() {
-                    ttype_keyword.append_number(n);
-                    if let Some(value) = hdu
-                        .header
-                        .get_card(ttype_keyword)
-                        .and_then(|card| card.get_value::<String>().ok())
-                    {
-                        if value.eq_ignore_ascii_case(name) {
-                            tform = Some(tform_value);
-                            break;
-                        }
-                    }
-
-                    column_start += tform_value.value();
-                }
-                n += 1;
-            } else {
-                break;
-            }
-        }
-        if let Some(tform) = tform {
-            return Some(*tform.create_column(hdu.data_raw(), column_start, row_len, num_rows));
-        }
-        None
+        let tfields = hdu.header.tfields().unwrap_or_default();
+        let index = (1..=tfields).find(|n| {
+            hdu.header
+                .ttype(*n)
+                .ok()
+                .flatten()
+                .is_some_and(|ttype| ttype.eq_ignore_ascii_case(name))
+        })?;
+        column_by_index(hdu, index)
     }
 
     /// Obtains the data in the column of the given index, or None if a column with the given index cannot be found.
     /// Note that column indeces start at 1.
     pub fn column_by_index<T>(hdu: &mut Hdu, index: u16) -> Option<Vec<T>> {
-        let mut n = 1;
-        let mut column_start = 0;
-        let mut tform = None;
-        let mut naxis_keyword = NAXIS_KEYWORD;
-        naxis_keyword[5] = b'2';
-        let num_rows = *hdu
-            .header
-            .get_card(naxis_keyword)
-            .and_then(|card| card.get_value::<u32>().ok())
-            .unwrap_or_default() as usize;
-        if index as usize > num_rows {
+        if index == 0 || index > hdu.header.tfields().unwrap_or_default() {
             return None;
         }
-        naxis_keyword[5] = b'1';
-        let row_len = *hdu
-            .header
-            .get_card(naxis_keyword)
-            .and_then(|card| card.get_value::<u32>().ok())
-            .unwrap_or_default() as usize;
-        let mut tform_keyword = FitsHeaderKeyword::from(TFORM_KEYWORD);
-        while n <= index {
-            tform_keyword.append_number(n);
-            if let Some(card) = hdu.header.get_card(tform_keyword) {
-                if let Ok(value) = card.get_value::<TForm>() {
-                    if n == index {
-                        tform = Some(value);
-                    } else {
-                        column_start += value.value();
-                    }
-                }
-                n += 1;
-            } else {
+        let row_len = hdu.header.naxisn(1).unwrap_or_default() as usize;
+        let num_rows = hdu.header.naxisn(2).unwrap_or_default() as usize;
+        let mut column_start = 0;
+        for n in 1..index {
+            column_start += hdu.header.tform(n).ok()?.value();
+        }
+        let tform = hdu.header.tform(index).ok()?;
+        Some(*tform.create_column(hdu.data_raw(), column_start, row_len, num_rows))
+    }
+
+    /// Reads the physical values of the numeric column with the given name, with TSCALn and TZEROn applied,
+    /// returning the number of the column along with the values, or None if the column cannot be found
+    /// or does not contain numbers.
+    #[cfg(any(feature = "time", feature = "units"))]
+    pub(crate) fn physical_column(
+        hdu: &mut Hdu,
+        name: &str,
+    ) -> Result<Option<(u16, Vec<f64>)>, FitsHeaderError> {
+        let tfields = hdu.header.tfields()?;
+        let mut index = None;
+        for n in 1..=tfields {
+            if hdu
+                .header
+                .ttype(n)?
+                .is_some_and(|ttype| ttype.eq_ignore_ascii_case(name))
+            {
+                index = Some(n);
                 break;
             }
         }
-        if let Some(tform) = tform {
-            return Some(*tform.create_column(hdu.data_raw(), column_start, row_len, num_rows));
+        let Some(index) = index else {
+            return Ok(None);
+        };
+        let mut column_start = 0;
+        for n in 1..index {
+            column_start += hdu.header.tform(n)?.value();
+        }
+        let tform = hdu.header.tform(index)?;
+        let element_len = match tform.t {
+            TFormType::UnsignedByte => 1,
+            TFormType::I16 => 2,
+            TFormType::I32 | TFormType::F32 => 4,
+            TFormType::F64 => 8,
+            _ => return Ok(None),
+        };
+        let tscal = hdu.header.tscal(index)?;
+        let tzero = hdu.header.tzero(index)?;
+        let row_len = hdu.header.naxisn(1)? as usize;
+        let num_rows = hdu.header.naxisn(2)? as usize;
+
+        let data_raw = hdu.data_raw();
+        let len = row_len * num_rows;
+        if data_raw.len() < len || column_start + tform.value() > row_len {
+            return Err(FitsHeaderError::InvalidLength {
+                expected: len,
+                found: data_raw.len(),
+                intent: format!("column {}", name),
+            });
+        }
+        let mut result = Vec::with_capacity(num_rows * tform.r);
+        for row in data_raw[..len].chunks_exact(row_len.max(1)) {
+            let field = &row[column_start..column_start + tform.value()];
+            for raw in field.chunks_exact(element_len) {
+                let stored = match tform.t {
+                    TFormType::UnsignedByte => f64::from(raw[0]),
+                    TFormType::I16 => f64::from(i16::from_be_slice(raw)),
+                    TFormType::I32 => f64::from(i32::from_be_slice(raw)),
+                    TFormType::F32 => f64::from(f32::from_be_slice(raw)),
+                    _ => f64::from_be_slice(raw),
+                };
+                result.push(tzero + tscal * stored);
+            }
         }
-        None
+        Ok(Some((index, result)))
     }
 }
@@ -17,13 +17,19 @@ pub const NAXIS_KEYWORD: [u8; 8] = *b"NAXIS   ";
 pub const END_KEYWORD: [u8; 8] = *b"END     ";
 /// The expected keyword for the first header card of each HDU following the primary.
 pub const XTENSION_KEYWORD: [u8; 8] = *b"XTENSION";
+/// The header keyword indicating the number of bytes following the main data table of an extension.
+pub const PCOUNT_KEYWORD: [u8; 8] = *b"PCOUNT  ";
+/// The header keyword indicating the number of groups in the data section of an extension.
+pub const GCOUNT_KEYWORD: [u8; 8] = *b"GCOUNT  ";
+/// The header keyword indicating the primary HDU contains data in the random groups format.
+pub const GROUPS_KEYWORD: [u8; 8] = *b"GROUPS  ";
 
 pub(crate) const FITS_RECORD_LEN: usize = 2880;
 pub(crate) const HEADER_CARD_LEN: usize = 80;
 pub(crate) const HEADER_KEYWORD_LEN: usize = 8;
 
 /// An enumeration of errors that could occur when processing a FITS header element.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum FitsHeaderError {
     /// Indicates an unexpected length of bytes was encountered during processing.
     #[error("unexpected byte count - expected {expected} bytes for {intent}, found {found}")]
@@ -46,6 +52,15 @@ pub enum FitsHeaderError {
     /// Indicates the expected type does not match the cached value type.
     #[error("expected type does not match cached value type")]
     InvalidType,
+    /// Indicates a required header card could not be found.
+    #[error("missing required keyword {keyword}")]
+    MissingKeyword {
+        /// The keyword of the missing card.
+        keyword: FitsHeaderKeyword,
+    },
+    /// Indicates the length of the data section described by the header cannot be represented.
+    #[error("data section length described by the header overflows")]
+    DataLengthOverflow,
 }
 
 /// The header portion of an HDU.
@@ -117,6 +132,18 @@ impl FitsHeader {
     /// bytes.resize(2880, b' ');
     ///
     /// assert_eq!(hdu.header.to_bytes(), bytes);
+    ///
+    /// // headers with more than 36 cards are padded to a whole number of records
+    /// let mut header = primary_hdu::default().header;
+    /// let mut comment = [b' '; 80];
+    /// comment[..7].copy_from_slice(b"COMMENT");
+    /// for _ in 0..40 {
+    ///     header.cards.insert(3, FitsHeaderCard::from(comment));
+    /// }
+    /// let bytes = header.to_bytes();
+    /// assert_eq!(bytes.len(), 5760);
+    /// assert_eq!(bytes[43 * 80..43 * 80 + 8], END_KEYWORD);
+    /// assert!(bytes[44 * 80..].iter().all(|b| *b == b' '));
     /// ```
     pub fn to_bytes(self) -> Vec<u8> {
         let mut result = Vec::with_capacity(FITS_RECORD_LEN);
@@ -125,9 +152,11 @@ impl FitsHeader {
             let card_raw: [u8; HEADER_CARD_LEN] = card.into();
             result.extend_from_slice(&card_raw);
         }
-        if filled_cards < 36 {
-            result.resize(FITS_RECORD_LEN, b' ');
-        }
+        // the header is padded with blank cards to fill its last record
+        let records = filled_cards
+            .div_ceil(FITS_RECORD_LEN / HEADER_CARD_LEN)
+            .max(1);
+        result.resize(records * FITS_RECORD_LEN, b' ');
         result
     }
 
@@ -189,16 +218,11 @@ impl FitsHeader {
         if let Some(card) = self.get_card(fits_keyword) {
             *card = new_card;
         } else {
-            let index = if self
+            let index = self
                 .cards
-                .last()
-                .map(|card| card.keyword == END_KEYWORD)
-                .unwrap_or_default()
-            {
-                self.cards.len() - 1
-            } else {
-                self.cards.len()
-            };
+                .iter()
+                .rposition(|card| card.keyword == END_KEYWORD)
+                .unwrap_or(self.cards.len());
             self.cards.insert(index, new_card);
         }
         Ok(())
@@ -246,21 +270,51 @@ impl FitsHeader {
                 keyword: fits_keyword,
                 value: FitsHeaderValueContainer::new(value, None)?,
             };
-            let index = if self
+            let index = self
                 .cards
-                .last()
-                .map(|card| card.keyword == END_KEYWORD)
-                .unwrap_or_default()
-            {
-                self.cards.len() - 1
-            } else {
-                self.cards.len()
-            };
+                .iter()
+                .rposition(|card| card.keyword == END_KEYWORD)
+                .unwrap_or(self.cards.len());
             self.cards.insert(index, new_card);
         }
         Ok(())
     }
 
+    /// Inserts a card with the given keyword and value at the given index, without a comment.
+    pub(crate) fn insert_value<K: Into<FitsHeaderKeyword>, T: FitsHeaderValue + 'static>(
+        &mut self,
+        index: usize,
+        keyword: K,
+        value: T,
+    ) -> Result<(), FitsHeaderError> {
+        let new_card = FitsHeaderCard {
+            keyword: keyword.into(),
+            value: FitsHeaderValueContainer::new(value, None)?,
+        };
+        self.cards.insert(index, new_card);
+        Ok(())
+    }
+
+    /// Removes the first card with the given keyword, returning it, or None if no such card exists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut header = primary_hdu::default().header;
+    /// assert!(header.remove_card(NAXIS_KEYWORD).is_some());
+    /// assert!(header.get_card(NAXIS_KEYWORD).is_none());
+    /// assert!(header.remove_card(NAXIS_KEYWORD).is_none());
+    /// ```
+    pub fn remove_card<K: PartialEq<FitsHeaderKeyword>>(
+        &mut self,
+        keyword: K,
+    ) -> Option<FitsHeaderCard> {
+        let index = self.cards.iter().position(|card| keyword == card.keyword)?;
+        Some(self.cards.remove(index))
+    }
+
     /// Sets the comment of the card with the given keyword.
     /// If a card already exists, the comment is overwritten, and the value is retained.
     /// If a card does not exist, this function has no effect.
@@ -342,6 +396,15 @@ impl FitsHeaderCard {
     /// // value is now cached, deserialization is not attempted, but types differ
     /// assert!(card.get_value::<u32>().is_err());
     /// assert!(card.get_value::<bool>().map(|value| *value).unwrap_or_default());
+    ///
+    /// // a value that has been read but not set is serialized with its original bytes
+    /// let mut card = FitsHeaderCard::from(
+    ///     *b"XTENSION= 'IMAGE   '           / extension type                                 ",
+    /// );
+    /// assert_eq!(*card.get_value::<String>()?, "IMAGE");
+    /// let card_raw: [u8; 80] = card.into();
+    /// assert_eq!(&card_raw[..47], b"XTENSION= 'IMAGE   '           / extension type");
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
     /// ```
     pub fn get_value<T: FitsHeaderValue + 'static>(&mut self) -> Result<Rc<T>, FitsHeaderError> {
         self.value.get_value()
@@ -452,6 +515,12 @@ impl FitsHeaderKeyword {
     }
 }
 
+impl std::fmt::Display for FitsHeaderKeyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(String::from_utf8_lossy(&self.raw).trim_end())
+    }
+}
+
 impl From<[u8; 8]> for FitsHeaderKeyword {
     fn from(raw: [u8; 8]) -> Self {
         FitsHeaderKeyword { raw }
@@ -515,6 +584,7 @@ pub struct FitsHeaderValueContainer {
     raw: Vec<u8>,
     value: Option<Rc<dyn FitsHeaderValue>>,
     comment: Option<Rc<String>>,
+    original: Option<[u8; 72]>,
 }
 
 impl FitsHeaderValueContainer {
@@ -528,6 +598,7 @@ impl FitsHeaderValueContainer {
             raw: Vec::new(),
             value: Some(Rc::new(value)),
             comment: comment.map(Rc::new),
+            original: None,
         })
     }
 
@@ -601,6 +672,7 @@ impl FitsHeaderValueContainer {
         };
         Self::check_comment_length(value.to_bytes(), Some(&comment))?;
         self.value = Some(Rc::new(value));
+        self.original = None;
         Ok(())
     }
 
@@ -675,6 +747,7 @@ impl FitsHeaderValueContainer {
         };
         Self::check_comment_length(value_raw, comment.as_ref())?;
         self.comment = comment.map(Rc::new);
+        self.original = None;
         Ok(())
     }
 
@@ -721,12 +794,17 @@ impl From<[u8; 72]> for FitsHeaderValueContainer {
             raw: raw.to_vec(),
             value: None,
             comment: None,
+            original: Some(raw),
         }
     }
 }
 
 impl From<FitsHeaderValueContainer> for [u8; 72] {
     fn from(container: FitsHeaderValueContainer) -> Self {
+        // a value and comment that have not been set keep their original bytes, even if they have been deserialized
+        if let Some(original) = container.original {
+            return original;
+        }
         match (container.value, container.comment) {
             (Some(value), Some(comment)) => {
                 let mut result = [b' '; 72];
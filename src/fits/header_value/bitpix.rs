@@ -13,6 +13,8 @@ pub enum Bitpix {
     I16,
     /// Indicates each data element is a signed 32 bit integer value.
     I32,
+    /// Indicates each data element is a signed 64 bit integer value.
+    I64,
     /// Indicates each data element is a signed 32 bit float value.
     F32,
     /// Indicates each data element is a signed 64 bit float value.
@@ -26,6 +28,7 @@ impl Bitpix {
             Bitpix::U8 => 8,
             Bitpix::I16 => 16,
             Bitpix::I32 => 32,
+            Bitpix::I64 => 64,
             Bitpix::F32 => 32,
             Bitpix::F64 => 64,
         }
@@ -38,6 +41,7 @@ impl FitsHeaderValue for Bitpix {
             b"8" => Ok(Bitpix::U8),
             b"16" => Ok(Bitpix::I16),
             b"32" => Ok(Bitpix::I32),
+            b"64" => Ok(Bitpix::I64),
             b"-32" => Ok(Bitpix::F32),
             b"-64" => Ok(Bitpix::F64),
             _ => Err(FitsHeaderError::DeserializationError {
@@ -65,6 +69,13 @@ impl FitsHeaderValue for Bitpix {
                     result[start + i] = *b;
                 }
             }
+            Bitpix::I64 => {
+                let value_raw = b"64";
+                let start = 20 - value_raw.len();
+                for (i, b) in value_raw.iter().enumerate() {
+                    result[start + i] = *b;
+                }
+            }
             Bitpix::F32 => {
                 let value_raw = b"-32";
                 let start = 20 - value_raw.len();
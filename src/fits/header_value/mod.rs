@@ -233,6 +233,158 @@ impl FitsHeaderValue for u32 {
     }
 }
 
+/// ```
+/// use astro_rs::fits::FitsHeaderError;
+/// use astro_rs::fits::FitsHeaderValue;
+///
+/// // successful deserialization
+/// let max_value: i32 = FitsHeaderValue::from_bytes(b"2147483647".to_vec())?;
+/// assert_eq!(max_value, 2147483647);
+/// let min_value: i32 = FitsHeaderValue::from_bytes(b"-2147483648".to_vec())?;
+/// assert_eq!(min_value, -2147483648);
+///
+/// // failed deserialization
+/// let result: Result<i32, FitsHeaderError> = FitsHeaderValue::from_bytes(b"2147483648".to_vec());
+/// assert!(result.is_err());
+/// let result: Result<i32, FitsHeaderError> = FitsHeaderValue::from_bytes(b"Not a number".to_vec());
+/// assert!(result.is_err());
+///
+/// // serialization
+/// assert_eq!(max_value.to_bytes(), *b"          2147483647                                                  ");
+/// assert_eq!(min_value.to_bytes(), *b"         -2147483648                                                  ");
+/// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+/// ```
+impl FitsHeaderValue for i32 {
+    fn from_bytes(raw: Vec<u8>) -> Result<Self, FitsHeaderError> {
+        let value_string =
+            String::from_utf8(raw).map_err(|er| FitsHeaderError::DeserializationError {
+                found: er.into_bytes(),
+                intent: String::from("header card i32 value"),
+            })?;
+        value_string
+            .parse()
+            .map_err(|_| FitsHeaderError::DeserializationError {
+                found: value_string.into_bytes(),
+                intent: String::from("header card i32 value"),
+            })
+    }
+
+    fn to_bytes(&self) -> [u8; 70] {
+        let mut result = [b' '; 70];
+        let value_raw = self.to_string().into_bytes();
+        let start = 20 - value_raw.len();
+        for (i, b) in value_raw.iter().enumerate() {
+            result[start + i] = *b;
+        }
+        result
+    }
+}
+
+/// ```
+/// use astro_rs::fits::FitsHeaderError;
+/// use astro_rs::fits::FitsHeaderValue;
+///
+/// // successful deserialization
+/// let max_value: i64 = FitsHeaderValue::from_bytes(b"9223372036854775807".to_vec())?;
+/// assert_eq!(max_value, 9223372036854775807);
+/// let min_value: i64 = FitsHeaderValue::from_bytes(b"-9223372036854775808".to_vec())?;
+/// assert_eq!(min_value, -9223372036854775808);
+///
+/// // failed deserialization
+/// let result: Result<i64, FitsHeaderError> = FitsHeaderValue::from_bytes(b"9223372036854775808".to_vec());
+/// assert!(result.is_err());
+/// let result: Result<i64, FitsHeaderError> = FitsHeaderValue::from_bytes(b"Not a number".to_vec());
+/// assert!(result.is_err());
+///
+/// // serialization
+/// assert_eq!(max_value.to_bytes(), *b" 9223372036854775807                                                  ");
+/// assert_eq!(min_value.to_bytes(), *b"-9223372036854775808                                                  ");
+/// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+/// ```
+impl FitsHeaderValue for i64 {
+    fn from_bytes(raw: Vec<u8>) -> Result<Self, FitsHeaderError> {
+        let value_string =
+            String::from_utf8(raw).map_err(|er| FitsHeaderError::DeserializationError {
+                found: er.into_bytes(),
+                intent: String::from("header card i64 value"),
+            })?;
+        value_string
+            .parse()
+            .map_err(|_| FitsHeaderError::DeserializationError {
+                found: value_string.into_bytes(),
+                intent: String::from("header card i64 value"),
+            })
+    }
+
+    fn to_bytes(&self) -> [u8; 70] {
+        let mut result = [b' '; 70];
+        let value_raw = self.to_string().into_bytes();
+        let start = 20 - value_raw.len();
+        for (i, b) in value_raw.iter().enumerate() {
+            result[start + i] = *b;
+        }
+        result
+    }
+}
+
+/// ```
+/// use astro_rs::fits::FitsHeaderError;
+/// use astro_rs::fits::FitsHeaderValue;
+///
+/// // successful deserialization
+/// let value: f64 = FitsHeaderValue::from_bytes(b"-1.5".to_vec())?;
+/// assert_eq!(value, -1.5);
+/// let integer_value: f64 = FitsHeaderValue::from_bytes(b"30".to_vec())?;
+/// assert_eq!(integer_value, 30.0);
+/// let exponent_value: f64 = FitsHeaderValue::from_bytes(b"2.5D-07".to_vec())?;
+/// assert_eq!(exponent_value, 2.5e-7);
+///
+/// // failed deserialization
+/// let result: Result<f64, FitsHeaderError> = FitsHeaderValue::from_bytes(b"Not a number".to_vec());
+/// assert!(result.is_err());
+///
+/// // serialization
+/// assert_eq!(value.to_bytes(), *b"                -1.5                                                  ");
+/// assert_eq!(integer_value.to_bytes(), *b"                30.0                                                  ");
+/// assert_eq!(exponent_value.to_bytes(), *b"              2.5E-7                                                  ");
+/// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+/// ```
+impl FitsHeaderValue for f64 {
+    fn from_bytes(raw: Vec<u8>) -> Result<Self, FitsHeaderError> {
+        let value_string =
+            String::from_utf8(raw).map_err(|er| FitsHeaderError::DeserializationError {
+                found: er.into_bytes(),
+                intent: String::from("header card f64 value"),
+            })?;
+        value_string.replace(['D', 'd'], "E").parse().map_err(|_| {
+            FitsHeaderError::DeserializationError {
+                found: value_string.into_bytes(),
+                intent: String::from("header card f64 value"),
+            }
+        })
+    }
+
+    fn to_bytes(&self) -> [u8; 70] {
+        let mut result = [b' '; 70];
+        let abs = self.abs();
+        let mut value_string = if abs == 0.0 || (1e-4..1e15).contains(&abs) {
+            format!("{:?}", self)
+        } else {
+            format!("{:E}", self)
+        };
+        // real values always include a decimal point or an exponent
+        if !value_string.contains(['.', 'E']) {
+            value_string.push_str(".0");
+        }
+        let value_raw = value_string.into_bytes();
+        let start = 20usize.saturating_sub(value_raw.len());
+        for (i, b) in value_raw.iter().enumerate() {
+            result[start + i] = *b;
+        }
+        result
+    }
+}
+
 /// ```
 /// use astro_rs::fits::FitsHeaderError;
 /// use astro_rs::fits::FitsHeaderValue;
@@ -20,6 +20,7 @@ pub enum TFormType {
     C64,
     C128,
     ArrayDescriptor,
+    LongArrayDescriptor,
 }
 
 impl TryFrom<char> for TFormType {
@@ -38,6 +39,7 @@ impl TryFrom<char> for TFormType {
             'C' => Ok(TFormType::C64),
             'M' => Ok(TFormType::C128),
             'P' => Ok(TFormType::ArrayDescriptor),
+            'Q' => Ok(TFormType::LongArrayDescriptor),
             _ => Err(FitsHeaderError::DeserializationError {
                 found: vec![value as u8],
                 intent: String::from("header card TFORM type value"),
@@ -62,7 +64,7 @@ impl TForm {
     pub fn value(&self) -> usize {
         let type_bytes = match self.t {
             TFormType::Logical => 1,
-            TFormType::Bit => todo!(),
+            TFormType::Bit => return self.r.div_ceil(8),
             TFormType::UnsignedByte => 1,
             TFormType::I16 => 2,
             TFormType::I32 => 4,
@@ -72,11 +74,29 @@ impl TForm {
             TFormType::C64 => 8,
             TFormType::C128 => 16,
             TFormType::ArrayDescriptor => 8,
+            TFormType::LongArrayDescriptor => 16,
         };
         self.r * type_bytes
     }
 
     /// Creates a column of values from the given data.
+    /// Array descriptor columns contain the raw descriptors, as pairs of the number of elements
+    /// and the offset into the heap, which are `i32` values for P and `i64` values for Q.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let tform: TForm = FitsHeaderValue::from_bytes(b"'1QB(80) '".to_vec())?;
+    /// let mut data = Vec::new();
+    /// for value in [80i64, 0, 40, 80] {
+    ///     data.extend_from_slice(&value.to_be_bytes());
+    /// }
+    /// let column = tform.create_column::<i64>(&data, 0, 16, 2);
+    /// assert_eq!(*column, vec![80, 0, 40, 80]);
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
     pub fn create_column<T>(
         &self,
         data: &[u8],
@@ -171,7 +191,28 @@ impl TForm {
                 }
                 TFormType::C64 => todo!(),
                 TFormType::C128 => todo!(),
-                TFormType::ArrayDescriptor => todo!(),
+                TFormType::ArrayDescriptor => {
+                    tform_macros::deserialize_column!(
+                        i32,
+                        num_rows,
+                        row_len,
+                        column_start,
+                        column_len,
+                        self.r * 2,
+                        data,
+                    )
+                }
+                TFormType::LongArrayDescriptor => {
+                    tform_macros::deserialize_column!(
+                        i64,
+                        num_rows,
+                        row_len,
+                        column_start,
+                        column_len,
+                        self.r * 2,
+                        data,
+                    )
+                }
             }
         }
     }
@@ -231,6 +272,11 @@ mod tform_macros {
 /// assert_eq!(comment_char_value.value(), 1);
 /// let short_complex_value: TForm = FitsHeaderValue::from_bytes(b"'M       '".to_vec())?;
 /// assert_eq!(short_complex_value.value(), 16);
+/// let bit_value: TForm = FitsHeaderValue::from_bytes(b"'12X     '".to_vec())?;
+/// assert_eq!(bit_value.value(), 2);
+/// let descriptor_value: TForm = FitsHeaderValue::from_bytes(b"'1QB(80) '".to_vec())?;
+/// assert_eq!(descriptor_value.value(), 16);
+/// assert_eq!(descriptor_value.a, "B(80)");
 ///
 /// // failed deserialization
 /// let result: Result<TForm, FitsHeaderError> = FitsHeaderValue::from_bytes(b"U".to_vec());
@@ -299,6 +345,7 @@ impl FitsHeaderValue for TForm {
             TFormType::C64 => result[i] = b'C',
             TFormType::C128 => result[i] = b'M',
             TFormType::ArrayDescriptor => result[i] = b'P',
+            TFormType::LongArrayDescriptor => result[i] = b'Q',
         }
         i += 1;
         for b in self.a.bytes() {
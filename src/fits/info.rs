@@ -0,0 +1,268 @@
+//! Summarize the HDUs of a FITS file and index its extensions by name and version.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use super::*;
+
+/// A summary of a single HDU, as listed in a [`TableOfContents`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HduInfo {
+    /// The position of the HDU within the list.
+    pub index: usize,
+    /// The value of the EXTNAME keyword, or an empty string if it is absent.
+    pub name: String,
+    /// The value of the EXTVER keyword, or 1 if it is absent.
+    pub version: u32,
+    /// The kind of the HDU.
+    pub kind: HduKind,
+    /// The number of header cards, excluding the END card.
+    pub num_cards: usize,
+    /// The lengths of the axes of the data array, given by NAXISn.
+    pub dimensions: Vec<usize>,
+    /// The type of each data element, or None if BITPIX is missing or invalid.
+    pub bitpix: Option<Bitpix>,
+    /// The number of columns of a table, given by TFIELDS, or None if the HDU is not a table.
+    pub num_fields: Option<u16>,
+}
+
+impl HduInfo {
+    fn new(index: usize, header: &FitsHeader) -> Self {
+        let kind = header_kind(header);
+        let num_fields = match kind {
+            HduKind::AsciiTable | HduKind::BinTable | HduKind::CompressedImage => {
+                header.peek_value(TFIELDS_KEYWORD).ok().flatten()
+            }
+            _ => None,
+        };
+        let (name, version) = extension_key(header);
+        let naxis = header
+            .peek_value::<u16>(NAXIS_KEYWORD)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        HduInfo {
+            index,
+            name,
+            version,
+            kind,
+            num_cards: header
+                .cards
+                .iter()
+                .filter(|card| *card.keyword() != END_KEYWORD)
+                .count(),
+            dimensions: (1..=naxis)
+                .map(|n| {
+                    header
+                        .peek_value::<u32>(numbered_keyword(NAXIS_KEYWORD, n))
+                        .ok()
+                        .flatten()
+                        .unwrap_or_default() as usize
+                })
+                .collect(),
+            bitpix: header.peek_value(BITPIX_KEYWORD).ok().flatten(),
+            num_fields,
+        }
+    }
+}
+
+impl Display for HduInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = if self.name.is_empty() && self.index == 0 {
+            "PRIMARY"
+        } else {
+            &self.name
+        };
+        let (dimensions, format) = match self.num_fields {
+            Some(num_fields) => (
+                format!(
+                    "{}R x {}C",
+                    self.dimensions.get(1).copied().unwrap_or_default(),
+                    num_fields
+                ),
+                String::new(),
+            ),
+            None => (
+                format!(
+                    "({})",
+                    self.dimensions
+                        .iter()
+                        .map(|len| len.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                self.bitpix.map(bitpix_name).unwrap_or_default().to_owned(),
+            ),
+        };
+        write!(
+            f,
+            "{:<5}{:<12}{:>4}  {:<18}{:>6}  {:<16}{}",
+            self.index,
+            name,
+            self.version,
+            self.kind.to_string(),
+            self.num_cards,
+            dimensions,
+            format
+        )
+    }
+}
+
+/// A summary of each HDU in an [`HduList`], similar to `astropy.io.fits.info`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableOfContents {
+    /// The summaries of the HDUs, in the order in which they appear in the list.
+    pub entries: Vec<HduInfo>,
+}
+
+impl Display for TableOfContents {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<5}{:<12}{:>4}  {:<18}{:>6}  {:<16}Format",
+            "No.", "Name", "Ver", "Type", "Cards", "Dimensions"
+        )?;
+        for entry in &self.entries {
+            write!(f, "\n{}", entry)?;
+        }
+        Ok(())
+    }
+}
+
+/// The positions of the extensions of an [`HduList`], by EXTNAME and EXTVER.
+/// HDUs are indexed as they are read or added to the end of the list, and the index is rebuilt when the list is rearranged.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExtensionIndex {
+    versions: HashMap<(String, u32), usize>,
+    names: HashMap<String, usize>,
+}
+
+impl ExtensionIndex {
+    /// Indexes the HDU with the given header at the given position, unless an earlier HDU has the same name and version.
+    pub(crate) fn insert(&mut self, position: usize, header: &FitsHeader) {
+        let (name, version) = extension_key(header);
+        if name.is_empty() {
+            return;
+        }
+        let name = name.to_ascii_uppercase();
+        self.names.entry(name.clone()).or_insert(position);
+        self.versions.entry((name, version)).or_insert(position);
+    }
+
+    /// Indexes the given HDUs from scratch.
+    pub(crate) fn rebuild(&mut self, hdus: &[Hdu]) {
+        self.versions.clear();
+        self.names.clear();
+        for (position, hdu) in hdus.iter().enumerate() {
+            self.insert(position, &hdu.header);
+        }
+    }
+
+    /// Gets the position of the first HDU with the given name and, if given, version.
+    pub(crate) fn position(&self, name: &str, ver: Option<u32>) -> Option<usize> {
+        let name = name.trim().to_ascii_uppercase();
+        match ver {
+            Some(ver) => self.versions.get(&(name, ver)).copied(),
+            None => self.names.get(&name).copied(),
+        }
+    }
+}
+
+impl<R: Read> HduList<R> {
+    /// Summarizes each HDU that has been read from the headers alone, without reading or modifying the HDUs.
+    /// The HDUs that have not yet been read are not listed; [`HduList::try_iter_mut`] reads them,
+    /// which for a list constructed with [`HduList::new_lazy`] reads only their headers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// let mut img_hdu = image_hdu::default();
+    /// img_hdu.header.set_extname(String::from("SCI"))?;
+    /// img_hdu.header.set_bitpix(Bitpix::F32)?;
+    /// img_hdu.header.set_dimensions(&[10, 20])?;
+    /// hdu_list.push(img_hdu);
+    ///
+    /// let toc = hdu_list.info();
+    /// assert_eq!(toc.entries.len(), 2);
+    /// assert_eq!(toc.entries[1].name, "SCI");
+    /// assert_eq!(toc.entries[1].version, 1);
+    /// assert_eq!(toc.entries[1].kind, HduKind::Image);
+    /// assert_eq!(toc.entries[1].dimensions, vec![10, 20]);
+    /// assert!(toc.to_string().lines().nth(2).unwrap().contains("(10, 20)"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn info(&self) -> TableOfContents {
+        let entries = self
+            .hdus
+            .iter()
+            .enumerate()
+            .map(|(index, hdu)| HduInfo::new(index, &hdu.header))
+            .collect();
+        TableOfContents { entries }
+    }
+
+    /// Maps the `EXTNAME` and `EXTVER` of each extension that has been read to its position.
+    /// Names are trimmed and converted to upper case, and an HDU without an `EXTVER` card has version 1.
+    /// HDUs without an `EXTNAME` card are not indexed; if several HDUs share a name and version,
+    /// the first one is indexed.
+    ///
+    /// The index is kept as HDUs are read, and is used by [`HduList::get_by_name`] and [`HduList::get_by_name_ver`].
+    /// Changes to the `EXTNAME` or `EXTVER` cards of an HDU already in the list are indexed
+    /// when the list is rearranged, or when a lookup does not find a match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// for ver in 1..=2u32 {
+    ///     for name in ["SCI", "ERR", "DQ"] {
+    ///         let mut img_hdu = image_hdu::default();
+    ///         img_hdu.header.set_extname(String::from(name))?;
+    ///         img_hdu.header.set_extver(ver)?;
+    ///         hdu_list.push(img_hdu);
+    ///     }
+    /// }
+    ///
+    /// let index = hdu_list.extension_index();
+    /// assert_eq!(index.len(), 6);
+    /// assert_eq!(index[&(String::from("ERR"), 2)], 5);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn extension_index(&self) -> &HashMap<(String, u32), usize> {
+        &self.extensions.versions
+    }
+}
+
+/// Gets the trimmed EXTNAME of the given header, or an empty string if it is absent, and its EXTVER, or 1 if it is absent.
+pub(crate) fn extension_key(header: &FitsHeader) -> (String, u32) {
+    let name = header
+        .peek_value::<String>(EXTNAME_KEYWORD)
+        .ok()
+        .flatten()
+        .map(|name| name.trim().to_owned())
+        .unwrap_or_default();
+    let version = header
+        .peek_value(EXTVER_KEYWORD)
+        .ok()
+        .flatten()
+        .unwrap_or(1);
+    (name, version)
+}
+
+fn bitpix_name(bitpix: Bitpix) -> &'static str {
+    match bitpix {
+        Bitpix::U8 => "uint8",
+        Bitpix::I16 => "int16",
+        Bitpix::I32 => "int32",
+        Bitpix::I64 => "int64",
+        Bitpix::F32 => "float32",
+        Bitpix::F64 => "float64",
+    }
+}
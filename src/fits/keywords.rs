@@ -0,0 +1,664 @@
+//! Typed access to the header keywords reserved by the FITS standard.
+//! See appendix C of <https://fits.gsfc.nasa.gov/standard40/fits_standard40aa-le.pdf> for the reserved keywords.
+
+use super::*;
+
+/// The header keyword indicating that extensions may follow the primary HDU.
+pub const EXTEND_KEYWORD: [u8; 8] = *b"EXTEND  ";
+/// The header keyword indicating the version of an extension among extensions of the same name.
+pub const EXTVER_KEYWORD: [u8; 8] = *b"EXTVER  ";
+/// The header keyword indicating the level of an extension within a hierarchy of extensions.
+pub const EXTLEVEL_KEYWORD: [u8; 8] = *b"EXTLEVEL";
+/// The header keyword indicating an extension inherits the cards of the primary header.
+pub const INHERIT_KEYWORD: [u8; 8] = *b"INHERIT ";
+/// The header keyword indicating the factor applied to array values.
+pub const BSCALE_KEYWORD: [u8; 8] = *b"BSCALE  ";
+/// The header keyword indicating the offset added to scaled array values.
+pub const BZERO_KEYWORD: [u8; 8] = *b"BZERO   ";
+/// The header keyword indicating the physical unit of array values.
+pub const BUNIT_KEYWORD: [u8; 8] = *b"BUNIT   ";
+/// The header keyword indicating the integer array value that represents an undefined value.
+pub const BLANK_KEYWORD: [u8; 8] = *b"BLANK   ";
+/// The header keyword indicating the minimum valid physical array value.
+pub const DATAMIN_KEYWORD: [u8; 8] = *b"DATAMIN ";
+/// The header keyword indicating the maximum valid physical array value.
+pub const DATAMAX_KEYWORD: [u8; 8] = *b"DATAMAX ";
+/// The header keyword indicating the date on which the HDU was created.
+pub const DATE_KEYWORD: [u8; 8] = *b"DATE    ";
+/// The header keyword indicating the start date of the observation.
+pub const DATE_OBS_KEYWORD: [u8; 8] = *b"DATE-OBS";
+/// The header keyword indicating the end date of the observation.
+pub const DATE_END_KEYWORD: [u8; 8] = *b"DATE-END";
+/// The header keyword indicating the start of the observation as a modified Julian date.
+pub const MJD_OBS_KEYWORD: [u8; 8] = *b"MJD-OBS ";
+/// The header keyword indicating the time scale of the time-related cards.
+pub const TIMESYS_KEYWORD: [u8; 8] = *b"TIMESYS ";
+/// The header keyword indicating the unit of times given relative to the reference time.
+pub const TIMEUNIT_KEYWORD: [u8; 8] = *b"TIMEUNIT";
+/// The header keyword indicating the offset added to times given relative to the reference time.
+pub const TIMEZERO_KEYWORD: [u8; 8] = *b"TIMEZERO";
+/// The header keyword indicating the reference time as a date.
+pub const DATEREF_KEYWORD: [u8; 8] = *b"DATEREF ";
+/// The header keyword indicating the reference time as a modified Julian date.
+pub const MJDREF_KEYWORD: [u8; 8] = *b"MJDREF  ";
+/// The header keyword indicating the integer part of the reference time as a modified Julian date.
+pub const MJDREFI_KEYWORD: [u8; 8] = *b"MJDREFI ";
+/// The header keyword indicating the fractional part of the reference time as a modified Julian date.
+pub const MJDREFF_KEYWORD: [u8; 8] = *b"MJDREFF ";
+/// The header keyword indicating the reference time as a Julian date.
+pub const JDREF_KEYWORD: [u8; 8] = *b"JDREF   ";
+/// The header keyword indicating the integer part of the reference time as a Julian date.
+pub const JDREFI_KEYWORD: [u8; 8] = *b"JDREFI  ";
+/// The header keyword indicating the fractional part of the reference time as a Julian date.
+pub const JDREFF_KEYWORD: [u8; 8] = *b"JDREFF  ";
+/// The header keyword indicating the start time of the data, relative to the reference time.
+pub const TSTART_KEYWORD: [u8; 8] = *b"TSTART  ";
+/// The header keyword indicating the stop time of the data, relative to the reference time.
+pub const TSTOP_KEYWORD: [u8; 8] = *b"TSTOP   ";
+/// The header keyword indicating the exposure time in seconds.
+pub const EXPTIME_KEYWORD: [u8; 8] = *b"EXPTIME ";
+/// The header keyword indicating the organization that created the file.
+pub const ORIGIN_KEYWORD: [u8; 8] = *b"ORIGIN  ";
+/// The header keyword indicating the telescope used to acquire the data.
+pub const TELESCOP_KEYWORD: [u8; 8] = *b"TELESCOP";
+/// The header keyword indicating the instrument used to acquire the data.
+pub const INSTRUME_KEYWORD: [u8; 8] = *b"INSTRUME";
+/// The header keyword indicating who acquired the data.
+pub const OBSERVER_KEYWORD: [u8; 8] = *b"OBSERVER";
+/// The header keyword indicating the name of the observed object.
+pub const OBJECT_KEYWORD: [u8; 8] = *b"OBJECT  ";
+/// The header keyword indicating who compiled the data.
+pub const AUTHOR_KEYWORD: [u8; 8] = *b"AUTHOR  ";
+/// The header keyword indicating where the data has been published.
+pub const REFERENC_KEYWORD: [u8; 8] = *b"REFERENC";
+/// The header keyword indicating the equinox in years of the celestial coordinate system.
+pub const EQUINOX_KEYWORD: [u8; 8] = *b"EQUINOX ";
+/// The deprecated header keyword indicating the equinox in years of the celestial coordinate system.
+pub const EPOCH_KEYWORD: [u8; 8] = *b"EPOCH   ";
+/// The header keyword of commentary cards.
+pub const COMMENT_KEYWORD: [u8; 8] = *b"COMMENT ";
+/// The header keyword of cards describing the history of the data.
+pub const HISTORY_KEYWORD: [u8; 8] = *b"HISTORY ";
+/// The header keyword indicating the number of fields in each row of a table.
+pub const TFIELDS_KEYWORD: [u8; 8] = *b"TFIELDS ";
+/// The header keyword indicating the offset in bytes of the heap of a binary table.
+pub const THEAP_KEYWORD: [u8; 8] = *b"THEAP   ";
+/// The header keyword indicating the physical unit of a table column.
+pub const TUNIT_KEYWORD: [u8; 8] = *b"TUNIT   ";
+/// The header keyword indicating the factor applied to the values of a table column.
+pub const TSCAL_KEYWORD: [u8; 8] = *b"TSCAL   ";
+/// The header keyword indicating the offset added to the scaled values of a table column.
+pub const TZERO_KEYWORD: [u8; 8] = *b"TZERO   ";
+/// The header keyword indicating the value that represents an undefined value in a table column.
+pub const TNULL_KEYWORD: [u8; 8] = *b"TNULL   ";
+/// The header keyword indicating the display format of a table column.
+pub const TDISP_KEYWORD: [u8; 8] = *b"TDISP   ";
+/// The header keyword indicating the starting position of a column in the rows of an ASCII table.
+pub const TBCOL_KEYWORD: [u8; 8] = *b"TBCOL   ";
+/// The header keyword indicating the dimensions of the array in each value of a table column.
+pub const TDIM_KEYWORD: [u8; 8] = *b"TDIM    ";
+/// The header keyword indicating the minimum physical value of a table column.
+pub const TDMIN_KEYWORD: [u8; 8] = *b"TDMIN   ";
+/// The header keyword indicating the maximum physical value of a table column.
+pub const TDMAX_KEYWORD: [u8; 8] = *b"TDMAX   ";
+/// The header keyword indicating the minimum legal value of a table column.
+pub const TLMIN_KEYWORD: [u8; 8] = *b"TLMIN   ";
+/// The header keyword indicating the maximum legal value of a table column.
+pub const TLMAX_KEYWORD: [u8; 8] = *b"TLMAX   ";
+/// The header keyword indicating the name of a parameter of each random group.
+pub const PTYPE_KEYWORD: [u8; 8] = *b"PTYPE   ";
+/// The header keyword indicating the factor applied to a parameter of each random group.
+pub const PSCAL_KEYWORD: [u8; 8] = *b"PSCAL   ";
+/// The header keyword indicating the offset added to a scaled parameter of each random group.
+pub const PZERO_KEYWORD: [u8; 8] = *b"PZERO   ";
+/// The header keyword indicating the number of world coordinate axes.
+pub const WCSAXES_KEYWORD: [u8; 8] = *b"WCSAXES ";
+/// The header keyword indicating the type of a world coordinate axis.
+pub const CTYPE_KEYWORD: [u8; 8] = *b"CTYPE   ";
+/// The header keyword indicating the unit of a world coordinate axis.
+pub const CUNIT_KEYWORD: [u8; 8] = *b"CUNIT   ";
+/// The header keyword indicating the world coordinate at the reference point.
+pub const CRVAL_KEYWORD: [u8; 8] = *b"CRVAL   ";
+/// The header keyword indicating the pixel coordinate of the reference point.
+pub const CRPIX_KEYWORD: [u8; 8] = *b"CRPIX   ";
+/// The header keyword indicating the increment of a world coordinate axis per pixel.
+pub const CDELT_KEYWORD: [u8; 8] = *b"CDELT   ";
+/// The deprecated header keyword indicating the rotation of a world coordinate axis.
+pub const CROTA_KEYWORD: [u8; 8] = *b"CROTA   ";
+/// The header keyword indicating the celestial reference frame.
+pub const RADESYS_KEYWORD: [u8; 8] = *b"RADESYS ";
+/// The header keyword indicating the native longitude of the celestial pole.
+pub const LONPOLE_KEYWORD: [u8; 8] = *b"LONPOLE ";
+/// The header keyword indicating the native latitude of the celestial pole.
+pub const LATPOLE_KEYWORD: [u8; 8] = *b"LATPOLE ";
+/// The header keyword indicating an HDU contains a tile-compressed image.
+pub const ZIMAGE_KEYWORD: [u8; 8] = *b"ZIMAGE  ";
+/// The header keyword indicating the algorithm used to compress the tiles of an image.
+pub const ZCMPTYPE_KEYWORD: [u8; 8] = *b"ZCMPTYPE";
+/// The header keyword indicating the number of axes of a tile-compressed image.
+pub const ZNAXIS_KEYWORD: [u8; 8] = *b"ZNAXIS  ";
+
+/// The prefixes of the keywords that describe each column of a table.
+const COLUMN_KEYWORDS: [[u8; 8]; 13] = [
+    TTYPE_KEYWORD,
+    TFORM_KEYWORD,
+    TUNIT_KEYWORD,
+    TSCAL_KEYWORD,
+    TZERO_KEYWORD,
+    TNULL_KEYWORD,
+    TDISP_KEYWORD,
+    TBCOL_KEYWORD,
+    TDIM_KEYWORD,
+    TDMIN_KEYWORD,
+    TDMAX_KEYWORD,
+    TLMIN_KEYWORD,
+    TLMAX_KEYWORD,
+];
+
+/// A trait for the types of values returned by the keyword accessors of [`FitsHeader`].
+pub(crate) trait KeywordValue: FitsHeaderValue + Sized + 'static {
+    /// Reads the value of the given card, accepting values cached as compatible types.
+    fn read(card: &mut FitsHeaderCard) -> Result<Self, FitsHeaderError>;
+}
+
+macro_rules! impl_keyword_value {
+    ($($value_type: ty),+) => {
+        $(
+            impl KeywordValue for $value_type {
+                fn read(card: &mut FitsHeaderCard) -> Result<Self, FitsHeaderError> {
+                    card.get_value::<$value_type>().map(|value| (*value).clone())
+                }
+            }
+        )+
+    };
+}
+
+macro_rules! impl_integer_keyword_value {
+    ($($value_type: ty),+) => {
+        $(
+            impl KeywordValue for $value_type {
+                fn read(card: &mut FitsHeaderCard) -> Result<Self, FitsHeaderError> {
+                    if let Ok(value) = card.get_value::<$value_type>() {
+                        return Ok(*value);
+                    }
+                    let value = card
+                        .get_value::<i64>()
+                        .map(|value| *value)
+                        .or_else(|_| card.get_value::<i32>().map(|value| *value as i64))
+                        .or_else(|_| card.get_value::<u32>().map(|value| *value as i64))
+                        .or_else(|_| card.get_value::<u16>().map(|value| *value as i64))
+                        .or_else(|_| card.get_value::<u8>().map(|value| *value as i64))?;
+                    <$value_type>::try_from(value).map_err(|_| FitsHeaderError::InvalidType)
+                }
+            }
+        )+
+    };
+}
+
+impl_keyword_value!(bool, String, Bitpix, TForm);
+impl_integer_keyword_value!(u16, u32, i64);
+
+impl KeywordValue for f64 {
+    fn read(card: &mut FitsHeaderCard) -> Result<Self, FitsHeaderError> {
+        numeric_value(card).ok_or(FitsHeaderError::InvalidType)
+    }
+}
+
+/// Defines accessors for keywords whose absence is reported as None.
+macro_rules! optional_keywords {
+    ($($getter: ident, $setter: ident: $value_type: ty = $keyword: expr, $name: literal, $description: literal;)+) => {
+        $(
+            #[doc = concat!("Gets ", $description, ", given by ", $name, ", or None if the card is absent.")]
+            pub fn $getter(&mut self) -> Result<Option<$value_type>, FitsHeaderError> {
+                self.optional_value($keyword)
+            }
+
+            #[doc = concat!("Sets ", $description, ", given by ", $name, ".")]
+            pub fn $setter(&mut self, value: impl Into<$value_type>) -> Result<(), FitsHeaderError> {
+                self.set_value($keyword, value.into())
+            }
+        )+
+    };
+}
+
+/// Defines accessors for keywords that take a default value in their absence.
+macro_rules! defaulted_keywords {
+    ($($getter: ident, $setter: ident: $value_type: ty = $keyword: expr, $name: literal, $description: literal, $default: expr;)+) => {
+        $(
+            #[doc = concat!("Gets ", $description, ", given by ", $name, ", or ", stringify!($default), " if the card is absent.")]
+            pub fn $getter(&mut self) -> Result<$value_type, FitsHeaderError> {
+                Ok(self.optional_value($keyword)?.unwrap_or($default))
+            }
+
+            #[doc = concat!("Sets ", $description, ", given by ", $name, ".")]
+            pub fn $setter(&mut self, value: impl Into<$value_type>) -> Result<(), FitsHeaderError> {
+                self.set_value($keyword, value.into())
+            }
+        )+
+    };
+}
+
+/// Defines accessors for numbered keywords such as TTYPEn, whose absence is reported as None.
+macro_rules! optional_indexed_keywords {
+    ($($getter: ident, $setter: ident: $value_type: ty = $keyword: expr, $name: literal, $description: literal;)+) => {
+        $(
+            #[doc = concat!("Gets ", $description, ", given by ", $name, ", or None if the card is absent.")]
+            pub fn $getter(&mut self, n: u16) -> Result<Option<$value_type>, FitsHeaderError> {
+                self.optional_value(numbered_keyword($keyword, n))
+            }
+
+            #[doc = concat!("Sets ", $description, ", given by ", $name, ".")]
+            pub fn $setter(&mut self, n: u16, value: impl Into<$value_type>) -> Result<(), FitsHeaderError> {
+                self.set_value(numbered_keyword($keyword, n), value.into())
+            }
+        )+
+    };
+}
+
+/// Defines accessors for numbered keywords such as TSCALn, which take a default value in their absence.
+macro_rules! defaulted_indexed_keywords {
+    ($($getter: ident, $setter: ident: $value_type: ty = $keyword: expr, $name: literal, $description: literal, $default: expr;)+) => {
+        $(
+            #[doc = concat!("Gets ", $description, ", given by ", $name, ", or ", stringify!($default), " if the card is absent.")]
+            pub fn $getter(&mut self, n: u16) -> Result<$value_type, FitsHeaderError> {
+                Ok(self.optional_value(numbered_keyword($keyword, n))?.unwrap_or($default))
+            }
+
+            #[doc = concat!("Sets ", $description, ", given by ", $name, ".")]
+            pub fn $setter(&mut self, n: u16, value: impl Into<$value_type>) -> Result<(), FitsHeaderError> {
+                self.set_value(numbered_keyword($keyword, n), value.into())
+            }
+        )+
+    };
+}
+
+impl FitsHeader {
+    optional_keywords! {
+        simple, set_simple: bool = SIMPLE_KEYWORD, "SIMPLE", "whether the file conforms to the FITS standard";
+        xtension, set_xtension: String = XTENSION_KEYWORD, "XTENSION", "the type of the extension";
+        extend, set_extend: bool = EXTEND_KEYWORD, "EXTEND", "whether extensions may follow the primary HDU";
+        groups, set_groups: bool = GROUPS_KEYWORD, "GROUPS", "whether the primary HDU is in the random groups format";
+        inherit, set_inherit: bool = INHERIT_KEYWORD, "INHERIT", "whether the extension inherits the cards of the primary header";
+        extname, set_extname: String = EXTNAME_KEYWORD, "EXTNAME", "the name of the extension";
+        bunit, set_bunit: String = BUNIT_KEYWORD, "BUNIT", "the physical unit of array values";
+        blank, set_blank: i64 = BLANK_KEYWORD, "BLANK", "the integer array value that represents an undefined value";
+        datamin, set_datamin: f64 = DATAMIN_KEYWORD, "DATAMIN", "the minimum valid physical array value";
+        datamax, set_datamax: f64 = DATAMAX_KEYWORD, "DATAMAX", "the maximum valid physical array value";
+        date, set_date: String = DATE_KEYWORD, "DATE", "the date on which the HDU was created";
+        date_obs, set_date_obs: String = DATE_OBS_KEYWORD, "DATE-OBS", "the start date of the observation";
+        date_end, set_date_end: String = DATE_END_KEYWORD, "DATE-END", "the end date of the observation";
+        mjd_obs, set_mjd_obs: f64 = MJD_OBS_KEYWORD, "MJD-OBS", "the start of the observation as a modified Julian date";
+        timesys, set_timesys: String = TIMESYS_KEYWORD, "TIMESYS", "the time scale of the time-related cards";
+        timeunit, set_timeunit: String = TIMEUNIT_KEYWORD, "TIMEUNIT", "the unit of times given relative to the reference time";
+        dateref, set_dateref: String = DATEREF_KEYWORD, "DATEREF", "the reference time as a date";
+        mjdref, set_mjdref: f64 = MJDREF_KEYWORD, "MJDREF", "the reference time as a modified Julian date";
+        mjdrefi, set_mjdrefi: i64 = MJDREFI_KEYWORD, "MJDREFI", "the integer part of the reference time as a modified Julian date";
+        mjdreff, set_mjdreff: f64 = MJDREFF_KEYWORD, "MJDREFF", "the fractional part of the reference time as a modified Julian date";
+        jdref, set_jdref: f64 = JDREF_KEYWORD, "JDREF", "the reference time as a Julian date";
+        jdrefi, set_jdrefi: i64 = JDREFI_KEYWORD, "JDREFI", "the integer part of the reference time as a Julian date";
+        jdreff, set_jdreff: f64 = JDREFF_KEYWORD, "JDREFF", "the fractional part of the reference time as a Julian date";
+        tstart, set_tstart: f64 = TSTART_KEYWORD, "TSTART", "the start time of the data, relative to the reference time";
+        tstop, set_tstop: f64 = TSTOP_KEYWORD, "TSTOP", "the stop time of the data, relative to the reference time";
+        exptime, set_exptime: f64 = EXPTIME_KEYWORD, "EXPTIME", "the exposure time in seconds";
+        origin, set_origin: String = ORIGIN_KEYWORD, "ORIGIN", "the organization that created the file";
+        telescop, set_telescop: String = TELESCOP_KEYWORD, "TELESCOP", "the telescope used to acquire the data";
+        instrume, set_instrume: String = INSTRUME_KEYWORD, "INSTRUME", "the instrument used to acquire the data";
+        observer, set_observer: String = OBSERVER_KEYWORD, "OBSERVER", "who acquired the data";
+        object, set_object: String = OBJECT_KEYWORD, "OBJECT", "the name of the observed object";
+        author, set_author: String = AUTHOR_KEYWORD, "AUTHOR", "who compiled the data";
+        referenc, set_referenc: String = REFERENC_KEYWORD, "REFERENC", "where the data has been published";
+        equinox, set_equinox: f64 = EQUINOX_KEYWORD, "EQUINOX", "the equinox in years of the celestial coordinate system";
+        epoch, set_epoch: f64 = EPOCH_KEYWORD, "EPOCH", "the equinox in years of the celestial coordinate system";
+        theap, set_theap: u32 = THEAP_KEYWORD, "THEAP", "the offset in bytes of the heap of a binary table";
+        wcsaxes, set_wcsaxes: u16 = WCSAXES_KEYWORD, "WCSAXES", "the number of world coordinate axes";
+        radesys, set_radesys: String = RADESYS_KEYWORD, "RADESYS", "the celestial reference frame";
+        lonpole, set_lonpole: f64 = LONPOLE_KEYWORD, "LONPOLE", "the native longitude of the celestial pole";
+        latpole, set_latpole: f64 = LATPOLE_KEYWORD, "LATPOLE", "the native latitude of the celestial pole";
+    }
+
+    defaulted_keywords! {
+        pcount, set_pcount: u32 = PCOUNT_KEYWORD, "PCOUNT", "the number of values following the array of each group", 0;
+        gcount, set_gcount: u32 = GCOUNT_KEYWORD, "GCOUNT", "the number of groups", 1;
+        extver, set_extver: u32 = EXTVER_KEYWORD, "EXTVER", "the version of the extension", 1;
+        extlevel, set_extlevel: u32 = EXTLEVEL_KEYWORD, "EXTLEVEL", "the level of the extension", 1;
+        bscale, set_bscale: f64 = BSCALE_KEYWORD, "BSCALE", "the factor applied to array values", 1.0;
+        bzero, set_bzero: f64 = BZERO_KEYWORD, "BZERO", "the offset added to scaled array values", 0.0;
+        timezero, set_timezero: f64 = TIMEZERO_KEYWORD, "TIMEZERO", "the offset added to times given relative to the reference time", 0.0;
+    }
+
+    optional_indexed_keywords! {
+        ttype, set_ttype: String = TTYPE_KEYWORD, "TTYPEn", "the name of column `n`";
+        tunit, set_tunit: String = TUNIT_KEYWORD, "TUNITn", "the physical unit of column `n`";
+        tnull, set_tnull: i64 = TNULL_KEYWORD, "TNULLn", "the value that represents an undefined value in column `n`";
+        tdisp, set_tdisp: String = TDISP_KEYWORD, "TDISPn", "the display format of column `n`";
+        tbcol, set_tbcol: u32 = TBCOL_KEYWORD, "TBCOLn", "the starting position of column `n` in the rows of an ASCII table";
+        tdmin, set_tdmin: f64 = TDMIN_KEYWORD, "TDMINn", "the minimum physical value of column `n`";
+        tdmax, set_tdmax: f64 = TDMAX_KEYWORD, "TDMAXn", "the maximum physical value of column `n`";
+        tlmin, set_tlmin: f64 = TLMIN_KEYWORD, "TLMINn", "the minimum legal value of column `n`";
+        tlmax, set_tlmax: f64 = TLMAX_KEYWORD, "TLMAXn", "the maximum legal value of column `n`";
+        ptype, set_ptype: String = PTYPE_KEYWORD, "PTYPEn", "the name of parameter `n` of each random group";
+        ctype, set_ctype: String = CTYPE_KEYWORD, "CTYPEn", "the type of world coordinate axis `n`";
+        cunit, set_cunit: String = CUNIT_KEYWORD, "CUNITn", "the unit of world coordinate axis `n`";
+        crota, set_crota: f64 = CROTA_KEYWORD, "CROTAn", "the rotation of world coordinate axis `n`";
+    }
+
+    defaulted_indexed_keywords! {
+        tscal, set_tscal: f64 = TSCAL_KEYWORD, "TSCALn", "the factor applied to the values of column `n`", 1.0;
+        tzero, set_tzero: f64 = TZERO_KEYWORD, "TZEROn", "the offset added to the scaled values of column `n`", 0.0;
+        pscal, set_pscal: f64 = PSCAL_KEYWORD, "PSCALn", "the factor applied to parameter `n` of each random group", 1.0;
+        pzero, set_pzero: f64 = PZERO_KEYWORD, "PZEROn", "the offset added to scaled parameter `n` of each random group", 0.0;
+        crval, set_crval: f64 = CRVAL_KEYWORD, "CRVALn", "the world coordinate of axis `n` at the reference point", 0.0;
+        crpix, set_crpix: f64 = CRPIX_KEYWORD, "CRPIXn", "the pixel coordinate of axis `n` of the reference point", 0.0;
+        cdelt, set_cdelt: f64 = CDELT_KEYWORD, "CDELTn", "the increment of world coordinate axis `n` per pixel", 1.0;
+    }
+
+    /// Gets the number of bits that represent each array value, given by BITPIX.
+    /// Returns an error if the card is absent.
+    pub fn bitpix(&mut self) -> Result<Bitpix, FitsHeaderError> {
+        self.required_value(BITPIX_KEYWORD)
+    }
+
+    /// Sets the number of bits that represent each array value, given by BITPIX.
+    /// BLANK is removed if the values are floating point, as it only applies to integers.
+    pub fn set_bitpix(&mut self, bitpix: Bitpix) -> Result<(), FitsHeaderError> {
+        self.set_value(BITPIX_KEYWORD, bitpix)?;
+        if matches!(bitpix, Bitpix::F32 | Bitpix::F64) {
+            self.remove_card(BLANK_KEYWORD);
+        }
+        Ok(())
+    }
+
+    /// Gets the number of axes of the array, given by NAXIS.
+    /// Returns an error if the card is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut header = image_hdu::default().header;
+    /// header.set_dimensions(&[100, 50])?;
+    /// assert_eq!(header.naxis()?, 2);
+    /// assert_eq!(header.naxisn(2)?, 50);
+    ///
+    /// header.set_naxis(1)?;
+    /// assert_eq!(header.dimensions()?, [100]);
+    /// assert!(header.get_card(*b"NAXIS2  ").is_none());
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn naxis(&mut self) -> Result<u16, FitsHeaderError> {
+        self.required_value(NAXIS_KEYWORD)
+    }
+
+    /// Sets the number of axes of the array, given by NAXIS.
+    /// The NAXISn cards of removed axes are removed, and added axes are given a length of 1.
+    pub fn set_naxis(&mut self, naxis: u16) -> Result<(), FitsHeaderError> {
+        let old_naxis = self.naxis().unwrap_or_default();
+        for n in naxis + 1..=old_naxis {
+            self.remove_card(numbered_keyword(NAXIS_KEYWORD, n));
+        }
+        self.set_value(NAXIS_KEYWORD, naxis)?;
+        for n in 1..=naxis {
+            let keyword = numbered_keyword(NAXIS_KEYWORD, n);
+            if self.get_card(keyword).is_none() {
+                let index = axes_end(self);
+                self.insert_value(index, keyword, 1u32)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the length of axis `n` of the array, given by NAXISn.
+    /// Returns an error if the card is absent.
+    pub fn naxisn(&mut self, n: u16) -> Result<u32, FitsHeaderError> {
+        self.required_value(numbered_keyword(NAXIS_KEYWORD, n))
+    }
+
+    /// Sets the length of axis `n` of the array, given by NAXISn.
+    /// If `n` exceeds NAXIS, NAXIS is increased, and the axes in between are given a length of 1.
+    pub fn set_naxisn(&mut self, n: u16, len: u32) -> Result<(), FitsHeaderError> {
+        if n > self.naxis().unwrap_or_default() {
+            self.set_naxis(n)?;
+        }
+        self.set_value(numbered_keyword(NAXIS_KEYWORD, n), len)
+    }
+
+    /// Gets the lengths of the axes of the array, given by NAXIS and the NAXISn cards.
+    /// Returns an error if any of the cards are absent.
+    pub fn dimensions(&mut self) -> Result<Vec<usize>, FitsHeaderError> {
+        (1..=self.naxis()?)
+            .map(|n| self.naxisn(n).map(|len| len as usize))
+            .collect()
+    }
+
+    /// Sets the lengths of the axes of the array, given by NAXIS and the NAXISn cards,
+    /// keeping the NAXISn cards in order after NAXIS.
+    pub fn set_dimensions(&mut self, dimensions: &[usize]) -> Result<(), FitsHeaderError> {
+        self.set_naxis(dimensions.len() as u16)?;
+        for (n, len) in (1..).zip(dimensions) {
+            self.set_value(numbered_keyword(NAXIS_KEYWORD, n), *len as u32)?;
+        }
+        Ok(())
+    }
+
+    /// Gets the number of fields in each row of a table, given by TFIELDS.
+    /// Returns an error if the card is absent.
+    pub fn tfields(&mut self) -> Result<u16, FitsHeaderError> {
+        self.required_value(TFIELDS_KEYWORD)
+    }
+
+    /// Sets the number of fields in each row of a table, given by TFIELDS.
+    /// The cards describing removed columns, such as their TTYPEn and TFORMn cards, are removed.
+    pub fn set_tfields(&mut self, tfields: u16) -> Result<(), FitsHeaderError> {
+        let old_tfields = self.tfields().unwrap_or_default();
+        for n in tfields + 1..=old_tfields {
+            for keyword in COLUMN_KEYWORDS {
+                self.remove_card(numbered_keyword(keyword, n));
+            }
+        }
+        self.set_value(TFIELDS_KEYWORD, tfields)
+    }
+
+    /// Gets the data type of column `n`, given by TFORMn.
+    /// Returns an error if the card is absent.
+    pub fn tform(&mut self, n: u16) -> Result<TForm, FitsHeaderError> {
+        self.required_value(numbered_keyword(TFORM_KEYWORD, n))
+    }
+
+    /// Sets the data type of column `n`, given by TFORMn.
+    pub fn set_tform(&mut self, n: u16, tform: TForm) -> Result<(), FitsHeaderError> {
+        self.set_value(numbered_keyword(TFORM_KEYWORD, n), tform)
+    }
+
+    /// Gets the dimensions of the array in each value of column `n`, given by TDIMn, or None if the card is absent.
+    pub fn tdim(&mut self, n: u16) -> Result<Option<Vec<usize>>, FitsHeaderError> {
+        let Some(tdim) = self.optional_value::<String>(numbered_keyword(TDIM_KEYWORD, n))? else {
+            return Ok(None);
+        };
+        let invalid = || FitsHeaderError::DeserializationError {
+            found: tdim.as_bytes().to_vec(),
+            intent: String::from("header card TDIM value"),
+        };
+        tdim.trim()
+            .strip_prefix('(')
+            .and_then(|tdim| tdim.strip_suffix(')'))
+            .ok_or_else(invalid)?
+            .split(',')
+            .map(|len| len.trim().parse::<usize>().map_err(|_| invalid()))
+            .collect::<Result<Vec<usize>, FitsHeaderError>>()
+            .map(Some)
+    }
+
+    /// Sets the dimensions of the array in each value of column `n`, given by TDIMn.
+    pub fn set_tdim(&mut self, n: u16, dimensions: &[usize]) -> Result<(), FitsHeaderError> {
+        let lens: Vec<String> = dimensions.iter().map(usize::to_string).collect();
+        self.set_value(
+            numbered_keyword(TDIM_KEYWORD, n),
+            format!("({})", lens.join(",")),
+        )
+    }
+
+    /// Gets element (`i`, `j`) of the linear transformation matrix of the world coordinate system, given by PCi_j,
+    /// or the corresponding element of the identity matrix if the card is absent.
+    pub fn pc(&mut self, i: u16, j: u16) -> Result<f64, FitsHeaderError> {
+        let default = if i == j { 1.0 } else { 0.0 };
+        Ok(self
+            .optional_value(matrix_keyword("PC", i, j)?)?
+            .unwrap_or(default))
+    }
+
+    /// Sets element (`i`, `j`) of the linear transformation matrix of the world coordinate system, given by PCi_j.
+    pub fn set_pc(&mut self, i: u16, j: u16, value: f64) -> Result<(), FitsHeaderError> {
+        self.set_value(matrix_keyword("PC", i, j)?, value)
+    }
+
+    /// Gets element (`i`, `j`) of the scaled linear transformation matrix of the world coordinate system, given by CDi_j,
+    /// or None if the card is absent.
+    pub fn cd(&mut self, i: u16, j: u16) -> Result<Option<f64>, FitsHeaderError> {
+        self.optional_value(matrix_keyword("CD", i, j)?)
+    }
+
+    /// Sets element (`i`, `j`) of the scaled linear transformation matrix of the world coordinate system, given by CDi_j.
+    pub fn set_cd(&mut self, i: u16, j: u16, value: f64) -> Result<(), FitsHeaderError> {
+        self.set_value(matrix_keyword("CD", i, j)?, value)
+    }
+
+    /// Gets the text of the COMMENT cards, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut header = primary_hdu::default().header;
+    /// header.add_comment("first");
+    /// header.add_comment("second");
+    /// header.add_history("created");
+    /// assert_eq!(header.comments(), ["first", "second"]);
+    /// assert_eq!(header.history(), ["created"]);
+    /// ```
+    pub fn comments(&self) -> Vec<String> {
+        self.commentary(COMMENT_KEYWORD)
+    }
+
+    /// Adds a COMMENT card with the given text before the END card,
+    /// continuing the text in further COMMENT cards if it does not fit in one.
+    pub fn add_comment(&mut self, text: &str) {
+        self.add_commentary(COMMENT_KEYWORD, text);
+    }
+
+    /// Gets the text of the HISTORY cards, in order.
+    pub fn history(&self) -> Vec<String> {
+        self.commentary(HISTORY_KEYWORD)
+    }
+
+    /// Adds a HISTORY card with the given text before the END card,
+    /// continuing the text in further HISTORY cards if it does not fit in one.
+    pub fn add_history(&mut self, text: &str) {
+        self.add_commentary(HISTORY_KEYWORD, text);
+    }
+
+    fn optional_value<T: KeywordValue>(
+        &mut self,
+        keyword: impl Into<FitsHeaderKeyword>,
+    ) -> Result<Option<T>, FitsHeaderError> {
+        self.get_card(keyword.into()).map(T::read).transpose()
+    }
+
+    /// Reads the value of the given keyword from a copy of its card, so that the header is not modified.
+    pub(crate) fn peek_value<T: KeywordValue>(
+        &self,
+        keyword: impl Into<FitsHeaderKeyword>,
+    ) -> Result<Option<T>, FitsHeaderError> {
+        let keyword = keyword.into();
+        self.cards
+            .iter()
+            .find(|card| *card.keyword() == keyword)
+            .cloned()
+            .map(|mut card| T::read(&mut card))
+            .transpose()
+    }
+
+    fn required_value<T: KeywordValue>(
+        &mut self,
+        keyword: impl Into<FitsHeaderKeyword>,
+    ) -> Result<T, FitsHeaderError> {
+        let keyword = keyword.into();
+        self.optional_value(keyword)?
+            .ok_or(FitsHeaderError::MissingKeyword { keyword })
+    }
+
+    fn commentary(&self, keyword: [u8; 8]) -> Vec<String> {
+        self.cards
+            .iter()
+            .filter(|card| *card.keyword() == keyword)
+            .map(|card| {
+                let raw: [u8; HEADER_CARD_LEN] = card.clone().into();
+                String::from_utf8_lossy(&raw[HEADER_KEYWORD_LEN..])
+                    .trim_end()
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    fn add_commentary(&mut self, keyword: [u8; 8], text: &str) {
+        let index = self
+            .cards
+            .iter()
+            .rposition(|card| *card.keyword() == END_KEYWORD)
+            .unwrap_or(self.cards.len());
+        let text_len = HEADER_CARD_LEN - HEADER_KEYWORD_LEN;
+        let bytes = text.as_bytes();
+        let chunks: Vec<&[u8]> = if bytes.is_empty() {
+            vec![bytes]
+        } else {
+            bytes.chunks(text_len).collect()
+        };
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut raw = [b' '; HEADER_CARD_LEN];
+            raw[..HEADER_KEYWORD_LEN].copy_from_slice(&keyword);
+            raw[HEADER_KEYWORD_LEN..HEADER_KEYWORD_LEN + chunk.len()].copy_from_slice(chunk);
+            self.cards.insert(index + i, FitsHeaderCard::from(raw));
+        }
+    }
+}
+
+/// Builds a keyword such as NAXIS1 from the given prefix and number.
+pub(crate) fn numbered_keyword(prefix: [u8; 8], n: u16) -> FitsHeaderKeyword {
+    let mut keyword = FitsHeaderKeyword::from(prefix);
+    keyword.append_number(n);
+    keyword
+}
+
+/// Builds a keyword such as PC1_2 from the given prefix and indices,
+/// returning an error if the keyword is longer than 8 characters.
+fn matrix_keyword(prefix: &str, i: u16, j: u16) -> Result<FitsHeaderKeyword, FitsHeaderError> {
+    wcs_keyword(prefix, i as usize, Some(j as usize), ' ').ok_or_else(|| {
+        FitsHeaderError::InvalidLength {
+            expected: HEADER_KEYWORD_LEN,
+            found: format!("{}{}_{}", prefix, i, j).len(),
+            intent: String::from("header card keyword"),
+        }
+    })
+}
+
+/// Finds the index following the NAXIS and NAXISn cards of the given header.
+pub(crate) fn axes_end(header: &mut FitsHeader) -> usize {
+    let naxis = header.naxis().unwrap_or_default();
+    let mut end = header
+        .cards
+        .iter()
+        .position(|card| *card.keyword() == NAXIS_KEYWORD)
+        .map_or(1, |index| index + 1);
+    for n in 1..=naxis {
+        let keyword = numbered_keyword(NAXIS_KEYWORD, n);
+        if let Some(index) = header
+            .cards
+            .iter()
+            .position(|card| *card.keyword() == keyword)
+        {
+            end = end.max(index + 1);
+        }
+    }
+    end
+}
@@ -0,0 +1,487 @@
+//! Distinguish the kinds of HDUs, and access them through typed wrappers.
+
+use std::fmt::Display;
+use std::ops::{Deref, DerefMut};
+
+use super::*;
+
+/// An enumeration of the kinds of HDUs, as indicated by the SIMPLE, XTENSION, GROUPS, and ZIMAGE cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HduKind {
+    /// A primary HDU containing an image, or no data.
+    Primary,
+    /// A primary HDU in the random groups format.
+    RandomGroups,
+    /// An IMAGE extension.
+    Image,
+    /// A TABLE extension, containing an ASCII table.
+    AsciiTable,
+    /// A BINTABLE extension, containing a binary table.
+    BinTable,
+    /// A BINTABLE extension containing a tile-compressed image.
+    CompressedImage,
+    /// A conforming extension of another type, such as FOREIGN or DUMP, with the value of XTENSION.
+    Conforming(String),
+    /// An HDU with neither a SIMPLE nor an XTENSION card.
+    Unknown,
+}
+
+impl Display for HduKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HduKind::Primary => write!(f, "primary"),
+            HduKind::RandomGroups => write!(f, "random groups"),
+            HduKind::Image => write!(f, "IMAGE"),
+            HduKind::AsciiTable => write!(f, "TABLE"),
+            HduKind::BinTable => write!(f, "BINTABLE"),
+            HduKind::CompressedImage => write!(f, "compressed image"),
+            HduKind::Conforming(xtension) => write!(f, "{}", xtension),
+            HduKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// An error returned when an HDU is not of the kind required by a typed wrapper.
+/// The HDU is returned within the error so that it is not lost.
+#[derive(Debug, Error)]
+#[error("expected {expected} HDU, found {found}")]
+pub struct HduKindError {
+    /// The kind of HDU required by the wrapper.
+    pub expected: HduKind,
+    /// The kind of the given HDU.
+    pub found: HduKind,
+    /// The given HDU.
+    pub hdu: Box<Hdu>,
+}
+
+impl Hdu {
+    /// Determines the kind of the HDU from its header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// assert_eq!(primary_hdu::default().kind(), HduKind::Primary);
+    /// assert_eq!(image_hdu::default().kind(), HduKind::Image);
+    /// assert_eq!(binary_table_hdu::default().kind(), HduKind::BinTable);
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(XTENSION_KEYWORD, String::from("FOREIGN"))?;
+    /// assert_eq!(hdu.kind(), HduKind::Conforming(String::from("FOREIGN")));
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn kind(&self) -> HduKind {
+        header_kind(&self.header)
+    }
+}
+
+/// Determines the kind of an HDU from the given header, without modifying it.
+pub(crate) fn header_kind(header: &FitsHeader) -> HduKind {
+    if header
+        .cards
+        .iter()
+        .any(|card| *card.keyword() == SIMPLE_KEYWORD)
+    {
+        let groups = header.peek_value::<bool>(GROUPS_KEYWORD).ok().flatten();
+        let naxis1 = header
+            .peek_value::<u32>(numbered_keyword(NAXIS_KEYWORD, 1))
+            .ok()
+            .flatten();
+        return if groups == Some(true) && naxis1 == Some(0) {
+            HduKind::RandomGroups
+        } else {
+            HduKind::Primary
+        };
+    }
+    let Ok(Some(xtension)) = header.peek_value::<String>(XTENSION_KEYWORD) else {
+        return HduKind::Unknown;
+    };
+    match xtension.trim() {
+        "IMAGE" => HduKind::Image,
+        "TABLE" => HduKind::AsciiTable,
+        "BINTABLE" => {
+            let zimage = header.peek_value::<bool>(ZIMAGE_KEYWORD).ok().flatten();
+            if zimage == Some(true) {
+                HduKind::CompressedImage
+            } else {
+                HduKind::BinTable
+            }
+        }
+        other => HduKind::Conforming(other.to_owned()),
+    }
+}
+
+macro_rules! typed_hdu {
+    ($(#[$attr: meta])* $name: ident => $kind: expr) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $name(Hdu);
+
+        impl $name {
+            /// Unwraps the underlying HDU.
+            pub fn into_inner(self) -> Hdu {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = Hdu;
+
+            fn deref(&self) -> &Hdu {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Hdu {
+                &mut self.0
+            }
+        }
+
+        impl From<$name> for Hdu {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TryFrom<Hdu> for $name {
+            type Error = HduKindError;
+
+            fn try_from(hdu: Hdu) -> Result<Self, Self::Error> {
+                let found = hdu.kind();
+                if found == $kind {
+                    Ok(Self(hdu))
+                } else {
+                    Err(HduKindError {
+                        expected: $kind,
+                        found,
+                        hdu: Box::new(hdu),
+                    })
+                }
+            }
+        }
+    };
+}
+
+typed_hdu!(
+    /// A primary HDU containing an image, or no data.
+    PrimaryHdu => HduKind::Primary
+);
+typed_hdu!(
+    /// An IMAGE extension.
+    ImageHdu => HduKind::Image
+);
+typed_hdu!(
+    /// A TABLE extension, containing an ASCII table.
+    AsciiTableHdu => HduKind::AsciiTable
+);
+typed_hdu!(
+    /// A BINTABLE extension, containing a binary table.
+    BinTableHdu => HduKind::BinTable
+);
+typed_hdu!(
+    /// A BINTABLE extension containing a tile-compressed image.
+    CompressedImageHdu => HduKind::CompressedImage
+);
+
+impl Default for PrimaryHdu {
+    fn default() -> Self {
+        Self(primary_hdu::default())
+    }
+}
+
+impl Default for ImageHdu {
+    fn default() -> Self {
+        Self(image_hdu::default())
+    }
+}
+
+impl Default for AsciiTableHdu {
+    fn default() -> Self {
+        Self(ascii_table_hdu::default())
+    }
+}
+
+impl Default for BinTableHdu {
+    fn default() -> Self {
+        Self(binary_table_hdu::default())
+    }
+}
+
+macro_rules! image_methods {
+    ($name: ident) => {
+        impl $name {
+            /// Gets the BITPIX value describing the pixels of the image.
+            pub fn bitpix(&mut self) -> Result<Bitpix, FitsHeaderError> {
+                self.header.bitpix()
+            }
+
+            /// Reads the pixels of the image, in the order of the data section, excluding padding.
+            /// Returns an error if BITPIX does not describe the pixel type, or if the data section is too short.
+            pub fn pixels<T: FitsDataElement>(&mut self) -> Result<Vec<T>, FitsHeaderError> {
+                if self.bitpix()? != T::BITPIX {
+                    return Err(FitsHeaderError::InvalidType);
+                }
+                let dimensions = self.get_dimensions();
+                let len = if dimensions.is_empty() {
+                    0
+                } else {
+                    dimensions.iter().product::<usize>()
+                };
+                let element_len = T::BITPIX.value() / 8;
+                let data_raw = self.data_raw();
+                let raw = data_raw.get(..len * element_len).ok_or_else(|| {
+                    FitsHeaderError::InvalidLength {
+                        expected: len * element_len,
+                        found: data_raw.len(),
+                        intent: String::from("image pixels"),
+                    }
+                })?;
+                Ok(raw
+                    .chunks_exact(element_len)
+                    .map(T::from_be_slice)
+                    .collect())
+            }
+
+            /// Sets the pixels of the image, along with the BITPIX, NAXIS, and NAXISn cards describing them.
+            /// The dimensions are given in the order of the NAXISn cards.
+            /// Returns an error if the number of pixels does not match the dimensions.
+            pub fn set_pixels<T: FitsDataElement>(
+                &mut self,
+                dimensions: &[usize],
+                pixels: &[T],
+            ) -> Result<(), FitsHeaderError> {
+                let len = if dimensions.is_empty() {
+                    0
+                } else {
+                    dimensions.iter().product::<usize>()
+                };
+                if pixels.len() != len {
+                    let element_len = T::BITPIX.value() / 8;
+                    return Err(FitsHeaderError::InvalidLength {
+                        expected: len * element_len,
+                        found: pixels.len() * element_len,
+                        intent: String::from("image pixels"),
+                    });
+                }
+                self.header.set_bitpix(T::BITPIX)?;
+                self.header.set_dimensions(dimensions)?;
+                let mut data_raw = Vec::with_capacity(len * T::BITPIX.value() / 8);
+                for pixel in pixels {
+                    pixel.extend_be_bytes(&mut data_raw);
+                }
+                self.set_data_raw(data_raw);
+                Ok(())
+            }
+        }
+    };
+}
+
+image_methods!(PrimaryHdu);
+image_methods!(ImageHdu);
+
+impl PrimaryHdu {
+    /// Converts the primary HDU into an IMAGE extension with the same image,
+    /// replacing SIMPLE with XTENSION, adding PCOUNT and GCOUNT, and removing EXTEND.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut primary = PrimaryHdu::default();
+    /// primary.set_pixels(&[2, 2], &[1i16, 2, 3, 4])?;
+    ///
+    /// let mut image = primary.into_image();
+    /// assert!(image.header.verify(1).is_empty());
+    /// assert_eq!(image.pixels::<i16>()?, [1, 2, 3, 4]);
+    ///
+    /// let mut primary = image.into_primary();
+    /// assert!(primary.header.verify(0).is_empty());
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn into_image(self) -> ImageHdu {
+        let mut hdu = self.0;
+        primary_to_image(&mut hdu.header);
+        ImageHdu(hdu)
+    }
+}
+
+impl ImageHdu {
+    /// Converts the IMAGE extension into a primary HDU with the same image,
+    /// replacing XTENSION with SIMPLE, and removing PCOUNT and GCOUNT.
+    pub fn into_primary(self) -> PrimaryHdu {
+        let mut hdu = self.0;
+        image_to_primary(&mut hdu.header);
+        PrimaryHdu(hdu)
+    }
+
+    /// Compresses the image into a tile-compressed image with the given options.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn compress(
+        &mut self,
+        options: &CompressionOptions,
+    ) -> Result<CompressedImageHdu, CompressionError> {
+        self.0.compress(options).map(CompressedImageHdu)
+    }
+}
+
+impl From<PrimaryHdu> for ImageHdu {
+    fn from(value: PrimaryHdu) -> Self {
+        value.into_image()
+    }
+}
+
+impl From<ImageHdu> for PrimaryHdu {
+    fn from(value: ImageHdu) -> Self {
+        value.into_primary()
+    }
+}
+
+macro_rules! table_methods {
+    ($name: ident) => {
+        impl $name {
+            /// Gets the number of rows of the table, given by NAXIS2.
+            pub fn num_rows(&mut self) -> usize {
+                self.get_dimensions().get(1).copied().unwrap_or_default()
+            }
+
+            /// Gets the length in bytes of each row of the table, given by NAXIS1.
+            pub fn row_len(&mut self) -> usize {
+                self.get_dimensions().first().copied().unwrap_or_default()
+            }
+
+            /// Gets the number of fields in each row of the table, given by TFIELDS.
+            pub fn num_fields(&mut self) -> usize {
+                self.header.tfields().unwrap_or_default() as usize
+            }
+        }
+    };
+}
+
+table_methods!(AsciiTableHdu);
+table_methods!(BinTableHdu);
+table_methods!(CompressedImageHdu);
+
+impl BinTableHdu {
+    /// Obtains the data in the column of the given name, or None if a column with the given name cannot be found.
+    pub fn column_by_name<T>(&mut self, name: &str) -> Option<Vec<T>> {
+        binary_table_hdu::column_by_name(&mut self.0, name)
+    }
+
+    /// Obtains the data in the column of the given index, or None if a column with the given index cannot be found.
+    /// Note that column indices start at 1.
+    pub fn column_by_index<T>(&mut self, index: u16) -> Option<Vec<T>> {
+        binary_table_hdu::column_by_index(&mut self.0, index)
+    }
+
+    /// Gets the bytes that follow the rows of the table, including the heap, as described by PCOUNT.
+    pub fn heap(&mut self) -> &[u8] {
+        let start = self.num_rows() * self.row_len();
+        let pcount = self.header.pcount().unwrap_or_default() as usize;
+        let data_raw = self.data_raw();
+        let start = start.min(data_raw.len());
+        &data_raw[start..(start + pcount).min(data_raw.len())]
+    }
+}
+
+impl CompressedImageHdu {
+    /// Gets the dimensions of the compressed image, given by the ZNAXISn cards.
+    pub fn image_dimensions(&mut self) -> Vec<usize> {
+        let znaxis = self
+            .header
+            .get_card(ZNAXIS_KEYWORD)
+            .and_then(|card| card.get_value::<u16>().ok())
+            .map_or(0, |znaxis| *znaxis);
+        let mut znaxisx_keyword = FitsHeaderKeyword::from(ZNAXIS_KEYWORD);
+        (1..=znaxis)
+            .map(|x| {
+                znaxisx_keyword.append_number(x);
+                self.header
+                    .get_card(znaxisx_keyword)
+                    .and_then(|card| card.get_value::<u32>().ok())
+                    .map_or(0, |znaxisx| *znaxisx as usize)
+            })
+            .collect()
+    }
+
+    /// Gets the name of the compression algorithm, given by ZCMPTYPE, or None if it is absent.
+    pub fn compression_type(&mut self) -> Option<String> {
+        self.header
+            .get_card(ZCMPTYPE_KEYWORD)
+            .and_then(|card| card.get_value::<String>().ok())
+            .map(|zcmptype| zcmptype.trim().to_owned())
+    }
+
+    /// Decompresses the image, returning an HDU with the original header and data.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    pub fn decompress(&mut self) -> Result<Hdu, CompressionError> {
+        self.0.decompress()
+    }
+}
+
+/// Converts the header of a primary HDU into the header of an IMAGE extension.
+pub(crate) fn primary_to_image(header: &mut FitsHeader) {
+    remove_padding(header);
+    header.cards.retain(|card| {
+        let keyword = card.keyword();
+        *keyword != SIMPLE_KEYWORD
+            && *keyword != EXTEND_KEYWORD
+            && *keyword != PCOUNT_KEYWORD
+            && *keyword != GCOUNT_KEYWORD
+    });
+    header
+        .cards
+        .insert(0, FitsHeaderCard::from(DEFAULT_IMAGE_XTENSION_BYTES));
+    let index = axes_end(header);
+    header
+        .cards
+        .insert(index, FitsHeaderCard::from(DEFAULT_GCOUNT_BYTES));
+    header
+        .cards
+        .insert(index, FitsHeaderCard::from(DEFAULT_PCOUNT_BYTES));
+}
+
+/// Converts the header of an IMAGE extension into the header of a primary HDU.
+pub(crate) fn image_to_primary(header: &mut FitsHeader) {
+    header.cards.retain(|card| {
+        let keyword = card.keyword();
+        *keyword != XTENSION_KEYWORD && *keyword != PCOUNT_KEYWORD && *keyword != GCOUNT_KEYWORD
+    });
+    header
+        .cards
+        .insert(0, FitsHeaderCard::from(DEFAULT_SIMPLE_BYTES));
+}
+
+/// Sets EXTEND to T in the given primary header, adding the card after the mandatory cards if it is absent.
+pub(crate) fn set_extend(header: &mut FitsHeader) -> Result<(), FitsHeaderError> {
+    match header.extend() {
+        Ok(Some(true)) => return Ok(()),
+        Ok(Some(false)) | Err(_) => return header.set_extend(true),
+        Ok(None) => {}
+    }
+    let mut index = axes_end(header);
+    while header.cards.get(index).is_some_and(|card| {
+        let keyword = card.keyword();
+        *keyword == GROUPS_KEYWORD || *keyword == PCOUNT_KEYWORD || *keyword == GCOUNT_KEYWORD
+    }) {
+        index += 1;
+    }
+    remove_padding(header);
+    header.insert_value(index, EXTEND_KEYWORD, true)
+}
+
+/// Removes the blank cards that follow END, which pad a header read from a file to a whole number of records,
+/// so that inserted cards do not cause the header to occupy another record.
+fn remove_padding(header: &mut FitsHeader) {
+    if let Some(end_index) = header
+        .cards
+        .iter()
+        .rposition(|card| *card.keyword() == END_KEYWORD)
+    {
+        header.cards.truncate(end_index + 1);
+    }
+}
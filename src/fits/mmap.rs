@@ -0,0 +1,317 @@
+//! Provides zero-copy access to memory-mapped FITS files.
+
+use std::fs::File;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use memmap2::{MmapMut, MmapOptions};
+
+use super::*;
+
+/// A representation of the entirety of a memory-mapped FITS file.
+/// Headers are deserialized when the file is opened, while data sections remain in the mapping
+/// and are only interpreted when an element is accessed.
+///
+/// The file is mapped copy-on-write, so converting a data section into native endianness
+/// never modifies the file on disk.
+#[derive(Debug)]
+pub struct MmapHduList {
+    mmap: MmapMut,
+    hdus: Vec<MmapHdu>,
+    read_issues: Vec<VerificationIssue>,
+}
+
+/// A Header Data Unit within a memory-mapped FITS file.
+#[derive(Debug, Clone)]
+pub struct MmapHdu {
+    /// The header section of the HDU.
+    pub header: FitsHeader,
+    data_offset: usize,
+    data_len: usize,
+    native: bool,
+}
+
+impl MmapHdu {
+    /// Gets the offset in bytes of the data section from the start of the file.
+    pub fn data_offset(&self) -> usize {
+        self.data_offset
+    }
+
+    /// Gets the length in bytes of the data section, excluding padding.
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Determines if the data section has been converted into native endianness.
+    pub fn is_native(&self) -> bool {
+        self.native
+    }
+}
+
+impl MmapHduList {
+    /// Memory-maps the file at the given path and deserializes the header of each HDU.
+    ///
+    /// # Safety
+    ///
+    /// The file is mapped into memory; the behavior is undefined if the file is modified
+    /// by another process while the mapping is alive.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self, FitsError> {
+        let io_error = |source| FitsError::Io {
+            source,
+            hdu_index: 0,
+            offset: 0,
+        };
+        let file = File::open(path).map_err(io_error)?;
+        // safety: the caller guarantees the file is not modified while mapped
+        let mmap = unsafe { MmapOptions::new().map_copy(&file).map_err(io_error)? };
+        let mut hdus = Vec::new();
+        let mut read_issues = Vec::new();
+        let mut offset = 0;
+        while offset < mmap.len() {
+            let hdu_index = hdus.len();
+            let mut remaining = &mmap[offset..];
+            let Some(header_raw) = read_header_raw(
+                &mut remaining,
+                hdu_index,
+                offset as u64,
+                ParseMode::Lenient,
+                &mut read_issues,
+            )?
+            else {
+                break;
+            };
+            // a short final header record is padded when reading leniently, so the data section
+            // may start beyond the end of the file
+            let data_offset = (offset + header_raw.len()).min(mmap.len());
+            let mut header = FitsHeader::from_bytes(header_raw);
+            let data_len =
+                try_unpadded_data_len(&mut header).map_err(|source| FitsError::Header {
+                    source,
+                    hdu_index,
+                    offset: offset as u64,
+                })?;
+            if data_offset + data_len > mmap.len() {
+                return Err(FitsError::Truncated {
+                    expected: data_len,
+                    found: mmap.len().saturating_sub(data_offset),
+                    intent: String::from("data section"),
+                    hdu_index,
+                    offset: data_offset as u64,
+                });
+            }
+            offset = data_offset + padded_len(data_len);
+            hdus.push(MmapHdu {
+                header,
+                data_offset,
+                data_len,
+                native: false,
+            });
+        }
+        Ok(MmapHduList {
+            mmap,
+            hdus,
+            read_issues,
+        })
+    }
+
+    /// Verifies the HDUs against the FITS standard.
+    /// The report includes deviations in the raw bytes that were found while opening the file,
+    /// as well as deviations in the structure of each header.
+    pub fn verify(&mut self) -> VerificationReport {
+        let headers = self.hdus.iter_mut().map(|hdu| &mut hdu.header);
+        verify_headers(self.read_issues.clone(), headers)
+    }
+
+    /// Gets the number of HDUs in the file.
+    pub fn len(&self) -> usize {
+        self.hdus.len()
+    }
+
+    /// Determines if the file contains no HDUs.
+    pub fn is_empty(&self) -> bool {
+        self.hdus.is_empty()
+    }
+
+    /// Retrieves the HDU at the given index, or None if an HDU doesn't exist at the index.
+    pub fn get_by_index(&mut self, index: usize) -> Option<&mut MmapHdu> {
+        self.hdus.get_mut(index)
+    }
+
+    /// Returns a mutable iterator over the HDUs.
+    pub fn iter_mut(&mut self) -> IterMut<'_, MmapHdu> {
+        self.hdus.iter_mut()
+    }
+
+    /// Gets the raw data section of the HDU at the given index, excluding padding.
+    /// If the data section has been converted into native endianness, the converted bytes are returned.
+    pub fn data_raw(&self, index: usize) -> Option<&[u8]> {
+        self.hdus
+            .get(index)
+            .map(|hdu| &self.mmap[hdu.data_offset..hdu.data_offset + hdu.data_len])
+    }
+
+    /// Creates a big-endian view over the data section of the HDU at the given index.
+    /// Returns None if an HDU doesn't exist at the index, if `T` does not match the BITPIX value of the HDU,
+    /// or if the data section has been converted into native endianness.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 3u32)?;
+    /// hdu.set_data(&vec![1i16, -2, 3]);
+    /// let path = std::env::temp_dir().join("astro_rs_data_view.fits");
+    /// std::fs::write(&path, hdu.to_bytes())?;
+    ///
+    /// let mut hdu_list = unsafe { MmapHduList::open(&path)? };
+    /// assert!(hdu_list.data_view::<f32>(0).is_none());
+    /// let view = hdu_list.data_view::<i16>(0).unwrap();
+    /// assert_eq!(view.len(), 3);
+    /// assert_eq!(view.get(1), Some(-2));
+    /// assert_eq!(view.iter().collect::<Vec<i16>>(), vec![1, -2, 3]);
+    ///
+    /// assert_eq!(hdu_list.to_native::<i16>(0).unwrap(), &mut [1, -2, 3]);
+    /// assert!(hdu_list.data_view::<i16>(0).is_none());
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn data_view<T: FitsDataElement>(&mut self, index: usize) -> Option<FitsDataView<'_, T>> {
+        let hdu = self.hdus.get_mut(index)?;
+        if hdu.native || !Self::matches_bitpix::<T>(hdu) {
+            return None;
+        }
+        Some(FitsDataView {
+            raw: &self.mmap[hdu.data_offset..hdu.data_offset + hdu.data_len],
+            phantom: PhantomData,
+        })
+    }
+
+    /// Converts the data section of the HDU at the given index into native endianness in place,
+    /// then returns it as a mutable slice. Subsequent calls return the converted slice without repeating the conversion.
+    /// Returns None without converting the data section if an HDU doesn't exist at the index,
+    /// if `T` does not match the BITPIX value of the HDU, or if the data section is not aligned for `T`.
+    pub fn to_native<T: FitsDataElement>(&mut self, index: usize) -> Option<&mut [T]> {
+        let hdu = self.hdus.get_mut(index)?;
+        if !Self::matches_bitpix::<T>(hdu) {
+            return None;
+        }
+        let raw = &mut self.mmap[hdu.data_offset..hdu.data_offset + hdu.data_len];
+        // safety: FitsDataElement guarantees every bit pattern is a valid value
+        let (prefix, _, suffix) = unsafe { raw.align_to::<T>() };
+        // the data section is left untouched if it cannot be viewed as a slice of T
+        if !prefix.is_empty() || !suffix.is_empty() {
+            return None;
+        }
+        if !hdu.native {
+            if cfg!(target_endian = "little") {
+                for chunk in raw.chunks_exact_mut(std::mem::size_of::<T>()) {
+                    chunk.reverse();
+                }
+            }
+            hdu.native = true;
+        }
+        // safety: FitsDataElement guarantees every bit pattern is a valid value, and the alignment was checked above
+        let (_, values, _) = unsafe { raw.align_to_mut::<T>() };
+        Some(values)
+    }
+
+    /// Attempts to create a new FitsDataCollection from the data section of the HDU at the given index.
+    /// The collection is deserialized directly from the mapping.
+    /// Returns None if an HDU doesn't exist at the index, or if the data section has been converted into native endianness.
+    pub fn get_data<T: FitsDataCollection>(
+        &self,
+        index: usize,
+    ) -> Option<Result<T, FitsHeaderError>> {
+        if self.hdus.get(index)?.native {
+            return None;
+        }
+        self.data_raw(index).map(T::from_bytes)
+    }
+
+    /// Copies the HDU at the given index into an HDU that is independent of the mapping.
+    /// If the data section has been converted into native endianness, it is converted back to big-endian.
+    pub fn to_hdu(&self, index: usize) -> Option<Hdu> {
+        let mmap_hdu = self.hdus.get(index)?;
+        let mut header = mmap_hdu.header.clone();
+        let mut data_raw = self.data_raw(index)?.to_vec();
+        if mmap_hdu.native && cfg!(target_endian = "little") {
+            let bitpix = header
+                .get_card(BITPIX_KEYWORD)
+                .and_then(|card| card.get_value::<Bitpix>().ok())?;
+            for chunk in data_raw.chunks_exact_mut(bitpix.value() / 8) {
+                chunk.reverse();
+            }
+        }
+        data_raw.resize(padded_len(data_raw.len()), 0);
+        let mut hdu = Hdu::new();
+        hdu.header = header;
+        hdu.set_data_raw(data_raw);
+        Some(hdu)
+    }
+
+    fn matches_bitpix<T: FitsDataElement>(hdu: &mut MmapHdu) -> bool {
+        hdu.header
+            .get_card(BITPIX_KEYWORD)
+            .and_then(|card| card.get_value::<Bitpix>().ok())
+            .is_some_and(|bitpix| *bitpix == T::BITPIX)
+    }
+}
+
+/// A borrowed view over a big-endian data section. Elements are deserialized as they are accessed.
+#[derive(Debug, Clone, Copy)]
+pub struct FitsDataView<'a, T> {
+    raw: &'a [u8],
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T: FitsDataElement> FitsDataView<'a, T> {
+    /// Constructs a view over the given big-endian bytes. Trailing bytes that do not form a whole element are ignored.
+    pub fn new(raw: &'a [u8]) -> Self {
+        let len = raw.len() - raw.len() % std::mem::size_of::<T>();
+        FitsDataView {
+            raw: &raw[..len],
+            phantom: PhantomData,
+        }
+    }
+
+    /// Gets the number of elements in the view.
+    pub fn len(&self) -> usize {
+        self.raw.len() / std::mem::size_of::<T>()
+    }
+
+    /// Determines if the view contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Deserializes the element at the given index, or returns None if the index is out of bounds.
+    pub fn get(&self, index: usize) -> Option<T> {
+        let size = std::mem::size_of::<T>();
+        let start = index.checked_mul(size)?;
+        self.raw
+            .get(start..start + size)
+            .map(|raw| T::from_be_slice(raw))
+    }
+
+    /// Returns an iterator that deserializes each element of the view.
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        self.raw
+            .chunks_exact(std::mem::size_of::<T>())
+            .map(|raw| T::from_be_slice(raw))
+    }
+
+    /// Gets the big-endian bytes underlying the view.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Attempts to create a new FitsDataCollection from the bytes underlying the view.
+    pub fn to_collection<C: FitsDataCollection>(&self) -> Result<C, FitsHeaderError> {
+        C::from_bytes(self.raw)
+    }
+}
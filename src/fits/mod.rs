@@ -1,34 +1,283 @@
 //! Serialize and deserialize FITS data.
 //! See <https://archive.stsci.edu/fits/fits_standard/fits_standard.html> for the FITS API.
 
+#[cfg(feature = "ndarray")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ndarray")))]
+mod array;
+mod checksum;
+#[cfg(feature = "compression")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+mod compression;
+mod diff;
+mod file;
+mod groups;
 mod hdu_types;
 mod header;
 mod header_value;
+mod info;
+mod keywords;
+mod kind;
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+mod mmap;
+mod section;
+mod stream;
+mod text;
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+mod time;
+#[cfg(feature = "units")]
+#[cfg_attr(docsrs, doc(cfg(feature = "units")))]
+mod units;
+mod update;
+mod verify;
+#[cfg(feature = "wcs")]
+#[cfg_attr(docsrs, doc(cfg(feature = "wcs")))]
+mod wcs;
 
+use std::cell::{OnceCell, RefCell};
 use std::fmt::Debug;
-use std::io::{BufReader, BufWriter, Cursor, Read, Write};
+use std::io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
 use std::slice::IterMut;
 
+use groups::is_random_groups;
+use kind::{image_to_primary, primary_to_image, set_extend};
+use thiserror::Error;
+use update::HduOrigin;
+
+#[cfg(feature = "ndarray")]
+pub use array::*;
+pub use checksum::*;
+#[cfg(feature = "compression")]
+pub use compression::*;
+pub use diff::*;
+pub use file::*;
+pub use groups::*;
 pub use hdu_types::*;
 pub use header::*;
 pub use header_value::*;
+pub use info::*;
+pub use keywords::*;
+pub use kind::*;
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+pub use section::*;
+pub use stream::*;
+pub use text::*;
+#[cfg(feature = "time")]
+pub use time::*;
+#[cfg(feature = "units")]
+pub use units::*;
+pub use verify::*;
+#[cfg(feature = "wcs")]
+pub use wcs::*;
 
 /// The expected keyword for the name of an extension.
 pub const EXTNAME_KEYWORD: [u8; 8] = *b"EXTNAME ";
-const BLANK_KEYWORD: [u8; 8] = *b"        ";
+const BLANK_CARD_KEYWORD: [u8; 8] = *b"        ";
 
 /// A representation of the entirety of a FITS file.
 #[derive(Debug)]
 pub struct HduList<R> {
-    reader: BufReader<R>,
+    reader: Rc<RefCell<BufReader<R>>>,
+    source: Option<Rc<RefCell<dyn DataSource>>>,
     hdus: Vec<Hdu>,
+    hdus_read: usize,
+    position: u64,
+    parse_mode: ParseMode,
+    normalize_keywords: bool,
+    read_issues: Vec<VerificationIssue>,
+    failure: Option<FitsError>,
+    // the bytes consumed by a read that failed and could not be rewound, which are written as they were read
+    unread: Vec<u8>,
+    extensions: ExtensionIndex,
+    verify_checksums: bool,
+    update_checksums: bool,
+    #[cfg(feature = "compression")]
+    decompress_images: bool,
+    #[cfg(feature = "compression")]
+    write_compression: Option<CompressionOptions>,
+}
+
+/// An enumeration of errors that could occur when reading a FITS file.
+/// Each error reports the index of the HDU within the file, and the byte offset from the start of the stream
+/// at which the affected section begins.
+#[derive(Debug, Error)]
+pub enum FitsError {
+    /// Indicates an I/O error occurred while reading an HDU.
+    #[error("I/O error in HDU {hdu_index} at byte offset {offset}: {source}")]
+    Io {
+        /// The underlying I/O error.
+        source: std::io::Error,
+        /// The index of the affected HDU.
+        hdu_index: usize,
+        /// The byte offset of the affected section.
+        offset: u64,
+    },
+    /// Indicates the stream ended before a header or data section was complete.
+    #[error(
+        "unexpected end of stream in HDU {hdu_index} at byte offset {offset} - expected {expected} bytes for {intent}, found {found}"
+    )]
+    Truncated {
+        /// The number of bytes expected by the operation.
+        expected: usize,
+        /// The number of bytes found by the operation.
+        found: usize,
+        /// The objective of the operation.
+        intent: String,
+        /// The index of the affected HDU.
+        hdu_index: usize,
+        /// The byte offset of the affected section.
+        offset: u64,
+    },
+    /// Indicates a header could not be processed.
+    #[error("invalid header in HDU {hdu_index} at byte offset {offset}: {source}")]
+    Header {
+        /// The underlying header error.
+        source: FitsHeaderError,
+        /// The index of the affected HDU.
+        hdu_index: usize,
+        /// The byte offset of the affected header.
+        offset: u64,
+    },
+    /// Indicates an HDU does not conform to the FITS standard while reading in [`ParseMode::Strict`].
+    #[error("HDU {hdu_index} at byte offset {offset} does not conform to the FITS standard: {}", issues.iter().map(|issue| issue.message.as_str()).collect::<Vec<_>>().join("; "))]
+    Invalid {
+        /// The deviations with [`Severity::Error`] that were found.
+        issues: Vec<VerificationIssue>,
+        /// The index of the affected HDU.
+        hdu_index: usize,
+        /// The byte offset of the affected header.
+        offset: u64,
+    },
+    /// Indicates a tile-compressed image could not be decompressed.
+    #[cfg(feature = "compression")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[error("failed to decompress HDU {hdu_index} at byte offset {offset}: {source}")]
+    Compression {
+        /// The underlying compression error.
+        source: CompressionError,
+        /// The index of the affected HDU.
+        hdu_index: usize,
+        /// The byte offset of the affected header.
+        offset: u64,
+    },
+}
+
+impl FitsError {
+    /// Creates an equivalent error, recreating an I/O error from its kind and message.
+    fn replay(&self) -> Self {
+        match self {
+            FitsError::Io {
+                source,
+                hdu_index,
+                offset,
+            } => FitsError::Io {
+                source: std::io::Error::new(source.kind(), source.to_string()),
+                hdu_index: *hdu_index,
+                offset: *offset,
+            },
+            FitsError::Truncated {
+                expected,
+                found,
+                intent,
+                hdu_index,
+                offset,
+            } => FitsError::Truncated {
+                expected: *expected,
+                found: *found,
+                intent: intent.clone(),
+                hdu_index: *hdu_index,
+                offset: *offset,
+            },
+            FitsError::Header {
+                source,
+                hdu_index,
+                offset,
+            } => FitsError::Header {
+                source: source.clone(),
+                hdu_index: *hdu_index,
+                offset: *offset,
+            },
+            FitsError::Invalid {
+                issues,
+                hdu_index,
+                offset,
+            } => FitsError::Invalid {
+                issues: issues.clone(),
+                hdu_index: *hdu_index,
+                offset: *offset,
+            },
+            #[cfg(feature = "compression")]
+            FitsError::Compression {
+                source,
+                hdu_index,
+                offset,
+            } => FitsError::Compression {
+                source: source.replay(),
+                hdu_index: *hdu_index,
+                offset: *offset,
+            },
+        }
+    }
 }
 
 impl Default for HduList<Cursor<Vec<u8>>> {
     fn default() -> Self {
-        Self {
-            reader: BufReader::new(Cursor::new(Vec::new())),
-            hdus: Default::default(),
+        Self::new(BufReader::new(Cursor::new(Vec::new())))
+    }
+}
+
+impl<R: Read + Seek + 'static> HduList<R> {
+    /// Constructs an empty HduList that defers reading data sections until they are accessed.
+    /// While searching for an HDU, the data section of each preceding HDU is skipped with `seek`,
+    /// and only its offset and length are recorded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 3u32)?;
+    /// hdu.set_data(&vec![1i32, 2, 3]);
+    /// let mut bytes = hdu.to_bytes();
+    /// bytes.append(&mut image_hdu::default().to_bytes());
+    ///
+    /// let mut hdu_list = HduList::new_lazy(BufReader::new(Cursor::new(bytes)));
+    /// assert!(hdu_list.get_by_index(1).is_some());
+    ///
+    /// let primary_hdu = hdu_list.first_mut().unwrap();
+    /// assert!(!primary_hdu.is_data_loaded());
+    /// assert_eq!(primary_hdu.get_data::<Vec<i32>>()?[0..3], [1, 2, 3]);
+    /// assert!(primary_hdu.is_data_loaded());
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn new_lazy(reader: BufReader<R>) -> Self {
+        let reader = Rc::new(RefCell::new(reader));
+        let source: Rc<RefCell<dyn DataSource>> = reader.clone();
+        HduList {
+            reader,
+            source: Some(source),
+            hdus: Vec::new(),
+            hdus_read: 0,
+            position: 0,
+            parse_mode: ParseMode::default(),
+            normalize_keywords: false,
+            read_issues: Vec::new(),
+            failure: None,
+            unread: Vec::new(),
+            extensions: ExtensionIndex::default(),
+            verify_checksums: false,
+            update_checksums: false,
+            #[cfg(feature = "compression")]
+            decompress_images: false,
+            #[cfg(feature = "compression")]
+            write_compression: None,
         }
     }
 }
@@ -37,12 +286,28 @@ impl<R: Read> HduList<R> {
     /// Constructs an empty HduList.
     pub fn new(reader: BufReader<R>) -> Self {
         HduList {
-            reader,
+            reader: Rc::new(RefCell::new(reader)),
+            source: None,
             hdus: Vec::new(),
+            hdus_read: 0,
+            position: 0,
+            parse_mode: ParseMode::default(),
+            normalize_keywords: false,
+            read_issues: Vec::new(),
+            failure: None,
+            unread: Vec::new(),
+            extensions: ExtensionIndex::default(),
+            verify_checksums: false,
+            update_checksums: false,
+            #[cfg(feature = "compression")]
+            decompress_images: false,
+            #[cfg(feature = "compression")]
+            write_compression: None,
         }
     }
 
     /// Retrieves the HDU at the given index, or None if an HDU doesn't exist at the index.
+    /// Errors encountered while reading are treated as the end of the list; see [`HduList::try_get_by_index`] to handle them.
     ///
     /// # Examples
     ///
@@ -65,8 +330,51 @@ impl<R: Read> HduList<R> {
         Some(&mut self.hdus[index])
     }
 
+    /// Retrieves the HDU at the given index, or None if an HDU doesn't exist at the index.
+    /// An error is returned if the HDU list cannot be read up to the index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut bytes = primary_hdu::default().to_bytes();
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F32)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 1000u32)?;
+    /// hdu.set_data(&vec![0.0f32; 1000]);
+    /// bytes.append(&mut hdu.to_bytes());
+    /// bytes.truncate(bytes.len() - 1000);
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes)));
+    /// // missing padding is only filled when reading leniently
+    /// hdu_list.set_parse_mode(ParseMode::Strict);
+    /// assert!(hdu_list.try_get_by_index(0)?.is_some());
+    /// match hdu_list.try_get_by_index(1) {
+    ///     Err(FitsError::Truncated { hdu_index, offset, found, .. }) => {
+    ///         assert_eq!(hdu_index, 1);
+    ///         assert_eq!(offset, 5760);
+    ///         assert_eq!(found, 4760);
+    ///     }
+    ///     result => panic!("unexpected result: {:?}", result),
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn try_get_by_index(&mut self, index: usize) -> Result<Option<&mut Hdu>, FitsError> {
+        while self.hdus.len() <= index {
+            match self.try_read_hdu()? {
+                Some(new_hdu) => self.hdus.push(new_hdu),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(&mut self.hdus[index]))
+    }
+
     /// Retrieves the HDU with the given value for the `EXTNAME` keyword, or None if an HDU
-    /// with the given name doesn't exist.
+    /// with the given name doesn't exist. Names are compared case-insensitively.
+    /// Errors encountered while reading are treated as the end of the list; see [`HduList::try_get_by_name`] to handle them.
     ///
     /// # Examples
     ///
@@ -90,31 +398,89 @@ impl<R: Read> HduList<R> {
     /// img_hdu.header.cards.insert(img_hdu.header.cards.len() - 1, name_card);
     /// hdu_list.push(img_hdu);
     /// assert!(hdu_list.get_by_name("hdu_name").is_some());
+    /// assert!(hdu_list.get_by_name("HDU_NAME").is_some());
     /// ```
     pub fn get_by_name(&mut self, name: &str) -> Option<&mut Hdu> {
-        let index = self
-            .hdus
-            .iter_mut()
-            .position(|hdu| hdu.get_name() == name)
-            .or_else(|| {
-                let mut index = self.hdus.len();
-                loop {
-                    let mut new_hdu = self.read_hdu()?;
-                    if new_hdu.get_name() == name {
-                        self.hdus.push(new_hdu);
-                        break;
-                    }
+        self.try_get_by_name(name).ok().flatten()
+    }
 
-                    self.hdus.push(new_hdu);
-                    index += 1;
-                }
-                Some(index)
-            });
+    /// Retrieves the HDU with the given value for the `EXTNAME` keyword, or None if an HDU
+    /// with the given name doesn't exist.
+    /// An error is returned if the HDU list cannot be read up to the matching HDU.
+    pub fn try_get_by_name(&mut self, name: &str) -> Result<Option<&mut Hdu>, FitsError> {
+        let index = self.find_extension(name, None)?;
+        Ok(index.map(|index| &mut self.hdus[index]))
+    }
+
+    /// Retrieves the HDU with the given values for the `EXTNAME` and `EXTVER` keywords,
+    /// or None if such an HDU doesn't exist. Names are compared case-insensitively,
+    /// and an HDU without an `EXTVER` card has version 1.
+    /// Errors encountered while reading are treated as the end of the list; see [`HduList::try_get_by_name_ver`] to handle them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// for ver in 1..=2u32 {
+    ///     let mut img_hdu = image_hdu::default();
+    ///     img_hdu.header.set_extname(String::from("SCI"))?;
+    ///     img_hdu.header.set_extver(ver)?;
+    ///     hdu_list.push(img_hdu);
+    /// }
+    ///
+    /// let hdu = hdu_list.get_by_name_ver("sci", 2).unwrap();
+    /// assert_eq!(hdu.header.extver()?, 2);
+    /// assert!(hdu_list.get_by_name_ver("SCI", 3).is_none());
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn get_by_name_ver(&mut self, name: &str, ver: u32) -> Option<&mut Hdu> {
+        self.try_get_by_name_ver(name, ver).ok().flatten()
+    }
+
+    /// Retrieves the HDU with the given values for the `EXTNAME` and `EXTVER` keywords,
+    /// or None if such an HDU doesn't exist.
+    /// An error is returned if the HDU list cannot be read up to the matching HDU.
+    pub fn try_get_by_name_ver(
+        &mut self,
+        name: &str,
+        ver: u32,
+    ) -> Result<Option<&mut Hdu>, FitsError> {
+        let index = self.find_extension(name, Some(ver))?;
+        Ok(index.map(|index| &mut self.hdus[index]))
+    }
 
-        Some(&mut self.hdus[index?])
+    /// Finds the position of the first HDU matching the given name and version,
+    /// deserializing further HDUs as necessary.
+    fn find_extension(&mut self, name: &str, ver: Option<u32>) -> Result<Option<usize>, FitsError> {
+        if let Some(index) = self.indexed_extension(name, ver) {
+            return Ok(Some(index));
+        }
+        while let Some(new_hdu) = self.try_read_hdu()? {
+            self.hdus.push(new_hdu);
+            if let Some(index) = self.indexed_extension(name, ver) {
+                return Ok(Some(index));
+            }
+        }
+        // the cards of an HDU may have been changed since it was indexed
+        self.extensions.rebuild(&self.hdus);
+        Ok(self.extensions.position(name, ver))
+    }
+
+    /// Looks up the given name and version in the extension index, rebuilding the index if the HDU found no longer matches.
+    fn indexed_extension(&mut self, name: &str, ver: Option<u32>) -> Option<usize> {
+        let index = self.extensions.position(name, ver)?;
+        if self.hdus[index].is_extension(name, ver) {
+            return Some(index);
+        }
+        self.extensions.rebuild(&self.hdus);
+        self.extensions.position(name, ver)
     }
 
     /// Returns a mutable pointer to the first HDU, or `None` if the list is empty.
+    /// Errors encountered while reading are treated as an empty list; see [`HduList::try_first_mut`] to handle them.
     ///
     /// # Examples
     ///
@@ -135,7 +501,14 @@ impl<R: Read> HduList<R> {
         Some(&mut self.hdus[0])
     }
 
+    /// Returns a mutable pointer to the first HDU, or `None` if the list is empty.
+    /// An error is returned if the first HDU cannot be read.
+    pub fn try_first_mut(&mut self) -> Result<Option<&mut Hdu>, FitsError> {
+        self.try_get_by_index(0)
+    }
+
     /// Deserializes all HDUs if necessary, then returns a mutable iterator over the HDUs.
+    /// Errors encountered while reading are treated as the end of the list; see [`HduList::try_iter_mut`] to handle them.
     ///
     /// # Examples
     ///
@@ -148,12 +521,7 @@ impl<R: Read> HduList<R> {
     /// // find the primary HDU
     /// assert!(hdu_list
     ///     .iter_mut()
-    ///     .find_map(|hdu| if hdu.header.get_card(SIMPLE_KEYWORD).is_some() {
-    ///         Some(hdu)
-    ///     } else {
-    ///         None
-    ///     })
-    ///     .is_some())
+    ///     .any(|hdu| hdu.kind() == HduKind::Primary))
     /// ```
     pub fn iter_mut(&mut self) -> IterMut<'_, Hdu> {
         while let Some(new_hdu) = self.read_hdu() {
@@ -162,6 +530,32 @@ impl<R: Read> HduList<R> {
         self.hdus.iter_mut()
     }
 
+    /// Deserializes all HDUs if necessary, then returns a mutable iterator over the HDUs.
+    /// An error is returned if any HDU cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut bytes = primary_hdu::default().to_bytes();
+    /// bytes.append(&mut b"XTENSION= 'IMAGE   '".to_vec());
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes)));
+    /// assert!(matches!(
+    ///     hdu_list.try_iter_mut(),
+    ///     Err(FitsError::Truncated { hdu_index: 1, offset: 2880, .. })
+    /// ));
+    /// assert_eq!(hdu_list.iter_mut().count(), 1);
+    /// ```
+    pub fn try_iter_mut(&mut self) -> Result<IterMut<'_, Hdu>, FitsError> {
+        while let Some(new_hdu) = self.try_read_hdu()? {
+            self.hdus.push(new_hdu);
+        }
+        Ok(self.hdus.iter_mut())
+    }
+
     /// Deserializes all HDUs up to `index` if necessary, then inserts the given `hdu`.
     ///
     /// # Panics
@@ -187,16 +581,106 @@ impl<R: Read> HduList<R> {
     /// assert_eq!(hdu_list.iter_mut().count(), 2);
     /// ```
     pub fn insert(&mut self, index: usize, hdu: Hdu) {
-        let mut cur_hdus = self.hdus.len();
-        while cur_hdus < index {
-            if let Some(new_hdu) = self.read_hdu() {
-                self.hdus.push(new_hdu);
-                cur_hdus += 1;
-            } else {
-                panic!("{} is out of bounds (max {})", index, cur_hdus);
-            }
-        }
+        self.read_hdus_to(index);
         self.hdus.insert(index, hdu);
+        self.extensions.rebuild(&self.hdus);
+    }
+
+    /// Deserializes all HDUs up to `index` if necessary, then removes and returns the HDU at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// hdu_list.push(image_hdu::default());
+    /// assert_eq!(hdu_list.remove(1).kind(), HduKind::Image);
+    /// assert_eq!(hdu_list.iter_mut().count(), 1);
+    /// ```
+    pub fn remove(&mut self, index: usize) -> Hdu {
+        self.read_hdus_to(index + 1);
+        let hdu = self.hdus.remove(index);
+        self.extensions.rebuild(&self.hdus);
+        hdu
+    }
+
+    /// Deserializes all HDUs up to `index` if necessary, then replaces the HDU at `index` with the given `hdu`,
+    /// returning the HDU that was replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// hdu_list.push(image_hdu::default());
+    /// let mut old_hdu = hdu_list.replace(1, binary_table_hdu::default());
+    /// assert_eq!(old_hdu.kind(), HduKind::Image);
+    /// assert_eq!(hdu_list.get_by_index(1).unwrap().kind(), HduKind::BinTable);
+    /// ```
+    pub fn replace(&mut self, index: usize, hdu: Hdu) -> Hdu {
+        self.read_hdus_to(index + 1);
+        let hdu = std::mem::replace(&mut self.hdus[index], hdu);
+        self.extensions.rebuild(&self.hdus);
+        hdu
+    }
+
+    /// Deserializes all HDUs up to the greater of `a` and `b` if necessary, then swaps the HDUs at `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// hdu_list.push(image_hdu::default());
+    /// hdu_list.push(binary_table_hdu::default());
+    /// hdu_list.swap(1, 2);
+    /// assert_eq!(hdu_list.get_by_index(1).unwrap().kind(), HduKind::BinTable);
+    /// assert_eq!(hdu_list.get_by_index(2).unwrap().kind(), HduKind::Image);
+    /// ```
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.read_hdus_to(a.max(b) + 1);
+        self.hdus.swap(a, b);
+        self.extensions.rebuild(&self.hdus);
+    }
+
+    /// Deserializes all HDUs if necessary, then retains only the HDUs for which the given predicate returns true.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(primary_hdu::default());
+    /// hdu_list.push(image_hdu::default());
+    /// hdu_list.push(binary_table_hdu::default());
+    /// hdu_list.retain(|hdu| hdu.kind() != HduKind::Image);
+    /// assert_eq!(hdu_list.iter_mut().count(), 2);
+    /// ```
+    pub fn retain<F: FnMut(&mut Hdu) -> bool>(&mut self, f: F) {
+        while let Some(new_hdu) = self.read_hdu() {
+            self.hdus.push(new_hdu);
+        }
+        self.hdus.retain_mut(f);
+        self.extensions.rebuild(&self.hdus);
     }
 
     /// Appends `hdu` to the end of the HDU list.
@@ -216,10 +700,18 @@ impl<R: Read> HduList<R> {
         while let Some(new_hdu) = self.read_hdu() {
             self.hdus.push(new_hdu);
         }
+        self.extensions.insert(self.hdus.len(), &hdu.header);
         self.hdus.push(hdu);
     }
 
     /// Writes the HDU list via the given writer.
+    /// The HDUs that have not yet been read are read first, and bytes that cannot be read as an HDU are written as they are.
+    ///
+    /// The HDUs are written so that the first is a primary HDU and the others are extensions, without modifying the HDU list.
+    /// An IMAGE extension at the start of the list is written as a primary HDU,
+    /// and an empty primary HDU is written before any other extension at the start of the list.
+    /// Primary HDUs later in the list are written as IMAGE extensions,
+    /// and EXTEND is set to T in the primary HDU if extensions follow it.
     ///
     /// # Examples
     ///
@@ -234,13 +726,49 @@ impl<R: Read> HduList<R> {
     /// hdu_list.write(&mut out_writer)?;
     /// assert_eq!(out_writer.get_ref().get_ref(), &SIMPLE_KEYWORD.to_vec());
     ///
+    /// // an image at the start of the list is written as the primary HDU
+    /// let mut hdu_list = HduList::default();
+    /// hdu_list.push(image_hdu::default());
+    /// let mut out_writer = BufWriter::new(Cursor::new(Vec::new()));
+    /// hdu_list.write(&mut out_writer)?;
+    /// assert_eq!(out_writer.get_ref().get_ref()[..8], SIMPLE_KEYWORD);
+    /// assert_eq!(hdu_list.first_mut().unwrap().kind(), HduKind::Image);
+    ///
     /// # Ok::<(), std::io::Error>(())
     /// ```
     pub fn write<W: Write>(&mut self, writer: &mut BufWriter<W>) -> Result<(), std::io::Error> {
-        for hdu in &self.hdus {
-            writer.write_all(&hdu.clone().to_bytes())?;
+        while let Some(new_hdu) = self.read_hdu() {
+            self.hdus.push(new_hdu);
+        }
+        let primary = self
+            .hdus
+            .first_mut()
+            .is_some_and(needs_primary)
+            .then(primary_hdu::default);
+        let len = self.hdus.len() + usize::from(primary.is_some());
+        let hdus = primary.iter().chain(&self.hdus);
+        for (index, hdu) in hdus.enumerate() {
+            let mut hdu = hdu.clone();
+            fix_position(&mut hdu, index, len).map_err(std::io::Error::other)?;
+            hdu.try_data_raw().map_err(std::io::Error::other)?;
+            #[cfg(feature = "compression")]
+            if let Some(options) = &self.write_compression {
+                let is_image = hdu
+                    .header
+                    .get_card(XTENSION_KEYWORD)
+                    .and_then(|card| card.get_value::<String>().ok())
+                    .is_some_and(|xtension| xtension.as_str() == "IMAGE");
+                if is_image && !hdu.get_dimensions().is_empty() {
+                    hdu = hdu.compress(options).map_err(std::io::Error::other)?;
+                }
+            }
+            if self.update_checksums {
+                hdu.update_checksum().map_err(std::io::Error::other)?;
+            }
+            writer.write_all(&hdu.to_bytes())?;
         }
-        std::io::copy(&mut self.reader, writer)?;
+        writer.write_all(&self.unread)?;
+        std::io::copy(&mut *self.reader.borrow_mut(), writer)?;
         writer.flush()?;
         Ok(())
     }
@@ -278,74 +806,548 @@ impl<R: Read> HduList<R> {
             .unwrap_or_default())
     }
 
+    /// Deserializes HDUs until at least `len` HDUs have been read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HDU list contains fewer than `len` HDUs.
+    fn read_hdus_to(&mut self, len: usize) {
+        while self.hdus.len() < len {
+            if let Some(new_hdu) = self.read_hdu() {
+                self.hdus.push(new_hdu);
+            } else {
+                panic!("{} is out of bounds (max {})", len, self.hdus.len());
+            }
+        }
+    }
+
+    /// Arranges the HDUs so that the first is a primary HDU and the others are extensions.
+    /// An IMAGE extension at the start of the list becomes a primary HDU,
+    /// and an empty primary HDU is inserted before any other extension at the start of the list.
+    /// Primary HDUs later in the list become IMAGE extensions,
+    /// and EXTEND is set to T in the primary HDU if extensions follow it.
+    /// The HDUs that have not yet been read are not considered.
+    pub(crate) fn fix_structure(&mut self) -> Result<(), FitsHeaderError> {
+        if self.hdus.first_mut().is_some_and(needs_primary) {
+            self.hdus.insert(0, primary_hdu::default());
+            self.extensions.rebuild(&self.hdus);
+        }
+        let len = self.hdus.len();
+        for (index, hdu) in self.hdus.iter_mut().enumerate() {
+            fix_position(hdu, index, len)?;
+        }
+        Ok(())
+    }
+
     fn read_hdu(&mut self) -> Option<Hdu> {
-        let mut header_raw = Vec::new();
-        let mut new_header_bytes = vec![0; FITS_RECORD_LEN];
-        self.reader.read_exact(&mut new_header_bytes).ok()?;
-        header_raw.append(&mut new_header_bytes);
+        self.try_read_hdu().ok().flatten()
+    }
 
-        // search for the END keyword.
-        // this should be the last keyword in the header, so if something other than ' ' is found, stop searching
-        loop {
-            let mut end_found = false;
-            for card in 1..=FITS_RECORD_LEN / HEADER_CARD_LEN {
-                let card_index = header_raw.len() - card * HEADER_CARD_LEN;
-                match header_raw[card_index..card_index + HEADER_KEYWORD_LEN]
-                    .try_into()
-                    .unwrap()
-                {
-                    END_KEYWORD => {
-                        end_found = true;
-                        break;
-                    }
-                    BLANK_KEYWORD => continue,
-                    _ => {
-                        end_found = false;
-                        break;
+    /// Reads the next HDU from the stream.
+    /// If reading fails, a seekable source is rewound to the start of the HDU, so that it can be read again;
+    /// otherwise the stream cannot be resumed, and the error is returned by every later attempt.
+    fn try_read_hdu(&mut self) -> Result<Option<Hdu>, FitsError> {
+        if let Some(failure) = &self.failure {
+            return Err(failure.replay());
+        }
+        let start = match &self.source {
+            Some(source) => Some(
+                source
+                    .borrow_mut()
+                    .offset()
+                    .map_err(|source| FitsError::Io {
+                        source,
+                        hdu_index: self.hdus_read,
+                        offset: self.position,
+                    })?,
+            ),
+            None => None,
+        };
+        self.unread.clear();
+        let result = self.read_next_hdu();
+        if let Err(err) = &result {
+            let rewound = match (&self.source, start) {
+                (Some(source), Some(start)) => source.borrow_mut().seek_to(start).is_ok(),
+                _ => false,
+            };
+            if !rewound {
+                self.failure = Some(err.replay());
+                return result;
+            }
+        }
+        self.unread.clear();
+        if let Ok(Some(hdu)) = &result {
+            // every HDU that is read is pushed to the end of the list
+            self.extensions.insert(self.hdus.len(), &hdu.header);
+        }
+        result
+    }
+
+    fn read_next_hdu(&mut self) -> Result<Option<Hdu>, FitsError> {
+        let hdu_index = self.hdus_read;
+        let header_offset = self.position;
+        let lenient = self.parse_mode == ParseMode::Lenient;
+        let mut issues = Vec::new();
+        let Some(mut header_raw) = read_header_raw(
+            &mut Recorder {
+                reader: &mut *self.reader.borrow_mut(),
+                bytes: &mut self.unread,
+            },
+            hdu_index,
+            header_offset,
+            self.parse_mode,
+            &mut issues,
+        )?
+        else {
+            return Ok(None);
+        };
+        let header_sum = ones_complement_sum(&header_raw);
+        // the layout of HDUs read from a seekable source is recorded so that they can be updated in place
+        let original_header = self.source.is_some().then(|| header_raw.clone());
+        issues.append(&mut verify_header_raw(&header_raw, hdu_index));
+        if lenient && self.normalize_keywords {
+            issues.append(&mut normalize_keywords(&mut header_raw, hdu_index));
+        }
+        let data_offset = header_offset + header_raw.len() as u64;
+        let mut header = FitsHeader::from_bytes(header_raw);
+        let structural_issues = if lenient {
+            Vec::new()
+        } else {
+            header.verify(hdu_index)
+        };
+        let unpadded_len =
+            try_unpadded_data_len(&mut header).map_err(|source| FitsError::Header {
+                source,
+                hdu_index,
+                offset: header_offset,
+            })?;
+        let data_len = padded_len(unpadded_len);
+        let truncated = |found| FitsError::Truncated {
+            expected: data_len,
+            found,
+            intent: String::from("data section"),
+            hdu_index,
+            offset: data_offset,
+        };
+        let missing_padding = |found| {
+            VerificationIssue::warning(
+                hdu_index,
+                None,
+                format!(
+                    "{} bytes of data padding are missing at the end of the stream",
+                    data_len - found
+                ),
+            )
+        };
+
+        let mut hdu = if let Some(source) = &self.source {
+            let (offset, found) = source
+                .borrow_mut()
+                .skip(data_len as u64)
+                .map_err(|source| FitsError::Io {
+                    source,
+                    hdu_index,
+                    offset: data_offset,
+                })?;
+            let found = found as usize;
+            if found < data_len {
+                if !lenient || found < unpadded_len {
+                    return Err(truncated(found));
+                }
+                issues.push(missing_padding(found));
+            }
+            Hdu {
+                header,
+                data_raw: OnceCell::new(),
+                deferred: Some(DeferredData {
+                    source: Rc::clone(source),
+                    offset,
+                    len: found,
+                    hdu_index,
+                }),
+                origin: original_header.map(|header_raw| HduOrigin {
+                    header_offset,
+                    header_raw,
+                    data_offset: offset,
+                    data_len,
+                    data_found: found,
+                    data_modified: false,
+                    source: Rc::downgrade(source),
+                }),
+            }
+        } else {
+            let mut data_raw = vec![0; data_len];
+            let found =
+                read_fully(&mut *self.reader.borrow_mut(), &mut data_raw).map_err(|source| {
+                    FitsError::Io {
+                        source,
+                        hdu_index,
+                        offset: data_offset,
                     }
+                })?;
+            if found < data_len {
+                if !lenient || found < unpadded_len {
+                    self.unread.extend_from_slice(&data_raw[..found]);
+                    return Err(truncated(found));
                 }
+                issues.push(missing_padding(found));
+            } else {
+                issues.append(&mut verify_data_padding(
+                    &mut header,
+                    &data_raw[unpadded_len..],
+                    hdu_index,
+                ));
             }
-            if end_found {
-                break;
+            Hdu {
+                header,
+                data_raw: OnceCell::from(data_raw),
+                deferred: None,
+                origin: None,
             }
-            new_header_bytes = vec![0; FITS_RECORD_LEN];
-            self.reader.read_exact(&mut new_header_bytes).ok()?;
-            header_raw.append(&mut new_header_bytes);
+        };
+
+        if self.verify_checksums {
+            let datasum = ones_complement_sum(hdu.try_data_raw()?);
+            issues.append(&mut verify_checksums(
+                &mut hdu.header,
+                header_sum,
+                datasum,
+                hdu_index,
+            ));
         }
 
-        let mut header = FitsHeader::from_bytes(header_raw);
-        let mut data_raw = Vec::new();
-
-        let naxis = *header
-            .get_card(NAXIS_KEYWORD)
-            .and_then(|card| card.get_value::<u16>().ok())
-            .unwrap_or_default();
-        if naxis != 0 {
-            if let Some(bitpix) = header
-                .get_card(BITPIX_KEYWORD)
-                .and_then(|card| card.get_value::<Bitpix>().ok())
+        if !lenient {
+            let mut errors: Vec<VerificationIssue> = issues
+                .iter()
+                .chain(structural_issues.iter())
+                .filter(|issue| issue.severity == Severity::Error)
+                .cloned()
+                .collect();
+            if !errors.is_empty() {
+                errors.sort_by_key(|issue| issue.card_index);
+                self.record_unread_data(&hdu);
+                return Err(FitsError::Invalid {
+                    issues: errors,
+                    hdu_index,
+                    offset: header_offset,
+                });
+            }
+        }
+        #[cfg(feature = "compression")]
+        if self.decompress_images && hdu.is_compressed_image() {
+            match hdu.decompress() {
+                Ok(decompressed) => hdu = decompressed,
+                Err(source) => {
+                    self.record_unread_data(&hdu);
+                    return Err(FitsError::Compression {
+                        source,
+                        hdu_index,
+                        offset: header_offset,
+                    });
+                }
+            }
+        }
+        self.read_issues.append(&mut issues);
+        self.hdus_read += 1;
+        self.position = data_offset + data_len as u64;
+        Ok(Some(hdu))
+    }
+
+    // keeps the data section of an HDU that was read in full but failed to validate
+    fn record_unread_data(&mut self, hdu: &Hdu) {
+        if self.source.is_none() {
+            self.unread.extend_from_slice(hdu.data_raw());
+        }
+    }
+}
+
+/// A reader that keeps a copy of the bytes read through it.
+struct Recorder<'a, R> {
+    reader: &'a mut R,
+    bytes: &'a mut Vec<u8>,
+}
+
+impl<R: Read> Read for Recorder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.bytes.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Reads from the given reader until the buffer is full or the reader is exhausted,
+/// returning the number of bytes read.
+pub(crate) fn read_fully<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+    let mut found = 0;
+    while found < buf.len() {
+        match reader.read(&mut buf[found..]) {
+            Ok(0) => break,
+            Ok(n) => found += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(found)
+}
+
+/// Reads the raw bytes of a header from the given reader, or None if the reader is exhausted.
+/// In [`ParseMode::Lenient`], a final record that ends after the END card is filled with blanks,
+/// and the deviation is recorded in `issues`.
+pub(crate) fn read_header_raw<R: Read>(
+    reader: &mut R,
+    hdu_index: usize,
+    offset: u64,
+    parse_mode: ParseMode,
+    issues: &mut Vec<VerificationIssue>,
+) -> Result<Option<Vec<u8>>, FitsError> {
+    let mut header_raw = Vec::new();
+    loop {
+        let mut new_header_bytes = vec![0; FITS_RECORD_LEN];
+        let found = read_fully(reader, &mut new_header_bytes).map_err(|source| FitsError::Io {
+            source,
+            hdu_index,
+            offset,
+        })?;
+        if found == 0 && header_raw.is_empty() {
+            return Ok(None);
+        }
+        if found < FITS_RECORD_LEN {
+            let end_found = new_header_bytes[..found]
+                .chunks_exact(HEADER_CARD_LEN)
+                .any(|card| card[0..HEADER_KEYWORD_LEN] == END_KEYWORD);
+            if parse_mode == ParseMode::Lenient && end_found {
+                new_header_bytes[found..].fill(b' ');
+                header_raw.append(&mut new_header_bytes);
+                issues.push(VerificationIssue::warning(
+                    hdu_index,
+                    None,
+                    format!(
+                        "{} bytes of header padding are missing at the end of the stream",
+                        FITS_RECORD_LEN - found
+                    ),
+                ));
+                return Ok(Some(header_raw));
+            }
+            return Err(FitsError::Truncated {
+                expected: header_raw.len() + FITS_RECORD_LEN,
+                found: header_raw.len() + found,
+                intent: String::from("header"),
+                hdu_index,
+                offset,
+            });
+        }
+        header_raw.append(&mut new_header_bytes);
+
+        // search for the END keyword.
+        // this should be the last keyword in the header, so if something other than ' ' is found, stop searching
+        let mut end_found = false;
+        for card in 1..=FITS_RECORD_LEN / HEADER_CARD_LEN {
+            let card_index = header_raw.len() - card * HEADER_CARD_LEN;
+            match header_raw[card_index..card_index + HEADER_KEYWORD_LEN]
+                .try_into()
+                .unwrap()
             {
-                let mut data_len = 1;
-                let mut naxisx_keyword = FitsHeaderKeyword::from(NAXIS_KEYWORD);
-                for x in 1..=naxis {
-                    naxisx_keyword.append_number(x);
-
-                    let naxisx = *header
-                        .get_card(naxisx_keyword)
-                        .and_then(|card| card.get_value::<u32>().ok())
-                        .unwrap_or_default() as usize;
-                    data_len *= naxisx;
+                END_KEYWORD => {
+                    end_found = true;
+                    break;
                 }
-                data_len *= bitpix.value() / 8;
-                if data_len % FITS_RECORD_LEN != 0 {
-                    let num_records = (data_len / FITS_RECORD_LEN) + 1;
-                    data_len = num_records * FITS_RECORD_LEN;
+                BLANK_CARD_KEYWORD => continue,
+                _ => {
+                    end_found = false;
+                    break;
                 }
-                data_raw = vec![0; data_len];
-                let _ = self.reader.read_exact(&mut data_raw);
             }
         }
-        Some(Hdu { header, data_raw })
+        if end_found {
+            return Ok(Some(header_raw));
+        }
+    }
+}
+
+/// Calculates the length in bytes of the data section described by the given header, including any heap, excluding padding.
+/// The length is |BITPIX| × GCOUNT × (PCOUNT + NAXIS1 × ... × NAXISn) bits for extensions of any type and random groups,
+/// and |BITPIX| × NAXIS1 × ... × NAXISn bits for other primary HDUs.
+/// Returns an error if a card required to determine the length is missing or invalid,
+/// or if the padded length cannot be represented.
+pub(crate) fn try_unpadded_data_len(header: &mut FitsHeader) -> Result<usize, FitsHeaderError> {
+    let naxis = header.naxis()?;
+    // NAXIS1 is 0 in the random groups format, and each group has PCOUNT parameters and an array of the other axes
+    let random_groups = is_random_groups(header);
+    let first_axis = if random_groups { 2 } else { 1 };
+    let mut data_len = usize::from(naxis > 0);
+    for x in first_axis..=naxis {
+        data_len = data_len
+            .checked_mul(header.naxisn(x)? as usize)
+            .ok_or(FitsHeaderError::DataLengthOverflow)?;
+    }
+    // each of the GCOUNT groups has PCOUNT values in addition to its array, such as the heap of an extension
+    if random_groups || header.get_card(XTENSION_KEYWORD).is_some() {
+        let pcount = header.pcount()? as usize;
+        let gcount = header.gcount()? as usize;
+        data_len = data_len
+            .checked_add(pcount)
+            .and_then(|len| len.checked_mul(gcount))
+            .ok_or(FitsHeaderError::DataLengthOverflow)?;
+    }
+    if data_len == 0 {
+        return Ok(0);
+    }
+    let bitpix = header.bitpix()?;
+    data_len
+        .checked_mul(bitpix.value() / 8)
+        // the length is padded to a whole number of records
+        .filter(|len| len.checked_add(FITS_RECORD_LEN).is_some())
+        .ok_or(FitsHeaderError::DataLengthOverflow)
+}
+
+/// Rounds the given length up to a multiple of the FITS record length.
+pub(crate) fn padded_len(len: usize) -> usize {
+    if len % FITS_RECORD_LEN != 0 {
+        let num_records = (len / FITS_RECORD_LEN) + 1;
+        num_records * FITS_RECORD_LEN
+    } else {
+        len
+    }
+}
+
+/// Gets the value of the given card as a number, whether it was written as an integer or a float.
+pub(crate) fn numeric_value(card: &mut FitsHeaderCard) -> Option<f64> {
+    card.get_value::<i32>()
+        .map(|value| *value as f64)
+        .or_else(|_| card.get_value::<u32>().map(|value| *value as f64))
+        .or_else(|_| card.get_value::<f64>().map(|value| *value))
+        .ok()
+}
+
+/// Determines if an empty primary HDU must be inserted before the given HDU for it to start an HDU list.
+fn needs_primary(hdu: &mut Hdu) -> bool {
+    !matches!(
+        hdu.kind(),
+        HduKind::Primary | HduKind::RandomGroups | HduKind::Unknown | HduKind::Image
+    )
+}
+
+/// Converts the given HDU into a primary HDU or an extension, as its position in an HDU list of `len` HDUs requires.
+fn fix_position(hdu: &mut Hdu, index: usize, len: usize) -> Result<(), FitsHeaderError> {
+    if index > 0 {
+        if hdu.kind() == HduKind::Primary {
+            primary_to_image(&mut hdu.header);
+        }
+        return Ok(());
+    }
+    if hdu.kind() == HduKind::Image {
+        image_to_primary(&mut hdu.header);
+    }
+    if len > 1 && hdu.kind() != HduKind::Unknown {
+        set_extend(&mut hdu.header)?;
+    }
+    Ok(())
+}
+
+/// Reads the big-endian elements of the given type as numbers, ignoring trailing bytes that do not form a whole element.
+pub(crate) fn stored_values(raw: &[u8], bitpix: Bitpix) -> Vec<f64> {
+    fn read<T: FitsDataElement + Into<f64>>(raw: &[u8]) -> Vec<f64> {
+        raw.chunks_exact(std::mem::size_of::<T>())
+            .map(|raw| T::from_be_slice(raw).into())
+            .collect()
+    }
+    match bitpix {
+        Bitpix::U8 => read::<u8>(raw),
+        Bitpix::I16 => read::<i16>(raw),
+        Bitpix::I32 => read::<i32>(raw),
+        // 64 bit integers are rounded to the nearest representable number
+        Bitpix::I64 => raw
+            .chunks_exact(8)
+            .map(|raw| i64::from_be_slice(raw) as f64)
+            .collect(),
+        Bitpix::F32 => read::<f32>(raw),
+        Bitpix::F64 => read::<f64>(raw),
+    }
+}
+
+/// A random-access source from which deferred data sections can be read.
+pub(crate) trait DataSource {
+    /// Skips over the given number of bytes, returning the offset at which the skipped section started
+    /// and the number of bytes that were available to skip.
+    fn skip(&mut self, len: u64) -> Result<(u64, u64), std::io::Error>;
+
+    /// Fills the given buffer with the bytes starting at the given offset.
+    /// The position of the source is restored afterwards.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), std::io::Error>;
+
+    /// Gets the current offset of the source.
+    fn offset(&mut self) -> Result<u64, std::io::Error>;
+
+    /// Moves the source to the given offset.
+    fn seek_to(&mut self, offset: u64) -> Result<(), std::io::Error>;
+}
+
+impl<R: Read + Seek> DataSource for BufReader<R> {
+    fn skip(&mut self, len: u64) -> Result<(u64, u64), std::io::Error> {
+        let offset = self.stream_position()?;
+        let end = self.seek(SeekFrom::End(0))?;
+        self.seek(SeekFrom::Start(offset + len))?;
+        Ok((offset, end.saturating_sub(offset).min(len)))
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), std::io::Error> {
+        let position = self.stream_position()?;
+        self.seek(SeekFrom::Start(offset))?;
+        let result = self.read_exact(buf);
+        self.seek(SeekFrom::Start(position))?;
+        result
+    }
+
+    fn offset(&mut self) -> Result<u64, std::io::Error> {
+        self.stream_position()
+    }
+
+    fn seek_to(&mut self, offset: u64) -> Result<(), std::io::Error> {
+        self.seek(SeekFrom::Start(offset)).map(|_| ())
+    }
+}
+
+impl Debug for dyn DataSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DataSource")
+    }
+}
+
+/// The location of a data section that has not yet been read.
+#[derive(Debug, Clone)]
+struct DeferredData {
+    source: Rc<RefCell<dyn DataSource>>,
+    offset: u64,
+    len: usize,
+    hdu_index: usize,
+}
+
+impl DeferredData {
+    fn load(&self) -> Result<Vec<u8>, FitsError> {
+        let mut data_raw = vec![0; self.len];
+        self.source
+            .borrow_mut()
+            .read_at(self.offset, &mut data_raw)
+            .map_err(|source| FitsError::Io {
+                source,
+                hdu_index: self.hdu_index,
+                offset: self.offset,
+            })?;
+        // padding missing from the end of the stream is filled when the section is loaded
+        data_raw.resize(padded_len(self.len), 0);
+        Ok(data_raw)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> Result<(), FitsError> {
+        let offset = self.offset + offset as u64;
+        self.source
+            .borrow_mut()
+            .read_at(offset, buf)
+            .map_err(|source| FitsError::Io {
+                source,
+                hdu_index: self.hdu_index,
+                offset,
+            })
     }
 }
 
@@ -354,7 +1356,9 @@ impl<R: Read> HduList<R> {
 pub struct Hdu {
     /// The header section of the HDU.
     pub header: FitsHeader,
-    data_raw: Vec<u8>,
+    data_raw: OnceCell<Vec<u8>>,
+    deferred: Option<DeferredData>,
+    origin: Option<HduOrigin>,
 }
 
 impl Hdu {
@@ -364,9 +1368,15 @@ impl Hdu {
     }
 
     /// Serializes the contents of the HDU to bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a deferred data section cannot be read.
     pub fn to_bytes(mut self) -> Vec<u8> {
+        self.data_raw();
+        let mut data_raw = self.data_raw.take().unwrap_or_default();
         let mut result = self.header.to_bytes();
-        result.append(&mut self.data_raw);
+        result.append(&mut data_raw);
         let remainder = result.len() % FITS_RECORD_LEN;
         if remainder != 0 {
             let num_cards = (result.len() / FITS_RECORD_LEN) + 1;
@@ -396,49 +1406,72 @@ impl Hdu {
             .unwrap_or_default()
     }
 
+    /// Determines if the HDU has the given `EXTNAME`, compared case-insensitively,
+    /// and, if given, the given `EXTVER`.
+    fn is_extension(&self, name: &str, ver: Option<u32>) -> bool {
+        let (extname, extver) = extension_key(&self.header);
+        !extname.is_empty()
+            && extname.eq_ignore_ascii_case(name.trim())
+            && ver.is_none_or(|ver| extver == ver)
+    }
+
     /// Gets the data section of the HDU.
+    /// If the data section has been deferred, it is read from the source of the HDU list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a deferred data section cannot be read. See [`Hdu::try_data_raw`] to handle the error.
     pub fn data_raw(&self) -> &Vec<u8> {
-        &self.data_raw
+        match self.try_data_raw() {
+            Ok(data_raw) => data_raw,
+            Err(err) => panic!("failed to read deferred data section: {err}"),
+        }
+    }
+
+    /// Gets the data section of the HDU.
+    /// If the data section has been deferred, it is read from the source of the HDU list,
+    /// and any error that occurs is returned.
+    pub fn try_data_raw(&self) -> Result<&Vec<u8>, FitsError> {
+        if let Some(data_raw) = self.data_raw.get() {
+            return Ok(data_raw);
+        }
+        let data_raw = match &self.deferred {
+            Some(deferred) => deferred.load()?,
+            None => Vec::new(),
+        };
+        Ok(self.data_raw.get_or_init(|| data_raw))
     }
 
     /// Sets the data section of the HDU.
     pub fn set_data_raw(&mut self, data_raw: Vec<u8>) {
-        self.data_raw = data_raw;
+        self.data_raw = OnceCell::from(data_raw);
+        self.deferred = None;
+        if let Some(origin) = &mut self.origin {
+            origin.data_modified = true;
+        }
+    }
+
+    /// Determines if the data section of the HDU has been read into memory.
+    pub fn is_data_loaded(&self) -> bool {
+        self.data_raw.get().is_some()
     }
 
     /// Attempts to create a new FitsDataCollection from the data section of the HDU.
     pub fn get_data<T: FitsDataCollection>(&self) -> Result<T, FitsHeaderError> {
-        T::from_bytes(&self.data_raw)
+        T::from_bytes(self.data_raw())
     }
 
     /// Sets the data section of the HDU.
     pub fn set_data<T: FitsDataCollection>(&mut self, data: &T) {
-        self.data_raw = data.to_bytes();
+        self.set_data_raw(data.to_bytes());
     }
 
     /// Creates a Vec containing the dimensions of the data section of the HDU as defined by the NAXIS keywords.
     pub fn get_dimensions(&mut self) -> Vec<usize> {
-        let naxis = *self
-            .header
-            .get_card(NAXIS_KEYWORD)
-            .and_then(|card| card.get_value::<u16>().ok())
-            .unwrap_or_default();
-        if naxis == 0 {
-            return Vec::new();
-        }
-        let mut result = Vec::with_capacity(naxis as usize);
-        let mut naxisx_keyword = FitsHeaderKeyword::from(NAXIS_KEYWORD);
-        for x in 1..=naxis {
-            naxisx_keyword.append_number(x);
-
-            let naxisx = *self
-                .header
-                .get_card(naxisx_keyword)
-                .and_then(|card| card.get_value::<u32>().ok())
-                .unwrap_or_default() as usize;
-            result.push(naxisx);
-        }
-        result
+        let naxis = self.header.naxis().unwrap_or_default();
+        (1..=naxis)
+            .map(|n| self.header.naxisn(n).unwrap_or_default() as usize)
+            .collect()
     }
 }
 
@@ -499,6 +1532,24 @@ impl FitsDataCollection for Vec<i32> {
     }
 }
 
+impl FitsDataCollection for Vec<i64> {
+    fn from_bytes(raw: &[u8]) -> Result<Self, FitsHeaderError> {
+        let mut data = Vec::with_capacity(raw.len() / 8);
+        for chunk in raw.chunks_exact(8) {
+            data.push(i64::from_be_bytes(chunk.try_into().unwrap()));
+        }
+        Ok(data)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.len() * 8);
+        for chunk in self {
+            data.extend_from_slice(&chunk.to_be_bytes());
+        }
+        data
+    }
+}
+
 impl FitsDataCollection for Vec<f32> {
     fn from_bytes(raw: &[u8]) -> Result<Self, FitsHeaderError> {
         let mut data = Vec::with_capacity(raw.len() / 4);
@@ -535,6 +1586,62 @@ impl FitsDataCollection for Vec<f64> {
     }
 }
 
+mod sealed {
+    /// Prevents [`FitsDataElement`](super::FitsDataElement) from being implemented outside of this crate.
+    pub trait Sealed {}
+}
+
+/// A trait that allows a single value to be serialized/deserialized as an element of the data section of an HDU.
+/// The trait is sealed; it is implemented for the element types of each BITPIX value.
+///
+/// # Safety
+///
+/// Implementors must be plain numeric types without padding, for which every bit pattern is a valid value,
+/// as data sections may be reinterpreted in place as slices of the implementing type.
+pub unsafe trait FitsDataElement: sealed::Sealed + Copy + Debug + Default + 'static {
+    /// The BITPIX value describing elements of this type.
+    const BITPIX: Bitpix;
+
+    /// Deserializes a value from the given big-endian bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `raw` is not equal to the size of the type.
+    fn from_be_slice(raw: &[u8]) -> Self;
+
+    /// Serializes the value as big-endian bytes, appending them to the given buffer.
+    fn extend_be_bytes(&self, buf: &mut Vec<u8>);
+}
+
+macro_rules! impl_fits_data_element {
+    ($($value_type: ty => $bitpix: expr),+ $(,)?) => {
+        $(
+            impl sealed::Sealed for $value_type {}
+
+            unsafe impl FitsDataElement for $value_type {
+                const BITPIX: Bitpix = $bitpix;
+
+                fn from_be_slice(raw: &[u8]) -> Self {
+                    <$value_type>::from_be_bytes(raw.try_into().unwrap())
+                }
+
+                fn extend_be_bytes(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_be_bytes());
+                }
+            }
+        )+
+    };
+}
+
+impl_fits_data_element!(
+    u8 => Bitpix::U8,
+    i16 => Bitpix::I16,
+    i32 => Bitpix::I32,
+    i64 => Bitpix::I64,
+    f32 => Bitpix::F32,
+    f64 => Bitpix::F64,
+);
+
 #[macro_use]
 pub(crate) mod hdu_macros {
     /// Creates a box of the given value and casts it to an implicit return type.
@@ -0,0 +1,316 @@
+//! Read sections of images without reading their entire data sections.
+
+use std::ops::Range;
+
+use thiserror::Error;
+
+use super::*;
+
+const CRPIX_PREFIX: &str = "CRPIX";
+const CDELT_PREFIX: &str = "CDELT";
+const CD_PREFIX: &str = "CD";
+/// The suffixes of the primary and alternate world coordinate systems.
+const WCS_SUFFIXES: &str = " ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// An enumeration of errors that could occur when reading a section of an image.
+#[derive(Debug, Error)]
+pub enum SectionError {
+    /// Indicates the header of the image could not be processed.
+    #[error(transparent)]
+    Header(#[from] FitsHeaderError),
+    /// Indicates the data section of the image could not be read.
+    #[error(transparent)]
+    Read(#[from] FitsError),
+    /// Indicates the number of ranges does not match the number of axes of the image.
+    #[error("expected a range for each of the {expected} axes, found {found}")]
+    Dimensions {
+        /// The number of axes of the image.
+        expected: usize,
+        /// The number of ranges given.
+        found: usize,
+    },
+    /// Indicates a range is empty, has a step of zero, or exceeds the length of its axis.
+    #[error("invalid range {start}..{stop} with step {step} for axis {axis} of length {len}")]
+    InvalidRange {
+        /// The number of the axis, starting at 1.
+        axis: usize,
+        /// The first index of the range.
+        start: usize,
+        /// The index after the end of the range.
+        stop: usize,
+        /// The step between selected indices.
+        step: usize,
+        /// The length of the axis.
+        len: usize,
+    },
+}
+
+/// A range of zero-based indices along one axis of an image, selecting every `step`th index from `start` up to `stop`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisRange {
+    /// The first index of the range.
+    pub start: usize,
+    /// The index after the end of the range.
+    pub stop: usize,
+    /// The step between selected indices.
+    pub step: usize,
+}
+
+impl AxisRange {
+    /// Constructs a range selecting every index from `start` up to `stop`.
+    pub fn new(start: usize, stop: usize) -> Self {
+        AxisRange {
+            start,
+            stop,
+            step: 1,
+        }
+    }
+
+    /// Sets the step between selected indices.
+    pub fn with_step(mut self, step: usize) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Gets the number of indices selected by the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// assert_eq!(AxisRange::new(2, 10).len(), 8);
+    /// assert_eq!(AxisRange::new(2, 10).with_step(3).len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        if self.step == 0 {
+            0
+        } else {
+            self.stop.saturating_sub(self.start).div_ceil(self.step)
+        }
+    }
+
+    /// Determines if the range does not select any index.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Range<usize>> for AxisRange {
+    fn from(range: Range<usize>) -> Self {
+        AxisRange::new(range.start, range.end)
+    }
+}
+
+impl Hdu {
+    /// Reads a section of the image in the data section of the HDU, given a range for each axis in the order of the NAXISn keywords.
+    /// If the data section has been deferred, only the bytes within the section are read.
+    ///
+    /// The returned HDU has the data of the section, and a copy of the header with updated NAXISn cards.
+    /// The CRPIXn cards of the primary and alternate world coordinate systems are shifted to the origin of the section,
+    /// and the CDELTn and CDi_n cards are scaled by the step of each range. Checksums are removed from the copied header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 10u32)?;
+    /// hdu.header.set_value(*b"NAXIS2  ", 10u32)?;
+    /// hdu.header.set_value(*b"CRPIX1  ", 5.0)?;
+    /// hdu.set_data(&(0..100u8).collect::<Vec<u8>>());
+    ///
+    /// let mut section = hdu.section(&[AxisRange::new(2, 8).with_step(2), (4..6).into()])?;
+    /// assert_eq!(section.get_dimensions(), vec![3, 2]);
+    /// assert_eq!(section.get_data::<Vec<u8>>()?[..6], [42, 44, 46, 52, 54, 56]);
+    /// let crpix1 = section.header.get_card(*b"CRPIX1  ").unwrap().get_value::<f64>()?;
+    /// assert_eq!(*crpix1, 2.0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn section(&mut self, ranges: &[AxisRange]) -> Result<Hdu, SectionError> {
+        let dimensions = self.get_dimensions();
+        if ranges.len() != dimensions.len() {
+            return Err(SectionError::Dimensions {
+                expected: dimensions.len(),
+                found: ranges.len(),
+            });
+        }
+        for (axis, (range, len)) in ranges.iter().zip(&dimensions).enumerate() {
+            if range.is_empty() || range.stop > *len {
+                return Err(SectionError::InvalidRange {
+                    axis: axis + 1,
+                    start: range.start,
+                    stop: range.stop,
+                    step: range.step,
+                    len: *len,
+                });
+            }
+        }
+        let bitpix = *self
+            .header
+            .get_card(BITPIX_KEYWORD)
+            .ok_or(FitsHeaderError::MissingKeyword {
+                keyword: BITPIX_KEYWORD.into(),
+            })?
+            .get_value::<Bitpix>()?;
+        let element_len = bitpix.value() / 8;
+
+        let mut data =
+            Vec::with_capacity(ranges.iter().map(AxisRange::len).product::<usize>() * element_len);
+        if let Some((first, others)) = ranges.split_first() {
+            let mut strides = Vec::with_capacity(others.len());
+            let mut stride = element_len;
+            for len in &dimensions[..others.len()] {
+                stride *= len;
+                strides.push(stride);
+            }
+            // each run spans the selected indices of the first axis
+            let mut run = vec![0; ((first.len() - 1) * first.step + 1) * element_len];
+            let mut indices = vec![0; others.len()];
+            'runs: loop {
+                let offset = first.start * element_len
+                    + others
+                        .iter()
+                        .zip(&indices)
+                        .zip(&strides)
+                        .map(|((range, index), stride)| (range.start + index * range.step) * stride)
+                        .sum::<usize>();
+                self.read_data_at(offset, &mut run)?;
+                if first.step == 1 {
+                    data.extend_from_slice(&run);
+                } else {
+                    for element in run.chunks(first.step * element_len) {
+                        data.extend_from_slice(&element[..element_len]);
+                    }
+                }
+
+                for (range, index) in others.iter().zip(indices.iter_mut()) {
+                    *index += 1;
+                    if *index < range.len() {
+                        continue 'runs;
+                    }
+                    *index = 0;
+                }
+                break;
+            }
+        }
+
+        let mut header = self.header.clone();
+        header.cards.retain(|card| {
+            *card.keyword() != CHECKSUM_KEYWORD && *card.keyword() != DATASUM_KEYWORD
+        });
+        let mut naxisx_keyword = FitsHeaderKeyword::from(NAXIS_KEYWORD);
+        for (axis, range) in ranges.iter().enumerate() {
+            naxisx_keyword.append_number(axis as u16 + 1);
+            header.set_value(naxisx_keyword, range.len() as u32)?;
+        }
+        update_wcs(&mut header, ranges)?;
+
+        let mut hdu = Hdu::new();
+        hdu.header = header;
+        hdu.set_data_raw(data);
+        Ok(hdu)
+    }
+
+    /// Fills the given buffer with the bytes of the data section starting at the given offset,
+    /// reading from the source of a deferred data section if it has not been loaded.
+    fn read_data_at(&self, offset: usize, buf: &mut [u8]) -> Result<(), FitsError> {
+        if let (None, Some(deferred)) = (self.data_raw.get(), &self.deferred) {
+            return deferred.read_at(offset, buf);
+        }
+        let data_raw = self.try_data_raw()?;
+        match data_raw.get(offset..offset + buf.len()) {
+            Some(bytes) => {
+                buf.copy_from_slice(bytes);
+                Ok(())
+            }
+            None => Err(FitsError::Truncated {
+                expected: offset + buf.len(),
+                found: data_raw.len(),
+                intent: String::from("image section"),
+                hdu_index: 0,
+                offset: 0,
+            }),
+        }
+    }
+}
+
+impl<R: Read> HduList<R> {
+    /// Reads a section of the image in the HDU at the given index, or None if an HDU doesn't exist at the index.
+    /// See [`Hdu::section`] for details.
+    /// For a list constructed with [`HduList::new_lazy`], only the bytes within the section are read.
+    pub fn section(
+        &mut self,
+        index: usize,
+        ranges: &[AxisRange],
+    ) -> Result<Option<Hdu>, SectionError> {
+        match self.try_get_by_index(index)? {
+            Some(hdu) => hdu.section(ranges).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Shifts the reference pixels of each world coordinate system to the origin of the section,
+/// and scales the pixel increments by the step of each range.
+fn update_wcs(header: &mut FitsHeader, ranges: &[AxisRange]) -> Result<(), FitsHeaderError> {
+    for suffix in WCS_SUFFIXES.chars() {
+        for (axis, range) in ranges.iter().enumerate() {
+            let axis = axis + 1;
+            if let Some(keyword) = wcs_keyword(CRPIX_PREFIX, axis, None, suffix) {
+                update_card(header, keyword, |crpix| {
+                    (crpix - range.start as f64 - 1.0) / range.step as f64 + 1.0
+                })?;
+            }
+            if range.step == 1 {
+                continue;
+            }
+            let step = range.step as f64;
+            if let Some(keyword) = wcs_keyword(CDELT_PREFIX, axis, None, suffix) {
+                update_card(header, keyword, |cdelt| cdelt * step)?;
+            }
+            for i in 1..=ranges.len() {
+                if let Some(keyword) = wcs_keyword(CD_PREFIX, i, Some(axis), suffix) {
+                    update_card(header, keyword, |cd| cd * step)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the numeric value of the card with the given keyword, if it exists.
+fn update_card(
+    header: &mut FitsHeader,
+    keyword: FitsHeaderKeyword,
+    update: impl Fn(f64) -> f64,
+) -> Result<(), FitsHeaderError> {
+    let Some(card) = header.get_card(keyword) else {
+        return Ok(());
+    };
+    let value = numeric_value(card).ok_or(FitsHeaderError::InvalidType)?;
+    header.set_value(keyword, update(value))
+}
+
+/// Builds a keyword such as CRPIX1A or CD1_2, or None if it would exceed the length of a keyword.
+pub(crate) fn wcs_keyword(
+    prefix: &str,
+    i: usize,
+    j: Option<usize>,
+    suffix: char,
+) -> Option<FitsHeaderKeyword> {
+    let mut name = format!("{}{}", prefix, i);
+    if let Some(j) = j {
+        name.push_str(&format!("_{}", j));
+    }
+    name.push(suffix);
+    let name = name.trim_end();
+    if name.len() > HEADER_KEYWORD_LEN {
+        return None;
+    }
+    let mut keyword = [b' '; HEADER_KEYWORD_LEN];
+    keyword[..name.len()].copy_from_slice(name.as_bytes());
+    Some(FitsHeaderKeyword::from(keyword))
+}
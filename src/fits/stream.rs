@@ -0,0 +1,138 @@
+//! Write HDUs whose data sections are too large to hold in memory.
+
+use std::io::ErrorKind;
+
+use super::*;
+
+/// A writer that streams a single HDU, writing its header immediately and its data section in chunks.
+/// The number of bytes written is checked against the length described by the header,
+/// and the data section is padded to a whole number of records by [`HduWriter::finish`].
+///
+/// Multiple HDUs can be written to the same stream by passing a mutable reference as the writer,
+/// and finishing each HDU before starting the next.
+///
+/// # Examples
+///
+/// ```
+/// use astro_rs::fits::*;
+///
+/// let mut header = primary_hdu::default().header;
+/// header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+/// header.set_value(NAXIS_KEYWORD, 3u16)?;
+/// header.set_value(*b"NAXIS1  ", 100u32)?;
+/// header.set_value(*b"NAXIS2  ", 50u32)?;
+/// header.set_value(*b"NAXIS3  ", 4u32)?;
+///
+/// let mut writer = HduWriter::new(Vec::new(), header)?;
+/// assert_eq!(writer.plane_len(), 100 * 50 * 2);
+/// for plane in 0..4 {
+///     writer.write_data(&vec![plane as i16; 100 * 50])?;
+/// }
+/// let bytes = writer.finish()?;
+/// assert_eq!(bytes.len(), 2880 * 15);
+///
+/// let mut hdu_list = HduList::new(std::io::BufReader::new(bytes.as_slice()));
+/// let data = hdu_list.first_mut().unwrap().get_data::<Vec<i16>>()?;
+/// assert_eq!(data[100 * 50 * 3], 3);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct HduWriter<W: Write> {
+    writer: W,
+    dimensions: Vec<usize>,
+    element_len: usize,
+    data_len: usize,
+    written: usize,
+}
+
+impl<W: Write> HduWriter<W> {
+    /// Writes the given header to the writer, returning a writer for the data section it describes.
+    /// Returns an error if the length of the data section cannot be determined from the header.
+    pub fn new(mut writer: W, mut header: FitsHeader) -> Result<Self, std::io::Error> {
+        let data_len = try_unpadded_data_len(&mut header).map_err(invalid_input)?;
+        let element_len = header
+            .get_card(BITPIX_KEYWORD)
+            .and_then(|card| card.get_value::<Bitpix>().ok())
+            .map_or(1, |bitpix| bitpix.value() / 8);
+        let mut hdu = Hdu::new();
+        hdu.header = header;
+        let dimensions = hdu.get_dimensions();
+
+        let mut header_raw = hdu.header.to_bytes();
+        header_raw.resize(padded_len(header_raw.len()), b' ');
+        writer.write_all(&header_raw)?;
+        Ok(HduWriter {
+            writer,
+            dimensions,
+            element_len,
+            data_len,
+            written: 0,
+        })
+    }
+
+    /// Gets the length in bytes of a row of the data section, or of a row of a table.
+    pub fn row_len(&self) -> usize {
+        self.dimensions.first().copied().unwrap_or_default() * self.element_len
+    }
+
+    /// Gets the length in bytes of a plane of the data section, spanning the first two axes.
+    pub fn plane_len(&self) -> usize {
+        self.dimensions.iter().take(2).product::<usize>() * self.element_len
+    }
+
+    /// Gets the length in bytes of the data section described by the header, including any heap, excluding padding.
+    pub fn data_len(&self) -> usize {
+        self.data_len
+    }
+
+    /// Gets the number of bytes of the data section that have been written.
+    pub fn bytes_written(&self) -> usize {
+        self.written
+    }
+
+    /// Writes the given chunk of the data section, such as a number of rows or planes.
+    /// Returns an error if the chunk would exceed the length described by the header.
+    pub fn write_data<T: FitsDataCollection>(&mut self, chunk: &T) -> Result<(), std::io::Error> {
+        self.write_raw(&chunk.to_bytes())
+    }
+
+    /// Writes the given bytes of the data section, which must already be big-endian.
+    /// Returns an error if the bytes would exceed the length described by the header.
+    pub fn write_raw(&mut self, chunk: &[u8]) -> Result<(), std::io::Error> {
+        if self.written + chunk.len() > self.data_len {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "expected {} bytes for data section, found at least {}",
+                    self.data_len,
+                    self.written + chunk.len()
+                ),
+            ));
+        }
+        self.writer.write_all(chunk)?;
+        self.written += chunk.len();
+        Ok(())
+    }
+
+    /// Pads the data section to a whole number of records, returning the underlying writer.
+    /// Returns an error if fewer bytes were written than the length described by the header.
+    pub fn finish(mut self) -> Result<W, std::io::Error> {
+        if self.written != self.data_len {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "expected {} bytes for data section, found {}",
+                    self.data_len, self.written
+                ),
+            ));
+        }
+        let padding = vec![0; padded_len(self.data_len) - self.data_len];
+        self.writer.write_all(&padding)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn invalid_input(error: FitsHeaderError) -> std::io::Error {
+    std::io::Error::new(ErrorKind::InvalidInput, error)
+}
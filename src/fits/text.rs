@@ -0,0 +1,365 @@
+//! Read and write headers as text with one card per line, such as header templates and `.hdr` files.
+
+use super::*;
+
+const VALUE_INDICATOR: &[u8; 2] = b"= ";
+const VALUE_START: usize = HEADER_KEYWORD_LEN + VALUE_INDICATOR.len();
+const FIXED_VALUE_END: usize = 30;
+const MIN_STRING_LEN: usize = 8;
+const COMMENT_KEYWORD_NAME: &str = "COMMENT";
+const HISTORY_KEYWORD_NAME: &str = "HISTORY";
+const END_KEYWORD_NAME: &str = "END";
+
+/// An enumeration of errors that could occur when reading a header from text.
+/// Each error reports the number of the offending line, starting from 1.
+#[derive(Debug, Error)]
+pub enum HeaderTextError {
+    /// Indicates a line contains a character that is not printable ASCII.
+    #[error("line {line}: invalid character {found:?}")]
+    InvalidCharacter {
+        /// The number of the line.
+        line: usize,
+        /// The invalid character.
+        found: char,
+    },
+    /// Indicates a keyword is longer than 8 characters or contains characters other than
+    /// upper case letters, digits, hyphens, and underscores.
+    #[error("line {line}: invalid keyword {keyword:?}")]
+    InvalidKeyword {
+        /// The number of the line.
+        line: usize,
+        /// The invalid keyword.
+        keyword: String,
+    },
+    /// Indicates a value could not be interpreted.
+    #[error("line {line}: invalid value {value:?}")]
+    InvalidValue {
+        /// The number of the line.
+        line: usize,
+        /// The invalid value.
+        value: String,
+    },
+    /// Indicates a string value is missing its closing quote.
+    #[error("line {line}: unterminated string")]
+    UnterminatedString {
+        /// The number of the line.
+        line: usize,
+    },
+    /// Indicates the keyword and value do not fit within a single card.
+    #[error("line {line}: card requires {length} characters, exceeding 80")]
+    CardTooLong {
+        /// The number of the line.
+        line: usize,
+        /// The number of characters required by the card.
+        length: usize,
+    },
+}
+
+impl FitsHeader {
+    /// Constructs a header from text with one card per line.
+    ///
+    /// Lines may be in the fixed format of serialized cards, or in a free format such as
+    /// `EXPTIME = 30.0 / seconds`, and are normalized into fixed-format cards:
+    /// keywords are converted to upper case, numbers and logical values are right-justified
+    /// in columns 11 to 30, and strings are quoted and padded to at least 8 characters.
+    /// Unquoted values that are not numbers, complex numbers, or logical values are read as strings,
+    /// and comments that do not fit within the card are truncated.
+    /// Lines without a value indicator, and lines with the COMMENT, HISTORY, or blank keyword, are commentary cards.
+    ///
+    /// Empty lines and lines starting with `#` are skipped, and tabs are treated as spaces.
+    /// Reading stops at the END line, and an END card is added if the text does not have one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let text = "\
+    /// # exposure template
+    /// SIMPLE = T
+    /// bitpix = -32
+    /// NAXIS = 0
+    /// EXPTIME = 30.0 / seconds
+    /// OBJECT = 'M31'
+    /// FILTER = F606W / unquoted strings are allowed
+    /// HISTORY created from a template
+    /// ";
+    /// let mut header = FitsHeader::from_text(text)?;
+    /// assert_eq!(header.bitpix()?, Bitpix::F32);
+    /// assert_eq!(header.exptime()?, Some(30.0));
+    /// assert_eq!(header.object()?.as_deref(), Some("M31"));
+    /// assert_eq!(
+    ///     *header.get_card(*b"FILTER  ").unwrap().get_value::<String>()?,
+    ///     "F606W"
+    /// );
+    /// assert_eq!(header.history(), ["created from a template"]);
+    /// assert!(header.get_card(END_KEYWORD).is_some());
+    ///
+    /// let card: [u8; 80] = header.cards[4].clone().into();
+    /// assert_eq!(
+    ///     card,
+    ///     *b"EXPTIME =                 30.0 / seconds                                        "
+    /// );
+    ///
+    /// let error = FitsHeader::from_text("SIMPLE = T\nEXPOSURETIME = 30.0").unwrap_err();
+    /// assert_eq!(error.to_string(), "line 2: invalid keyword \"EXPOSURETIME\"");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_text(text: &str) -> Result<FitsHeader, HeaderTextError> {
+        let mut cards = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.replace('\t', " ");
+            if let Some(found) = line.chars().find(|c| !(' '..='~').contains(c)) {
+                return Err(HeaderTextError::InvalidCharacter {
+                    line: line_number,
+                    found,
+                });
+            }
+            let content = line.trim_start();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+
+            let card = parse_card(&line, line_number)?;
+            let is_end = *card.keyword() == END_KEYWORD;
+            cards.push(card);
+            if is_end {
+                break;
+            }
+        }
+        if !cards.iter().any(|card| *card.keyword() == END_KEYWORD) {
+            cards.push(FitsHeaderCard::from(blank_card(END_KEYWORD)));
+        }
+
+        Ok(FitsHeader { cards })
+    }
+
+    /// Serializes the header into text with one fixed-format card per line, omitting trailing spaces.
+    /// Cards following the END card are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let header = primary_hdu::default().header;
+    /// let text = header.to_text();
+    /// assert_eq!(
+    ///     text,
+    ///     "SIMPLE  =                    T\nBITPIX  =                    8\nNAXIS   =                    0\nEND\n"
+    /// );
+    /// assert_eq!(FitsHeader::from_text(&text)?.to_bytes(), header.to_bytes());
+    /// # Ok::<(), astro_rs::fits::HeaderTextError>(())
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut result = String::with_capacity(self.cards.len() * (HEADER_CARD_LEN + 1));
+        for card in &self.cards {
+            let is_end = *card.keyword() == END_KEYWORD;
+            let card_raw: [u8; HEADER_CARD_LEN] = card.clone().into();
+            result.push_str(String::from_utf8_lossy(&card_raw).trim_end());
+            result.push('\n');
+            if is_end {
+                break;
+            }
+        }
+        result
+    }
+}
+
+/// Parses a non-empty line into a fixed-format card.
+fn parse_card(line: &str, line_number: usize) -> Result<FitsHeaderCard, HeaderTextError> {
+    // a line starting with 8 spaces is commentary with a blank keyword
+    if line.len() > HEADER_KEYWORD_LEN && line[..HEADER_KEYWORD_LEN].trim().is_empty() {
+        return commentary_card([b' '; 8], &line[HEADER_KEYWORD_LEN..], line_number);
+    }
+
+    let line = line.trim_start();
+    let keyword_len = line.find([' ', '=']).unwrap_or(line.len());
+    let keyword_name = line[..keyword_len].to_ascii_uppercase();
+    let keyword = parse_keyword(&keyword_name, line_number)?;
+    let rest = &line[keyword_len..];
+
+    let is_commentary = matches!(
+        keyword_name.as_str(),
+        COMMENT_KEYWORD_NAME | HISTORY_KEYWORD_NAME | END_KEYWORD_NAME
+    );
+    match rest.trim_start().strip_prefix('=') {
+        Some(value) if !is_commentary => value_card(keyword, value, line_number),
+        _ => {
+            // the text of a fixed-format commentary card starts in column 9
+            let text = if line.len() >= HEADER_KEYWORD_LEN
+                && line[keyword_len..HEADER_KEYWORD_LEN].trim().is_empty()
+            {
+                &line[HEADER_KEYWORD_LEN..]
+            } else {
+                rest.trim_start()
+            };
+            if keyword_name == END_KEYWORD_NAME {
+                return Ok(FitsHeaderCard::from(blank_card(END_KEYWORD)));
+            }
+            commentary_card(keyword, text, line_number)
+        }
+    }
+}
+
+fn parse_keyword(name: &str, line_number: usize) -> Result<[u8; 8], HeaderTextError> {
+    if name.len() > HEADER_KEYWORD_LEN
+        || !name
+            .bytes()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == b'-' || c == b'_')
+    {
+        return Err(HeaderTextError::InvalidKeyword {
+            line: line_number,
+            keyword: name.to_owned(),
+        });
+    }
+    let mut keyword = [b' '; HEADER_KEYWORD_LEN];
+    keyword[..name.len()].copy_from_slice(name.as_bytes());
+    Ok(keyword)
+}
+
+fn blank_card(keyword: [u8; 8]) -> [u8; HEADER_CARD_LEN] {
+    let mut card = [b' '; HEADER_CARD_LEN];
+    card[..HEADER_KEYWORD_LEN].copy_from_slice(&keyword);
+    card
+}
+
+fn commentary_card(
+    keyword: [u8; 8],
+    text: &str,
+    line_number: usize,
+) -> Result<FitsHeaderCard, HeaderTextError> {
+    let text = text.trim_end();
+    let length = HEADER_KEYWORD_LEN + text.len();
+    if length > HEADER_CARD_LEN {
+        return Err(HeaderTextError::CardTooLong {
+            line: line_number,
+            length,
+        });
+    }
+    let mut card = blank_card(keyword);
+    card[HEADER_KEYWORD_LEN..length].copy_from_slice(text.as_bytes());
+    Ok(FitsHeaderCard::from(card))
+}
+
+fn value_card(
+    keyword: [u8; 8],
+    value: &str,
+    line_number: usize,
+) -> Result<FitsHeaderCard, HeaderTextError> {
+    let value = value.trim_start();
+    let (value_field, comment) = if let Some(quoted) = value.strip_prefix('\'') {
+        let (string, rest) = split_string(quoted, line_number)?;
+        let rest = rest.trim();
+        let comment = match rest.strip_prefix('/') {
+            Some(comment) => Some(comment),
+            None if rest.is_empty() => None,
+            None => {
+                return Err(HeaderTextError::InvalidValue {
+                    line: line_number,
+                    value: value.to_owned(),
+                });
+            }
+        };
+        (format_string(&string), comment)
+    } else {
+        let (value, comment) = match value.split_once('/') {
+            Some((value, comment)) => (value.trim(), Some(comment)),
+            None => (value.trim(), None),
+        };
+        let value_field = if value.is_empty() {
+            String::new()
+        } else if let Some(value) = format_literal(value, line_number)? {
+            format!("{:>width$}", value, width = FIXED_VALUE_END - VALUE_START)
+        } else {
+            format_string(value)
+        };
+        (value_field, comment)
+    };
+
+    let mut card_text = format!(
+        "{:<width$}",
+        value_field,
+        width = FIXED_VALUE_END - VALUE_START
+    );
+    let length = VALUE_START + card_text.trim_end().len();
+    if length > HEADER_CARD_LEN {
+        return Err(HeaderTextError::CardTooLong {
+            line: line_number,
+            length,
+        });
+    }
+    if let Some(comment) = comment.map(str::trim).filter(|comment| !comment.is_empty()) {
+        card_text.push_str(" / ");
+        card_text.push_str(comment);
+        card_text.truncate(HEADER_CARD_LEN - VALUE_START);
+    }
+
+    let mut card = blank_card(keyword);
+    card[HEADER_KEYWORD_LEN..VALUE_START].copy_from_slice(VALUE_INDICATOR);
+    card[VALUE_START..VALUE_START + card_text.len()].copy_from_slice(card_text.as_bytes());
+    Ok(FitsHeaderCard::from(card))
+}
+
+/// Splits the text following an opening quote into the unescaped string and the remaining text.
+pub(crate) fn split_string(
+    quoted: &str,
+    line_number: usize,
+) -> Result<(String, &str), HeaderTextError> {
+    let mut string = String::new();
+    let mut chars = quoted.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c == '\'' {
+            if chars.peek().is_some_and(|(_, next)| *next == '\'') {
+                chars.next();
+            } else {
+                return Ok((string, &quoted[index + 1..]));
+            }
+        }
+        string.push(c);
+    }
+    Err(HeaderTextError::UnterminatedString { line: line_number })
+}
+
+fn format_string(value: &str) -> String {
+    let value = value.trim_end().replace('\'', "''");
+    format!("'{:<width$}'", value, width = MIN_STRING_LEN)
+}
+
+/// Normalizes a logical, integer, floating point, or complex value,
+/// or returns None if the value is not one of these types.
+fn format_literal(value: &str, line_number: usize) -> Result<Option<String>, HeaderTextError> {
+    if value == "T" || value == "F" {
+        return Ok(Some(value.to_owned()));
+    }
+    if let Some(parts) = value
+        .strip_prefix('(')
+        .and_then(|value| value.strip_suffix(')'))
+    {
+        let parts = parts
+            .split(',')
+            .map(|part| format_number(part.trim()))
+            .collect::<Option<Vec<_>>>();
+        return match parts {
+            Some(parts) if parts.len() == 2 => Ok(Some(format!("({}, {})", parts[0], parts[1]))),
+            _ => Err(HeaderTextError::InvalidValue {
+                line: line_number,
+                value: value.to_owned(),
+            }),
+        };
+    }
+    Ok(format_number(value))
+}
+
+/// Normalizes an integer or floating point value, using an upper case exponent,
+/// or returns None if the value is not a number.
+fn format_number(value: &str) -> Option<String> {
+    let normalized = value.to_ascii_uppercase();
+    let is_number = normalized
+        .bytes()
+        .all(|c| c.is_ascii_digit() || matches!(c, b'+' | b'-' | b'.' | b'E' | b'D'))
+        && normalized.bytes().any(|c| c.is_ascii_digit())
+        && normalized.replace('D', "E").parse::<f64>().is_ok();
+    is_number.then_some(normalized)
+}
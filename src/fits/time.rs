@@ -0,0 +1,362 @@
+//! Interpret the time cards and time columns of an HDU as epochs.
+//! See section 9 of <https://fits.gsfc.nasa.gov/standard40/fits_standard40aa-le.pdf> for the representation of time.
+
+use hifitime::{Duration, Epoch, HifitimeError, TimeScale, Unit};
+use thiserror::Error;
+
+use super::*;
+
+/// The difference between a Julian date and a modified Julian date, in days.
+const MJD_OFFSET: f64 = 2_400_000.5;
+
+/// An enumeration of errors that could occur when interpreting times.
+#[derive(Debug, Error)]
+pub enum TimeError {
+    /// Indicates a time card could not be processed.
+    #[error(transparent)]
+    Header(#[from] FitsHeaderError),
+    /// Indicates a date is not in the format of the FITS standard, or does not exist.
+    #[error("invalid date {value}")]
+    InvalidDate {
+        /// The value of the date card.
+        value: String,
+        /// The reason the date is invalid, if given by hifitime.
+        #[source]
+        source: Option<HifitimeError>,
+    },
+    /// Indicates the time scale given by TIMESYS is not supported.
+    #[error("unsupported time scale {timesys}")]
+    UnsupportedTimeScale {
+        /// The value of TIMESYS.
+        timesys: String,
+    },
+    /// Indicates the unit of a time is not supported.
+    #[error("unsupported time unit {unit}")]
+    UnsupportedTimeUnit {
+        /// The value of TIMEUNIT or TUNITn.
+        unit: String,
+    },
+    /// Indicates a column cannot be found, or does not contain numeric values.
+    #[error("invalid time column {name}")]
+    InvalidColumn {
+        /// The name of the column.
+        name: String,
+    },
+}
+
+impl FitsHeader {
+    /// Gets the time scale of the time cards and columns, given by TIMESYS, or UTC if the card is absent.
+    pub fn time_scale(&mut self) -> Result<TimeScale, TimeError> {
+        let Some(timesys) = self.timesys()? else {
+            return Ok(TimeScale::UTC);
+        };
+        match timesys.trim().to_ascii_uppercase().as_str() {
+            "UTC" | "GMT" => Ok(TimeScale::UTC),
+            "TAI" | "IAT" => Ok(TimeScale::TAI),
+            // ET is the historical name of TT in the FITS standard
+            "TT" | "TDT" | "ET" => Ok(TimeScale::TT),
+            "TDB" => Ok(TimeScale::TDB),
+            "TCG" => Ok(TimeScale::TCG),
+            "TCB" => Ok(TimeScale::TCB),
+            "GPS" => Ok(TimeScale::GPST),
+            _ => Err(TimeError::UnsupportedTimeScale { timesys }),
+        }
+    }
+
+    /// Gets the duration of one unit of the times given relative to the reference time,
+    /// given by TIMEUNIT, or one second if the card is absent.
+    pub fn time_unit(&mut self) -> Result<Duration, TimeError> {
+        match self.timeunit()? {
+            Some(unit) => parse_time_unit(&unit),
+            None => Ok(Unit::Second * 1),
+        }
+    }
+
+    /// Gets the reference time of the times given relative to it, such as TSTART and time columns.
+    /// The reference time is given by MJDREFI and MJDREFF, MJDREF, JDREFI and JDREFF, JDREF, or DATEREF,
+    /// in that order of precedence, or is MJD 0 if none of the cards are present.
+    pub fn time_reference(&mut self) -> Result<Epoch, TimeError> {
+        let time_scale = self.time_scale()?;
+        let (mjdrefi, mjdreff) = (self.mjdrefi()?, self.mjdreff()?);
+        if mjdrefi.is_some() || mjdreff.is_some() {
+            return Ok(split_mjd(
+                mjdrefi.unwrap_or_default() as f64,
+                mjdreff.unwrap_or_default(),
+                time_scale,
+            ));
+        }
+        if let Some(mjdref) = self.mjdref()? {
+            return Ok(Epoch::from_mjd_in_time_scale(mjdref, time_scale));
+        }
+        let (jdrefi, jdreff) = (self.jdrefi()?, self.jdreff()?);
+        if jdrefi.is_some() || jdreff.is_some() {
+            return Ok(split_mjd(
+                jdrefi.unwrap_or_default() as f64 - MJD_OFFSET,
+                jdreff.unwrap_or_default(),
+                time_scale,
+            ));
+        }
+        if let Some(jdref) = self.jdref()? {
+            return Ok(Epoch::from_mjd_in_time_scale(
+                jdref - MJD_OFFSET,
+                time_scale,
+            ));
+        }
+        if let Some(dateref) = self.dateref()? {
+            return parse_date(&dateref, time_scale);
+        }
+        Ok(Epoch::from_mjd_in_time_scale(0.0, time_scale))
+    }
+
+    /// Converts a time given relative to the reference time, such as TSTART or a value of a time column,
+    /// into an epoch, applying TIMEZERO and TIMEUNIT.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use hifitime::{Epoch, TimeScale, Unit};
+    ///
+    /// let mut header = primary_hdu::default().header;
+    /// header.set_timesys(String::from("TT"))?;
+    /// header.set_mjdrefi(51910)?;
+    /// header.set_mjdreff(0.5)?;
+    /// header.set_timeunit(String::from("d"))?;
+    /// header.set_tstart(1.25)?;
+    ///
+    /// let reference = Epoch::from_gregorian_at_noon(2001, 1, 1, TimeScale::TT);
+    /// assert_eq!(header.time_reference()?, reference);
+    /// assert_eq!(header.time_to_epoch(2.0)?, reference + Unit::Day * 2);
+    /// assert_eq!(header.tstart_epoch()?, Some(reference + Unit::Hour * 30));
+    /// # Ok::<(), astro_rs::fits::TimeError>(())
+    /// ```
+    pub fn time_to_epoch(&mut self, time: f64) -> Result<Epoch, TimeError> {
+        let reference = self.time_reference()?;
+        let unit = self.time_unit()?;
+        let timezero = self.timezero()?;
+        Ok(reference + unit * (time + timezero))
+    }
+
+    /// Gets the start time of the data, given by TSTART, as an epoch, or None if the card is absent.
+    pub fn tstart_epoch(&mut self) -> Result<Option<Epoch>, TimeError> {
+        self.tstart()?
+            .map(|tstart| self.time_to_epoch(tstart))
+            .transpose()
+    }
+
+    /// Gets the stop time of the data, given by TSTOP, as an epoch, or None if the card is absent.
+    pub fn tstop_epoch(&mut self) -> Result<Option<Epoch>, TimeError> {
+        self.tstop()?
+            .map(|tstop| self.time_to_epoch(tstop))
+            .transpose()
+    }
+
+    /// Gets the start date of the observation, given by DATE-OBS in the time scale given by TIMESYS,
+    /// as an epoch, or None if the card is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use hifitime::{Epoch, TimeScale};
+    ///
+    /// let mut header = primary_hdu::default().header;
+    /// header.set_date_obs(String::from("2024-03-01T12:30:15.5"))?;
+    /// assert_eq!(
+    ///     header.date_obs_epoch()?,
+    ///     Some(Epoch::from_gregorian(2024, 3, 1, 12, 30, 15, 500_000_000, TimeScale::UTC))
+    /// );
+    ///
+    /// header.set_timesys(String::from("TAI"))?;
+    /// header.set_date_obs(String::from("2024-03-01"))?;
+    /// assert_eq!(
+    ///     header.date_obs_epoch()?,
+    ///     Some(Epoch::from_gregorian_at_midnight(2024, 3, 1, TimeScale::TAI))
+    /// );
+    /// # Ok::<(), astro_rs::fits::TimeError>(())
+    /// ```
+    pub fn date_obs_epoch(&mut self) -> Result<Option<Epoch>, TimeError> {
+        let time_scale = self.time_scale()?;
+        self.date_obs()?
+            .map(|date| parse_date(&date, time_scale))
+            .transpose()
+    }
+
+    /// Gets the end date of the observation, given by DATE-END in the time scale given by TIMESYS,
+    /// as an epoch, or None if the card is absent.
+    pub fn date_end_epoch(&mut self) -> Result<Option<Epoch>, TimeError> {
+        let time_scale = self.time_scale()?;
+        self.date_end()?
+            .map(|date| parse_date(&date, time_scale))
+            .transpose()
+    }
+
+    /// Gets the start of the observation, given by MJD-OBS in the time scale given by TIMESYS,
+    /// as an epoch, or None if the card is absent.
+    pub fn mjd_obs_epoch(&mut self) -> Result<Option<Epoch>, TimeError> {
+        let time_scale = self.time_scale()?;
+        Ok(self
+            .mjd_obs()?
+            .map(|mjd| Epoch::from_mjd_in_time_scale(mjd, time_scale)))
+    }
+}
+
+impl Hdu {
+    /// Converts the values of the binary table column with the given name, such as TIME, into epochs.
+    /// The values are relative to the reference time offset by TIMEZERO, with TSCALn and TZEROn applied,
+    /// and in the unit given by TUNITn, or by TIMEUNIT if TUNITn is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use hifitime::{Epoch, TimeScale, Unit};
+    ///
+    /// let mut hdu = binary_table_hdu::default();
+    /// hdu.header.set_dimensions(&[8, 2])?;
+    /// hdu.header.set_tfields(1)?;
+    /// hdu.header.set_ttype(1, String::from("TIME"))?;
+    /// hdu.header.set_tform(1, TForm { r: 1, t: TFormType::F64, a: String::new() })?;
+    /// hdu.header.set_mjdref(60000.0)?;
+    /// hdu.set_data(&vec![0.0f64, 90.0]);
+    ///
+    /// let reference = Epoch::from_mjd_in_time_scale(60000.0, TimeScale::UTC);
+    /// assert_eq!(hdu.time_column("TIME")?, [reference, reference + Unit::Second * 90]);
+    /// # Ok::<(), astro_rs::fits::TimeError>(())
+    /// ```
+    pub fn time_column(&mut self, name: &str) -> Result<Vec<Epoch>, TimeError> {
+        let (index, times) = binary_table_hdu::physical_column(self, name)?.ok_or_else(|| {
+            TimeError::InvalidColumn {
+                name: name.to_owned(),
+            }
+        })?;
+        // TIMEZERO is given in the unit of TIMEUNIT, which the column may override
+        let time_unit = self.header.time_unit()?;
+        let reference = self.header.time_reference()? + time_unit * self.header.timezero()?;
+        let unit = match self.header.tunit(index)? {
+            Some(unit) => parse_time_unit(&unit)?,
+            None => time_unit,
+        };
+        Ok(times
+            .into_iter()
+            .map(|time| reference + unit * time)
+            .collect())
+    }
+}
+
+/// Parses a date in the format of the FITS standard, either `YYYY-MM-DD[Thh:mm:ss[.s...]]`
+/// or the deprecated `DD/MM/YY` denoting a year of the twentieth century, as an epoch in the given time scale.
+///
+/// # Examples
+///
+/// ```
+/// use astro_rs::fits::*;
+/// use hifitime::{Epoch, TimeScale};
+///
+/// assert_eq!(
+///     parse_date("1999-12-31T23:59:59", TimeScale::TT)?,
+///     Epoch::from_gregorian_hms(1999, 12, 31, 23, 59, 59, TimeScale::TT)
+/// );
+/// assert_eq!(
+///     parse_date("31/12/98", TimeScale::UTC)?,
+///     Epoch::from_gregorian_at_midnight(1998, 12, 31, TimeScale::UTC)
+/// );
+/// assert!(parse_date("2024-02-30", TimeScale::UTC).is_err());
+/// # Ok::<(), astro_rs::fits::TimeError>(())
+/// ```
+pub fn parse_date(value: &str, time_scale: TimeScale) -> Result<Epoch, TimeError> {
+    let invalid = || TimeError::InvalidDate {
+        value: value.to_owned(),
+        source: None,
+    };
+    let number = |digits: &str, len: usize| -> Result<u32, TimeError> {
+        if digits.len() != len || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        digits.parse().map_err(|_| invalid())
+    };
+
+    let value_trimmed = value.trim();
+    let (year, month, day, time) = if let Some((date, time)) = value_trimmed.split_once('T') {
+        let (year, month, day) = split_date(date).ok_or_else(invalid)?;
+        (year, month, day, Some(time))
+    } else if let Some((year, month, day)) = split_date(value_trimmed) {
+        (year, month, day, None)
+    } else {
+        let mut parts = value_trimmed.split('/');
+        let (Some(day), Some(month), Some(year), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let year = format!("19{}", year);
+        (year, month.to_owned(), day.to_owned(), None)
+    };
+    let year = number(&year, 4)? as i32;
+    let month = number(&month, 2)? as u8;
+    let day = number(&day, 2)? as u8;
+
+    let (mut hour, mut minute, mut second, mut nanos) = (0, 0, 0, 0);
+    if let Some(time) = time {
+        let (time, fraction) = match time.split_once('.') {
+            Some((time, fraction)) => (time, Some(fraction)),
+            None => (time, None),
+        };
+        let mut parts = time.split(':');
+        let (Some(h), Some(m), Some(s), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        hour = number(h, 2)? as u8;
+        minute = number(m, 2)? as u8;
+        second = number(s, 2)? as u8;
+        if let Some(fraction) = fraction {
+            // digits beyond nanoseconds are truncated
+            let digits = &fraction[..fraction.len().min(9)];
+            if digits.is_empty() {
+                return Err(invalid());
+            }
+            nanos = number(digits, digits.len())? * 10u32.pow(9 - digits.len() as u32);
+        }
+    }
+    Epoch::maybe_from_gregorian(year, month, day, hour, minute, second, nanos, time_scale).map_err(
+        |source| TimeError::InvalidDate {
+            value: value.to_owned(),
+            source: Some(source),
+        },
+    )
+}
+
+/// Splits a date of the form `YYYY-MM-DD` into its year, month, and day.
+fn split_date(date: &str) -> Option<(String, String, String)> {
+    let mut parts = date.split('-');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(year), Some(month), Some(day), None) => {
+            Some((year.to_owned(), month.to_owned(), day.to_owned()))
+        }
+        _ => None,
+    }
+}
+
+/// Parses the unit of a time, as given by TIMEUNIT or TUNITn, into the duration of one unit.
+fn parse_time_unit(unit: &str) -> Result<Duration, TimeError> {
+    match unit.trim() {
+        "s" => Ok(Unit::Second * 1),
+        "min" => Ok(Unit::Minute * 1),
+        "h" => Ok(Unit::Hour * 1),
+        "d" => Ok(Unit::Day * 1),
+        // the Julian year and century
+        "a" | "yr" => Ok(Unit::Day * 365.25),
+        "cy" => Ok(Unit::Century * 1),
+        _ => Err(TimeError::UnsupportedTimeUnit {
+            unit: unit.to_owned(),
+        }),
+    }
+}
+
+/// Builds an epoch from a modified Julian date split into integer and fractional parts,
+/// retaining the precision of the fractional part.
+fn split_mjd(integer: f64, fraction: f64, time_scale: TimeScale) -> Epoch {
+    Epoch::from_mjd_in_time_scale(integer, time_scale) + Unit::Day * fraction
+}
@@ -0,0 +1,837 @@
+//! Parse the unit strings of BUNIT, TUNITn, and CUNITn, and attach the units to values.
+//! See section 4.3 of <https://fits.gsfc.nasa.gov/standard40/fits_standard40aa-le.pdf> for the unit string grammar.
+
+use std::f64::consts::PI;
+use std::fmt::Display;
+use std::ops::{Div, Mul};
+use std::str::FromStr;
+
+use thiserror::Error;
+use uom::si::f64::{
+    AmountOfSubstance, Angle, Area, ElectricCurrent, Energy, Force, Frequency, Length,
+    LuminousIntensity, Mass, Power, Pressure, Ratio, SolidAngle, ThermodynamicTemperature, Time,
+    Velocity, Volume,
+};
+
+use super::*;
+
+/// An enumeration of errors that could occur when parsing or converting units.
+#[derive(Debug, Error)]
+pub enum UnitError {
+    /// Indicates a unit card could not be processed.
+    #[error(transparent)]
+    Header(#[from] FitsHeaderError),
+    /// Indicates a unit string does not follow the grammar of the FITS standard.
+    #[error("invalid unit string {unit}")]
+    InvalidUnit {
+        /// The unit string.
+        unit: String,
+    },
+    /// Indicates a unit string contains an unrecognized unit.
+    #[error("unknown unit {name}")]
+    UnknownUnit {
+        /// The name of the unit.
+        name: String,
+    },
+    /// Indicates a unit string contains a logarithmic or exponential unit, which has no linear scale.
+    #[error("unsupported unit {name}")]
+    UnsupportedUnit {
+        /// The name of the unit or function.
+        name: String,
+    },
+    /// Indicates values cannot be converted between units of different dimensions.
+    #[error("cannot convert from {from} to {to}")]
+    IncompatibleUnits {
+        /// The unit of the values, in SI units.
+        from: String,
+        /// The requested unit, in SI units.
+        to: String,
+    },
+    /// Indicates a column cannot be found, or does not contain numeric values.
+    #[error("invalid column {name}")]
+    InvalidColumn {
+        /// The name of the column.
+        name: String,
+    },
+}
+
+/// The exponents of the base quantities of a unit.
+/// Plane angles, counts, pixels, and bits are tracked as base quantities,
+/// so that units such as degrees and counts are not mistaken for dimensionless numbers.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct UnitDimension {
+    /// The exponent of length, in meters.
+    pub length: f64,
+    /// The exponent of mass, in kilograms.
+    pub mass: f64,
+    /// The exponent of time, in seconds.
+    pub time: f64,
+    /// The exponent of electric current, in amperes.
+    pub current: f64,
+    /// The exponent of thermodynamic temperature, in kelvins.
+    pub temperature: f64,
+    /// The exponent of amount of substance, in moles.
+    pub amount: f64,
+    /// The exponent of luminous intensity, in candelas.
+    pub luminous_intensity: f64,
+    /// The exponent of plane angle, in radians. A solid angle has an exponent of 2.
+    pub angle: f64,
+    /// The exponent of events, such as counts and photons.
+    pub count: f64,
+    /// The exponent of pixels.
+    pub pixel: f64,
+    /// The exponent of information, in bits.
+    pub bit: f64,
+}
+
+impl UnitDimension {
+    const SYMBOLS: [&'static str; 11] = [
+        "m", "kg", "s", "A", "K", "mol", "cd", "rad", "count", "pixel", "bit",
+    ];
+
+    fn exponents(&self) -> [f64; 11] {
+        [
+            self.length,
+            self.mass,
+            self.time,
+            self.current,
+            self.temperature,
+            self.amount,
+            self.luminous_intensity,
+            self.angle,
+            self.count,
+            self.pixel,
+            self.bit,
+        ]
+    }
+
+    fn from_exponents(exponents: [f64; 11]) -> Self {
+        let [
+            length,
+            mass,
+            time,
+            current,
+            temperature,
+            amount,
+            luminous_intensity,
+            angle,
+            count,
+            pixel,
+            bit,
+        ] = exponents;
+        UnitDimension {
+            length,
+            mass,
+            time,
+            current,
+            temperature,
+            amount,
+            luminous_intensity,
+            angle,
+            count,
+            pixel,
+            bit,
+        }
+    }
+
+    /// Adds the exponents of the other dimension, multiplied by the given factor, to the exponents of this dimension.
+    fn add_scaled(&self, other: &Self, factor: f64) -> Self {
+        let (a, b) = (self.exponents(), other.exponents());
+        Self::from_exponents(std::array::from_fn(|i| a[i] + factor * b[i]))
+    }
+
+    /// Determines if the dimensions are equal, allowing for rounding of fractional exponents.
+    pub fn is_equivalent(&self, other: &Self) -> bool {
+        self.exponents()
+            .iter()
+            .zip(other.exponents())
+            .all(|(a, b)| (a - b).abs() < 1e-9)
+    }
+}
+
+/// A unit, described by its scale relative to the SI units of its dimension.
+///
+/// # Examples
+///
+/// ```
+/// use astro_rs::fits::*;
+///
+/// let flux_density: FitsUnit = "erg/s/cm**2/Angstrom".parse()?;
+/// let si: FitsUnit = "W m-2 m-1".parse()?;
+/// assert!(flux_density.is_compatible(&si));
+/// assert!((flux_density.convert(1.0, &si)? - 1e7).abs() < 1e-6);
+///
+/// let scaled: FitsUnit = "10**-17 W m-2 nm-1".parse()?;
+/// assert!((scaled.convert(1.0, &flux_density)? - 1e-15).abs() < 1e-27);
+///
+/// let rate: FitsUnit = "count/s".parse()?;
+/// assert!(rate.conversion_factor(&"Hz".parse()?).is_err());
+/// # Ok::<(), astro_rs::fits::UnitError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitsUnit {
+    /// The factor converting a value in the unit into the SI units of its dimension.
+    pub scale: f64,
+    /// The dimension of the unit.
+    pub dimension: UnitDimension,
+}
+
+impl Default for FitsUnit {
+    fn default() -> Self {
+        Self::DIMENSIONLESS
+    }
+}
+
+impl FitsUnit {
+    /// The unit of dimensionless numbers, given by an empty unit string.
+    pub const DIMENSIONLESS: FitsUnit = FitsUnit {
+        scale: 1.0,
+        dimension: UnitDimension {
+            length: 0.0,
+            mass: 0.0,
+            time: 0.0,
+            current: 0.0,
+            temperature: 0.0,
+            amount: 0.0,
+            luminous_intensity: 0.0,
+            angle: 0.0,
+            count: 0.0,
+            pixel: 0.0,
+            bit: 0.0,
+        },
+    };
+
+    /// Parses a unit string following the grammar of the FITS standard, such as `erg/s/cm**2/Angstrom`,
+    /// `10**-17 W m-2 nm-1`, or `sqrt(Hz)`.
+    pub fn parse(unit: &str) -> Result<Self, UnitError> {
+        UnitParser::new(unit).parse()
+    }
+
+    /// Raises the unit to the given power.
+    pub fn powf(&self, exponent: f64) -> Self {
+        FitsUnit {
+            scale: self.scale.powf(exponent),
+            dimension: UnitDimension::default().add_scaled(&self.dimension, exponent),
+        }
+    }
+
+    /// Determines if values can be converted between this unit and the given unit.
+    pub fn is_compatible(&self, other: &FitsUnit) -> bool {
+        self.dimension.is_equivalent(&other.dimension)
+    }
+
+    /// Gets the factor converting a value in this unit into the given unit.
+    /// Returns an error if the units have different dimensions.
+    pub fn conversion_factor(&self, to: &FitsUnit) -> Result<f64, UnitError> {
+        if !self.is_compatible(to) {
+            return Err(UnitError::IncompatibleUnits {
+                from: self.to_string(),
+                to: to.to_string(),
+            });
+        }
+        Ok(self.scale / to.scale)
+    }
+
+    /// Converts a value in this unit into the given unit.
+    /// Returns an error if the units have different dimensions.
+    pub fn convert(&self, value: f64, to: &FitsUnit) -> Result<f64, UnitError> {
+        Ok(value * self.conversion_factor(to)?)
+    }
+
+    /// Converts a value in this unit into the SI units of its dimension.
+    pub fn to_si(&self, value: f64) -> f64 {
+        value * self.scale
+    }
+
+    /// Converts a value in this unit into a `uom` quantity.
+    /// Returns an error if the dimension of the unit does not match the quantity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use uom::si::angle::degree;
+    /// use uom::si::f64::{Angle, Length};
+    /// use uom::si::length::meter;
+    ///
+    /// let length = FitsUnit::parse("kpc")?.quantity::<Length>(2.0)?;
+    /// assert!((length.get::<meter>() - 6.171355162982735e19).abs() < 1e5);
+    ///
+    /// let angle = FitsUnit::parse("arcsec")?.quantity::<Angle>(3600.0)?;
+    /// assert!((angle.get::<degree>() - 1.0).abs() < 1e-12);
+    /// assert!(FitsUnit::parse("deg")?.quantity::<Length>(1.0).is_err());
+    /// # Ok::<(), astro_rs::fits::UnitError>(())
+    /// ```
+    pub fn quantity<Q: FitsQuantity>(&self, value: f64) -> Result<Q, UnitError> {
+        let unit = Q::unit();
+        Ok(Q::from_si(self.convert(value, &unit)? * unit.scale))
+    }
+}
+
+impl FromStr for FitsUnit {
+    type Err = UnitError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Mul for FitsUnit {
+    type Output = FitsUnit;
+
+    fn mul(self, rhs: FitsUnit) -> FitsUnit {
+        FitsUnit {
+            scale: self.scale * rhs.scale,
+            dimension: self.dimension.add_scaled(&rhs.dimension, 1.0),
+        }
+    }
+}
+
+impl Div for FitsUnit {
+    type Output = FitsUnit;
+
+    fn div(self, rhs: FitsUnit) -> FitsUnit {
+        FitsUnit {
+            scale: self.scale / rhs.scale,
+            dimension: self.dimension.add_scaled(&rhs.dimension, -1.0),
+        }
+    }
+}
+
+impl Display for FitsUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if self.scale != 1.0 {
+            parts.push(self.scale.to_string());
+        }
+        for (symbol, exponent) in UnitDimension::SYMBOLS
+            .iter()
+            .zip(self.dimension.exponents())
+        {
+            if exponent == 1.0 {
+                parts.push((*symbol).to_owned());
+            } else if exponent.fract() == 0.0 && exponent != 0.0 {
+                parts.push(format!("{}{}", symbol, exponent));
+            } else if exponent != 0.0 {
+                parts.push(format!("{}**({})", symbol, exponent));
+            }
+        }
+        if parts.is_empty() {
+            return Ok(());
+        }
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// A trait for the `uom` quantities that values with a [`FitsUnit`] can be converted into.
+pub trait FitsQuantity: Sized {
+    /// Gets the SI unit of the quantity.
+    fn unit() -> FitsUnit;
+
+    /// Constructs the quantity from a value in SI units.
+    fn from_si(value: f64) -> Self;
+}
+
+macro_rules! impl_fits_quantity {
+    ($($quantity: ty => $unit_module: ident::$unit: ident, $symbol: literal;)+) => {
+        $(
+            impl FitsQuantity for $quantity {
+                fn unit() -> FitsUnit {
+                    FitsUnit::parse($symbol).expect("valid SI unit")
+                }
+
+                fn from_si(value: f64) -> Self {
+                    <$quantity>::new::<uom::si::$unit_module::$unit>(value)
+                }
+            }
+        )+
+    };
+}
+
+impl_fits_quantity! {
+    Ratio => ratio::ratio, "";
+    Length => length::meter, "m";
+    Mass => mass::kilogram, "kg";
+    Time => time::second, "s";
+    ElectricCurrent => electric_current::ampere, "A";
+    ThermodynamicTemperature => thermodynamic_temperature::kelvin, "K";
+    AmountOfSubstance => amount_of_substance::mole, "mol";
+    LuminousIntensity => luminous_intensity::candela, "cd";
+    Angle => angle::radian, "rad";
+    SolidAngle => solid_angle::steradian, "sr";
+    Area => area::square_meter, "m2";
+    Volume => volume::cubic_meter, "m3";
+    Velocity => velocity::meter_per_second, "m/s";
+    Frequency => frequency::hertz, "Hz";
+    Force => force::newton, "N";
+    Pressure => pressure::pascal, "Pa";
+    Energy => energy::joule, "J";
+    Power => power::watt, "W";
+}
+
+/// Values read from an HDU, with the unit given by its header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantities {
+    /// The physical values.
+    pub values: Vec<f64>,
+    /// The unit of the values.
+    pub unit: FitsUnit,
+}
+
+impl Quantities {
+    /// Converts the values into the given unit.
+    /// Returns an error if the units have different dimensions.
+    pub fn convert_to(&self, unit: &FitsUnit) -> Result<Vec<f64>, UnitError> {
+        let factor = self.unit.conversion_factor(unit)?;
+        Ok(self.values.iter().map(|value| value * factor).collect())
+    }
+
+    /// Converts the values into `uom` quantities.
+    /// Returns an error if the dimension of the unit does not match the quantity.
+    pub fn to_quantities<Q: FitsQuantity>(&self) -> Result<Vec<Q>, UnitError> {
+        self.values
+            .iter()
+            .map(|value| self.unit.quantity(*value))
+            .collect()
+    }
+}
+
+impl FitsHeader {
+    /// Gets the unit of the array values, given by BUNIT, or None if the card is absent.
+    pub fn data_unit(&mut self) -> Result<Option<FitsUnit>, UnitError> {
+        self.bunit()?.map(|unit| FitsUnit::parse(&unit)).transpose()
+    }
+
+    /// Gets the unit of column `n`, given by TUNITn, or None if the card is absent.
+    pub fn column_unit(&mut self, n: u16) -> Result<Option<FitsUnit>, UnitError> {
+        self.tunit(n)?
+            .map(|unit| FitsUnit::parse(&unit))
+            .transpose()
+    }
+
+    /// Gets the unit of world coordinate axis `n`, given by CUNITn, or None if the card is absent.
+    pub fn axis_unit(&mut self, n: u16) -> Result<Option<FitsUnit>, UnitError> {
+        self.cunit(n)?
+            .map(|unit| FitsUnit::parse(&unit))
+            .transpose()
+    }
+}
+
+impl Hdu {
+    /// Reads the physical values of the image, with BSCALE and BZERO applied and BLANK values as NaN,
+    /// in the unit given by BUNIT, or dimensionless if BUNIT is absent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_dimensions(&[3])?;
+    /// hdu.header.set_bitpix(Bitpix::I16)?;
+    /// hdu.header.set_bscale(0.5)?;
+    /// hdu.header.set_blank(-1)?;
+    /// hdu.header.set_bunit(String::from("mJy"))?;
+    /// hdu.set_data(&vec![2i16, 4, -1]);
+    ///
+    /// let image = hdu.image_quantities()?;
+    /// assert_eq!(image.values[..2], [1.0, 2.0]);
+    /// assert!(image.values[2].is_nan());
+    /// assert_eq!(image.convert_to(&"uJy".parse()?)?[..2], [1000.0, 2000.0]);
+    /// # Ok::<(), astro_rs::fits::UnitError>(())
+    /// ```
+    pub fn image_quantities(&mut self) -> Result<Quantities, UnitError> {
+        let unit = self.header.data_unit()?.unwrap_or_default();
+        let values = image_hdu::physical_pixels(self)?;
+        Ok(Quantities { values, unit })
+    }
+
+    /// Reads the physical values of the binary table column with the given name, with TSCALn and TZEROn applied,
+    /// in the unit given by TUNITn, or dimensionless if TUNITn is absent.
+    pub fn column_quantities(&mut self, name: &str) -> Result<Quantities, UnitError> {
+        let (index, values) = binary_table_hdu::physical_column(self, name)?.ok_or_else(|| {
+            UnitError::InvalidColumn {
+                name: name.to_owned(),
+            }
+        })?;
+        let unit = self.header.column_unit(index)?.unwrap_or_default();
+        Ok(Quantities { values, unit })
+    }
+}
+
+/// The prefixes of the units that accept them, with their factors.
+const PREFIXES: [(&str, f64); 20] = [
+    ("da", 1e1),
+    ("y", 1e-24),
+    ("z", 1e-21),
+    ("a", 1e-18),
+    ("f", 1e-15),
+    ("p", 1e-12),
+    ("n", 1e-9),
+    ("u", 1e-6),
+    ("m", 1e-3),
+    ("c", 1e-2),
+    ("d", 1e-1),
+    ("h", 1e2),
+    ("k", 1e3),
+    ("M", 1e6),
+    ("G", 1e9),
+    ("T", 1e12),
+    ("P", 1e15),
+    ("E", 1e18),
+    ("Z", 1e21),
+    ("Y", 1e24),
+];
+
+/// Gets the unit with the given symbol, with the dimension given by the exponents of
+/// m, kg, s, A, K, mol, cd, rad, count, pixel, and bit, and whether it accepts a prefix.
+fn base_unit(symbol: &str) -> Option<(FitsUnit, bool)> {
+    let unit = |scale: f64, exponents: [i8; 11]| FitsUnit {
+        scale,
+        dimension: UnitDimension::from_exponents(exponents.map(f64::from)),
+    };
+    let day = 86400.0;
+    let year = 365.25 * day;
+    let electron_volt = 1.602176634e-19;
+    let (unit, prefixable) = match symbol {
+        // SI base and derived units
+        "m" => (unit(1.0, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "g" => (unit(1e-3, [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "s" => (unit(1.0, [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "A" => (unit(1.0, [0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]), true),
+        "K" => (unit(1.0, [0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0]), true),
+        "mol" => (unit(1.0, [0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0]), true),
+        "cd" => (unit(1.0, [0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0]), true),
+        "rad" => (unit(1.0, [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]), true),
+        "sr" => (unit(1.0, [0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]), true),
+        "Hz" => (unit(1.0, [0, 0, -1, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "J" => (unit(1.0, [2, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "W" => (unit(1.0, [2, 1, -3, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "V" => (unit(1.0, [2, 1, -3, -1, 0, 0, 0, 0, 0, 0, 0]), true),
+        "N" => (unit(1.0, [1, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "Pa" => (unit(1.0, [-1, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "C" => (unit(1.0, [0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0]), true),
+        "Ohm" => (unit(1.0, [2, 1, -3, -2, 0, 0, 0, 0, 0, 0, 0]), true),
+        "S" => (unit(1.0, [-2, -1, 3, 2, 0, 0, 0, 0, 0, 0, 0]), true),
+        "F" => (unit(1.0, [-2, -1, 4, 2, 0, 0, 0, 0, 0, 0, 0]), true),
+        "Wb" => (unit(1.0, [2, 1, -2, -1, 0, 0, 0, 0, 0, 0, 0]), true),
+        "T" => (unit(1.0, [0, 1, -2, -1, 0, 0, 0, 0, 0, 0, 0]), true),
+        "H" => (unit(1.0, [2, 1, -2, -2, 0, 0, 0, 0, 0, 0, 0]), true),
+        "lm" => (unit(1.0, [0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0]), true),
+        "lx" => (unit(1.0, [-2, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0]), true),
+        // other units recognized by the FITS standard
+        "deg" => (unit(PI / 180.0, [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]), false),
+        "arcmin" => (unit(PI / 10800.0, [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]), false),
+        "arcsec" => (
+            unit(PI / 648000.0, [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]),
+            false,
+        ),
+        "mas" => (unit(PI / 648e6, [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]), false),
+        "min" => (unit(60.0, [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "h" => (unit(3600.0, [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "d" => (unit(day, [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "a" | "yr" => (unit(year, [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "eV" => (
+            unit(electron_volt, [2, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0]),
+            true,
+        ),
+        "erg" => (unit(1e-7, [2, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "Ry" => (
+            unit(
+                13.605693122994 * electron_volt,
+                [2, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0],
+            ),
+            false,
+        ),
+        "solMass" => (unit(1.9891e30, [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "u" => (
+            unit(1.66053906660e-27, [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            false,
+        ),
+        "solLum" => (unit(3.8268e26, [2, 1, -3, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "Angstrom" => (unit(1e-10, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "solRad" => (unit(6.9599e8, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), false),
+        "AU" => (
+            unit(1.495978707e11, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            false,
+        ),
+        "lyr" => (
+            unit(9.4607304725808e15, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            false,
+        ),
+        "pc" => (
+            unit(3.085677581491367e16, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+            true,
+        ),
+        "count" | "ct" | "photon" | "ph" => (unit(1.0, [0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0]), false),
+        "Jy" => (unit(1e-26, [0, 1, -2, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        // the rayleigh is 10**10 / (4 pi) photons per square meter per second per steradian
+        "R" => (
+            unit(1e10 / (4.0 * PI), [-2, 0, -1, 0, 0, 0, 0, -2, 1, 0, 0]),
+            true,
+        ),
+        "G" => (unit(1e-4, [0, 1, -2, -1, 0, 0, 0, 0, 0, 0, 0]), true),
+        "barn" => (unit(1e-28, [2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), true),
+        "D" => (
+            unit(1e-21 / 299792458.0, [1, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0]),
+            true,
+        ),
+        "pixel" | "pix" | "voxel" => (unit(1.0, [0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0]), false),
+        "bit" => (unit(1.0, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]), true),
+        "byte" => (unit(8.0, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]), true),
+        // channels, beams, and analog-to-digital units have no physical scale
+        "chan" | "beam" | "adu" => (FitsUnit::DIMENSIONLESS, false),
+        _ => return None,
+    };
+    Some((unit, prefixable))
+}
+
+/// Gets the unit with the given name, which may be a symbol with a prefix.
+fn lookup_unit(name: &str) -> Result<FitsUnit, UnitError> {
+    if let Some((unit, _)) = base_unit(name) {
+        return Ok(unit);
+    }
+    for (prefix, factor) in PREFIXES {
+        if let Some((unit, true)) = name.strip_prefix(prefix).and_then(base_unit) {
+            return Ok(FitsUnit {
+                scale: unit.scale * factor,
+                dimension: unit.dimension,
+            });
+        }
+    }
+    if matches!(name, "mag" | "dex" | "log" | "ln" | "exp") {
+        return Err(UnitError::UnsupportedUnit {
+            name: name.to_owned(),
+        });
+    }
+    Err(UnitError::UnknownUnit {
+        name: name.to_owned(),
+    })
+}
+
+/// A recursive descent parser of unit strings.
+struct UnitParser<'a> {
+    unit: &'a str,
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl<'a> UnitParser<'a> {
+    fn new(unit: &'a str) -> Self {
+        UnitParser {
+            unit,
+            chars: unit.trim().chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn invalid(&self) -> UnitError {
+        UnitError::InvalidUnit {
+            unit: self.unit.to_owned(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn skip_spaces(&mut self) -> bool {
+        let start = self.position;
+        while self.peek() == Some(' ') {
+            self.position += 1;
+        }
+        self.position > start
+    }
+
+    fn parse(mut self) -> Result<FitsUnit, UnitError> {
+        if self.chars.is_empty() {
+            return Ok(FitsUnit::DIMENSIONLESS);
+        }
+        let mut result = FitsUnit::DIMENSIONLESS;
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            result.scale = self.parse_scale()?;
+            self.skip_spaces();
+            if self.peek().is_none() {
+                return Ok(result);
+            }
+        }
+        result = result * self.parse_product()?;
+        if self.peek().is_some() {
+            return Err(self.invalid());
+        }
+        Ok(result)
+    }
+
+    /// Parses a leading scale factor, such as `10**-17`, `10^3`, `10+3`, or `1e-17`.
+    fn parse_scale(&mut self) -> Result<f64, UnitError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.position += 1;
+        }
+        let mantissa: String = self.chars[start..self.position].iter().collect();
+        if mantissa == "10" {
+            if self.peek_power_operator() {
+                return Ok(10f64.powf(self.parse_exponent()?));
+            }
+            if matches!(self.peek(), Some('+' | '-'))
+                && self.peek_at(1).is_some_and(|c| c.is_ascii_digit())
+            {
+                return Ok(10f64.powf(self.parse_integer()?));
+            }
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            self.position += 1;
+            let exponent = self.parse_integer()?;
+            let mantissa: f64 = mantissa.parse().map_err(|_| self.invalid())?;
+            return Ok(mantissa * 10f64.powf(exponent));
+        }
+        mantissa.parse().map_err(|_| self.invalid())
+    }
+
+    /// Parses terms separated by spaces, periods, or asterisks for multiplication, or slashes for division.
+    fn parse_product(&mut self) -> Result<FitsUnit, UnitError> {
+        let mut result = self.parse_term()?;
+        loop {
+            let spaced = self.skip_spaces();
+            match self.peek() {
+                Some('/') => {
+                    self.position += 1;
+                    self.skip_spaces();
+                    result = result / self.parse_term()?;
+                }
+                Some('.' | '*') => {
+                    self.position += 1;
+                    self.skip_spaces();
+                    result = result * self.parse_term()?;
+                }
+                Some(c) if spaced && (c.is_ascii_alphabetic() || c == '(') => {
+                    result = result * self.parse_term()?;
+                }
+                _ => return Ok(result),
+            }
+        }
+    }
+
+    /// Parses a unit or parenthesized product, with an optional power.
+    fn parse_term(&mut self) -> Result<FitsUnit, UnitError> {
+        let base = match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                self.skip_spaces();
+                let product = self.parse_product()?;
+                self.expect(')')?;
+                product
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.position;
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.position += 1;
+                }
+                let name: String = self.chars[start..self.position].iter().collect();
+                if self.peek() == Some('(') {
+                    return self.parse_function(&name);
+                }
+                lookup_unit(&name)?
+            }
+            _ => return Err(self.invalid()),
+        };
+        if self.peek_power_operator() {
+            return Ok(base.powf(self.parse_exponent()?));
+        }
+        // a power may directly follow a unit, as in m-2
+        if self.peek().is_some_and(|c| c.is_ascii_digit())
+            || (matches!(self.peek(), Some('+' | '-'))
+                && self.peek_at(1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            return Ok(base.powf(self.parse_integer()?));
+        }
+        Ok(base)
+    }
+
+    /// Parses the argument of a function such as `sqrt(Hz)`.
+    fn parse_function(&mut self, name: &str) -> Result<FitsUnit, UnitError> {
+        if name != "sqrt" {
+            return Err(UnitError::UnsupportedUnit {
+                name: name.to_owned(),
+            });
+        }
+        self.position += 1;
+        self.skip_spaces();
+        let argument = self.parse_product()?;
+        self.expect(')')?;
+        Ok(argument.powf(0.5))
+    }
+
+    fn peek_power_operator(&mut self) -> bool {
+        match (self.peek(), self.peek_at(1)) {
+            (Some('*'), Some('*')) => {
+                self.position += 2;
+                true
+            }
+            (Some('^'), _) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses an exponent following `**` or `^`, which is an integer or a parenthesized number or fraction.
+    fn parse_exponent(&mut self) -> Result<f64, UnitError> {
+        if self.peek() != Some('(') {
+            return self.parse_integer();
+        }
+        self.position += 1;
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | '/'))
+        {
+            self.position += 1;
+        }
+        let exponent: String = self.chars[start..self.position].iter().collect();
+        self.expect(')')?;
+        let value = match exponent.split_once('/') {
+            Some((numerator, denominator)) => {
+                let numerator: f64 = numerator.parse().map_err(|_| self.invalid())?;
+                let denominator: f64 = denominator.parse().map_err(|_| self.invalid())?;
+                numerator / denominator
+            }
+            None => exponent.parse().map_err(|_| self.invalid())?,
+        };
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(self.invalid())
+        }
+    }
+
+    /// Parses an integer with an optional sign.
+    fn parse_integer(&mut self) -> Result<f64, UnitError> {
+        let start = self.position;
+        if matches!(self.peek(), Some('+' | '-')) {
+            self.position += 1;
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        let integer: String = self.chars[start..self.position].iter().collect();
+        integer
+            .parse::<i32>()
+            .map(f64::from)
+            .map_err(|_| self.invalid())
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), UnitError> {
+        self.skip_spaces();
+        if self.peek() != Some(c) {
+            return Err(self.invalid());
+        }
+        self.position += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+//! Update FITS files on disk in place.
+
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::rc::Weak;
+
+use super::*;
+
+/// The size of the buffer used to move data sections within a file.
+const MOVE_BUFFER_LEN: usize = 1 << 20;
+
+/// The location of an HDU within the file from which it was read.
+#[derive(Debug, Clone)]
+pub(crate) struct HduOrigin {
+    /// The offset of the header section.
+    pub(crate) header_offset: u64,
+    /// The header section as it was read.
+    pub(crate) header_raw: Vec<u8>,
+    /// The offset of the data section.
+    pub(crate) data_offset: u64,
+    /// The length of the data section, including padding.
+    pub(crate) data_len: usize,
+    /// The number of bytes of the data section that exist in the file.
+    pub(crate) data_found: usize,
+    /// Whether the data section has been replaced since it was read.
+    pub(crate) data_modified: bool,
+    /// The source from which the HDU was read, which the offsets refer to.
+    pub(crate) source: Weak<RefCell<dyn DataSource>>,
+}
+
+impl HduOrigin {
+    /// Determines if the HDU was read from the given source.
+    fn is_from(&self, source: Option<&Rc<RefCell<dyn DataSource>>>) -> bool {
+        source.is_some_and(|source| Weak::ptr_eq(&self.source, &Rc::downgrade(source)))
+    }
+}
+
+/// The destination of an HDU when an updated file is flushed.
+struct HduPlacement {
+    header_offset: u64,
+    header_raw: Vec<u8>,
+    header_changed: bool,
+    data_offset: u64,
+    data_len: usize,
+    /// The offset and length of the unmodified data section in the file, or None if the data section is written from memory.
+    data_source: Option<(u64, usize)>,
+}
+
+impl HduList<File> {
+    /// Opens the FITS file at the given path for reading and writing.
+    /// Data sections are read as they are accessed, as with [`HduList::new_lazy`],
+    /// and changes are written back to the file by [`HduList::flush`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let path = std::env::temp_dir().join("astro_rs_doc_open_update.fits");
+    /// let mut hdu = primary_hdu::default();
+    /// hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 4u32)?;
+    /// hdu.set_data(&vec![1u8, 2, 3, 4]);
+    /// std::fs::write(&path, hdu.to_bytes())?;
+    ///
+    /// let mut hdu_list = HduList::open_update(&path)?;
+    /// let hdu = hdu_list.try_first_mut()?.unwrap();
+    /// hdu.header.set_value(EXTNAME_KEYWORD, String::from("SCI"))?;
+    /// hdu_list.flush()?;
+    /// assert_eq!(std::fs::metadata(&path)?.len(), 5760);
+    ///
+    /// let mut hdu_list = HduList::open(&path)?;
+    /// assert!(hdu_list.try_get_by_name("SCI")?.is_some());
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn open_update<P: AsRef<Path>>(path: P) -> Result<Self, FitsError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|source| FitsError::Io {
+                source,
+                hdu_index: 0,
+                offset: 0,
+            })?;
+        Ok(HduList::new_lazy(BufReader::new(file)))
+    }
+
+    /// Writes the changes made to the HDUs back to the file they were read from.
+    ///
+    /// A header that still fits in the records it occupied is rewritten in place, padded with blank cards if it shrank;
+    /// otherwise, the sections that follow it are moved. Data sections that have not been replaced are never read into memory,
+    /// and are only written if they must be moved. HDUs that were inserted or reordered are written from memory.
+    pub fn flush(&mut self) -> Result<(), FitsError> {
+        while let Some(new_hdu) = self.try_read_hdu()? {
+            self.hdus.push(new_hdu);
+        }
+        self.fix_structure().map_err(|source| FitsError::Header {
+            source,
+            hdu_index: 0,
+            offset: 0,
+        })?;
+
+        let mut placements = Vec::with_capacity(self.hdus.len());
+        let mut offset = 0;
+        let mut previous_end = 0;
+        for (hdu_index, hdu) in self.hdus.iter_mut().enumerate() {
+            // HDUs read from another source are written from memory, as are HDUs that precede their position in the file,
+            // which cannot be moved without overwriting other sections
+            let origin = hdu.origin.clone().filter(|origin| {
+                origin.is_from(self.source.as_ref()) && origin.header_offset >= previous_end
+            });
+            if let Some(origin) = &origin {
+                previous_end = origin.data_offset + origin.data_found as u64;
+            }
+            let data_source = origin
+                .as_ref()
+                .filter(|origin| !origin.data_modified)
+                .map(|origin| (origin.data_offset, origin.data_found));
+            let data_len = match (&origin, data_source) {
+                (Some(origin), Some(_)) => origin.data_len,
+                // data written from memory is read before any section is overwritten
+                _ => padded_len(hdu.try_data_raw()?.len()),
+            };
+
+            // the header keeps the records it occupied if its cards still fit
+            let capacity = origin
+                .as_ref()
+                .map(|origin| origin.header_raw.len() / HEADER_CARD_LEN);
+            let header_raw = if self.update_checksums {
+                let datasum = ones_complement_sum(hdu.try_data_raw()?);
+                update_checksum(&mut hdu.header, datasum, hdu_index, offset)?;
+                let mut header = hdu.header.clone();
+                fit_header(&mut header, capacity);
+                // the checksum covers the blank cards that pad the header
+                update_checksum(&mut header, datasum, hdu_index, offset)?;
+                if let (Some(fitted), Some(card)) = (
+                    header.get_card(CHECKSUM_KEYWORD),
+                    hdu.header.get_card(CHECKSUM_KEYWORD),
+                ) {
+                    *card = fitted.clone();
+                }
+                header.to_bytes()
+            } else {
+                let mut header = hdu.header.clone();
+                fit_header(&mut header, capacity);
+                header.to_bytes()
+            };
+            let header_changed = origin.as_ref().is_none_or(|origin| {
+                origin.header_raw != header_raw || origin.header_offset != offset
+            });
+            let data_offset = offset + header_raw.len() as u64;
+            offset = data_offset + data_len as u64;
+            placements.push(HduPlacement {
+                header_offset: data_offset - header_raw.len() as u64,
+                header_raw,
+                header_changed,
+                data_offset,
+                data_len,
+                data_source,
+            });
+        }
+
+        {
+            let mut reader = self.reader.borrow_mut();
+            let file = reader.get_mut();
+            // sections moving towards the start of the file are moved first, in order,
+            // then sections moving towards the end of the file, in reverse order
+            for placement in &placements {
+                if let Some((source_offset, len)) = placement.data_source {
+                    if placement.data_offset < source_offset {
+                        move_section(file, source_offset, placement.data_offset, len)
+                            .map_err(io_error(placement.data_offset))?;
+                    }
+                }
+            }
+            for placement in placements.iter().rev() {
+                if let Some((source_offset, len)) = placement.data_source {
+                    if placement.data_offset > source_offset {
+                        move_section(file, source_offset, placement.data_offset, len)
+                            .map_err(io_error(placement.data_offset))?;
+                    }
+                }
+            }
+
+            for (hdu, placement) in self.hdus.iter().zip(&placements) {
+                if placement.header_changed {
+                    write_at(file, placement.header_offset, &placement.header_raw)
+                        .map_err(io_error(placement.header_offset))?;
+                }
+                let (data, written) = match placement.data_source {
+                    Some((_, found)) => (&[][..], found),
+                    None => {
+                        let data = hdu.try_data_raw()?;
+                        (data.as_slice(), 0)
+                    }
+                };
+                let padding = vec![0; placement.data_len.saturating_sub(written + data.len())];
+                if !data.is_empty() || !padding.is_empty() {
+                    let start = placement.data_offset + written as u64;
+                    write_at(file, start, &[data, &padding].concat()).map_err(io_error(start))?;
+                }
+            }
+            file.set_len(offset).map_err(io_error(offset))?;
+            file.flush().map_err(io_error(offset))?;
+            // discard any buffered bytes, which may have been overwritten
+            reader
+                .seek(SeekFrom::Start(offset))
+                .map_err(io_error(offset))?;
+        }
+
+        for (hdu, placement) in self.hdus.iter_mut().zip(placements) {
+            let Some(source) = &self.source else {
+                break;
+            };
+            // deferred data sections, including those of HDUs read from another source, now refer to this file
+            if hdu.deferred.is_some() || !hdu.is_data_loaded() || placement.data_source.is_some() {
+                hdu.deferred = Some(DeferredData {
+                    source: Rc::clone(source),
+                    offset: placement.data_offset,
+                    len: placement.data_len,
+                    hdu_index: 0,
+                });
+            }
+            hdu.origin = Some(HduOrigin {
+                header_offset: placement.header_offset,
+                header_raw: placement.header_raw,
+                data_offset: placement.data_offset,
+                data_len: placement.data_len,
+                data_found: placement.data_len,
+                data_modified: false,
+                source: Rc::downgrade(source),
+            });
+        }
+        for (hdu_index, hdu) in self.hdus.iter_mut().enumerate() {
+            if let Some(deferred) = &mut hdu.deferred {
+                deferred.hdu_index = hdu_index;
+            }
+        }
+        self.hdus_read = self.hdus.len();
+        self.position = offset;
+        Ok(())
+    }
+}
+
+/// Removes the blank cards that pad the header, then pads it to fill the given number of cards if possible,
+/// or otherwise to fill a whole number of records.
+fn fit_header(header: &mut FitsHeader, capacity: Option<usize>) {
+    let blank_card = || FitsHeaderCard::from([b' '; HEADER_CARD_LEN]);
+    let is_blank = |card: &FitsHeaderCard| {
+        <[u8; HEADER_CARD_LEN]>::from(card.clone()) == [b' '; HEADER_CARD_LEN]
+    };
+    let Some(end_index) = header
+        .cards
+        .iter()
+        .rposition(|card| *card.keyword() == END_KEYWORD)
+    else {
+        return;
+    };
+    header.cards.truncate(end_index + 1);
+    let mut num_cards = header.cards.len();
+    if let Some(capacity) = capacity {
+        while num_cards > capacity && num_cards > 1 && is_blank(&header.cards[num_cards - 2]) {
+            header.cards.remove(num_cards - 2);
+            num_cards -= 1;
+        }
+    }
+    let cards_per_record = FITS_RECORD_LEN / HEADER_CARD_LEN;
+    let target = match capacity {
+        Some(capacity) if num_cards <= capacity => capacity,
+        _ => num_cards.div_ceil(cards_per_record) * cards_per_record,
+    };
+    header.cards.resize_with(target, blank_card);
+}
+
+fn update_checksum(
+    header: &mut FitsHeader,
+    datasum: u32,
+    hdu_index: usize,
+    offset: u64,
+) -> Result<(), FitsError> {
+    update_header_checksum(header, datasum).map_err(|source| FitsError::Header {
+        source,
+        hdu_index,
+        offset,
+    })
+}
+
+fn io_error(offset: u64) -> impl Fn(std::io::Error) -> FitsError {
+    move |source| FitsError::Io {
+        source,
+        hdu_index: 0,
+        offset,
+    }
+}
+
+fn write_at(file: &mut File, offset: u64, bytes: &[u8]) -> Result<(), std::io::Error> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(bytes)
+}
+
+/// Copies `len` bytes within the file, in an order that is safe for overlapping sections.
+fn move_section(file: &mut File, from: u64, to: u64, len: usize) -> Result<(), std::io::Error> {
+    let mut buf = vec![0; MOVE_BUFFER_LEN.min(len)];
+    let mut moved = 0;
+    while moved < len {
+        let chunk_len = buf.len().min(len - moved);
+        // towards the end of the file, the last chunk is moved first
+        let start = if to > from {
+            (len - moved - chunk_len) as u64
+        } else {
+            moved as u64
+        };
+        file.seek(SeekFrom::Start(from + start))?;
+        file.read_exact(&mut buf[..chunk_len])?;
+        write_at(file, to + start, &buf[..chunk_len])?;
+        moved += chunk_len;
+    }
+    Ok(())
+}
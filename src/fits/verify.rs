@@ -0,0 +1,630 @@
+//! Verify conformance to the FITS standard.
+
+use std::fmt::Display;
+
+use super::*;
+
+const MANDATORY_FIXED_KEYWORDS: [&str; 8] = [
+    "SIMPLE", "BITPIX", "NAXIS", "PCOUNT", "GCOUNT", "TFIELDS", "EXTEND", "XTENSION",
+];
+const COMMENTARY_KEYWORDS: [&str; 4] = ["COMMENT", "HISTORY", "", "CONTINUE"];
+
+/// An enumeration of the ways deviations from the FITS standard are handled while reading.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Recoverable deviations are recorded and read through.
+    /// Missing padding at the end of the stream is filled, and keywords are normalized to upper case
+    /// if requested with [`HduList::set_normalize_keywords`].
+    #[default]
+    Lenient,
+    /// Any deviation with [`Severity::Error`] causes reading to fail.
+    Strict,
+}
+
+/// An enumeration of the severities of a verification issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Indicates a deviation from recommended practice.
+    Warning,
+    /// Indicates a violation of the FITS standard.
+    Error,
+}
+
+/// A deviation from the FITS standard found during verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationIssue {
+    /// The severity of the deviation.
+    pub severity: Severity,
+    /// The index of the HDU in which the deviation was found.
+    pub hdu_index: usize,
+    /// The index of the header card at which the deviation was found, if applicable.
+    pub card_index: Option<usize>,
+    /// A description of the deviation.
+    pub message: String,
+}
+
+impl VerificationIssue {
+    pub(crate) fn error(hdu_index: usize, card_index: Option<usize>, message: String) -> Self {
+        VerificationIssue {
+            severity: Severity::Error,
+            hdu_index,
+            card_index,
+            message,
+        }
+    }
+
+    pub(crate) fn warning(hdu_index: usize, card_index: Option<usize>, message: String) -> Self {
+        VerificationIssue {
+            severity: Severity::Warning,
+            hdu_index,
+            card_index,
+            message,
+        }
+    }
+}
+
+impl Display for VerificationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{} in HDU {}", severity, self.hdu_index)?;
+        if let Some(card_index) = self.card_index {
+            write!(f, ", card {}", card_index)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The collection of deviations from the FITS standard found during verification.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// The deviations that were found.
+    pub issues: Vec<VerificationIssue>,
+}
+
+impl VerificationReport {
+    /// Determines if no deviations with [`Severity::Error`] were found.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns an iterator over the deviations with [`Severity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &VerificationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over the deviations with [`Severity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &VerificationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+}
+
+impl Display for VerificationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        write!(
+            f,
+            "{} error(s), {} warning(s)",
+            self.errors().count(),
+            self.warnings().count()
+        )
+    }
+}
+
+impl<R: Read> HduList<R> {
+    /// Gets the mode used to handle deviations from the FITS standard while reading.
+    pub fn parse_mode(&self) -> ParseMode {
+        self.parse_mode
+    }
+
+    /// Sets the mode used to handle deviations from the FITS standard while reading.
+    /// The mode applies to HDUs that have not yet been read.
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+
+    /// Determines if keywords are normalized to upper case while reading in [`ParseMode::Lenient`].
+    pub fn normalize_keywords(&self) -> bool {
+        self.normalize_keywords
+    }
+
+    /// Sets whether keywords are normalized to upper case while reading in [`ParseMode::Lenient`].
+    /// By default, the original bytes of each header are kept.
+    /// The setting applies to HDUs that have not yet been read.
+    pub fn set_normalize_keywords(&mut self, normalize_keywords: bool) {
+        self.normalize_keywords = normalize_keywords;
+    }
+
+    /// Deserializes all HDUs if necessary, then verifies the HDU list against the FITS standard.
+    /// The report includes deviations in the raw bytes that were found while reading,
+    /// as well as deviations in the structure of each header.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use std::io::*;
+    ///
+    /// let mut bytes = b"SIMPLE  =                    T                                                  NAXIS   =                    0                                                  BITPIX  =                    8                                                  exptime =                 30.0                                                  END".to_vec();
+    /// bytes.resize(2880, b' ');
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes.clone())));
+    /// let report = hdu_list.verify()?;
+    /// assert!(!report.is_valid());
+    /// assert_eq!(report.errors().count(), 3);
+    /// // the lowercase keyword is kept unless normalization is requested
+    /// assert!(hdu_list.first_mut().unwrap().header.get_card(*b"EXPTIME ").is_none());
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes.clone())));
+    /// hdu_list.set_normalize_keywords(true);
+    /// assert!(hdu_list.first_mut().unwrap().header.get_card(*b"EXPTIME ").is_some());
+    ///
+    /// let mut hdu_list = HduList::new(BufReader::new(Cursor::new(bytes)));
+    /// hdu_list.set_parse_mode(ParseMode::Strict);
+    /// assert!(matches!(hdu_list.try_first_mut(), Err(FitsError::Invalid { .. })));
+    /// # Ok::<(), astro_rs::fits::FitsError>(())
+    /// ```
+    pub fn verify(&mut self) -> Result<VerificationReport, FitsError> {
+        while let Some(new_hdu) = self.try_read_hdu()? {
+            self.hdus.push(new_hdu);
+        }
+        let headers = self.hdus.iter_mut().map(|hdu| &mut hdu.header);
+        Ok(verify_headers(self.read_issues.clone(), headers))
+    }
+}
+
+/// Verifies the structure of the given headers of an HDU list,
+/// appending the deviations to the given issues that were found while reading.
+pub(crate) fn verify_headers<'a>(
+    mut issues: Vec<VerificationIssue>,
+    headers: impl ExactSizeIterator<Item = &'a mut FitsHeader>,
+) -> VerificationReport {
+    let num_hdus = headers.len();
+    for (hdu_index, header) in headers.enumerate() {
+        issues.append(&mut header.verify(hdu_index));
+        if hdu_index == 0 && num_hdus > 1 {
+            let extend = header
+                .get_card(*b"EXTEND  ")
+                .and_then(|card| card.get_value::<bool>().ok())
+                .is_some_and(|extend| *extend);
+            if !extend {
+                issues.push(VerificationIssue::warning(
+                    hdu_index,
+                    None,
+                    String::from("extensions are present, but EXTEND is not T"),
+                ));
+            }
+        }
+    }
+    VerificationReport { issues }
+}
+
+impl FitsHeader {
+    /// Verifies the structure of the header against the FITS standard.
+    /// The header is expected to belong to a primary HDU if `hdu_index` is 0, and to an extension otherwise.
+    ///
+    /// The mandatory keywords for primary, IMAGE, TABLE, and BINTABLE HDUs are checked for existence, order, and value,
+    /// along with the characters used in each keyword and the placement of the END keyword.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = primary_hdu::default();
+    /// assert!(hdu.header.verify(0).is_empty());
+    /// // a primary header cannot be used as an extension
+    /// assert!(!hdu.header.verify(1).is_empty());
+    ///
+    /// let mut hdu = binary_table_hdu::default();
+    /// assert!(hdu.header.verify(1).is_empty());
+    /// hdu.header.set_value(*b"TFIELDS ", 1u16)?;
+    /// let issues = hdu.header.verify(1);
+    /// assert_eq!(issues.len(), 1);
+    /// assert_eq!(issues[0].message, "missing required keyword TFORM1");
+    /// # Ok::<(), astro_rs::fits::FitsHeaderError>(())
+    /// ```
+    pub fn verify(&mut self, hdu_index: usize) -> Vec<VerificationIssue> {
+        let mut issues = Vec::new();
+
+        let mut keywords = Vec::with_capacity(self.cards.len());
+        for (card_index, card) in self.cards.iter().enumerate() {
+            let keyword = card.keyword().to_string();
+            if let Some(message) = invalid_keyword_message(&keyword) {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    Some(card_index),
+                    message,
+                ));
+            }
+            keywords.push(keyword);
+        }
+
+        match keywords.iter().position(|keyword| keyword == "END") {
+            Some(end_index) if keywords[end_index + 1..].iter().any(|k| !k.is_empty()) => {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    Some(end_index),
+                    String::from("END is not the last keyword"),
+                ));
+            }
+            None => {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    None,
+                    String::from("missing required keyword END"),
+                ));
+            }
+            _ => {}
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for (card_index, keyword) in keywords.iter().enumerate() {
+            if !COMMENTARY_KEYWORDS.contains(&keyword.as_str()) && !seen.insert(keyword) {
+                issues.push(VerificationIssue::warning(
+                    hdu_index,
+                    Some(card_index),
+                    format!("duplicate keyword {}", keyword),
+                ));
+            }
+        }
+
+        let mut mandatory = Vec::new();
+        if hdu_index == 0 {
+            mandatory.push(String::from("SIMPLE"));
+            if keywords.first().is_some_and(|keyword| keyword == "SIMPLE") {
+                let simple = self.cards[0].get_value::<bool>().map(|value| *value);
+                if simple.is_err() {
+                    issues.push(VerificationIssue::error(
+                        hdu_index,
+                        Some(0),
+                        String::from("SIMPLE value is not a logical constant"),
+                    ));
+                } else if simple.is_ok_and(|value| !value) {
+                    issues.push(VerificationIssue::warning(
+                        hdu_index,
+                        Some(0),
+                        String::from("SIMPLE is F, so the file may not conform to the standard"),
+                    ));
+                }
+            }
+        } else {
+            mandatory.push(String::from("XTENSION"));
+            if keywords.contains(&String::from("SIMPLE")) {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    keywords.iter().position(|keyword| keyword == "SIMPLE"),
+                    String::from("SIMPLE is only allowed in the primary HDU"),
+                ));
+            }
+        }
+        if hdu_index == 0 && keywords.contains(&String::from("XTENSION")) {
+            issues.push(VerificationIssue::error(
+                hdu_index,
+                keywords.iter().position(|keyword| keyword == "XTENSION"),
+                String::from("XTENSION is not allowed in the primary HDU"),
+            ));
+        }
+        mandatory.push(String::from("BITPIX"));
+        mandatory.push(String::from("NAXIS"));
+
+        if self
+            .get_card(BITPIX_KEYWORD)
+            .is_some_and(|card| card.get_value::<Bitpix>().is_err())
+        {
+            issues.push(VerificationIssue::error(
+                hdu_index,
+                keywords.iter().position(|keyword| keyword == "BITPIX"),
+                String::from("BITPIX value is not one of 8, 16, 32, -32, or -64"),
+            ));
+        }
+        let naxis = self
+            .get_card(NAXIS_KEYWORD)
+            .and_then(|card| card.get_value::<u16>().ok())
+            .map(|naxis| *naxis);
+        if naxis.is_none_or(|naxis| naxis > 999) && keywords.iter().any(|k| k == "NAXIS") {
+            issues.push(VerificationIssue::error(
+                hdu_index,
+                keywords.iter().position(|keyword| keyword == "NAXIS"),
+                String::from("NAXIS value is not an integer between 0 and 999"),
+            ));
+        }
+        for x in 1..=naxis.unwrap_or_default() {
+            mandatory.push(format!("NAXIS{}", x));
+        }
+
+        let xtension = if hdu_index == 0 {
+            None
+        } else {
+            self.get_card(XTENSION_KEYWORD)
+                .and_then(|card| card.get_value::<String>().ok())
+                .map(|value| value.trim().to_owned())
+        };
+        match xtension.as_deref() {
+            Some("IMAGE") => {
+                mandatory.push(String::from("PCOUNT"));
+                mandatory.push(String::from("GCOUNT"));
+                self.verify_integer_value(*b"PCOUNT  ", Some(0), hdu_index, &mut issues);
+                self.verify_integer_value(*b"GCOUNT  ", Some(1), hdu_index, &mut issues);
+            }
+            Some(table @ ("TABLE" | "BINTABLE")) => {
+                mandatory.push(String::from("PCOUNT"));
+                mandatory.push(String::from("GCOUNT"));
+                mandatory.push(String::from("TFIELDS"));
+                if self
+                    .get_card(BITPIX_KEYWORD)
+                    .and_then(|card| card.get_value::<Bitpix>().ok())
+                    .is_some_and(|bitpix| *bitpix != Bitpix::U8)
+                {
+                    issues.push(VerificationIssue::error(
+                        hdu_index,
+                        keywords.iter().position(|keyword| keyword == "BITPIX"),
+                        format!("BITPIX must be 8 in a {} extension", table),
+                    ));
+                }
+                if naxis.is_some_and(|naxis| naxis != 2) {
+                    issues.push(VerificationIssue::error(
+                        hdu_index,
+                        keywords.iter().position(|keyword| keyword == "NAXIS"),
+                        format!("NAXIS must be 2 in a {} extension", table),
+                    ));
+                }
+                let pcount = if table == "TABLE" { Some(0) } else { None };
+                self.verify_integer_value(*b"PCOUNT  ", pcount, hdu_index, &mut issues);
+                self.verify_integer_value(*b"GCOUNT  ", Some(1), hdu_index, &mut issues);
+                let tfields = self
+                    .get_card(*b"TFIELDS ")
+                    .and_then(|card| card.get_value::<u16>().ok())
+                    .map(|tfields| *tfields)
+                    .unwrap_or_default();
+                let mut field_keywords = vec![FitsHeaderKeyword::from(TFORM_KEYWORD)];
+                if table == "TABLE" {
+                    field_keywords.push(FitsHeaderKeyword::from(*b"TBCOL   "));
+                }
+                for n in 1..=tfields {
+                    for field_keyword in field_keywords.iter_mut() {
+                        field_keyword.append_number(n);
+                        if self.get_card(*field_keyword).is_none() {
+                            issues.push(VerificationIssue::error(
+                                hdu_index,
+                                None,
+                                format!("missing required keyword {}", field_keyword),
+                            ));
+                        }
+                    }
+                }
+            }
+            Some(other) => {
+                // conforming extensions share the structure of the standard extensions
+                mandatory.push(String::from("PCOUNT"));
+                mandatory.push(String::from("GCOUNT"));
+                issues.push(VerificationIssue::warning(
+                    hdu_index,
+                    Some(0),
+                    format!("{} is not a standard extension type", other),
+                ));
+            }
+            None => {}
+        }
+
+        for (position, expected) in mandatory.iter().enumerate() {
+            match keywords.get(position) {
+                Some(found) if found == expected => {}
+                found => {
+                    let actual = keywords.iter().position(|keyword| keyword == expected);
+                    let message = match (actual, found) {
+                        (Some(_), Some(found)) => format!(
+                            "mandatory keyword {} is out of order, expected at position {} but found {}",
+                            expected, position, found
+                        ),
+                        (Some(_), None) => format!(
+                            "mandatory keyword {} is out of order, expected at position {}",
+                            expected, position
+                        ),
+                        (None, _) => format!("missing required keyword {}", expected),
+                    };
+                    issues.push(VerificationIssue::error(hdu_index, actual, message));
+                }
+            }
+        }
+        issues
+    }
+
+    fn verify_integer_value(
+        &mut self,
+        keyword: [u8; 8],
+        expected: Option<u32>,
+        hdu_index: usize,
+        issues: &mut Vec<VerificationIssue>,
+    ) {
+        let card_index = self
+            .cards
+            .iter()
+            .position(|card| keyword == *card.keyword());
+        let Some(card) = self.get_card(keyword) else {
+            return;
+        };
+        let keyword = FitsHeaderKeyword::from(keyword);
+        match (card.get_value::<u32>().map(|value| *value), expected) {
+            (Err(_), _) => issues.push(VerificationIssue::error(
+                hdu_index,
+                card_index,
+                format!("{} value is not a non-negative integer", keyword),
+            )),
+            (Ok(value), Some(expected)) if value != expected => {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    card_index,
+                    format!("{} must be {}, found {}", keyword, expected, value),
+                ))
+            }
+            _ => {}
+        }
+    }
+}
+
+fn invalid_keyword_message(keyword: &str) -> Option<String> {
+    if keyword
+        .bytes()
+        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'-' || b == b'_')
+    {
+        None
+    } else if keyword.bytes().any(|b| b.is_ascii_lowercase()) {
+        Some(format!(
+            "keyword {} contains lower case characters",
+            keyword
+        ))
+    } else {
+        Some(format!("keyword '{}' contains invalid characters", keyword))
+    }
+}
+
+/// Verifies the raw bytes of a header against the FITS standard.
+/// The characters of each card, the fixed format of mandatory values, and the padding following the END card are checked.
+pub(crate) fn verify_header_raw(raw: &[u8], hdu_index: usize) -> Vec<VerificationIssue> {
+    let mut issues = Vec::new();
+    let mut end_found = false;
+    for (card_index, card) in raw.chunks_exact(HEADER_CARD_LEN).enumerate() {
+        if end_found {
+            if card.iter().any(|b| *b != b' ') {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    Some(card_index),
+                    String::from("header padding following END is not filled with blanks"),
+                ));
+                break;
+            }
+            continue;
+        }
+        if card.iter().any(|b| !(b' '..=b'~').contains(b)) {
+            issues.push(VerificationIssue::error(
+                hdu_index,
+                Some(card_index),
+                String::from("card contains characters outside of the printable ASCII range"),
+            ));
+        }
+
+        let keyword = String::from_utf8_lossy(&card[0..HEADER_KEYWORD_LEN]);
+        let keyword = keyword.trim_end();
+        if keyword == "END" {
+            end_found = true;
+            if card[3..].iter().any(|b| *b != b' ') {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    Some(card_index),
+                    String::from("END card is not filled with blanks"),
+                ));
+            }
+            continue;
+        }
+
+        let upper_keyword = keyword.to_ascii_uppercase();
+        let is_mandatory = MANDATORY_FIXED_KEYWORDS.contains(&upper_keyword.as_str())
+            || upper_keyword
+                .strip_prefix("NAXIS")
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        if is_mandatory {
+            if &card[8..10] != b"= " {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    Some(card_index),
+                    format!("{} is missing the value indicator", keyword),
+                ));
+            } else if !is_fixed_format(&card[10..]) {
+                issues.push(VerificationIssue::error(
+                    hdu_index,
+                    Some(card_index),
+                    format!("{} value is not in fixed format", keyword),
+                ));
+            }
+        } else if card[8] == b'='
+            && card[9] != b' '
+            && !COMMENTARY_KEYWORDS.contains(&upper_keyword.as_str())
+        {
+            issues.push(VerificationIssue::warning(
+                hdu_index,
+                Some(card_index),
+                format!("{} value indicator is not followed by a blank", keyword),
+            ));
+        }
+    }
+    issues
+}
+
+/// Determines if the given value field (columns 11 through 80) is in fixed format:
+/// strings begin in column 11, while other values are right-justified to column 30.
+fn is_fixed_format(value_field: &[u8]) -> bool {
+    if value_field[0] == b'\'' {
+        return true;
+    }
+    let value_end = 20;
+    if value_field[value_end - 1] == b' ' {
+        return false;
+    }
+    let value_start = value_field[0..value_end]
+        .iter()
+        .rposition(|b| *b == b' ')
+        .map(|idx| idx + 1)
+        .unwrap_or_default();
+    value_field[0..value_start].iter().all(|b| *b == b' ')
+        && value_field[value_end..]
+            .iter()
+            .find(|b| **b != b' ')
+            .is_none_or(|b| *b == b'/')
+}
+
+/// Converts lower case characters in the keywords of the given raw header to upper case.
+/// Returns a record of each keyword that was normalized.
+pub(crate) fn normalize_keywords(raw: &mut [u8], hdu_index: usize) -> Vec<VerificationIssue> {
+    let mut issues = Vec::new();
+    for (card_index, card) in raw.chunks_exact_mut(HEADER_CARD_LEN).enumerate() {
+        let keyword = &mut card[0..HEADER_KEYWORD_LEN];
+        if *keyword == END_KEYWORD {
+            break;
+        }
+        if keyword.iter().any(u8::is_ascii_lowercase) {
+            let original = String::from_utf8_lossy(keyword).trim_end().to_owned();
+            keyword.make_ascii_uppercase();
+            issues.push(VerificationIssue::warning(
+                hdu_index,
+                Some(card_index),
+                format!("keyword {} was normalized to upper case", original),
+            ));
+        }
+    }
+    issues
+}
+
+/// Verifies that the padding following a data section is filled with zeros,
+/// or with blanks for an ASCII table.
+pub(crate) fn verify_data_padding(
+    header: &mut FitsHeader,
+    padding: &[u8],
+    hdu_index: usize,
+) -> Vec<VerificationIssue> {
+    let is_ascii_table = header
+        .get_card(XTENSION_KEYWORD)
+        .and_then(|card| card.get_value::<String>().ok())
+        .is_some_and(|xtension| xtension.trim() == "TABLE");
+    let fill = if is_ascii_table { b' ' } else { 0 };
+    if padding.iter().all(|b| *b == fill) {
+        Vec::new()
+    } else {
+        vec![VerificationIssue::error(
+            hdu_index,
+            None,
+            format!(
+                "data padding is not filled with {}",
+                if is_ascii_table { "blanks" } else { "zeros" }
+            ),
+        )]
+    }
+}
@@ -0,0 +1,343 @@
+//! Distortion polynomials of the SIP and TPV conventions.
+//! See <https://irsa.ipac.caltech.edu/data/SPITZER/docs/files/spitzer/shupeADASS.pdf> for SIP,
+//! and <https://fits.gsfc.nasa.gov/registry/tpvwcs/tpv.html> for TPV.
+
+use super::*;
+
+const ORDER_SUFFIX: &str = "_ORDER";
+const TPV_PREFIX: &str = "PV";
+/// The number of coefficients of a TPV polynomial, up to the seventh order.
+const TPV_TERMS: usize = 40;
+/// The largest order of a SIP polynomial read from a header, beyond which the number of coefficients is impractical.
+const MAX_SIP_ORDER: usize = 9;
+/// The largest number of iterations used to invert a distortion.
+const MAX_ITERATIONS: usize = 50;
+
+/// An enumeration of the distortions applied in addition to the linear transformation of a world coordinate system.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distortion {
+    /// The simple imaging polynomial convention, identified by the `-SIP` suffix of CTYPEn,
+    /// which distorts the pixel coordinates relative to the reference pixel.
+    Sip(Sip),
+    /// The TPV convention, identified by the `TPV` projection code of CTYPEn,
+    /// which distorts the intermediate world coordinates of a gnomonic projection.
+    Tpv(Tpv),
+}
+
+/// A polynomial in the pixel coordinates `u` and `v` relative to the reference pixel,
+/// given by cards such as `A_ORDER` and `A_p_q`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SipPolynomial {
+    /// The largest total degree of the terms of the polynomial.
+    pub order: usize,
+    /// The coefficients of the terms `u^p v^q`, indexed as `[p][q]`.
+    pub coefficients: Vec<Vec<f64>>,
+}
+
+impl SipPolynomial {
+    /// Constructs a polynomial of the given order with coefficients of zero.
+    pub fn new(order: usize) -> Self {
+        SipPolynomial {
+            order,
+            coefficients: vec![vec![0.0; order + 1]; order + 1],
+        }
+    }
+
+    /// Evaluates the polynomial at the given pixel coordinates relative to the reference pixel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut a = SipPolynomial::new(2);
+    /// a.coefficients[2][0] = 1e-3;
+    /// a.coefficients[0][1] = 2e-2;
+    /// assert!((a.evaluate(10.0, 5.0) - 0.2).abs() < 1e-12);
+    /// ```
+    pub fn evaluate(&self, u: f64, v: f64) -> f64 {
+        let mut result = 0.0;
+        let mut u_power = 1.0;
+        for (p, row) in self.coefficients.iter().enumerate() {
+            let mut term = u_power;
+            for coefficient in row.iter().take(self.order + 1 - p) {
+                result += coefficient * term;
+                term *= v;
+            }
+            u_power *= u;
+        }
+        result
+    }
+
+    /// Reads the polynomial with the given prefix, such as `A` or `AP`, or None if its order is not given.
+    /// Returns an error if the order is negative or greater than 9.
+    fn from_header(header: &mut FitsHeader, prefix: &str) -> Result<Option<Self>, WcsError> {
+        let keyword = order_keyword(prefix);
+        let Some(order) = number_value(header, Some(keyword))? else {
+            return Ok(None);
+        };
+        if !(0.0..=MAX_SIP_ORDER as f64).contains(&order) {
+            return Err(WcsError::UnsupportedOrder {
+                keyword,
+                order,
+                max: MAX_SIP_ORDER,
+            });
+        }
+        let mut polynomial = SipPolynomial::new(order as usize);
+        let prefix = format!("{}_", prefix);
+        for p in 0..=polynomial.order {
+            for q in 0..=polynomial.order - p {
+                if let Some(value) = number_value(header, wcs_keyword(&prefix, p, Some(q), ' '))? {
+                    polynomial.coefficients[p][q] = value;
+                }
+            }
+        }
+        Ok(Some(polynomial))
+    }
+
+    /// Sets the order and the non-zero coefficients of the polynomial with the given prefix,
+    /// removing any other coefficients up to the order.
+    fn set_cards(&self, header: &mut FitsHeader, prefix: &str) -> Result<(), FitsHeaderError> {
+        header.set_card(order_keyword(prefix), self.order as u32, None)?;
+        let prefix = format!("{}_", prefix);
+        for p in 0..=self.order {
+            for q in 0..=self.order - p {
+                let Some(keyword) = wcs_keyword(&prefix, p, Some(q), ' ') else {
+                    continue;
+                };
+                match self.coefficients.get(p).and_then(|row| row.get(q)) {
+                    Some(value) if *value != 0.0 => header.set_card(keyword, *value, None)?,
+                    _ => header.cards.retain(|card| *card.keyword() != keyword),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The polynomials of the SIP convention.
+///
+/// The forward polynomials are added to the pixel coordinates relative to the reference pixel,
+/// `u' = u + A(u, v)` and `v' = v + B(u, v)`, where `u` is along the first axis of the image.
+/// The optional reverse polynomials approximate the inverse, `u = u' + AP(u', v')` and `v = v' + BP(u', v')`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sip {
+    /// The forward polynomial of the first axis.
+    pub a: SipPolynomial,
+    /// The forward polynomial of the second axis.
+    pub b: SipPolynomial,
+    /// The reverse polynomials of the first and second axes,
+    /// or None if the inverse is found by iteration.
+    pub reverse: Option<(SipPolynomial, SipPolynomial)>,
+}
+
+impl Sip {
+    /// Reads the SIP polynomials from the given header.
+    /// The reverse polynomials are only used if both `AP_ORDER` and `BP_ORDER` are given.
+    pub(crate) fn from_header(header: &mut FitsHeader) -> Result<Self, WcsError> {
+        let forward = |header: &mut FitsHeader, prefix: &str| {
+            SipPolynomial::from_header(header, prefix)?.ok_or_else(|| {
+                WcsError::Header(FitsHeaderError::MissingKeyword {
+                    keyword: order_keyword(prefix),
+                })
+            })
+        };
+        let a = forward(header, "A")?;
+        let b = forward(header, "B")?;
+        let reverse = match (
+            SipPolynomial::from_header(header, "AP")?,
+            SipPolynomial::from_header(header, "BP")?,
+        ) {
+            (Some(ap), Some(bp)) => Some((ap, bp)),
+            _ => None,
+        };
+        Ok(Sip { a, b, reverse })
+    }
+
+    /// Sets the cards of the polynomials in the given header.
+    fn set_cards(&self, header: &mut FitsHeader) -> Result<(), FitsHeaderError> {
+        self.a.set_cards(header, "A")?;
+        self.b.set_cards(header, "B")?;
+        if let Some((ap, bp)) = &self.reverse {
+            ap.set_cards(header, "AP")?;
+            bp.set_cards(header, "BP")?;
+        }
+        Ok(())
+    }
+
+    /// Applies the forward polynomials to the given pixel coordinates relative to the reference pixel.
+    pub fn apply(&self, u: f64, v: f64) -> (f64, f64) {
+        (u + self.a.evaluate(u, v), v + self.b.evaluate(u, v))
+    }
+
+    /// Removes the distortion from the given pixel coordinates relative to the reference pixel,
+    /// with the reverse polynomials if they are given, or otherwise by iteration.
+    pub fn invert(&self, u: f64, v: f64) -> Result<(f64, f64), WcsError> {
+        match &self.reverse {
+            Some((ap, bp)) => Ok((u + ap.evaluate(u, v), v + bp.evaluate(u, v))),
+            None => invert(|u, v| self.apply(u, v), u, v),
+        }
+    }
+}
+
+/// The polynomials of the TPV convention, given by the `PVi_m` cards of the longitude and latitude axes.
+///
+/// Each polynomial maps the intermediate world coordinates `(x, y)` in degrees, and `r = sqrt(x² + y²)`,
+/// to the corrected coordinate of its axis, with the roles of `x` and `y` exchanged for the latitude axis.
+/// Without any `PVi_m` cards, the polynomials are the identity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tpv {
+    /// The coefficients `PVi_0` to `PVi_39` of the longitude axis, where missing coefficients are zero.
+    pub lon: Vec<f64>,
+    /// The coefficients `PVi_0` to `PVi_39` of the latitude axis, where missing coefficients are zero.
+    pub lat: Vec<f64>,
+}
+
+impl Tpv {
+    /// Reads the TPV polynomials of the given axes, numbered from 1, from the given header.
+    pub(crate) fn from_header(header: &mut FitsHeader, axes: [usize; 2]) -> Result<Self, WcsError> {
+        let mut coefficients = [vec![0.0; TPV_TERMS], vec![0.0; TPV_TERMS]];
+        for (axis, terms) in axes.iter().zip(coefficients.iter_mut()) {
+            terms[1] = 1.0;
+            for (m, term) in terms.iter_mut().enumerate() {
+                if let Some(value) =
+                    number_value(header, wcs_keyword(TPV_PREFIX, *axis, Some(m), ' '))?
+                {
+                    *term = value;
+                }
+            }
+        }
+        let [lon, lat] = coefficients;
+        Ok(Tpv { lon, lat })
+    }
+
+    /// Sets the coefficients of the given axes, numbered from 1, that differ from their defaults in the given header.
+    fn set_cards(&self, header: &mut FitsHeader, axes: [usize; 2]) -> Result<(), FitsHeaderError> {
+        for (axis, terms) in axes.iter().zip([&self.lon, &self.lat]) {
+            for m in 0..TPV_TERMS {
+                let Some(keyword) = wcs_keyword(TPV_PREFIX, *axis, Some(m), ' ') else {
+                    continue;
+                };
+                let value = terms.get(m).copied().unwrap_or_default();
+                if value != if m == 1 { 1.0 } else { 0.0 } {
+                    header.set_card(keyword, value, None)?;
+                } else {
+                    header.cards.retain(|card| *card.keyword() != keyword);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the polynomials to the given intermediate world coordinates in degrees.
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            tpv_polynomial(&self.lon, x, y),
+            tpv_polynomial(&self.lat, y, x),
+        )
+    }
+
+    /// Removes the distortion from the given intermediate world coordinates in degrees by iteration.
+    pub fn invert(&self, x: f64, y: f64) -> Result<(f64, f64), WcsError> {
+        invert(|x, y| self.apply(x, y), x, y)
+    }
+}
+
+impl Distortion {
+    /// Reads the distortion identified by the given code, which is either `-SIP` following the projection code,
+    /// or `TPV` in place of the projection code. Returns None for an empty code.
+    pub(crate) fn from_header(
+        header: &mut FitsHeader,
+        code: &str,
+        axes: [usize; 2],
+    ) -> Result<Option<Self>, WcsError> {
+        match code {
+            "" => Ok(None),
+            "-SIP" => Ok(Some(Distortion::Sip(Sip::from_header(header)?))),
+            "TPV" => Ok(Some(Distortion::Tpv(Tpv::from_header(header, axes)?))),
+            _ => Err(WcsError::UnsupportedDistortion {
+                code: code.to_owned(),
+            }),
+        }
+    }
+
+    /// Sets the cards of the distortion of the given axes, numbered from 1, in the given header.
+    pub(crate) fn set_cards(
+        &self,
+        header: &mut FitsHeader,
+        axes: [usize; 2],
+    ) -> Result<(), FitsHeaderError> {
+        match self {
+            Distortion::Sip(sip) => sip.set_cards(header),
+            Distortion::Tpv(tpv) => tpv.set_cards(header, axes),
+        }
+    }
+}
+
+/// Evaluates a TPV polynomial with the standard ordering of its 40 terms.
+fn tpv_polynomial(terms: &[f64], x: f64, y: f64) -> f64 {
+    let term = |index: usize| terms.get(index).copied().unwrap_or_default();
+    let r = x.hypot(y);
+    let mut result = term(0);
+    let mut index = 1;
+    for order in 1..=7 {
+        for y_power in 0..=order {
+            result += term(index) * x.powi(order - y_power) * y.powi(y_power);
+            index += 1;
+        }
+        // the odd powers of r follow the terms of each odd degree
+        if order % 2 == 1 {
+            result += term(index) * r.powi(order);
+            index += 1;
+        }
+    }
+    result
+}
+
+/// Finds the coordinates that the given function maps to the target coordinates with Newton's method,
+/// starting from the target coordinates.
+fn invert(
+    function: impl Fn(f64, f64) -> (f64, f64),
+    target_x: f64,
+    target_y: f64,
+) -> Result<(f64, f64), WcsError> {
+    let (mut x, mut y) = (target_x, target_y);
+    let tolerance = 1e-12 * target_x.abs().max(target_y.abs()).max(1.0);
+    for _ in 0..MAX_ITERATIONS {
+        let (fx, fy) = function(x, y);
+        let (residual_x, residual_y) = (fx - target_x, fy - target_y);
+        if residual_x.abs() <= tolerance && residual_y.abs() <= tolerance {
+            return Ok((x, y));
+        }
+
+        // the Jacobian is estimated with central differences
+        let h = 1e-6 * x.abs().max(y.abs()).max(1e-3);
+        let (x_plus, y_plus) = function(x + h, y);
+        let (x_minus, y_minus) = function(x - h, y);
+        let (dfx_dx, dfy_dx) = (
+            (x_plus - x_minus) / (2.0 * h),
+            (y_plus - y_minus) / (2.0 * h),
+        );
+        let (x_plus, y_plus) = function(x, y + h);
+        let (x_minus, y_minus) = function(x, y - h);
+        let (dfx_dy, dfy_dy) = (
+            (x_plus - x_minus) / (2.0 * h),
+            (y_plus - y_minus) / (2.0 * h),
+        );
+        let determinant = dfx_dx * dfy_dy - dfx_dy * dfy_dx;
+        if determinant == 0.0 || !determinant.is_finite() {
+            break;
+        }
+        x -= (dfy_dy * residual_x - dfx_dy * residual_y) / determinant;
+        y -= (dfx_dx * residual_y - dfy_dx * residual_x) / determinant;
+    }
+    Err(WcsError::NotConverged(target_x, target_y))
+}
+
+/// Builds the keyword of the order of the SIP polynomial with the given prefix of at most two characters, such as `AP_ORDER`.
+fn order_keyword(prefix: &str) -> FitsHeaderKeyword {
+    let name = format!("{}{}", prefix, ORDER_SUFFIX);
+    let mut keyword = [b' '; HEADER_KEYWORD_LEN];
+    keyword[..name.len()].copy_from_slice(name.as_bytes());
+    FitsHeaderKeyword::from(keyword)
+}
@@ -0,0 +1,343 @@
+//! Fit a world coordinate system to pixel positions matched to catalogue positions.
+
+use uom::si::angle::radian;
+
+use super::*;
+
+/// The largest number of iterations used to move the reference point onto the reference pixel.
+const MAX_ITERATIONS: usize = 20;
+/// The number of samples along each axis of the grid used to fit the reverse SIP polynomials.
+const REVERSE_GRID_LEN: usize = 32;
+
+/// The result of fitting a world coordinate system to matched stars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WcsFit {
+    /// The fitted world coordinate system.
+    pub wcs: Wcs,
+    /// The angular distance between each catalogue position and the fitted position of its pixel, in the given order.
+    pub residuals: Vec<Angle>,
+    /// The root mean square of the angular residuals.
+    pub rms: Angle,
+    /// The root mean square of the distances between each pixel position and the fitted pixel position of its catalogue position.
+    pub pixel_rms: f64,
+}
+
+impl Wcs {
+    /// Fits a gnomonic (TAN) world coordinate system in ICRS to the given pixel positions matched to catalogue positions,
+    /// with SIP distortion polynomials of the given order if it is at least 2.
+    ///
+    /// Pixel positions are one-based along the first and second axes of the image, which become the right ascension
+    /// and declination axes. The reference pixel is the mean pixel position, and the reference point, CD matrix, and
+    /// SIP coefficients are found by linear least squares in the tangent plane. Reverse SIP polynomials of one order
+    /// higher are fitted over the area covered by the stars.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::coordinates::EquatorialCoord;
+    /// use astro_rs::fits::*;
+    /// use uom::si::angle::{degree, second};
+    /// use uom::si::f64::Angle;
+    ///
+    /// let mut matches = Vec::new();
+    /// for (x, y) in [(10.0, 20.0), (900.0, 40.0), (500.0, 500.0), (30.0, 950.0), (980.0, 990.0)] {
+    ///     let coords = EquatorialCoord {
+    ///         ra: Angle::new::<degree>(150.0 - (x - 500.0) * 1e-4),
+    ///         dec: Angle::new::<degree>(2.0 + (y - 500.0) * 1e-4),
+    ///     };
+    ///     matches.push(((x, y), coords));
+    /// }
+    ///
+    /// let fit = Wcs::fit(&matches, None)?;
+    /// assert!(fit.rms.get::<second>() < 0.05);
+    ///
+    /// let mut header = image_hdu::default().header;
+    /// fit.wcs.set_cards(&mut header)?;
+    /// assert_eq!(Wcs::from_header(&mut header)?, fit.wcs);
+    /// # Ok::<(), astro_rs::fits::WcsError>(())
+    /// ```
+    pub fn fit(
+        matches: &[((f64, f64), EquatorialCoord)],
+        sip_order: Option<usize>,
+    ) -> Result<WcsFit, WcsError> {
+        let order = sip_order.unwrap_or(1).max(1);
+        let terms = polynomial_terms(order);
+        if matches.len() < terms.len() {
+            return Err(WcsError::InsufficientStars {
+                required: terms.len(),
+                found: matches.len(),
+            });
+        }
+
+        let count = matches.len() as f64;
+        let crpix = [
+            matches.iter().map(|((x, _), _)| x).sum::<f64>() / count,
+            matches.iter().map(|((_, y), _)| y).sum::<f64>() / count,
+        ];
+        let offsets: Vec<(f64, f64)> = matches
+            .iter()
+            .map(|((x, y), _)| (x - crpix[0], y - crpix[1]))
+            .collect();
+        // the offsets are scaled to unit size to keep the least squares problem well conditioned
+        let scale = offsets
+            .iter()
+            .map(|(u, v)| u.abs().max(v.abs()))
+            .fold(0.0, f64::max)
+            .max(1.0);
+        let design: Vec<Vec<f64>> = offsets
+            .iter()
+            .map(|(u, v)| evaluate_terms(&terms, u / scale, v / scale))
+            .collect();
+
+        // the reference point starts at the mean direction of the catalogue positions
+        let mut sum = [0.0; 3];
+        for (_, coords) in matches {
+            for (total, value) in sum.iter_mut().zip(unit_vector(coords)) {
+                *total += value;
+            }
+        }
+        let mut crval = [
+            sum[1].atan2(sum[0]).to_degrees().rem_euclid(360.0),
+            sum[2].atan2(sum[0].hypot(sum[1])).to_degrees(),
+        ];
+
+        let mut solution = (Vec::new(), Vec::new());
+        for _ in 0..MAX_ITERATIONS {
+            let plane = Wcs::tangent_plane(crval);
+            let mut xi = Vec::with_capacity(matches.len());
+            let mut eta = Vec::with_capacity(matches.len());
+            for (_, coords) in matches {
+                let (x, y) = plane
+                    .celestial_to_pixel(coords.ra.get::<degree>(), coords.dec.get::<degree>())?;
+                xi.push(x);
+                eta.push(y);
+            }
+            solution = (
+                least_squares(&design, &xi).ok_or(WcsError::SingularMatrix)?,
+                least_squares(&design, &eta).ok_or(WcsError::SingularMatrix)?,
+            );
+            // the constant terms are the intermediate coordinates of the reference pixel, which are moved to the origin
+            let (x, y) = (solution.0[0], solution.1[0]);
+            let (lon, lat) = plane.pixel_to_celestial(x, y)?;
+            crval = [lon, lat];
+            if x.hypot(y) < 1e-12 {
+                break;
+            }
+        }
+
+        let (xi, eta) = solution;
+        let cd = [
+            [xi[1] / scale, xi[2] / scale],
+            [eta[1] / scale, eta[2] / scale],
+        ];
+        let determinant = cd[0][0] * cd[1][1] - cd[0][1] * cd[1][0];
+        if determinant == 0.0 || !determinant.is_finite() {
+            return Err(WcsError::SingularMatrix);
+        }
+        let inverse = [
+            [cd[1][1] / determinant, -cd[0][1] / determinant],
+            [-cd[1][0] / determinant, cd[0][0] / determinant],
+        ];
+
+        let distortion = if order >= 2 {
+            let mut a = SipPolynomial::new(order);
+            let mut b = SipPolynomial::new(order);
+            for (index, (p, q)) in terms.iter().enumerate().filter(|(_, (p, q))| p + q >= 2) {
+                let term_scale = scale.powi((p + q) as i32);
+                let (x, y) = (xi[index] / term_scale, eta[index] / term_scale);
+                a.coefficients[*p][*q] = inverse[0][0] * x + inverse[0][1] * y;
+                b.coefficients[*p][*q] = inverse[1][0] * x + inverse[1][1] * y;
+            }
+            let mut sip = Sip {
+                a,
+                b,
+                reverse: None,
+            };
+            sip.reverse = Some(fit_reverse(&sip, &offsets, order + 1)?);
+            Some(Distortion::Sip(sip))
+        } else {
+            None
+        };
+
+        let suffix = if distortion.is_some() { "-SIP" } else { "" };
+        let wcs = Wcs {
+            axes: [0, 1],
+            ctype: [format!("RA---TAN{}", suffix), format!("DEC--TAN{}", suffix)],
+            crval,
+            crpix,
+            cdelt: [1.0; 2],
+            pc: cd,
+            lonpole: None,
+            latpole: None,
+            projection: Projection::Tan,
+            frame: CelestialFrame::Icrs,
+            distortion,
+        };
+
+        let mut residuals = Vec::with_capacity(matches.len());
+        let mut pixel_sum = 0.0;
+        for ((x, y), coords) in matches {
+            let (ra, dec) = wcs.pixel_to_celestial(*x, *y)?;
+            let fitted = EquatorialCoord {
+                ra: Angle::new::<degree>(ra),
+                dec: Angle::new::<degree>(dec),
+            };
+            let (a, b) = (unit_vector(coords), unit_vector(&fitted));
+            let cross = [
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ];
+            let cross = cross.iter().map(|value| value * value).sum::<f64>().sqrt();
+            let dot = a.iter().zip(&b).map(|(a, b)| a * b).sum::<f64>();
+            residuals.push(Angle::new::<radian>(cross.atan2(dot)));
+
+            let (px, py) =
+                wcs.celestial_to_pixel(coords.ra.get::<degree>(), coords.dec.get::<degree>())?;
+            pixel_sum += (px - x).powi(2) + (py - y).powi(2);
+        }
+        let rms = residuals
+            .iter()
+            .map(|residual| residual.get::<radian>().powi(2))
+            .sum::<f64>()
+            / count;
+        Ok(WcsFit {
+            wcs,
+            residuals,
+            rms: Angle::new::<radian>(rms.sqrt()),
+            pixel_rms: (pixel_sum / count).sqrt(),
+        })
+    }
+
+    /// Constructs a WCS whose pixel coordinates are the intermediate world coordinates of a gnomonic projection
+    /// around the given reference point.
+    fn tangent_plane(crval: [f64; 2]) -> Self {
+        Wcs {
+            axes: [0, 1],
+            ctype: [String::from("RA---TAN"), String::from("DEC--TAN")],
+            crval,
+            crpix: [0.0; 2],
+            cdelt: [1.0; 2],
+            pc: [[1.0, 0.0], [0.0, 1.0]],
+            lonpole: None,
+            latpole: None,
+            projection: Projection::Tan,
+            frame: CelestialFrame::Icrs,
+            distortion: None,
+        }
+    }
+}
+
+/// Fits reverse polynomials of the given order to the given SIP distortion over a grid covering the given pixel offsets.
+fn fit_reverse(
+    sip: &Sip,
+    offsets: &[(f64, f64)],
+    order: usize,
+) -> Result<(SipPolynomial, SipPolynomial), WcsError> {
+    let terms = polynomial_terms(order);
+    let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+    for (u, v) in offsets {
+        min = [min[0].min(*u), min[1].min(*v)];
+        max = [max[0].max(*u), max[1].max(*v)];
+    }
+    let step = |axis: usize| (max[axis] - min[axis]) / (REVERSE_GRID_LEN - 1) as f64;
+
+    let mut distorted = Vec::with_capacity(REVERSE_GRID_LEN * REVERSE_GRID_LEN);
+    let mut du = Vec::with_capacity(distorted.capacity());
+    let mut dv = Vec::with_capacity(distorted.capacity());
+    for i in 0..REVERSE_GRID_LEN {
+        for j in 0..REVERSE_GRID_LEN {
+            let (u, v) = (min[0] + i as f64 * step(0), min[1] + j as f64 * step(1));
+            let (distorted_u, distorted_v) = sip.apply(u, v);
+            distorted.push((distorted_u, distorted_v));
+            du.push(u - distorted_u);
+            dv.push(v - distorted_v);
+        }
+    }
+    let scale = distorted
+        .iter()
+        .map(|(u, v)| u.abs().max(v.abs()))
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let design: Vec<Vec<f64>> = distorted
+        .iter()
+        .map(|(u, v)| evaluate_terms(&terms, u / scale, v / scale))
+        .collect();
+    let ap = least_squares(&design, &du).ok_or(WcsError::SingularMatrix)?;
+    let bp = least_squares(&design, &dv).ok_or(WcsError::SingularMatrix)?;
+
+    let mut reverse = (SipPolynomial::new(order), SipPolynomial::new(order));
+    for (index, (p, q)) in terms.iter().enumerate() {
+        let term_scale = scale.powi((p + q) as i32);
+        reverse.0.coefficients[*p][*q] = ap[index] / term_scale;
+        reverse.1.coefficients[*p][*q] = bp[index] / term_scale;
+    }
+    Ok(reverse)
+}
+
+/// Lists the powers `(p, q)` of the terms `u^p v^q` of a polynomial of the given order, by increasing total degree.
+fn polynomial_terms(order: usize) -> Vec<(usize, usize)> {
+    (0..=order)
+        .flat_map(|total| (0..=total).map(move |q| (total - q, q)))
+        .collect()
+}
+
+/// Evaluates each of the given terms at the given coordinates.
+fn evaluate_terms(terms: &[(usize, usize)], u: f64, v: f64) -> Vec<f64> {
+    terms
+        .iter()
+        .map(|(p, q)| u.powi(*p as i32) * v.powi(*q as i32))
+        .collect()
+}
+
+/// Solves the linear least squares problem for the given design matrix and observations with the normal equations,
+/// or returns None if the problem is singular.
+fn least_squares(design: &[Vec<f64>], observations: &[f64]) -> Option<Vec<f64>> {
+    let n = design.first().map_or(0, Vec::len);
+    // the normal equations are augmented with the right-hand side
+    let mut system = vec![vec![0.0; n + 1]; n];
+    for (row, observation) in design.iter().zip(observations) {
+        for i in 0..n {
+            for j in 0..n {
+                system[i][j] += row[i] * row[j];
+            }
+            system[i][n] += row[i] * observation;
+        }
+    }
+    let norm = (0..n).map(|i| system[i][i].abs()).fold(0.0, f64::max);
+
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            system[*a][column]
+                .abs()
+                .total_cmp(&system[*b][column].abs())
+        })?;
+        if system[pivot][column].abs() <= 1e-12 * norm {
+            return None;
+        }
+        system.swap(column, pivot);
+        let (upper, lower) = system.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for row in lower {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(column) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum = (row + 1..n)
+            .map(|k| system[row][k] * solution[k])
+            .sum::<f64>();
+        solution[row] = (system[row][n] - sum) / system[row][row];
+    }
+    Some(solution)
+}
+
+/// Converts the given coordinates to a unit vector.
+fn unit_vector(coords: &EquatorialCoord) -> [f64; 3] {
+    let (sin_ra, cos_ra) = coords.ra.get::<radian>().sin_cos();
+    let (sin_dec, cos_dec) = coords.dec.get::<radian>().sin_cos();
+    [cos_dec * cos_ra, cos_dec * sin_ra, sin_dec]
+}
@@ -0,0 +1,166 @@
+//! Celestial reference frames of world coordinate systems, and their rotations to ICRS.
+
+use std::fmt::Display;
+
+use super::WcsError;
+
+/// The obliquity of the ecliptic at J2000.0 in arcseconds, as used by the IAU 2000 frame bias.
+const OBLIQUITY_J2000: f64 = 84381.448;
+
+/// The rotation from ICRS to galactic coordinates, as defined for the Hipparcos catalogue.
+const ICRS_TO_GALACTIC: [[f64; 3]; 3] = [
+    [-0.0548755604162154, -0.873437090234885, -0.4838350155487132],
+    [0.4941094278755837, -0.4448296299600112, 0.746982244497219],
+    [-0.8676661490190047, -0.1980763734312015, 0.4559837761750669],
+];
+
+/// An enumeration of the celestial reference frames described by the RADESYS keyword and the CTYPEn prefixes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CelestialFrame {
+    /// The International Celestial Reference System.
+    Icrs,
+    /// The mean place of the FK5 system at the given Julian equinox.
+    Fk5 {
+        /// The equinox of the mean place, in Julian years.
+        equinox: f64,
+    },
+    /// The mean place of the FK4 system at the given Besselian equinox.
+    Fk4 {
+        /// The equinox of the mean place, in Besselian years.
+        equinox: f64,
+    },
+    /// The IAU 1958 galactic coordinate system.
+    Galactic,
+    /// Another system, identified by its RADESYS value or CTYPEn prefix.
+    Other(String),
+}
+
+impl Display for CelestialFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CelestialFrame::Icrs => write!(f, "ICRS"),
+            CelestialFrame::Fk5 { equinox } => write!(f, "FK5 (J{})", equinox),
+            CelestialFrame::Fk4 { equinox } => write!(f, "FK4 (B{})", equinox),
+            CelestialFrame::Galactic => write!(f, "galactic"),
+            CelestialFrame::Other(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl CelestialFrame {
+    /// Determines the frame from the CTYPEn prefix of the longitude axis, and the RADESYS and EQUINOX values.
+    /// The defaults of the FITS standard apply if RADESYS or EQUINOX is absent.
+    pub(crate) fn from_keywords(prefix: &str, radesys: Option<&str>, equinox: Option<f64>) -> Self {
+        match prefix {
+            "RA" => {}
+            "GLON" => return CelestialFrame::Galactic,
+            _ => return CelestialFrame::Other(prefix.to_owned()),
+        }
+        let radesys = match radesys {
+            Some(radesys) => radesys,
+            None => match equinox {
+                None => "ICRS",
+                Some(equinox) if equinox < 1984.0 => "FK4",
+                Some(_) => "FK5",
+            },
+        };
+        match radesys {
+            "ICRS" => CelestialFrame::Icrs,
+            "FK5" => CelestialFrame::Fk5 {
+                equinox: equinox.unwrap_or(2000.0),
+            },
+            "FK4" | "FK4-NO-E" => CelestialFrame::Fk4 {
+                equinox: equinox.unwrap_or(1950.0),
+            },
+            other => CelestialFrame::Other(other.to_owned()),
+        }
+    }
+
+    /// Gets the rotation from the frame to ICRS,
+    /// or an error if the frame cannot be related to ICRS by a rotation.
+    pub(crate) fn to_icrs(&self) -> Result<[[f64; 3]; 3], WcsError> {
+        match self {
+            CelestialFrame::Icrs => Ok([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+            CelestialFrame::Fk5 { equinox } => {
+                let to_fk5 = multiply(&precession(*equinox), &frame_bias());
+                Ok(transpose(&to_fk5))
+            }
+            CelestialFrame::Galactic => Ok(transpose(&ICRS_TO_GALACTIC)),
+            _ => Err(WcsError::UnsupportedFrame {
+                frame: self.to_string(),
+            }),
+        }
+    }
+}
+
+/// The IAU 2000 frame bias, rotating ICRS to the mean equator and equinox of J2000.0.
+fn frame_bias() -> [[f64; 3]; 3] {
+    let arcsec = |value: f64| (value / 3600.0).to_radians();
+    let dpsi = arcsec(-0.041775);
+    let deps = arcsec(-0.0068192);
+    let dra = arcsec(-0.0146);
+    let rotation = multiply(
+        &rotate_y(dpsi * arcsec(OBLIQUITY_J2000).sin()),
+        &rotate_z(dra),
+    );
+    multiply(&rotate_x(-deps), &rotation)
+}
+
+/// The IAU 1976 precession, rotating the mean equator and equinox of J2000.0 to that of the given Julian epoch.
+fn precession(equinox: f64) -> [[f64; 3]; 3] {
+    let t = (equinox - 2000.0) / 100.0;
+    let arcsec = |value: f64| (value / 3600.0).to_radians();
+    let zeta = arcsec(t * (2306.2181 + t * (0.30188 + t * 0.017998)));
+    let z = arcsec(t * (2306.2181 + t * (1.09468 + t * 0.018203)));
+    let theta = arcsec(t * (2004.3109 + t * (-0.42665 - t * 0.041833)));
+    multiply(&rotate_z(-z), &multiply(&rotate_y(theta), &rotate_z(-zeta)))
+}
+
+fn rotate_x(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, cos, sin], [0.0, -sin, cos]]
+}
+
+fn rotate_y(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, 0.0, -sin], [0.0, 1.0, 0.0], [sin, 0.0, cos]]
+}
+
+fn rotate_z(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [[cos, sin, 0.0], [-sin, cos, 0.0], [0.0, 0.0, 1.0]]
+}
+
+fn multiply(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut result = [[0.0; 3]; 3];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+pub(crate) fn transpose(a: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut result = [[0.0; 3]; 3];
+    for (i, row) in a.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            result[j][i] = *value;
+        }
+    }
+    result
+}
+
+/// Rotates the given longitude and latitude in degrees by the given matrix.
+pub(crate) fn rotate(matrix: &[[f64; 3]; 3], lon: f64, lat: f64) -> (f64, f64) {
+    let (sin_lon, cos_lon) = lon.to_radians().sin_cos();
+    let (sin_lat, cos_lat) = lat.to_radians().sin_cos();
+    let v = [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat];
+    let r: Vec<f64> = matrix
+        .iter()
+        .map(|row| row.iter().zip(&v).map(|(a, b)| a * b).sum())
+        .collect();
+    let lon = r[1].atan2(r[0]).to_degrees().rem_euclid(360.0);
+    let lat = r[2].clamp(-1.0, 1.0).asin().to_degrees();
+    (lon, lat)
+}
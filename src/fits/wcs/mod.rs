@@ -0,0 +1,586 @@
+//! Map pixel coordinates to celestial coordinates with the FITS world coordinate system (WCS).
+//! See <https://fits.gsfc.nasa.gov/fits_wcs.html> for the WCS standard.
+
+mod distortion;
+mod fit;
+mod frame;
+mod projection;
+mod reproject;
+
+use thiserror::Error;
+use uom::si::angle::degree;
+use uom::si::f64::Angle;
+
+use crate::coordinates::{EquatorialCoord, Icrs};
+
+use super::*;
+use frame::{rotate, transpose};
+
+pub use distortion::{Distortion, Sip, SipPolynomial, Tpv};
+pub use fit::WcsFit;
+pub use frame::CelestialFrame;
+pub use projection::Projection;
+pub use reproject::{Reprojection, Resampling};
+
+const CTYPE_PREFIX: &str = "CTYPE";
+const CRVAL_PREFIX: &str = "CRVAL";
+const CRPIX_PREFIX: &str = "CRPIX";
+const CDELT_PREFIX: &str = "CDELT";
+const CUNIT_PREFIX: &str = "CUNIT";
+const CD_PREFIX: &str = "CD";
+const PC_PREFIX: &str = "PC";
+
+/// An enumeration of errors that could occur when interpreting or applying a world coordinate system.
+#[derive(Debug, Error)]
+pub enum WcsError {
+    /// Indicates a WCS card could not be processed.
+    #[error(transparent)]
+    Header(#[from] FitsHeaderError),
+    /// Indicates the header does not describe a pair of celestial longitude and latitude axes.
+    #[error("missing celestial longitude and latitude axes in CTYPEn")]
+    MissingCelestialAxes,
+    /// Indicates the projection of the celestial axes is not supported.
+    #[error("unsupported projection {code}")]
+    UnsupportedProjection {
+        /// The projection code of CTYPEn.
+        code: String,
+    },
+    /// Indicates the celestial frame cannot be related to ICRS.
+    #[error("unsupported celestial frame {frame}")]
+    UnsupportedFrame {
+        /// The description of the frame.
+        frame: String,
+    },
+    /// Indicates the unit of a celestial axis is not an angle.
+    #[error("unsupported unit {unit} for axis {axis}")]
+    UnsupportedUnit {
+        /// The value of CUNITn.
+        unit: String,
+        /// The number of the axis, starting at 1.
+        axis: usize,
+    },
+    /// Indicates the linear transformation from pixel coordinates cannot be inverted.
+    #[error("singular linear transformation matrix")]
+    SingularMatrix,
+    /// Indicates the native coordinates of the celestial pole cannot satisfy the reference point.
+    #[error("invalid celestial pole for the reference point")]
+    InvalidPole,
+    /// Indicates a coordinate lies outside the domain of the projection.
+    #[error("coordinate ({0}, {1}) lies outside the projection")]
+    OutOfBounds(f64, f64),
+    /// Indicates the distortion convention following the projection code of CTYPEn is not supported.
+    #[error("unsupported distortion {code}")]
+    UnsupportedDistortion {
+        /// The distortion code of CTYPEn.
+        code: String,
+    },
+    /// Indicates the order of a distortion polynomial is negative or exceeds the largest supported order.
+    #[error("unsupported order {order} for {keyword}, expected at most {max}")]
+    UnsupportedOrder {
+        /// The keyword of the order card.
+        keyword: FitsHeaderKeyword,
+        /// The value of the order card.
+        order: f64,
+        /// The largest supported order.
+        max: usize,
+    },
+    /// Indicates the iterative inverse of a distortion did not converge for a coordinate.
+    #[error("inverse distortion did not converge for ({0}, {1})")]
+    NotConverged(f64, f64),
+    /// Indicates too few matched stars were given to fit the terms of a WCS.
+    #[error("expected at least {required} matched stars, found {found}")]
+    InsufficientStars {
+        /// The number of terms of each fitted polynomial.
+        required: usize,
+        /// The number of matched stars given.
+        found: usize,
+    },
+    /// Indicates an image to reproject does not have exactly two axes.
+    #[error("expected an image with 2 axes, found {found}")]
+    Dimensions {
+        /// The number of axes of the image.
+        found: usize,
+    },
+}
+
+/// The celestial world coordinate system of an image, describing a pair of longitude and latitude axes.
+///
+/// Each array holds the value for the longitude axis, followed by the latitude axis.
+/// Angular values are in degrees, with the units of CUNITn converted when the header is read.
+/// Pixel coordinates are one-based, as in the FITS standard, so the center of the first pixel is (1, 1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wcs {
+    /// The zero-based indices of the longitude and latitude axes within the image.
+    pub axes: [usize; 2],
+    /// The values of CTYPEn, such as `RA---TAN` and `DEC--TAN`.
+    pub ctype: [String; 2],
+    /// The celestial coordinates of the reference point.
+    pub crval: [f64; 2],
+    /// The pixel coordinates of the reference point.
+    pub crpix: [f64; 2],
+    /// The scale of each axis in degrees per pixel, or 1 if the transformation is given by `CDi_j`.
+    pub cdelt: [f64; 2],
+    /// The linear transformation matrix given by `PCi_j`, or the scaled matrix given by `CDi_j`.
+    pub pc: [[f64; 2]; 2],
+    /// The native longitude of the celestial pole, or None for the default.
+    pub lonpole: Option<f64>,
+    /// The native latitude of the celestial pole, or None for the default.
+    pub latpole: Option<f64>,
+    /// The projection of the celestial axes.
+    pub projection: Projection,
+    /// The celestial frame of the world coordinates.
+    pub frame: CelestialFrame,
+    /// The distortion applied in addition to the linear transformation, if any.
+    pub distortion: Option<Distortion>,
+}
+
+impl Wcs {
+    /// Reads the celestial world coordinate system from the given header.
+    ///
+    /// The celestial axes are identified by the `RA--`/`DEC-` or `GLON`/`GLAT` prefixes of CTYPEn,
+    /// and the transformation is given by CRVALn, CRPIXn, and either CDi_j, or PCi_j with CDELTn.
+    /// Any coupling between the celestial axes and other axes of the image is ignored.
+    /// The SIP distortion is applied if CTYPEn ends with `-SIP`, and the TPV distortion if the projection code is `TPV`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut header = image_hdu::default().header;
+    /// header.set_value(*b"CTYPE1  ", String::from("RA---TAN"))?;
+    /// header.set_value(*b"CTYPE2  ", String::from("DEC--TAN"))?;
+    /// header.set_value(*b"CRVAL1  ", 150.0)?;
+    /// header.set_value(*b"CRVAL2  ", 2.0)?;
+    /// header.set_value(*b"CRPIX1  ", 512.0)?;
+    /// header.set_value(*b"CRPIX2  ", 512.0)?;
+    /// header.set_value(*b"CDELT1  ", -0.0002)?;
+    /// header.set_value(*b"CDELT2  ", 0.0002)?;
+    ///
+    /// let wcs = Wcs::from_header(&mut header)?;
+    /// assert_eq!(wcs.projection, Projection::Tan);
+    /// assert_eq!(wcs.frame, CelestialFrame::Icrs);
+    ///
+    /// let (ra, dec) = wcs.pixel_to_celestial(512.0, 512.0)?;
+    /// assert!((ra - 150.0).abs() < 1e-12 && (dec - 2.0).abs() < 1e-12);
+    /// let (x, y) = wcs.celestial_to_pixel(150.01, 2.01)?;
+    /// assert!((x - 462.0).abs() < 0.1 && (y - 562.0).abs() < 0.1);
+    /// # Ok::<(), astro_rs::fits::WcsError>(())
+    /// ```
+    pub fn from_header(header: &mut FitsHeader) -> Result<Self, WcsError> {
+        // without WCSAXES, the number of axes is the larger of NAXIS and the largest axis number of CTYPEn
+        let naxis = match header.get_card(WCSAXES_KEYWORD) {
+            Some(card) => numeric_value(card).unwrap_or_default() as usize,
+            None => {
+                let naxis = header
+                    .get_card(NAXIS_KEYWORD)
+                    .and_then(|card| card.get_value::<u16>().ok())
+                    .map_or(0, |naxis| *naxis as usize);
+                let max_ctype = header
+                    .cards
+                    .iter()
+                    .filter_map(|card| {
+                        let keyword = card.keyword().to_string();
+                        keyword
+                            .trim_end()
+                            .strip_prefix(CTYPE_PREFIX)?
+                            .parse::<usize>()
+                            .ok()
+                    })
+                    .max()
+                    .unwrap_or_default();
+                naxis.max(max_ctype)
+            }
+        };
+        let mut lon = None;
+        let mut lat = None;
+        for axis in 1..=naxis {
+            let Some(ctype) = string_value(header, wcs_keyword(CTYPE_PREFIX, axis, None, ' '))?
+            else {
+                continue;
+            };
+            let prefix = ctype.get(..4).unwrap_or_default();
+            if prefix == "RA--" || prefix.get(1..) == Some("LON") {
+                lon.get_or_insert((axis, ctype));
+            } else if prefix == "DEC-" || prefix.get(1..) == Some("LAT") {
+                lat.get_or_insert((axis, ctype));
+            }
+        }
+        let (Some((lon_axis, lon_ctype)), Some((lat_axis, lat_ctype))) = (lon, lat) else {
+            return Err(WcsError::MissingCelestialAxes);
+        };
+        // TPV is a gnomonic projection with distortion, while other distortions follow the projection code
+        let (code, distortion_code) = match lon_ctype.get(5..8).unwrap_or_default() {
+            "TPV" => ("TAN", "TPV"),
+            code => (code, lon_ctype.get(8..).unwrap_or_default().trim_end()),
+        };
+        let projection =
+            Projection::from_code(code).ok_or_else(|| WcsError::UnsupportedProjection {
+                code: code.to_owned(),
+            })?;
+        let distortion = Distortion::from_header(header, distortion_code, [lon_axis, lat_axis])?;
+
+        let celestial_axes = [lon_axis, lat_axis];
+        let mut crval = [0.0; 2];
+        let mut crpix = [0.0; 2];
+        let mut cdelt = [1.0; 2];
+        let mut pc = [[1.0, 0.0], [0.0, 1.0]];
+        let mut cd = None;
+        for (i, axis) in celestial_axes.iter().enumerate() {
+            let scale = match string_value(header, wcs_keyword(CUNIT_PREFIX, *axis, None, ' '))?
+                .as_deref()
+            {
+                None | Some("") | Some("deg") => 1.0,
+                Some("arcmin") => 1.0 / 60.0,
+                Some("arcsec") => 1.0 / 3600.0,
+                Some("mas") => 1.0 / 3_600_000.0,
+                Some("rad") => 180.0 / std::f64::consts::PI,
+                Some(unit) => {
+                    return Err(WcsError::UnsupportedUnit {
+                        unit: unit.to_owned(),
+                        axis: *axis,
+                    });
+                }
+            };
+            crval[i] = scale
+                * number_value(header, wcs_keyword(CRVAL_PREFIX, *axis, None, ' '))?.unwrap_or(0.0);
+            crpix[i] =
+                number_value(header, wcs_keyword(CRPIX_PREFIX, *axis, None, ' '))?.unwrap_or(0.0);
+            cdelt[i] = scale
+                * number_value(header, wcs_keyword(CDELT_PREFIX, *axis, None, ' '))?.unwrap_or(1.0);
+            for (j, other_axis) in celestial_axes.iter().enumerate() {
+                if let Some(value) = number_value(
+                    header,
+                    wcs_keyword(PC_PREFIX, *axis, Some(*other_axis), ' '),
+                )? {
+                    pc[i][j] = value;
+                }
+                if let Some(value) = number_value(
+                    header,
+                    wcs_keyword(CD_PREFIX, *axis, Some(*other_axis), ' '),
+                )? {
+                    cd.get_or_insert([[0.0; 2]; 2])[i][j] = scale * value;
+                }
+            }
+        }
+        if let Some(cd) = cd {
+            pc = cd;
+            cdelt = [1.0; 2];
+        }
+
+        let radesys = string_value(header, Some(RADESYS_KEYWORD.into()))?;
+        let equinox = match number_value(header, Some(EQUINOX_KEYWORD.into()))? {
+            Some(equinox) => Some(equinox),
+            None => number_value(header, Some(EPOCH_KEYWORD.into()))?,
+        };
+        let frame_prefix = lon_ctype
+            .get(..4)
+            .unwrap_or_default()
+            .trim_end_matches('-')
+            .to_owned();
+        Ok(Wcs {
+            axes: [lon_axis - 1, lat_axis - 1],
+            ctype: [lon_ctype, lat_ctype],
+            crval,
+            crpix,
+            cdelt,
+            pc,
+            lonpole: number_value(header, Some(LONPOLE_KEYWORD.into()))?,
+            latpole: number_value(header, Some(LATPOLE_KEYWORD.into()))?,
+            projection,
+            frame: CelestialFrame::from_keywords(&frame_prefix, radesys.as_deref(), equinox),
+            distortion,
+        })
+    }
+
+    /// Sets the cards describing the WCS in the given header with [`FitsHeader::set_card`],
+    /// so that [`Wcs::from_header`] reads an equivalent WCS.
+    /// The linear transformation is written as CDi_j in degrees, and the PCi_j and CDELTn cards of the celestial axes are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut header = image_hdu::default().header;
+    /// header.set_value(*b"CTYPE1  ", String::from("GLON-AIT"))?;
+    /// header.set_value(*b"CTYPE2  ", String::from("GLAT-AIT"))?;
+    /// header.set_value(*b"CDELT1  ", -0.5)?;
+    /// header.set_value(*b"CDELT2  ", 0.5)?;
+    /// let wcs = Wcs::from_header(&mut header)?;
+    ///
+    /// let mut copy = image_hdu::default().header;
+    /// wcs.set_cards(&mut copy)?;
+    /// assert_eq!(*copy.get_card(*b"CD1_1   ").unwrap().get_value::<f64>()?, -0.5);
+    /// let copied_wcs = Wcs::from_header(&mut copy)?;
+    /// assert_eq!(copied_wcs.pixel_to_celestial(3.0, 4.0)?, wcs.pixel_to_celestial(3.0, 4.0)?);
+    /// # Ok::<(), astro_rs::fits::WcsError>(())
+    /// ```
+    pub fn set_cards(&self, header: &mut FitsHeader) -> Result<(), FitsHeaderError> {
+        let axes = [self.axes[0] + 1, self.axes[1] + 1];
+        for (i, axis) in axes.iter().enumerate() {
+            let keyword = |prefix: &str, j: Option<usize>| wcs_keyword(prefix, *axis, j, ' ');
+            if let Some(keyword) = keyword(CTYPE_PREFIX, None) {
+                header.set_card(keyword, self.ctype[i].clone(), None)?;
+            }
+            if let Some(keyword) = keyword(CUNIT_PREFIX, None) {
+                header.set_card(keyword, String::from("deg"), None)?;
+            }
+            if let Some(keyword) = keyword(CRVAL_PREFIX, None) {
+                header.set_card(keyword, self.crval[i], None)?;
+            }
+            if let Some(keyword) = keyword(CRPIX_PREFIX, None) {
+                header.set_card(keyword, self.crpix[i], None)?;
+            }
+            if let Some(keyword) = keyword(CDELT_PREFIX, None) {
+                header.cards.retain(|card| *card.keyword() != keyword);
+            }
+            for (j, other_axis) in axes.iter().enumerate() {
+                if let Some(keyword) = keyword(PC_PREFIX, Some(*other_axis)) {
+                    header.cards.retain(|card| *card.keyword() != keyword);
+                }
+                if let Some(keyword) = keyword(CD_PREFIX, Some(*other_axis)) {
+                    header.set_card(keyword, self.cdelt[i] * self.pc[i][j], None)?;
+                }
+            }
+        }
+        if let Some(lonpole) = self.lonpole {
+            header.set_card(LONPOLE_KEYWORD, lonpole, None)?;
+        }
+        if let Some(latpole) = self.latpole {
+            header.set_card(LATPOLE_KEYWORD, latpole, None)?;
+        }
+        match self.frame {
+            CelestialFrame::Icrs => header.set_card(RADESYS_KEYWORD, String::from("ICRS"), None)?,
+            CelestialFrame::Fk5 { equinox } => {
+                header.set_card(RADESYS_KEYWORD, String::from("FK5"), None)?;
+                header.set_card(EQUINOX_KEYWORD, equinox, None)?;
+            }
+            CelestialFrame::Fk4 { equinox } => {
+                header.set_card(RADESYS_KEYWORD, String::from("FK4"), None)?;
+                header.set_card(EQUINOX_KEYWORD, equinox, None)?;
+            }
+            CelestialFrame::Galactic | CelestialFrame::Other(_) => {}
+        }
+        if let Some(distortion) = &self.distortion {
+            distortion.set_cards(header, axes)?;
+        }
+        Ok(())
+    }
+
+    /// Transforms the given pixel coordinates along the longitude and latitude axes to ICRS coordinates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    /// use uom::si::angle::degree;
+    ///
+    /// let mut header = image_hdu::default().header;
+    /// header.set_value(*b"CTYPE1  ", String::from("GLON-CAR"))?;
+    /// header.set_value(*b"CTYPE2  ", String::from("GLAT-CAR"))?;
+    /// header.set_value(*b"CRPIX1  ", 1.0)?;
+    /// header.set_value(*b"CRPIX2  ", 1.0)?;
+    ///
+    /// let wcs = Wcs::from_header(&mut header)?;
+    /// // the galactic center
+    /// let icrs = wcs.pixel_to_world(1.0, 1.0)?;
+    /// assert!((icrs.coords.ra.get::<degree>() - 266.405).abs() < 1e-3);
+    /// assert!((icrs.coords.dec.get::<degree>() + 28.936).abs() < 1e-3);
+    /// # Ok::<(), astro_rs::fits::WcsError>(())
+    /// ```
+    pub fn pixel_to_world(&self, x: f64, y: f64) -> Result<Icrs, WcsError> {
+        let (lon, lat) = self.pixel_to_celestial(x, y)?;
+        let (ra, dec) = rotate(&self.frame.to_icrs()?, lon, lat);
+        Ok(Icrs {
+            coords: EquatorialCoord {
+                ra: Angle::new::<degree>(ra),
+                dec: Angle::new::<degree>(dec),
+            },
+        })
+    }
+
+    /// Transforms the given ICRS coordinates to pixel coordinates along the longitude and latitude axes.
+    pub fn world_to_pixel(&self, coords: &Icrs) -> Result<(f64, f64), WcsError> {
+        let from_icrs = transpose(&self.frame.to_icrs()?);
+        let (lon, lat) = rotate(
+            &from_icrs,
+            coords.coords.ra.get::<degree>(),
+            coords.coords.dec.get::<degree>(),
+        );
+        self.celestial_to_pixel(lon, lat)
+    }
+
+    /// Transforms the given pixel coordinates to the longitude and latitude of the frame of the WCS, in degrees.
+    pub fn pixel_to_celestial(&self, x: f64, y: f64) -> Result<(f64, f64), WcsError> {
+        let (ix, iy) = self.pixel_to_intermediate(x, y);
+        let (phi, theta) = self
+            .projection
+            .deproject(ix, iy)
+            .ok_or(WcsError::OutOfBounds(x, y))?;
+        let (alpha_p, delta_p, phi_p) = self.celestial_pole()?;
+        let (sin_theta, cos_theta) = theta.to_radians().sin_cos();
+        let (sin_dphi, cos_dphi) = (phi - phi_p).to_radians().sin_cos();
+        let (sin_delta_p, cos_delta_p) = delta_p.to_radians().sin_cos();
+        let x = sin_theta * cos_delta_p - cos_theta * sin_delta_p * cos_dphi;
+        let y = -cos_theta * sin_dphi;
+        let lon = alpha_p + y.atan2(x).to_degrees();
+        // the latitude is found with atan2 to keep its precision near the poles
+        let lat = (sin_theta * sin_delta_p + cos_theta * cos_delta_p * cos_dphi)
+            .atan2(x.hypot(y))
+            .to_degrees();
+        Ok((lon.rem_euclid(360.0), lat))
+    }
+
+    /// Transforms the given longitude and latitude of the frame of the WCS, in degrees, to pixel coordinates.
+    pub fn celestial_to_pixel(&self, lon: f64, lat: f64) -> Result<(f64, f64), WcsError> {
+        let (alpha_p, delta_p, phi_p) = self.celestial_pole()?;
+        let (sin_delta, cos_delta) = lat.to_radians().sin_cos();
+        let (sin_dalpha, cos_dalpha) = (lon - alpha_p).to_radians().sin_cos();
+        let (sin_delta_p, cos_delta_p) = delta_p.to_radians().sin_cos();
+        let x = sin_delta * cos_delta_p - cos_delta * sin_delta_p * cos_dalpha;
+        let y = -cos_delta * sin_dalpha;
+        let phi = phi_p + y.atan2(x).to_degrees();
+        let theta = (sin_delta * sin_delta_p + cos_delta * cos_delta_p * cos_dalpha)
+            .atan2(x.hypot(y))
+            .to_degrees();
+        let (ix, iy) = self
+            .projection
+            .project(phi, theta)
+            .ok_or(WcsError::OutOfBounds(lon, lat))?;
+        self.intermediate_to_pixel(ix, iy)
+    }
+
+    /// Applies the distortion and linear transformation to the given pixel coordinates,
+    /// giving intermediate world coordinates in degrees.
+    pub(crate) fn pixel_to_intermediate(&self, x: f64, y: f64) -> (f64, f64) {
+        let (mut dx, mut dy) = (x - self.crpix[0], y - self.crpix[1]);
+        if let Some(Distortion::Sip(sip)) = &self.distortion {
+            (dx, dy) = self.along_image_axes(dx, dy);
+            (dx, dy) = sip.apply(dx, dy);
+            (dx, dy) = self.along_image_axes(dx, dy);
+        }
+        let intermediate = (
+            self.cdelt[0] * (self.pc[0][0] * dx + self.pc[0][1] * dy),
+            self.cdelt[1] * (self.pc[1][0] * dx + self.pc[1][1] * dy),
+        );
+        match &self.distortion {
+            Some(Distortion::Tpv(tpv)) => tpv.apply(intermediate.0, intermediate.1),
+            _ => intermediate,
+        }
+    }
+
+    /// Inverts the linear transformation and distortion for the given intermediate world coordinates in degrees.
+    pub(crate) fn intermediate_to_pixel(&self, ix: f64, iy: f64) -> Result<(f64, f64), WcsError> {
+        let (ix, iy) = match &self.distortion {
+            Some(Distortion::Tpv(tpv)) => tpv.invert(ix, iy)?,
+            _ => (ix, iy),
+        };
+        let m = [
+            [self.cdelt[0] * self.pc[0][0], self.cdelt[0] * self.pc[0][1]],
+            [self.cdelt[1] * self.pc[1][0], self.cdelt[1] * self.pc[1][1]],
+        ];
+        let determinant = m[0][0] * m[1][1] - m[0][1] * m[1][0];
+        if determinant == 0.0 || !determinant.is_finite() {
+            return Err(WcsError::SingularMatrix);
+        }
+        let mut dx = (m[1][1] * ix - m[0][1] * iy) / determinant;
+        let mut dy = (m[0][0] * iy - m[1][0] * ix) / determinant;
+        if let Some(Distortion::Sip(sip)) = &self.distortion {
+            (dx, dy) = self.along_image_axes(dx, dy);
+            (dx, dy) = sip.invert(dx, dy)?;
+            (dx, dy) = self.along_image_axes(dx, dy);
+        }
+        Ok((dx + self.crpix[0], dy + self.crpix[1]))
+    }
+
+    /// Reorders the given pixel coordinates between the order of the longitude and latitude axes,
+    /// and the order of the axes of the image.
+    fn along_image_axes(&self, dx: f64, dy: f64) -> (f64, f64) {
+        if self.axes[0] < self.axes[1] {
+            (dx, dy)
+        } else {
+            (dy, dx)
+        }
+    }
+
+    /// Determines the celestial coordinates of the native pole, and the native longitude of the celestial pole, in degrees.
+    fn celestial_pole(&self) -> Result<(f64, f64, f64), WcsError> {
+        let (phi_0, theta_0) = self.projection.reference_point();
+        let [alpha_0, delta_0] = self.crval;
+        let phi_p = self
+            .lonpole
+            .unwrap_or(if delta_0 >= theta_0 { 0.0 } else { 180.0 });
+        if theta_0 == 90.0 {
+            return Ok((alpha_0, delta_0, phi_p));
+        }
+
+        let (sin_theta_0, cos_theta_0) = theta_0.to_radians().sin_cos();
+        let (sin_dphi, cos_dphi) = (phi_p - phi_0).to_radians().sin_cos();
+        let (sin_delta_0, cos_delta_0) = delta_0.to_radians().sin_cos();
+        let base = sin_theta_0.atan2(cos_theta_0 * cos_dphi).to_degrees();
+        let ratio = sin_delta_0 / (1.0 - (cos_theta_0 * sin_dphi).powi(2)).sqrt();
+        if ratio.abs() > 1.0 + 1e-12 {
+            return Err(WcsError::InvalidPole);
+        }
+        let offset = ratio.clamp(-1.0, 1.0).acos().to_degrees();
+        let latpole = self.latpole.unwrap_or(90.0);
+        let delta_p = [base + offset, base - offset]
+            .into_iter()
+            .map(projection::normalize_longitude)
+            .filter(|delta_p| delta_p.abs() <= 90.0 + 1e-12)
+            .min_by(|a, b| (a - latpole).abs().total_cmp(&(b - latpole).abs()))
+            .ok_or(WcsError::InvalidPole)?
+            .clamp(-90.0, 90.0);
+
+        let alpha_p = if (delta_0.abs() - 90.0).abs() < 1e-12 {
+            alpha_0
+        } else if (delta_p - 90.0).abs() < 1e-12 {
+            alpha_0 + phi_p - phi_0 - 180.0
+        } else if (delta_p + 90.0).abs() < 1e-12 {
+            alpha_0 - phi_p + phi_0
+        } else {
+            let (sin_delta_p, cos_delta_p) = delta_p.to_radians().sin_cos();
+            alpha_0
+                - (sin_dphi * cos_theta_0 / cos_delta_0)
+                    .atan2((sin_theta_0 - sin_delta_p * sin_delta_0) / (cos_delta_p * cos_delta_0))
+                    .to_degrees()
+        };
+        Ok((alpha_p, delta_p, phi_p))
+    }
+}
+
+impl Hdu {
+    /// Reads the celestial world coordinate system from the header of the HDU.
+    /// See [`Wcs::from_header`] for details.
+    pub fn wcs(&mut self) -> Result<Wcs, WcsError> {
+        Wcs::from_header(&mut self.header)
+    }
+}
+
+/// Gets the numeric value of the card with the given keyword, or None if the card does not exist.
+fn number_value(
+    header: &mut FitsHeader,
+    keyword: Option<FitsHeaderKeyword>,
+) -> Result<Option<f64>, FitsHeaderError> {
+    match keyword.and_then(|keyword| header.get_card(keyword)) {
+        Some(card) => numeric_value(card)
+            .map(Some)
+            .ok_or(FitsHeaderError::InvalidType),
+        None => Ok(None),
+    }
+}
+
+/// Gets the string value of the card with the given keyword, or None if the card does not exist.
+fn string_value(
+    header: &mut FitsHeader,
+    keyword: Option<FitsHeaderKeyword>,
+) -> Result<Option<String>, FitsHeaderError> {
+    keyword
+        .and_then(|keyword| header.get_card(keyword))
+        .map(|card| {
+            card.get_value::<String>()
+                .map(|value| value.as_str().to_owned())
+        })
+        .transpose()
+}
@@ -0,0 +1,210 @@
+//! Spherical projections between native spherical coordinates and the projection plane.
+//! See <https://www.atnf.csiro.au/people/mcalabre/WCS/ccs.pdf> for the definitions of each projection.
+
+use std::f64::consts::{FRAC_PI_2, PI, SQRT_2};
+
+/// The number of degrees in a radian, the radius of the generating sphere of each projection.
+const R0: f64 = 180.0 / PI;
+
+/// An enumeration of the supported spherical projections, identified by the last three characters of CTYPEn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// The gnomonic projection.
+    Tan,
+    /// The slant orthographic projection, without the optional obliqueness parameters.
+    Sin,
+    /// The zenithal equidistant projection.
+    Arc,
+    /// The plate carrée projection.
+    Car,
+    /// The Hammer-Aitoff projection.
+    Ait,
+    /// The Mollweide projection.
+    Mol,
+    /// The zenithal equal-area projection.
+    Zea,
+    /// The stereographic projection.
+    Stg,
+}
+
+impl Projection {
+    /// Gets the projection identified by the given three-letter code, or None if the projection is not supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// assert_eq!(Projection::from_code("TAN"), Some(Projection::Tan));
+    /// assert_eq!(Projection::from_code("COE"), None);
+    /// ```
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "TAN" => Some(Projection::Tan),
+            "SIN" => Some(Projection::Sin),
+            "ARC" => Some(Projection::Arc),
+            "CAR" => Some(Projection::Car),
+            "AIT" => Some(Projection::Ait),
+            "MOL" => Some(Projection::Mol),
+            "ZEA" => Some(Projection::Zea),
+            "STG" => Some(Projection::Stg),
+            _ => None,
+        }
+    }
+
+    /// Gets the three-letter code that identifies the projection.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Projection::Tan => "TAN",
+            Projection::Sin => "SIN",
+            Projection::Arc => "ARC",
+            Projection::Car => "CAR",
+            Projection::Ait => "AIT",
+            Projection::Mol => "MOL",
+            Projection::Zea => "ZEA",
+            Projection::Stg => "STG",
+        }
+    }
+
+    /// Determines if the projection is zenithal, with its reference point at the native pole.
+    pub fn is_zenithal(&self) -> bool {
+        matches!(
+            self,
+            Projection::Tan | Projection::Sin | Projection::Arc | Projection::Zea | Projection::Stg
+        )
+    }
+
+    /// Gets the native longitude and latitude of the reference point of the projection, in degrees.
+    pub(crate) fn reference_point(&self) -> (f64, f64) {
+        if self.is_zenithal() {
+            (0.0, 90.0)
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// Projects the given native longitude and latitude onto the projection plane, in degrees,
+    /// or returns None if the point cannot be projected.
+    pub(crate) fn project(&self, phi: f64, theta: f64) -> Option<(f64, f64)> {
+        let phi = normalize_longitude(phi);
+        if self.is_zenithal() {
+            let r = match self {
+                Projection::Tan if theta > 0.0 => R0 / theta.to_radians().tan(),
+                Projection::Sin if theta >= 0.0 => R0 * theta.to_radians().cos(),
+                Projection::Arc => 90.0 - theta,
+                Projection::Zea => 2.0 * R0 * ((90.0 - theta) / 2.0).to_radians().sin(),
+                Projection::Stg if theta > -90.0 => {
+                    2.0 * R0 * ((90.0 - theta) / 2.0).to_radians().tan()
+                }
+                _ => return None,
+            };
+            let (sin_phi, cos_phi) = phi.to_radians().sin_cos();
+            return Some((r * sin_phi, -r * cos_phi));
+        }
+        let (sin_theta, cos_theta) = theta.to_radians().sin_cos();
+        match self {
+            Projection::Car => Some((phi, theta)),
+            Projection::Ait => {
+                let (sin_half_phi, cos_half_phi) = (phi / 2.0).to_radians().sin_cos();
+                let gamma = R0 * (2.0 / (1.0 + cos_theta * cos_half_phi)).sqrt();
+                Some((2.0 * gamma * cos_theta * sin_half_phi, gamma * sin_theta))
+            }
+            Projection::Mol => {
+                let gamma = mollweide_gamma(theta.to_radians());
+                Some((
+                    2.0 * SQRT_2 / PI * phi * gamma.cos(),
+                    SQRT_2 * R0 * gamma.sin(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Deprojects the given point on the projection plane, in degrees, to native longitude and latitude,
+    /// or returns None if the point lies outside the boundary of the projection.
+    pub(crate) fn deproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        if self.is_zenithal() {
+            let r = x.hypot(y);
+            let phi = if r == 0.0 {
+                0.0
+            } else {
+                x.atan2(-y).to_degrees()
+            };
+            let theta = match self {
+                Projection::Tan => R0.atan2(r).to_degrees(),
+                Projection::Sin if r <= R0 => (r / R0).acos().to_degrees(),
+                Projection::Arc if r <= 180.0 => 90.0 - r,
+                Projection::Zea if r <= 2.0 * R0 => {
+                    90.0 - 2.0 * (r / (2.0 * R0)).asin().to_degrees()
+                }
+                Projection::Stg => 90.0 - 2.0 * (r / (2.0 * R0)).atan().to_degrees(),
+                _ => return None,
+            };
+            return Some((phi, theta));
+        }
+        match self {
+            Projection::Car if y.abs() <= 90.0 => Some((x, y)),
+            Projection::Ait => {
+                let z_squared = 1.0 - (x / (4.0 * R0)).powi(2) - (y / (2.0 * R0)).powi(2);
+                if z_squared < 0.5 {
+                    return None;
+                }
+                let z = z_squared.sqrt();
+                let phi = 2.0 * (z * x / (2.0 * R0)).atan2(2.0 * z_squared - 1.0);
+                let theta = (y * z / R0).clamp(-1.0, 1.0).asin();
+                Some((phi.to_degrees(), theta.to_degrees()))
+            }
+            Projection::Mol => {
+                let sin_gamma = y / (SQRT_2 * R0);
+                if sin_gamma.abs() > 1.0 {
+                    return None;
+                }
+                let gamma = sin_gamma.asin();
+                let cos_gamma = gamma.cos();
+                let phi = if cos_gamma == 0.0 {
+                    0.0
+                } else {
+                    PI * x / (2.0 * SQRT_2 * cos_gamma)
+                };
+                if phi.abs() > 180.0 + 1e-9 {
+                    return None;
+                }
+                let theta = ((2.0 * gamma + (2.0 * gamma).sin()) / PI)
+                    .clamp(-1.0, 1.0)
+                    .asin();
+                Some((phi, theta.to_degrees()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Normalizes the given longitude to [-180, 180] degrees.
+pub(crate) fn normalize_longitude(phi: f64) -> f64 {
+    let phi = phi % 360.0;
+    if phi > 180.0 {
+        phi - 360.0
+    } else if phi < -180.0 {
+        phi + 360.0
+    } else {
+        phi
+    }
+}
+
+/// Solves 2γ + sin(2γ) = π sin(θ) for the auxiliary angle of the Mollweide projection.
+fn mollweide_gamma(theta: f64) -> f64 {
+    if (FRAC_PI_2 - theta.abs()).abs() < 1e-12 {
+        return theta;
+    }
+    let target = PI * theta.sin();
+    let mut gamma = theta;
+    for _ in 0..100 {
+        let delta =
+            (2.0 * gamma + (2.0 * gamma).sin() - target) / (2.0 + 2.0 * (2.0 * gamma).cos());
+        gamma -= delta;
+        if delta.abs() < 1e-14 {
+            break;
+        }
+    }
+    gamma
+}
@@ -0,0 +1,337 @@
+//! Resample images from one world coordinate system onto the pixel grid of another.
+
+use super::*;
+
+/// An enumeration of the methods used to resample an image onto another pixel grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resampling {
+    /// Takes the value of the input pixel containing the center of each output pixel.
+    Nearest,
+    /// Interpolates linearly between the four input pixels surrounding the center of each output pixel.
+    Bilinear,
+    /// Averages the input pixels weighted by the area of their overlap with each output pixel,
+    /// with the edges of each input pixel mapped onto the output grid as straight lines.
+    /// This conserves the surface brightness, and so the total flux over any region of the sky.
+    Exact,
+}
+
+/// The result of reprojecting an image onto the pixel grid of another world coordinate system.
+#[derive(Debug, Clone)]
+pub struct Reprojection {
+    /// The reprojected image, with the target header and BITPIX -64.
+    /// Output pixels that are not covered by the input image are NaN.
+    pub hdu: Hdu,
+    /// The coverage of each output pixel by the input image, in the same order as the data of the reprojected image.
+    /// The coverage is 0 or 1 for the nearest and bilinear methods, and the covered fraction of each pixel for the exact method.
+    pub footprint: Vec<f64>,
+}
+
+impl Wcs {
+    /// Resamples the given image, described by this WCS, onto the pixel grid of the target WCS.
+    /// The shapes are given as `[NAXIS1, NAXIS2]`, and the image is in the order of the data section, with NAXIS1 varying fastest.
+    /// Returns the resampled image and its footprint, as described by [`Reprojection`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut header = image_hdu::default().header;
+    /// header.set_value(*b"CTYPE1  ", String::from("RA---TAN"))?;
+    /// header.set_value(*b"CTYPE2  ", String::from("DEC--TAN"))?;
+    /// header.set_value(*b"CDELT1  ", -0.1)?;
+    /// header.set_value(*b"CDELT2  ", 0.1)?;
+    /// let source = Wcs::from_header(&mut header)?;
+    /// // the target grid is shifted by one pixel along the first axis
+    /// header.set_value(*b"CRPIX1  ", -1.0)?;
+    /// let target = Wcs::from_header(&mut header)?;
+    ///
+    /// let image = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    /// let (data, footprint) = source.reproject(&image, [3, 2], &target, [3, 2], Resampling::Nearest)?;
+    /// assert_eq!(data[..2], [2.0, 3.0]);
+    /// assert!(data[2].is_nan());
+    /// assert_eq!(footprint, [1.0, 1.0, 0.0, 1.0, 1.0, 0.0]);
+    /// # Ok::<(), astro_rs::fits::WcsError>(())
+    /// ```
+    pub fn reproject<T: Copy + Into<f64>>(
+        &self,
+        data: &[T],
+        shape: [usize; 2],
+        target: &Wcs,
+        target_shape: [usize; 2],
+        resampling: Resampling,
+    ) -> Result<(Vec<f64>, Vec<f64>), WcsError> {
+        let len = shape[0] * shape[1];
+        if data.len() < len {
+            return Err(WcsError::Header(FitsHeaderError::InvalidLength {
+                expected: len,
+                found: data.len(),
+                intent: String::from("reprojected image"),
+            }));
+        }
+        let value = |i: usize, j: usize| data[j * shape[0] + i].into();
+
+        let target_len = target_shape[0] * target_shape[1];
+        let mut result = vec![f64::NAN; target_len];
+        let mut footprint = vec![0.0; target_len];
+        if resampling == Resampling::Exact {
+            self.reproject_exact(
+                &value,
+                shape,
+                target,
+                target_shape,
+                &mut result,
+                &mut footprint,
+            )?;
+            return Ok((result, footprint));
+        }
+
+        for l in 0..target_shape[1] {
+            for k in 0..target_shape[0] {
+                let Some((x, y)) = transform(target, self, k as f64 + 1.0, l as f64 + 1.0)? else {
+                    continue;
+                };
+                // the input image covers half a pixel beyond the centers of its edge pixels
+                let (x, y) = (x - 1.0, y - 1.0);
+                if !(-0.5..shape[0] as f64 - 0.5).contains(&x)
+                    || !(-0.5..shape[1] as f64 - 0.5).contains(&y)
+                {
+                    continue;
+                }
+                let index = l * target_shape[0] + k;
+                footprint[index] = 1.0;
+                result[index] = match resampling {
+                    Resampling::Nearest => value(x.round() as usize, y.round() as usize),
+                    _ => {
+                        let (i, j) = (x.floor(), y.floor());
+                        let (t, u) = (x - i, y - j);
+                        let clamp = |index: f64, len: usize| (index.max(0.0) as usize).min(len - 1);
+                        let (i0, i1) = (clamp(i, shape[0]), clamp(i + 1.0, shape[0]));
+                        let (j0, j1) = (clamp(j, shape[1]), clamp(j + 1.0, shape[1]));
+                        (1.0 - t) * (1.0 - u) * value(i0, j0)
+                            + t * (1.0 - u) * value(i1, j0)
+                            + (1.0 - t) * u * value(i0, j1)
+                            + t * u * value(i1, j1)
+                    }
+                };
+            }
+        }
+        Ok((result, footprint))
+    }
+
+    /// Resamples an image by the area of overlap between the input pixels and the output pixels.
+    fn reproject_exact(
+        &self,
+        value: &impl Fn(usize, usize) -> f64,
+        shape: [usize; 2],
+        target: &Wcs,
+        target_shape: [usize; 2],
+        result: &mut [f64],
+        footprint: &mut [f64],
+    ) -> Result<(), WcsError> {
+        // the corners of the input pixels are shared with their neighbours
+        let mut corners = Vec::with_capacity((shape[0] + 1) * (shape[1] + 1));
+        for j in 0..=shape[1] {
+            for i in 0..=shape[0] {
+                corners.push(transform(self, target, i as f64 + 0.5, j as f64 + 0.5)?);
+            }
+        }
+        let corner = |i: usize, j: usize| corners[j * (shape[0] + 1) + i];
+
+        let mut weighted = vec![0.0; result.len()];
+        for j in 0..shape[1] {
+            for i in 0..shape[0] {
+                let value = value(i, j);
+                if value.is_nan() {
+                    continue;
+                }
+                let Some(polygon) = [
+                    corner(i, j),
+                    corner(i + 1, j),
+                    corner(i + 1, j + 1),
+                    corner(i, j + 1),
+                ]
+                .into_iter()
+                .collect::<Option<Vec<(f64, f64)>>>() else {
+                    continue;
+                };
+                let (min, max) =
+                    polygon
+                        .iter()
+                        .fold(([f64::MAX; 2], [f64::MIN; 2]), |(min, max), (x, y)| {
+                            (
+                                [min[0].min(*x), min[1].min(*y)],
+                                [max[0].max(*x), max[1].max(*y)],
+                            )
+                        });
+                // output pixel k spans k + 0.5 to k + 1.5
+                let range = |axis: usize| {
+                    let start = (min[axis] - 0.5).floor().max(0.0) as usize;
+                    let stop = ((max[axis] - 0.5).ceil().max(0.0) as usize).min(target_shape[axis]);
+                    start..stop
+                };
+                for l in range(1) {
+                    for k in range(0) {
+                        let area = overlap_area(&polygon, [k as f64 + 0.5, l as f64 + 0.5]);
+                        if area > 0.0 {
+                            let index = l * target_shape[0] + k;
+                            weighted[index] += value * area;
+                            footprint[index] += area;
+                        }
+                    }
+                }
+            }
+        }
+
+        for ((result, weighted), footprint) in
+            result.iter_mut().zip(weighted).zip(footprint.iter_mut())
+        {
+            if *footprint > 0.0 {
+                *result = weighted / *footprint;
+                *footprint = footprint.min(1.0);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Hdu {
+    /// Reprojects the image in the data section of the HDU onto the pixel grid described by the given header,
+    /// with the given resampling method. Both images must have exactly two axes, which are their celestial axes.
+    /// The physical values of the pixels are resampled, with BSCALE and BZERO applied and pixels equal to BLANK read as NaN.
+    /// The reprojected HDU has a copy of the target header with BITPIX -64,
+    /// without the BSCALE, BZERO, BLANK, CHECKSUM, and DATASUM cards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use astro_rs::fits::*;
+    ///
+    /// let mut hdu = image_hdu::default();
+    /// hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+    /// hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+    /// hdu.header.set_value(*b"NAXIS1  ", 4u32)?;
+    /// hdu.header.set_value(*b"NAXIS2  ", 4u32)?;
+    /// hdu.header.set_value(*b"CTYPE1  ", String::from("RA---TAN"))?;
+    /// hdu.header.set_value(*b"CTYPE2  ", String::from("DEC--TAN"))?;
+    /// hdu.header.set_value(*b"CRPIX1  ", 2.5)?;
+    /// hdu.header.set_value(*b"CRPIX2  ", 2.5)?;
+    /// hdu.header.set_value(*b"CDELT1  ", -0.001)?;
+    /// hdu.header.set_value(*b"CDELT2  ", 0.001)?;
+    /// hdu.set_data(&vec![1i16; 16]);
+    ///
+    /// // the target grid has pixels twice as large
+    /// let mut target = hdu.header.clone();
+    /// target.set_value(*b"NAXIS1  ", 2u32)?;
+    /// target.set_value(*b"NAXIS2  ", 2u32)?;
+    /// target.set_value(*b"CRPIX1  ", 1.5)?;
+    /// target.set_value(*b"CRPIX2  ", 1.5)?;
+    /// target.set_value(*b"CDELT1  ", -0.002)?;
+    /// target.set_value(*b"CDELT2  ", 0.002)?;
+    ///
+    /// let reprojection = hdu.reproject(&target, Resampling::Exact)?;
+    /// let data = reprojection.hdu.get_data::<Vec<f64>>()?;
+    /// assert!(data.iter().all(|value| (value - 1.0).abs() < 1e-9));
+    /// assert!(reprojection.footprint.iter().all(|coverage| (coverage - 1.0).abs() < 1e-6));
+    /// # Ok::<(), astro_rs::fits::WcsError>(())
+    /// ```
+    pub fn reproject(
+        &mut self,
+        target: &FitsHeader,
+        resampling: Resampling,
+    ) -> Result<Reprojection, WcsError> {
+        let source = self.wcs()?;
+        let shape = image_shape(self)?;
+        let data = image_hdu::physical_pixels(self)?;
+
+        let mut hdu = Hdu::new();
+        hdu.header = target.clone();
+        hdu.header.cards.retain(|card| {
+            ![
+                BSCALE_KEYWORD,
+                BZERO_KEYWORD,
+                BLANK_KEYWORD,
+                CHECKSUM_KEYWORD,
+                DATASUM_KEYWORD,
+            ]
+            .iter()
+            .any(|keyword| *card.keyword() == *keyword)
+        });
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F64)?;
+        let target_wcs = hdu.wcs()?;
+        let target_shape = image_shape(&mut hdu)?;
+
+        let (data, footprint) =
+            source.reproject(&data, shape, &target_wcs, target_shape, resampling)?;
+        hdu.set_data(&data);
+        Ok(Reprojection { hdu, footprint })
+    }
+}
+
+/// Gets the shape of the image in the data section of the given HDU as `[NAXIS1, NAXIS2]`,
+/// or an error if the image does not have two axes.
+fn image_shape(hdu: &mut Hdu) -> Result<[usize; 2], WcsError> {
+    match hdu.get_dimensions()[..] {
+        [naxis1, naxis2] => Ok([naxis1, naxis2]),
+        ref dimensions => Err(WcsError::Dimensions {
+            found: dimensions.len(),
+        }),
+    }
+}
+
+/// Transforms the given one-based pixel coordinates along the axes of an image described by one WCS
+/// to the pixel coordinates of another, or returns None if the pixel lies outside either projection.
+fn transform(from: &Wcs, to: &Wcs, x: f64, y: f64) -> Result<Option<(f64, f64)>, WcsError> {
+    let (x, y) = from.along_image_axes(x, y);
+    let result = if from.frame == to.frame {
+        from.pixel_to_celestial(x, y)
+            .and_then(|(lon, lat)| to.celestial_to_pixel(lon, lat))
+    } else {
+        from.pixel_to_world(x, y)
+            .and_then(|coords| to.world_to_pixel(&coords))
+    };
+    match result {
+        Ok((x, y)) => Ok(Some(to.along_image_axes(x, y))),
+        Err(WcsError::OutOfBounds(..) | WcsError::NotConverged(..)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Computes the area of the overlap between the given convex polygon and the unit square with the given lower corner.
+fn overlap_area(polygon: &[(f64, f64)], corner: [f64; 2]) -> f64 {
+    let mut clipped = polygon.to_vec();
+    // each edge of the square is given by an axis, a bound, and whether points must lie above the bound
+    for (axis, bound, above) in [
+        (0, corner[0], true),
+        (0, corner[0] + 1.0, false),
+        (1, corner[1], true),
+        (1, corner[1] + 1.0, false),
+    ] {
+        let coordinate = |point: &(f64, f64)| if axis == 0 { point.0 } else { point.1 };
+        let inside = |point: &(f64, f64)| (coordinate(point) >= bound) == above;
+        let mut next = Vec::with_capacity(clipped.len() + 1);
+        for (index, point) in clipped.iter().enumerate() {
+            let previous = &clipped[(index + clipped.len() - 1) % clipped.len()];
+            if inside(point) != inside(previous) {
+                let t = (bound - coordinate(previous)) / (coordinate(point) - coordinate(previous));
+                next.push((
+                    previous.0 + t * (point.0 - previous.0),
+                    previous.1 + t * (point.1 - previous.1),
+                ));
+            }
+            if inside(point) {
+                next.push(*point);
+            }
+        }
+        if next.is_empty() {
+            return 0.0;
+        }
+        clipped = next;
+    }
+    let twice_area = clipped
+        .iter()
+        .zip(clipped.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum::<f64>();
+    twice_area.abs() / 2.0
+}
@@ -42,6 +42,2574 @@ mod fits_tests {
         Ok(())
     }
 
+    #[test]
+    fn test_hdu_list_lazy() -> Result<(), Box<dyn Error>> {
+        let mut fits_bytes = Vec::new();
+        for i in 0..3 {
+            let mut hdu = if i == 0 {
+                let mut hdu = primary_hdu::default();
+                hdu.header.set_value(*b"EXTEND  ", true)?;
+                hdu
+            } else {
+                image_hdu::default()
+            };
+            hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F32)?;
+            hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+            hdu.header.set_value(*b"NAXIS1  ", 1000u32)?;
+            hdu.set_data(&vec![i as f32; 1000]);
+            fits_bytes.append(&mut hdu.to_bytes());
+        }
+
+        let in_cursor = Cursor::new(fits_bytes.clone());
+        let mut hdu_list = HduList::new_lazy(BufReader::new(in_cursor));
+        let last_hdu = hdu_list.get_by_index(2).unwrap();
+        assert!(!last_hdu.is_data_loaded());
+        assert_eq!(last_hdu.get_data::<Vec<f32>>()?[0..1000], [2.0; 1000]);
+        assert!(last_hdu.is_data_loaded());
+        for hdu in hdu_list.iter_mut().take(2) {
+            assert!(!hdu.is_data_loaded());
+        }
+        let first_hdu = hdu_list.first_mut().unwrap();
+        assert_eq!(first_hdu.get_data::<Vec<f32>>()?[0..1000], [0.0; 1000]);
+
+        let out_cursor = Cursor::new(Vec::new());
+        let mut out_writer = BufWriter::new(out_cursor);
+        hdu_list.write(&mut out_writer)?;
+        assert_eq!(out_writer.get_ref().get_ref(), &fits_bytes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_errors() -> Result<(), Box<dyn Error>> {
+        let mut hdu = image_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", 2000u32)?;
+        hdu.set_data(&vec![0i16; 2000]);
+        let mut fits_bytes = primary_hdu::default().to_bytes();
+        fits_bytes.append(&mut hdu.to_bytes());
+        fits_bytes.truncate(fits_bytes.len() - 2000);
+
+        // truncated data section
+        let in_cursor = Cursor::new(fits_bytes.clone());
+        let mut hdu_list = HduList::new_lazy(BufReader::new(in_cursor));
+        // retrying reads the failed HDU again rather than the bytes that follow it
+        for _ in 0..2 {
+            match hdu_list.try_get_by_index(1) {
+                Err(FitsError::Truncated {
+                    expected,
+                    found,
+                    hdu_index,
+                    offset,
+                    ..
+                }) => {
+                    assert_eq!(expected, 5760);
+                    assert_eq!(found, 3760);
+                    assert_eq!(hdu_index, 1);
+                    assert_eq!(offset, 5760);
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+        }
+        assert_eq!(hdu_list.iter_mut().count(), 1);
+
+        // malformed header
+        let naxis_start = 2880 + 2 * 80;
+        fits_bytes[naxis_start + 29] = b'X';
+        for lazy in [false, true] {
+            let in_cursor = Cursor::new(fits_bytes.clone());
+            let mut hdu_list = if lazy {
+                HduList::new_lazy(BufReader::new(in_cursor))
+            } else {
+                HduList::new(BufReader::new(in_cursor))
+            };
+            assert!(hdu_list.try_first_mut()?.is_some());
+            for _ in 0..2 {
+                assert!(matches!(
+                    hdu_list.try_get_by_name("SCI"),
+                    Err(FitsError::Header {
+                        hdu_index: 1,
+                        offset: 2880,
+                        ..
+                    })
+                ));
+                assert!(matches!(
+                    hdu_list.try_get_by_index(2),
+                    Err(FitsError::Header { hdu_index: 1, .. })
+                ));
+            }
+        }
+
+        // data section length overflows
+        let mut hdu = primary_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F64)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 3u16)?;
+        for n in 1..=3u16 {
+            hdu.header.set_naxisn(n, 4000000000u32)?;
+        }
+        let in_cursor = Cursor::new(hdu.to_bytes());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        assert!(matches!(
+            hdu_list.try_get_by_index(0),
+            Err(FitsError::Header {
+                source: FitsHeaderError::DataLengthOverflow,
+                hdu_index: 0,
+                offset: 0,
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_extension_sizes() -> Result<(), Box<dyn Error>> {
+        let mut primary = primary_hdu::default();
+        // PCOUNT and GCOUNT do not apply to a primary HDU outside the random groups format
+        primary.header.set_value(PCOUNT_KEYWORD, 5000u32)?;
+        primary.header.set_value(GCOUNT_KEYWORD, 2u32)?;
+        primary.header.set_value(*b"EXTEND  ", true)?;
+        let mut fits_bytes = primary.to_bytes();
+
+        // a binary table with a heap that spans more than one record
+        let mut table = binary_table_hdu::default();
+        table.header.set_value(*b"NAXIS1  ", 8u32)?;
+        table.header.set_value(*b"NAXIS2  ", 2u32)?;
+        table.header.set_value(PCOUNT_KEYWORD, 3000u32)?;
+        table.set_data_raw((0..3016).map(|i| (i % 251) as u8).collect());
+        fits_bytes.append(&mut table.to_bytes());
+
+        // conforming extensions are sized with the general formula and kept as they are
+        let mut foreign = image_hdu::default();
+        foreign
+            .header
+            .set_value(XTENSION_KEYWORD, String::from("FOREIGN"))?;
+        foreign.header.set_value(PCOUNT_KEYWORD, 2900u32)?;
+        foreign.set_data_raw(vec![b'F'; 2900]);
+        fits_bytes.append(&mut foreign.to_bytes());
+
+        let mut dump = image_hdu::default();
+        dump.header
+            .set_value(XTENSION_KEYWORD, String::from("DUMP"))?;
+        dump.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+        dump.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        dump.header.set_value(*b"NAXIS1  ", 1000u32)?;
+        dump.header.set_value(PCOUNT_KEYWORD, 500u32)?;
+        dump.header.set_value(GCOUNT_KEYWORD, 3u32)?;
+        dump.set_data(&vec![-1i16; 4500]);
+        fits_bytes.append(&mut dump.to_bytes());
+
+        let mut image = image_hdu::default();
+        image.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+        image.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        image.header.set_value(*b"NAXIS1  ", 4u32)?;
+        image.set_data(&vec![1i16, 2, 3, 4]);
+        fits_bytes.append(&mut image.to_bytes());
+
+        // 64 bit integers take 8 bytes per element
+        let long_data: Vec<i64> = (0..400).map(|i| i64::MIN + i * (1 << 40)).collect();
+        let mut image = image_hdu::default();
+        image.header.set_bitpix(Bitpix::I64)?;
+        image.header.set_dimensions(&[400])?;
+        image.set_data(&long_data);
+        assert!(image.header.verify(5).is_empty());
+        fits_bytes.append(&mut image.to_bytes());
+
+        for lazy in [false, true] {
+            let in_cursor = Cursor::new(fits_bytes.clone());
+            let mut hdu_list = if lazy {
+                HduList::new_lazy(BufReader::new(in_cursor))
+            } else {
+                HduList::new(BufReader::new(in_cursor))
+            };
+            assert_eq!(hdu_list.try_iter_mut()?.count(), 6);
+            assert!(hdu_list.first_mut().unwrap().data_raw().is_empty());
+            assert_eq!(hdu_list.get_by_index(1).unwrap().data_raw().len(), 2880 * 2);
+            assert_eq!(
+                hdu_list.get_by_index(2).unwrap().data_raw()[..2900],
+                [b'F'; 2900]
+            );
+            assert_eq!(hdu_list.get_by_index(3).unwrap().data_raw().len(), 2880 * 4);
+            let image = hdu_list.get_by_index(4).unwrap();
+            assert_eq!(image.get_data::<Vec<i16>>()?[0..4], [1, 2, 3, 4]);
+            let image = hdu_list.get_by_index(5).unwrap();
+            assert_eq!(image.header.bitpix()?, Bitpix::I64);
+            assert_eq!(image.data_raw().len(), 2880 * 2);
+            assert_eq!(image.get_data::<Vec<i64>>()?[0..400], long_data);
+
+            let out_cursor = Cursor::new(Vec::new());
+            let mut out_writer = BufWriter::new(out_cursor);
+            hdu_list.write(&mut out_writer)?;
+            assert_eq!(out_writer.get_ref().get_ref(), &fits_bytes);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_verify() -> Result<(), Box<dyn Error>> {
+        let mut primary = primary_hdu::default();
+        primary.header.set_value(*b"EXTEND  ", true)?;
+        let mut hdu = image_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        let naxis1_card = FitsHeaderCard::from(
+            *b"NAXIS1  =                 2000                                                  ",
+        );
+        hdu.header.cards.insert(3, naxis1_card);
+        hdu.set_data(&vec![7i16; 2000]);
+        let mut fits_bytes = primary.to_bytes();
+        fits_bytes.append(&mut hdu.to_bytes());
+
+        // conforming file
+        let in_cursor = Cursor::new(fits_bytes.clone());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        hdu_list.set_parse_mode(ParseMode::Strict);
+        let report = hdu_list.verify()?;
+        assert!(report.issues.is_empty(), "{}", report);
+
+        // missing data padding is filled in lenient mode
+        let mut truncated_bytes = fits_bytes.clone();
+        truncated_bytes.truncate(truncated_bytes.len() - 100);
+        let in_cursor = Cursor::new(truncated_bytes.clone());
+        let mut hdu_list = HduList::new_lazy(BufReader::new(in_cursor));
+        let report = hdu_list.verify()?;
+        assert!(report.is_valid());
+        assert_eq!(report.warnings().count(), 1);
+        let image = hdu_list.get_by_index(1).unwrap();
+        assert_eq!(image.data_raw().len(), 5760);
+        assert_eq!(image.get_data::<Vec<i16>>()?[1999], 7);
+
+        let in_cursor = Cursor::new(truncated_bytes);
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        hdu_list.set_parse_mode(ParseMode::Strict);
+        assert!(matches!(
+            hdu_list.try_get_by_index(1),
+            Err(FitsError::Truncated { found: 5660, .. })
+        ));
+
+        // non-zero data padding and a misplaced mandatory keyword
+        let mut invalid_bytes = fits_bytes;
+        let last = invalid_bytes.len() - 1;
+        invalid_bytes[last] = 1;
+        let bitpix_start = 2880 + 80;
+        let naxis_start = 2880 + 2 * 80;
+        let bitpix_card = invalid_bytes[bitpix_start..naxis_start].to_vec();
+        invalid_bytes.copy_within(naxis_start..naxis_start + 80, bitpix_start);
+        invalid_bytes[naxis_start..naxis_start + 80].copy_from_slice(&bitpix_card);
+
+        let in_cursor = Cursor::new(invalid_bytes.clone());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        let report = hdu_list.verify()?;
+        assert_eq!(report.errors().count(), 3, "{}", report);
+        assert!(report.errors().all(|issue| issue.hdu_index == 1));
+
+        for lazy in [false, true] {
+            let in_cursor = Cursor::new(invalid_bytes.clone());
+            let mut hdu_list = if lazy {
+                HduList::new_lazy(BufReader::new(in_cursor))
+            } else {
+                HduList::new(BufReader::new(in_cursor))
+            };
+            hdu_list.set_parse_mode(ParseMode::Strict);
+            assert!(hdu_list.try_first_mut()?.is_some());
+            // a rejected HDU is not skipped when reading is retried
+            for _ in 0..2 {
+                match hdu_list.try_get_by_index(1) {
+                    Err(FitsError::Invalid {
+                        issues,
+                        hdu_index,
+                        offset,
+                    }) => {
+                        // the padding of a deferred data section is not read
+                        assert_eq!(issues.len(), if lazy { 2 } else { 3 });
+                        assert_eq!(hdu_index, 1);
+                        assert_eq!(offset, 2880);
+                    }
+                    result => panic!("unexpected result: {:?}", result),
+                }
+            }
+            // a seekable list can be read again leniently
+            hdu_list.set_parse_mode(ParseMode::Lenient);
+            assert_eq!(hdu_list.try_get_by_index(1).is_ok(), lazy);
+        }
+
+        // lowercase keywords are kept unless normalization is requested
+        let mut lowercase_bytes = primary_hdu::default().to_bytes();
+        lowercase_bytes.copy_within(3 * 80..4 * 80, 4 * 80);
+        lowercase_bytes[3 * 80..4 * 80].copy_from_slice(
+            b"exptime =                 30.0                                                  ",
+        );
+        let in_cursor = Cursor::new(lowercase_bytes.clone());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        assert!(hdu_list.try_first_mut()?.is_some());
+        let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut writer)?;
+        assert_eq!(writer.into_inner()?.into_inner(), lowercase_bytes);
+
+        let in_cursor = Cursor::new(lowercase_bytes);
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        hdu_list.set_normalize_keywords(true);
+        let report = hdu_list.verify()?;
+        assert_eq!(report.warnings().count(), 1, "{}", report);
+        let primary = hdu_list.first_mut().unwrap();
+        assert_eq!(primary.header.exptime()?, Some(30.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_checksum() -> Result<(), Box<dyn Error>> {
+        let mut hdu_list = HduList::default();
+        let mut primary = primary_hdu::default();
+        primary.header.set_value(*b"EXTEND  ", true)?;
+        hdu_list.push(primary);
+        let mut hdu = image_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F64)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        let naxis1_card = FitsHeaderCard::from(
+            *b"NAXIS1  =                  500                                                  ",
+        );
+        hdu.header.cards.insert(3, naxis1_card);
+        hdu.set_data(&(0..500).map(|i| i as f64 * 0.5).collect::<Vec<f64>>());
+        hdu_list.push(hdu);
+        hdu_list.set_update_checksums(true);
+        let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut writer)?;
+        let mut fits_bytes = writer.into_inner()?.into_inner();
+
+        let in_cursor = Cursor::new(fits_bytes.clone());
+        let mut hdu_list = HduList::new_lazy(BufReader::new(in_cursor));
+        hdu_list.set_parse_mode(ParseMode::Strict);
+        hdu_list.set_verify_checksums(true);
+        assert_eq!(hdu_list.try_iter_mut()?.count(), 2);
+        for hdu in hdu_list.iter_mut() {
+            let checksum = hdu.header.get_card(CHECKSUM_KEYWORD).unwrap();
+            let checksum = checksum.get_value::<String>()?;
+            assert_eq!(checksum.len(), 16);
+            assert!(checksum.bytes().all(|b| b.is_ascii_alphanumeric()));
+            assert_eq!(hdu.verify_datasum(), Some(true));
+            assert_eq!(hdu.verify_checksum(), Some(true));
+        }
+        let primary = hdu_list.first_mut().unwrap();
+        let datasum: [u8; 80] = primary
+            .header
+            .get_card(DATASUM_KEYWORD)
+            .unwrap()
+            .clone()
+            .into();
+        assert_eq!(&datasum[..20], b"DATASUM = '0       '");
+
+        // corrupt a data element
+        fits_bytes[2 * 2880 + 100] ^= 0xFF;
+        let in_cursor = Cursor::new(fits_bytes);
+        let mut hdu_list = HduList::new_lazy(BufReader::new(in_cursor));
+        hdu_list.set_verify_checksums(true);
+        let report = hdu_list.verify()?;
+        assert_eq!(report.errors().count(), 2, "{}", report);
+        assert!(report.errors().all(|issue| issue.hdu_index == 1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_file_compression() -> Result<(), Box<dyn Error>> {
+        #[allow(unused_mut)]
+        let mut formats = vec![("fits", FileCompression::None)];
+        #[cfg(feature = "gzip")]
+        formats.push(("fits.gz", FileCompression::Gzip));
+        #[cfg(feature = "bzip2")]
+        formats.push(("fits.bz2", FileCompression::Bzip2));
+        #[cfg(feature = "zstd")]
+        formats.push(("fits.zst", FileCompression::Zstd));
+
+        let data: Vec<i32> = (0..1000).map(|x| x * x).collect();
+        for (extension, compression) in formats {
+            let mut hdu = primary_hdu::default();
+            hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+            hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+            hdu.header.set_value(*b"NAXIS1  ", 1000u32)?;
+            hdu.set_data(&data);
+            let mut hdu_list = HduList::default();
+            hdu_list.push(hdu);
+            hdu_list.push(image_hdu::default());
+
+            let path =
+                std::env::temp_dir().join(format!("astro_rs_test_file_compression.{}", extension));
+            let mut writer = HduList::create(&path)?;
+            hdu_list.write(&mut writer)?;
+            writer.into_inner()?.finish()?;
+            let file_bytes = std::fs::read(&path)?;
+            assert_eq!(FileCompression::from_magic(&file_bytes), compression);
+
+            let mut hdu_list = HduList::open(&path)?;
+            assert_eq!(hdu_list.try_iter_mut()?.count(), 2);
+            let primary_hdu = hdu_list.first_mut().unwrap();
+            assert_eq!(primary_hdu.get_data::<Vec<i32>>()?[0..1000], data);
+
+            // only uncompressed files can be seeked, so only their data sections are deferred
+            let mut hdu_list = HduList::open_lazy(&path)?;
+            assert_eq!(hdu_list.try_iter_mut()?.count(), 2);
+            let primary_hdu = hdu_list.first_mut().unwrap();
+            assert_eq!(
+                primary_hdu.is_data_loaded(),
+                compression != FileCompression::None
+            );
+            assert_eq!(primary_hdu.get_data::<Vec<i32>>()?[0..1000], data);
+            std::fs::remove_file(&path)?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_update() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("astro_rs_test_update.fits");
+        let data: Vec<i32> = (0..1000).collect();
+        let mut hdu_list = HduList::default();
+        let mut hdu = primary_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", 1000u32)?;
+        hdu.set_data(&data);
+        hdu_list.push(hdu);
+        let mut hdu = image_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", 1000u32)?;
+        hdu.set_data(&data);
+        hdu_list.push(hdu);
+        let mut writer = HduList::create(&path)?;
+        hdu_list.write(&mut writer)?;
+        writer.into_inner()?.finish()?;
+        let original_bytes = std::fs::read(&path)?;
+        assert_eq!(original_bytes.len(), 4 * 2880 + 2 * 1440 * 2);
+
+        // a header that fits in its records is rewritten in place
+        let mut hdu_list = HduList::open_update(&path)?;
+        let hdu = hdu_list.try_get_by_index(1)?.unwrap();
+        hdu.header.set_value(EXTNAME_KEYWORD, String::from("SCI"))?;
+        hdu_list.flush()?;
+        assert!(!hdu_list.first_mut().unwrap().is_data_loaded());
+        assert!(!hdu_list.get_by_index(1).unwrap().is_data_loaded());
+        let bytes = std::fs::read(&path)?;
+        assert_eq!(bytes.len(), original_bytes.len());
+        let header_offset = 2880 + 4 * 1440;
+        assert_eq!(bytes[..header_offset], original_bytes[..header_offset]);
+        assert_ne!(
+            bytes[header_offset..header_offset + 2880],
+            original_bytes[header_offset..header_offset + 2880]
+        );
+        assert_eq!(
+            bytes[header_offset + 2880..],
+            original_bytes[header_offset + 2880..]
+        );
+
+        // a header that outgrows its records moves the sections that follow it
+        let hdu = hdu_list.first_mut().unwrap();
+        for i in 0..40 {
+            hdu.header.set_value(
+                FitsHeaderKeyword::from(
+                    *format!("KEY{:<5}", i)
+                        .as_bytes()
+                        .first_chunk::<8>()
+                        .unwrap(),
+                ),
+                i as u32,
+            )?;
+        }
+        hdu_list.flush()?;
+        assert!(!hdu_list.get_by_index(1).unwrap().is_data_loaded());
+        assert_eq!(
+            std::fs::metadata(&path)?.len() as usize,
+            original_bytes.len() + 2880
+        );
+        let mut hdu_list = HduList::open(&path)?;
+        assert_eq!(
+            hdu_list.first_mut().unwrap().get_data::<Vec<i32>>()?[..1000],
+            data
+        );
+        let hdu = hdu_list.try_get_by_name("SCI")?.unwrap();
+        assert_eq!(hdu.get_data::<Vec<i32>>()?[..1000], data);
+
+        // smaller data shrinks the file, and the following header is moved back
+        let mut hdu_list = HduList::open_update(&path)?;
+        let hdu = hdu_list.try_first_mut()?.unwrap();
+        hdu.header.set_value(*b"NAXIS1  ", 10u32)?;
+        hdu.set_data(&data[..10].to_vec());
+        hdu_list.flush()?;
+        assert_eq!(
+            std::fs::metadata(&path)?.len() as usize,
+            3 * 2880 + 2880 + 4 * 1440
+        );
+        let mut hdu_list = HduList::open(&path)?;
+        assert_eq!(
+            hdu_list.first_mut().unwrap().get_data::<Vec<i32>>()?[..10],
+            data[..10]
+        );
+        let hdu = hdu_list.try_get_by_name("SCI")?.unwrap();
+        assert_eq!(hdu.get_data::<Vec<i32>>()?[..1000], data);
+
+        // an HDU read from another file is written from memory, and the headers in memory are not padded
+        let other_path = std::env::temp_dir().join("astro_rs_test_update_other.fits");
+        let other_data: Vec<i32> = (0..4000).rev().collect();
+        let mut other_list = HduList::default();
+        let mut hdu = primary_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", 4000u32)?;
+        hdu.set_data(&other_data);
+        other_list.push(hdu.clone());
+        hdu.header = image_hdu::default().header;
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", 4000u32)?;
+        hdu.header
+            .set_value(EXTNAME_KEYWORD, String::from("OTHER"))?;
+        other_list.push(hdu);
+        let mut writer = HduList::create(&other_path)?;
+        other_list.write(&mut writer)?;
+        writer.into_inner()?.finish()?;
+        let mut other_list = HduList::open_lazy(&other_path)?;
+        let other_hdu = other_list.try_get_by_name("OTHER")?.unwrap().clone();
+
+        let mut hdu_list = HduList::open_update(&path)?;
+        hdu_list.replace(1, other_hdu);
+        let num_cards = hdu_list.first_mut().unwrap().header.cards.len();
+        hdu_list.flush()?;
+        assert_eq!(hdu_list.first_mut().unwrap().header.cards.len(), num_cards);
+        let mut hdu_list = HduList::open(&path)?;
+        let hdu = hdu_list.try_get_by_name("OTHER")?.unwrap();
+        assert_eq!(hdu.get_data::<Vec<i32>>()?[..4000], other_data);
+
+        // the checksums cover the blank cards that pad the headers in the file
+        let mut hdu_list = HduList::open_update(&path)?;
+        hdu_list.set_update_checksums(true);
+        let hdu = hdu_list.try_get_by_name("OTHER")?.unwrap();
+        hdu.header.set_value(*b"OBSERVER", String::from("Hubble"))?;
+        hdu_list.flush()?;
+        let mut hdu_list = HduList::open(&path)?;
+        for hdu in hdu_list.try_iter_mut()? {
+            assert_eq!(hdu.verify_datasum(), Some(true));
+            assert_eq!(hdu.verify_checksum(), Some(true));
+        }
+
+        std::fs::remove_file(&other_path)?;
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_writer() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("astro_rs_test_hdu_writer.fits");
+        let mut file = std::fs::File::create(&path)?;
+
+        let mut header = primary_hdu::default().header;
+        header.set_value(BITPIX_KEYWORD, Bitpix::F32)?;
+        header.set_value(NAXIS_KEYWORD, 2u16)?;
+        header.set_value(*b"NAXIS1  ", 300u32)?;
+        header.set_value(*b"NAXIS2  ", 200u32)?;
+        let mut writer = HduWriter::new(&mut file, header)?;
+        assert_eq!(writer.row_len(), 1200);
+        for row in 0..200 {
+            let values: Vec<f32> = (0..300).map(|col| (row * 300 + col) as f32).collect();
+            writer.write_data(&values)?;
+        }
+        writer.finish()?;
+
+        let mut header = binary_table_hdu::default().header;
+        header.set_value(*b"NAXIS1  ", 8u32)?;
+        header.set_value(*b"NAXIS2  ", 1000u32)?;
+        let mut writer = HduWriter::new(&mut file, header)?;
+        assert_eq!(writer.data_len(), 8000);
+        for row in 0..1000u32 {
+            writer.write_raw(&[row.to_be_bytes(), (row * 2).to_be_bytes()].concat())?;
+        }
+        assert!(writer.write_raw(&[0]).is_err());
+        writer.finish()?;
+        drop(file);
+        assert_eq!(std::fs::metadata(&path)?.len(), 2880 * 89);
+
+        let mut hdu_list = HduList::open(&path)?;
+        let primary_hdu = hdu_list.first_mut().unwrap();
+        let data = primary_hdu.get_data::<Vec<f32>>()?;
+        assert_eq!(
+            data[..60000],
+            (0..60000).map(|x| x as f32).collect::<Vec<_>>()
+        );
+        let table_hdu = hdu_list.get_by_index(1).unwrap();
+        assert_eq!(
+            table_hdu.data_raw()[8 * 999..8 * 1000],
+            [0, 0, 3, 231, 0, 0, 7, 206]
+        );
+
+        // the data section must be complete
+        let mut header = image_hdu::default().header;
+        header.set_value(NAXIS_KEYWORD, 1u16)?;
+        header.set_value(*b"NAXIS1  ", 10u32)?;
+        let mut writer = HduWriter::new(Vec::new(), header)?;
+        writer.write_data(&vec![0u8; 5])?;
+        assert_eq!(writer.bytes_written(), 5);
+        assert!(writer.finish().is_err());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_section() -> Result<(), Box<dyn Error>> {
+        let path = std::env::temp_dir().join("astro_rs_test_section.fits");
+        let (nx, ny, nz) = (40, 30, 5);
+        let data: Vec<i32> = (0..(nx * ny * nz) as i32).collect();
+        let mut hdu = primary_hdu::default();
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::I32)?;
+        hdu.header.set_value(NAXIS_KEYWORD, 3u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", nx as u32)?;
+        hdu.header.set_value(*b"NAXIS2  ", ny as u32)?;
+        hdu.header.set_value(*b"NAXIS3  ", nz as u32)?;
+        hdu.header.set_value(*b"CRPIX1  ", 17.0)?;
+        hdu.header.set_value(*b"CRPIX2  ", 15.5)?;
+        hdu.header.set_value(*b"CD1_1   ", -0.001)?;
+        hdu.header.set_value(*b"CD2_2   ", 0.001)?;
+        hdu.header.set_value(*b"CRPIX1A ", 2.0)?;
+        hdu.header.set_value(*b"CDELT1A ", 2.0)?;
+        hdu.set_data(&data);
+        hdu.update_checksum()?;
+        std::fs::write(&path, hdu.to_bytes())?;
+
+        let file = std::fs::File::open(&path)?;
+        let mut hdu_list = HduList::new_lazy(BufReader::new(file));
+        let ranges = [
+            AxisRange::new(10, 20).with_step(3),
+            AxisRange::new(5, 9),
+            AxisRange::new(1, 5).with_step(2),
+        ];
+        let mut section = hdu_list.section(0, &ranges)?.unwrap();
+        assert!(!hdu_list.first_mut().unwrap().is_data_loaded());
+
+        assert_eq!(section.get_dimensions(), vec![4, 4, 2]);
+        let mut expected = Vec::new();
+        for z in [1, 3] {
+            for y in 5..9 {
+                for x in [10, 13, 16, 19] {
+                    expected.push(data[(z * ny + y) * nx + x]);
+                }
+            }
+        }
+        assert_eq!(section.get_data::<Vec<i32>>()?[..32], expected);
+
+        let mut value = |keyword: [u8; 8]| -> Result<f64, Box<dyn Error>> {
+            Ok(*section
+                .header
+                .get_card(keyword)
+                .unwrap()
+                .get_value::<f64>()?)
+        };
+        assert_eq!(value(*b"CRPIX1  ")?, 3.0);
+        assert_eq!(value(*b"CRPIX2  ")?, 10.5);
+        assert_eq!(value(*b"CD1_1   ")?, -0.003);
+        assert_eq!(value(*b"CD2_2   ")?, 0.001);
+        assert_eq!(value(*b"CRPIX1A ")?, -2.0);
+        assert_eq!(value(*b"CDELT1A ")?, 6.0);
+        assert!(section.header.get_card(CHECKSUM_KEYWORD).is_none());
+
+        // sections of loaded data sections are copied from memory
+        let hdu = hdu_list.first_mut().unwrap();
+        hdu.data_raw();
+        let loaded_section = hdu.section(&ranges)?;
+        assert_eq!(loaded_section.get_data::<Vec<i32>>()?[..32], expected);
+
+        assert!(matches!(
+            hdu.section(&ranges[..2]),
+            Err(SectionError::Dimensions {
+                expected: 3,
+                found: 2
+            })
+        ));
+        assert!(matches!(
+            hdu.section(&[(0..41).into(), (0..1).into(), (0..1).into()]),
+            Err(SectionError::InvalidRange { axis: 1, .. })
+        ));
+        assert!(hdu_list.section(1, &ranges)?.is_none());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn test_hdu_array() -> Result<(), Box<dyn Error>> {
+        use ndarray::{Array, Axis};
+
+        // a cube indexed as [z, y, x], in Fortran order in memory
+        let cube = Array::from_shape_fn((3, 4, 5), |(z, y, x)| (z * 100 + y * 10 + x) as i32)
+            .reversed_axes()
+            .as_standard_layout()
+            .reversed_axes()
+            .to_owned();
+        assert!(!cube.is_standard_layout());
+
+        let mut hdu = primary_hdu::default();
+        hdu.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        hdu.header.set_value(*b"NAXIS1  ", 7u32)?;
+        hdu.set_array(&cube)?;
+        assert_eq!(hdu.get_dimensions(), vec![5, 4, 3]);
+        assert!(hdu.header.get_card(*b"NAXIS4  ").is_none());
+        assert!(hdu.header.verify(0).is_empty());
+        assert_eq!(hdu.get_data::<Vec<i32>>()?[..6], [0, 1, 2, 3, 4, 10]);
+
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(hdu.to_bytes())));
+        let hdu = hdu_list.first_mut().unwrap();
+        let array = hdu.get_array::<i32>()?;
+        assert_eq!(array.shape(), [3, 4, 5]);
+        assert_eq!(array[[2, 3, 1]], 231);
+        assert_eq!(
+            array.index_axis(Axis(0), 1),
+            cube.index_axis(Axis(0), 1).into_dyn()
+        );
+        assert!(matches!(
+            hdu.get_array::<f32>(),
+            Err(ArrayError::Header(FitsHeaderError::InvalidType))
+        ));
+
+        // a deferred data section that can no longer be read is reported as an error
+        let path = std::env::temp_dir().join("astro_rs_test_array.fits");
+        std::fs::write(&path, hdu.clone().to_bytes())?;
+        let mut hdu_list = HduList::open_lazy(&path)?;
+        let hdu = hdu_list.try_first_mut()?.unwrap();
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)?
+            .set_len(2880)?;
+        assert!(matches!(
+            hdu.get_array::<i32>(),
+            Err(ArrayError::Read(FitsError::Io { hdu_index: 0, .. }))
+        ));
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "wcs")]
+    fn wcs_header(
+        ctype: [&str; 2],
+        crval: [f64; 2],
+        cdelt: f64,
+    ) -> Result<FitsHeader, Box<dyn Error>> {
+        let mut header = image_hdu::default().header;
+        header.set_value(NAXIS_KEYWORD, 2u16)?;
+        header.set_value(*b"NAXIS1  ", 200u32)?;
+        header.set_value(*b"NAXIS2  ", 100u32)?;
+        header.set_value(*b"CTYPE1  ", ctype[0].to_owned())?;
+        header.set_value(*b"CTYPE2  ", ctype[1].to_owned())?;
+        header.set_value(*b"CRVAL1  ", crval[0])?;
+        header.set_value(*b"CRVAL2  ", crval[1])?;
+        header.set_value(*b"CRPIX1  ", 100.5)?;
+        header.set_value(*b"CRPIX2  ", 50.5)?;
+        header.set_value(*b"CDELT1  ", -cdelt)?;
+        header.set_value(*b"CDELT2  ", cdelt)?;
+        Ok(header)
+    }
+
+    #[cfg(feature = "wcs")]
+    #[test]
+    fn test_wcs() -> Result<(), Box<dyn Error>> {
+        use uom::si::angle::degree;
+
+        for (code, cdelt) in [
+            ("TAN", 0.01),
+            ("SIN", 0.01),
+            ("ARC", 0.5),
+            ("ZEA", 0.5),
+            ("STG", 0.5),
+            ("CAR", 1.0),
+            ("AIT", 1.0),
+            ("MOL", 1.0),
+        ] {
+            let ctype = [format!("RA---{}", code), format!("DEC--{}", code)];
+            let mut header = wcs_header([&ctype[0], &ctype[1]], [45.0, 30.0], cdelt)?;
+            let wcs = Wcs::from_header(&mut header)?;
+            assert_eq!(wcs.projection.code(), code);
+
+            let (ra, dec) = wcs.pixel_to_celestial(100.5, 50.5)?;
+            assert!(
+                (ra - 45.0).abs() < 1e-9 && (dec - 30.0).abs() < 1e-9,
+                "{}",
+                code
+            );
+            for (x, y) in [(1.0, 1.0), (200.0, 1.0), (37.0, 88.0), (200.0, 100.0)] {
+                let icrs = wcs.pixel_to_world(x, y)?;
+                let (px, py) = wcs.world_to_pixel(&icrs)?;
+                assert!(
+                    (px - x).abs() < 1e-6 && (py - y).abs() < 1e-6,
+                    "{} {} {}",
+                    code,
+                    px,
+                    py
+                );
+            }
+        }
+
+        // the sky is only visible from one side of the orthographic projection
+        let mut header = wcs_header(["RA---SIN", "DEC--SIN"], [0.0, 0.0], 1.0)?;
+        let wcs = Wcs::from_header(&mut header)?;
+        assert!(matches!(
+            wcs.pixel_to_celestial(200.0, 50.5),
+            Err(WcsError::OutOfBounds(..))
+        ));
+        assert!(matches!(
+            wcs.celestial_to_pixel(180.0, 0.0),
+            Err(WcsError::OutOfBounds(..))
+        ));
+
+        // a rotated CD matrix in arcseconds, with the latitude as the first axis
+        let mut header = wcs_header(["DEC--TAN", "RA---TAN"], [-20.0, 210.0], 1.0)?;
+        header.set_value(*b"CUNIT1  ", String::from("arcsec"))?;
+        header.set_value(*b"CUNIT2  ", String::from("arcsec"))?;
+        header.set_value(*b"CRVAL1  ", -72000.0)?;
+        header.set_value(*b"CRVAL2  ", 756000.0)?;
+        header.set_value(*b"CD1_1   ", 0.0)?;
+        header.set_value(*b"CD1_2   ", 0.5)?;
+        header.set_value(*b"CD2_1   ", -0.5)?;
+        header.set_value(*b"CD2_2   ", 0.0)?;
+        let wcs = Wcs::from_header(&mut header)?;
+        assert_eq!(wcs.axes, [1, 0]);
+        assert_eq!(wcs.crval, [210.0, -20.0]);
+        assert_eq!(wcs.crpix, [50.5, 100.5]);
+        assert_eq!(wcs.pc, [[0.0, -0.5 / 3600.0], [0.5 / 3600.0, 0.0]]);
+        let (ra, dec) = wcs.pixel_to_celestial(50.5, 100.5)?;
+        assert!((ra - 210.0).abs() < 1e-9 && (dec + 20.0).abs() < 1e-9);
+        let (ra, dec) = wcs.pixel_to_celestial(50.5, 100.5 + 3600.0)?;
+        assert!((dec + 20.0).abs() < 1e-2);
+        assert!(ra < 210.0 && ra > 209.4);
+
+        // FK5 coordinates at the equinox of J1950 are precessed to ICRS
+        let mut header = wcs_header(["RA---TAN", "DEC--TAN"], [359.3592, -0.2783], 0.01)?;
+        header.set_value(*b"RADESYS ", String::from("FK5"))?;
+        header.set_value(*b"EQUINOX ", 1950.0)?;
+        let wcs = Wcs::from_header(&mut header)?;
+        assert_eq!(wcs.frame, CelestialFrame::Fk5 { equinox: 1950.0 });
+        let icrs = wcs.pixel_to_world(100.5, 50.5)?;
+        let ra = icrs.coords.ra.get::<degree>();
+        assert!(ra.min(360.0 - ra) < 1e-3);
+        assert!(icrs.coords.dec.get::<degree>().abs() < 1e-3);
+
+        header.set_value(*b"RADESYS ", String::from("FK4"))?;
+        let wcs = Wcs::from_header(&mut header)?;
+        assert!(matches!(
+            wcs.pixel_to_world(100.5, 50.5),
+            Err(WcsError::UnsupportedFrame { .. })
+        ));
+        header.set_value(*b"CTYPE1  ", String::from("RA---COE"))?;
+        assert!(matches!(
+            Wcs::from_header(&mut header),
+            Err(WcsError::UnsupportedProjection { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "wcs")]
+    #[test]
+    fn test_wcs_distortion() -> Result<(), Box<dyn Error>> {
+        let mut header = wcs_header(["RA---TAN", "DEC--TAN"], [150.0, 2.0], 0.001)?;
+        let linear = Wcs::from_header(&mut header)?;
+
+        header.set_value(*b"CTYPE1  ", String::from("RA---TAN-SIP"))?;
+        header.set_value(*b"CTYPE2  ", String::from("DEC--TAN-SIP"))?;
+        header.set_value(*b"A_ORDER ", 2u32)?;
+        header.set_value(*b"B_ORDER ", 2u32)?;
+        header.set_value(*b"A_2_0   ", 2e-4)?;
+        header.set_value(*b"A_1_1   ", -1e-4)?;
+        header.set_value(*b"B_0_2   ", 3e-4)?;
+        let sip = Wcs::from_header(&mut header)?;
+        let Some(Distortion::Sip(polynomials)) = &sip.distortion else {
+            panic!("expected SIP distortion");
+        };
+        assert_eq!(polynomials.a.coefficients[2][0], 2e-4);
+        assert!(polynomials.reverse.is_none());
+
+        // an order beyond those used in practice is rejected rather than allocated
+        header.set_value(*b"A_ORDER ", 1_000_000_000u32)?;
+        assert!(matches!(
+            Wcs::from_header(&mut header),
+            Err(WcsError::UnsupportedOrder { order, max: 9, .. }) if order == 1e9
+        ));
+        header.set_value(*b"A_ORDER ", 2u32)?;
+
+        for (x, y) in [(1.0, 1.0), (200.0, 1.0), (37.0, 88.0), (100.5, 50.5)] {
+            let (u, v) = (x - 100.5, y - 50.5);
+            let expected =
+                linear.pixel_to_celestial(x + 2e-4 * u * u - 1e-4 * u * v, y + 3e-4 * v * v)?;
+            let (ra, dec) = sip.pixel_to_celestial(x, y)?;
+            assert!((ra - expected.0).abs() < 1e-12 && (dec - expected.1).abs() < 1e-12);
+            // the inverse is found by iteration
+            let (px, py) = sip.celestial_to_pixel(ra, dec)?;
+            assert!((px - x).abs() < 1e-8 && (py - y).abs() < 1e-8);
+        }
+
+        // the reverse polynomials are used when given
+        header.set_value(*b"AP_ORDER", 2u32)?;
+        header.set_value(*b"BP_ORDER", 2u32)?;
+        header.set_value(*b"AP_2_0  ", -2e-4)?;
+        header.set_value(*b"AP_1_1  ", 1e-4)?;
+        header.set_value(*b"BP_0_2  ", -3e-4)?;
+        let sip = Wcs::from_header(&mut header)?;
+        let (ra, dec) = sip.pixel_to_celestial(90.0, 40.0)?;
+        let (px, py) = sip.celestial_to_pixel(ra, dec)?;
+        assert!((px - 90.0).abs() < 0.01 && (py - 40.0).abs() < 0.01);
+        assert!((px - 90.0).abs() > 1e-8);
+
+        // TPV without coefficients is the gnomonic projection
+        let mut header = wcs_header(["RA---TPV", "DEC--TPV"], [150.0, 2.0], 0.001)?;
+        let tpv = Wcs::from_header(&mut header)?;
+        assert_eq!(tpv.projection, Projection::Tan);
+        assert_eq!(
+            tpv.pixel_to_celestial(37.0, 88.0)?,
+            linear.pixel_to_celestial(37.0, 88.0)?
+        );
+
+        header.set_value(*b"PV1_0   ", 1e-4)?;
+        header.set_value(*b"PV1_4   ", 0.5)?;
+        header.set_value(*b"PV2_1   ", 0.99)?;
+        header.set_value(*b"PV2_11  ", 2.0)?;
+        let tpv = Wcs::from_header(&mut header)?;
+        for (x, y) in [(1.0, 1.0), (200.0, 1.0), (37.0, 88.0), (100.5, 50.5)] {
+            let (ix, iy): (f64, f64) = (-0.001 * (x - 100.5), 0.001 * (y - 50.5));
+            let r = ix.hypot(iy);
+            let (ra, dec) = tpv.pixel_to_celestial(x, y)?;
+            let (px, py) = linear.celestial_to_pixel(ra, dec)?;
+            let (expected_x, expected_y) = (1e-4 + ix + 0.5 * ix * ix, 0.99 * iy + 2.0 * r * r * r);
+            assert!((-0.001 * (px - 100.5) - expected_x).abs() < 1e-9);
+            assert!((0.001 * (py - 50.5) - expected_y).abs() < 1e-9);
+            let (px, py) = tpv.celestial_to_pixel(ra, dec)?;
+            assert!((px - x).abs() < 1e-8 && (py - y).abs() < 1e-8);
+        }
+
+        header.set_value(*b"CTYPE1  ", String::from("RA---TAN-ZPX"))?;
+        assert!(matches!(
+            Wcs::from_header(&mut header),
+            Err(WcsError::UnsupportedDistortion { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "wcs")]
+    #[test]
+    fn test_wcs_reference_values() -> Result<(), Box<dyn Error>> {
+        // computed by assets/wcs/reference.py, an independent implementation of the published formulas
+        let expected = [
+            ("TAN", 1.0, 1.0, 46.143073011412746, 29.50012485596105),
+            ("TAN", 200.0, 1.0, 43.856926988587254, 29.50012485596105),
+            ("TAN", 37.0, 88.0, 45.73597558048366, 30.37293254052237),
+            ("TAN", 200.0, 100.0, 43.84546973351119, 30.489901254091883),
+            ("SIN", 1.0, 1.0, 46.143286969781315, 29.500029902438097),
+            ("SIN", 200.0, 1.0, 43.856713030218685, 29.500029902438097),
+            ("SIN", 37.0, 88.0, 45.736036775745546, 30.372963261269824),
+            ("SIN", 200.0, 100.0, 43.84525146472441, 30.489992454698356),
+            ("AIT", 1.0, 1.0, 151.95171380626277, -60.60301926310832),
+            ("AIT", 200.0, 1.0, 298.0482861937372, -60.60301926310832),
+            ("AIT", 37.0, 88.0, 144.79095523239596, 37.195701932250486),
+            ("AIT", 200.0, 100.0, 254.613365932478, 18.160300741357556),
+            ("SIP", 1.0, 1.0, 150.09806920742136, 1.9512322307385876),
+            ("SIP", 200.0, 1.0, 149.89796840857394, 1.9512319952858879),
+            ("SIP", 37.0, 88.0, 150.0624949139922, 2.0379206582086336),
+            ("SIP", 200.0, 100.0, 149.8989479294535, 2.05023187611194),
+            ("TPV", 1.0, 1.0, 150.1046107872279, 1.9537368475038037),
+            ("TPV", 200.0, 1.0, 149.90549530384436, 1.9537374458366514),
+            ("TPV", 37.0, 88.0, 150.06565760993166, 2.0379257947731273),
+            ("TPV", 200.0, 100.0, 149.9054896584035, 2.051747288382996),
+        ];
+        for (code, x, y, ra, dec) in expected {
+            let mut header = match code {
+                "SIP" => {
+                    let mut header =
+                        wcs_header(["RA---TAN-SIP", "DEC--TAN-SIP"], [150.0, 2.0], 0.001)?;
+                    header.set_value(*b"A_ORDER ", 2u32)?;
+                    header.set_value(*b"B_ORDER ", 2u32)?;
+                    header.set_value(*b"A_2_0   ", 2e-4)?;
+                    header.set_value(*b"A_1_1   ", -1e-4)?;
+                    header.set_value(*b"B_0_2   ", 3e-4)?;
+                    header
+                }
+                "TPV" => {
+                    let mut header = wcs_header(["RA---TPV", "DEC--TPV"], [150.0, 2.0], 0.001)?;
+                    header.set_value(*b"PV1_0   ", 1e-4)?;
+                    header.set_value(*b"PV1_4   ", 0.5)?;
+                    header.set_value(*b"PV2_1   ", 0.99)?;
+                    header.set_value(*b"PV2_11  ", 2.0)?;
+                    header
+                }
+                _ => {
+                    let cdelt = if code == "AIT" { 1.0 } else { 0.01 };
+                    let ctype = [format!("RA---{}", code), format!("DEC--{}", code)];
+                    wcs_header([&ctype[0], &ctype[1]], [45.0, 30.0], cdelt)?
+                }
+            };
+            let wcs = Wcs::from_header(&mut header)?;
+            let (actual_ra, actual_dec) = wcs.pixel_to_celestial(x, y)?;
+            let ra_diff = (actual_ra - ra).rem_euclid(360.0);
+            assert!(
+                ra_diff.min(360.0 - ra_diff) < 1e-9 && (actual_dec - dec).abs() < 1e-9,
+                "{} ({}, {}): ({}, {})",
+                code,
+                x,
+                y,
+                actual_ra,
+                actual_dec
+            );
+            let (px, py) = wcs.celestial_to_pixel(ra, dec)?;
+            assert!(
+                (px - x).abs() < 1e-6 && (py - y).abs() < 1e-6,
+                "{} ({}, {}): ({}, {})",
+                code,
+                x,
+                y,
+                px,
+                py
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "wcs")]
+    #[test]
+    fn test_wcs_fit() -> Result<(), Box<dyn Error>> {
+        use astro_rs::coordinates::EquatorialCoord;
+        use uom::si::angle::{degree, second};
+        use uom::si::f64::Angle;
+
+        let mut header = wcs_header(["RA---TAN-SIP", "DEC--TAN-SIP"], [359.9, -30.0], 1.0)?;
+        header.set_value(*b"CD1_1   ", -2e-4)?;
+        header.set_value(*b"CD1_2   ", 5e-5)?;
+        header.set_value(*b"CD2_1   ", 5e-5)?;
+        header.set_value(*b"CD2_2   ", 2e-4)?;
+        header.set_value(*b"A_ORDER ", 2u32)?;
+        header.set_value(*b"B_ORDER ", 2u32)?;
+        header.set_value(*b"A_2_0   ", 2e-4)?;
+        header.set_value(*b"A_1_1   ", -1e-4)?;
+        header.set_value(*b"B_0_2   ", 3e-4)?;
+        let truth = Wcs::from_header(&mut header)?;
+
+        let mut matches = Vec::new();
+        for i in 0..12 {
+            for j in 0..10 {
+                let (x, y) = (1.0 + 18.0 * i as f64 + j as f64, 1.0 + 11.0 * j as f64);
+                let (ra, dec) = truth.pixel_to_celestial(x, y)?;
+                let coords = EquatorialCoord {
+                    ra: Angle::new::<degree>(ra),
+                    dec: Angle::new::<degree>(dec),
+                };
+                matches.push(((x, y), coords));
+            }
+        }
+
+        // a linear fit leaves the distortion in the residuals
+        let linear = Wcs::fit(&matches, None)?;
+        assert!(linear.wcs.distortion.is_none());
+        assert_eq!(linear.wcs.ctype, ["RA---TAN", "DEC--TAN"]);
+        assert!(linear.rms.get::<second>() > 0.01);
+
+        let fit = Wcs::fit(&matches, Some(2))?;
+        assert_eq!(fit.residuals.len(), matches.len());
+        assert!(fit.rms.get::<second>() < 1e-6);
+        assert!(fit.pixel_rms < 1e-3);
+        assert_eq!(fit.wcs.ctype, ["RA---TAN-SIP", "DEC--TAN-SIP"]);
+        for ((x, y), _) in &matches {
+            let (ra, dec) = fit.wcs.pixel_to_celestial(*x, *y)?;
+            let (expected_ra, expected_dec) = truth.pixel_to_celestial(*x, *y)?;
+            let ra_difference = (ra - expected_ra + 180.0).rem_euclid(360.0) - 180.0;
+            assert!(ra_difference.abs() < 1e-9 && (dec - expected_dec).abs() < 1e-9);
+        }
+
+        let mut hdu = image_hdu::default();
+        hdu.header.set_value(*b"PC1_1   ", 2.0)?;
+        hdu.header.set_value(*b"CDELT1  ", 2.0)?;
+        hdu.header.set_value(*b"A_0_2   ", 1.0)?;
+        fit.wcs.set_cards(&mut hdu.header)?;
+        assert!(hdu.header.get_card(*b"PC1_1   ").is_none());
+        assert!(hdu.header.get_card(*b"CDELT1  ").is_none());
+        assert_eq!(
+            *hdu.header
+                .get_card(*b"CTYPE1  ")
+                .unwrap()
+                .get_value::<String>()?,
+            "RA---TAN-SIP"
+        );
+        assert_eq!(hdu.wcs()?, fit.wcs);
+
+        // the cards survive serialization
+        let mut hdu_list = HduList::default();
+        hdu_list.push(primary_hdu::default());
+        hdu_list.push(hdu);
+        let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut writer)?;
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(
+            writer.into_inner()?.into_inner(),
+        )));
+        let wcs = hdu_list.try_get_by_index(1)?.unwrap().wcs()?;
+        let (ra, dec) = wcs.pixel_to_celestial(50.0, 60.0)?;
+        assert_eq!((ra, dec), fit.wcs.pixel_to_celestial(50.0, 60.0)?);
+
+        assert!(matches!(
+            Wcs::fit(&matches[..5], Some(2)),
+            Err(WcsError::InsufficientStars {
+                required: 6,
+                found: 5
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "wcs")]
+    #[test]
+    fn test_wcs_reproject() -> Result<(), Box<dyn Error>> {
+        let mut hdu = image_hdu::default();
+        hdu.header = wcs_header(["RA---TAN", "DEC--TAN"], [83.6, 22.0], 0.001)?;
+        hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F32)?;
+        hdu.header.set_value(*b"NAXIS1  ", 40u32)?;
+        hdu.header.set_value(*b"NAXIS2  ", 30u32)?;
+        hdu.header.set_value(*b"CRPIX1  ", 20.5)?;
+        hdu.header.set_value(*b"CRPIX2  ", 15.5)?;
+        let data: Vec<f32> = (0..30)
+            .flat_map(|j| (0..40).map(move |i| (i + 2 * j) as f32))
+            .collect();
+        hdu.set_data(&data);
+        let source = hdu.wcs()?;
+
+        // the target grid is rotated, with smaller pixels, and extends beyond the source image
+        let mut target = wcs_header(["RA---SIN", "DEC--SIN"], [83.601, 22.002], 1.0)?;
+        target.set_value(*b"NAXIS1  ", 100u32)?;
+        target.set_value(*b"NAXIS2  ", 90u32)?;
+        target.set_value(*b"CRPIX1  ", 50.0)?;
+        target.set_value(*b"CRPIX2  ", 45.0)?;
+        target.set_value(*b"CD1_1   ", -6e-4)?;
+        target.set_value(*b"CD1_2   ", 3e-4)?;
+        target.set_value(*b"CD2_1   ", 3e-4)?;
+        target.set_value(*b"CD2_2   ", 6e-4)?;
+        target.set_value(*b"BSCALE  ", 2.0)?;
+        let target_wcs = Wcs::from_header(&mut target.clone())?;
+
+        let source_pixel = |k: usize, l: usize| -> Result<(f64, f64), WcsError> {
+            let (lon, lat) = target_wcs.pixel_to_celestial(k as f64 + 1.0, l as f64 + 1.0)?;
+            let (x, y) = source.celestial_to_pixel(lon, lat)?;
+            Ok((x - 1.0, y - 1.0))
+        };
+
+        let mut nearest = hdu.reproject(&target, Resampling::Nearest)?;
+        let bilinear = hdu.reproject(&target, Resampling::Bilinear)?;
+        assert_eq!(nearest.hdu.get_dimensions(), vec![100, 90]);
+        assert_eq!(
+            *nearest
+                .hdu
+                .header
+                .get_card(BITPIX_KEYWORD)
+                .unwrap()
+                .get_value::<Bitpix>()?,
+            Bitpix::F64
+        );
+        assert!(nearest.hdu.header.get_card(*b"BSCALE  ").is_none());
+        let nearest_data = nearest.hdu.get_data::<Vec<f64>>()?;
+        let bilinear_data = bilinear.hdu.get_data::<Vec<f64>>()?;
+        let mut covered = 0;
+        for l in 0..90 {
+            for k in 0..100 {
+                let index = l * 100 + k;
+                let (x, y) = source_pixel(k, l)?;
+                let inside = (-0.5..39.5).contains(&x) && (-0.5..29.5).contains(&y);
+                assert_eq!(nearest.footprint[index], if inside { 1.0 } else { 0.0 });
+                assert_eq!(bilinear.footprint[index], nearest.footprint[index]);
+                if !inside {
+                    assert!(nearest_data[index].is_nan() && bilinear_data[index].is_nan());
+                    continue;
+                }
+                covered += 1;
+                assert_eq!(nearest_data[index], x.round() + 2.0 * y.round());
+                if (0.0..39.0).contains(&x) && (0.0..29.0).contains(&y) {
+                    assert!((bilinear_data[index] - (x + 2.0 * y)).abs() < 1e-9);
+                }
+            }
+        }
+        assert!(covered > 2500 && covered < 2800);
+
+        // the exact method conserves the flux
+        let exact = hdu.reproject(&target, Resampling::Exact)?;
+        let exact_data = exact.hdu.get_data::<Vec<f64>>()?;
+        let source_area = 0.001 * 0.001;
+        let target_area = 6e-4 * 6e-4 + 3e-4 * 3e-4;
+        let source_flux = data.iter().map(|value| *value as f64).sum::<f64>() * source_area;
+        let target_flux = exact_data
+            .iter()
+            .zip(&exact.footprint)
+            .filter(|(_, coverage)| **coverage > 0.0)
+            .map(|(value, coverage)| value * coverage)
+            .sum::<f64>()
+            * target_area;
+        assert!((target_flux / source_flux - 1.0).abs() < 1e-3);
+        let source_coverage = 40.0 * 30.0 * source_area;
+        let target_coverage = exact.footprint.iter().sum::<f64>() * target_area;
+        assert!((target_coverage / source_coverage - 1.0).abs() < 1e-3);
+        assert!(
+            exact
+                .footprint
+                .iter()
+                .all(|coverage| (0.0..=1.0).contains(coverage))
+        );
+        assert!(
+            exact_data
+                .iter()
+                .zip(&exact.footprint)
+                .all(|(value, coverage)| { (*coverage == 0.0) == value.is_nan() })
+        );
+
+        // the target may use another celestial frame
+        let mut galactic = wcs_header(["GLON-CAR", "GLAT-CAR"], [184.5533, -5.818], 0.001)?;
+        galactic.set_value(*b"NAXIS1  ", 20u32)?;
+        galactic.set_value(*b"NAXIS2  ", 20u32)?;
+        galactic.set_value(*b"CRPIX1  ", 10.5)?;
+        galactic.set_value(*b"CRPIX2  ", 10.5)?;
+        let reprojection = hdu.reproject(&galactic, Resampling::Bilinear)?;
+        assert_eq!(reprojection.footprint.iter().sum::<f64>(), 400.0);
+
+        hdu.header.set_value(NAXIS_KEYWORD, 3u16)?;
+        hdu.header.set_value(*b"NAXIS3  ", 1u32)?;
+        assert!(matches!(
+            hdu.reproject(&galactic, Resampling::Nearest),
+            Err(WcsError::Dimensions { found: 3 })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_groups() -> Result<(), Box<dyn Error>> {
+        let mut primary = random_groups_hdu::default();
+        primary.header.set_value(BITPIX_KEYWORD, Bitpix::F32)?;
+        primary.header.set_value(NAXIS_KEYWORD, 3u16)?;
+        primary.header.set_value(*b"NAXIS2  ", 2u32)?;
+        primary.header.set_value(*b"NAXIS3  ", 3u32)?;
+        primary.header.set_value(PCOUNT_KEYWORD, 3u32)?;
+        primary.header.set_value(GCOUNT_KEYWORD, 100u32)?;
+        primary.header.set_value(*b"PTYPE1  ", String::from("UU"))?;
+        primary.header.set_value(*b"PSCAL1  ", 0.5)?;
+        primary
+            .header
+            .set_value(*b"PTYPE2  ", String::from("DATE"))?;
+        primary.header.set_value(*b"PZERO2  ", 2451545.0)?;
+        primary
+            .header
+            .set_value(*b"PTYPE3  ", String::from("DATE"))?;
+        primary.header.set_value(*b"BZERO   ", 10.0)?;
+        primary.header.set_value(*b"EXTEND  ", true)?;
+        let mut data = Vec::new();
+        for group in 0..100 {
+            data.extend([group as f32, 1.0, 0.25]);
+            data.extend((0..6).map(|value| (group * 6 + value) as f32));
+        }
+        primary.set_data(&data);
+
+        let mut image = image_hdu::default();
+        image.header.set_value(BITPIX_KEYWORD, Bitpix::I16)?;
+        image.header.set_value(NAXIS_KEYWORD, 1u16)?;
+        image.header.set_value(*b"NAXIS1  ", 4u32)?;
+        image.set_data(&vec![1i16, 2, 3, 4]);
+
+        let mut fits_bytes = primary.to_bytes();
+        assert_eq!(fits_bytes.len(), 2880 * 3);
+        fits_bytes.append(&mut image.to_bytes());
+
+        // the data of the random groups HDU is sized as (PCOUNT + NAXIS2 * NAXIS3) * GCOUNT values
+        let in_cursor = Cursor::new(fits_bytes.clone());
+        let mut hdu_list = HduList::new_lazy(BufReader::new(in_cursor));
+        let image = hdu_list.get_by_index(1).unwrap();
+        assert_eq!(image.get_data::<Vec<i16>>()?[0..4], [1, 2, 3, 4]);
+        assert!(!image.is_random_groups());
+
+        let primary = hdu_list.first_mut().unwrap();
+        assert!(primary.is_random_groups());
+        let parameters = primary.group_parameters()?;
+        assert_eq!(
+            parameters[1],
+            GroupParameter {
+                name: Some(String::from("DATE")),
+                scale: 1.0,
+                zero: 2451545.0,
+            }
+        );
+        let groups = primary.get_random_groups()?;
+        assert_eq!(groups.shape, [2, 3]);
+        assert_eq!(groups.groups.len(), 100);
+        assert_eq!(groups.groups[7].parameters, [3.5, 2451546.0, 0.25]);
+        assert_eq!(groups.groups[7].array, [52.0, 53.0, 54.0, 55.0, 56.0, 57.0]);
+        assert_eq!(groups.parameter(7, "UU"), Some(3.5));
+        assert_eq!(groups.parameter(7, "DATE"), Some(2451546.25));
+        assert_eq!(groups.parameter(7, "VV"), None);
+        assert_eq!(groups.parameter(100, "UU"), None);
+
+        let out_cursor = Cursor::new(Vec::new());
+        let mut out_writer = BufWriter::new(out_cursor);
+        hdu_list.write(&mut out_writer)?;
+        assert_eq!(out_writer.get_ref().get_ref(), &fits_bytes);
+
+        let mut image = image_hdu::default();
+        assert!(matches!(
+            image.get_random_groups(),
+            Err(FitsHeaderError::MissingKeyword { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_hdu_compression_fixtures() -> Result<(), Box<dyn Error>> {
+        // generated by assets/compression/generate.py, an encoder independent of this crate;
+        // the EXPECTED extension holds the pixels a conforming reader restores
+        for name in [
+            "rice_dither1",
+            "gzip_dither2",
+            "hcompress_dither1",
+            "rice_i16",
+        ] {
+            let fits_file = File::open(format!("assets/compression/{}.fits", name))?;
+            let mut hdu_list = HduList::new(BufReader::new(fits_file));
+            hdu_list.set_parse_mode(ParseMode::Strict);
+            hdu_list.set_decompress_images(true);
+            let expected = hdu_list.try_get_by_name("EXPECTED")?.unwrap().clone();
+            let image = hdu_list.try_get_by_index(1)?.unwrap();
+            assert_eq!(image.get_dimensions(), vec![45, 31], "{}", name);
+            let bitpix = image.header.bitpix()?;
+            let data_raw = image.data_raw();
+            // the decompressed data section is not padded
+            assert_eq!(data_raw.len(), 45 * 31 * bitpix.value() / 8, "{}", name);
+            assert!(
+                data_raw[..] == expected.data_raw()[..data_raw.len()],
+                "{}",
+                name
+            );
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_hdu_compression() -> Result<(), Box<dyn Error>> {
+        let new_image = |bitpix: Bitpix, data: &dyn Fn(&mut Hdu)| -> Result<Hdu, Box<dyn Error>> {
+            let mut hdu = image_hdu::default();
+            hdu.header.set_value(BITPIX_KEYWORD, bitpix)?;
+            hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+            hdu.header.cards.insert(
+                3,
+                FitsHeaderCard::from(
+                    *b"NAXIS1  =                   30                                                  ",
+                ),
+            );
+            hdu.header.cards.insert(
+                4,
+                FitsHeaderCard::from(
+                    *b"NAXIS2  =                   20                                                  ",
+                ),
+            );
+            hdu.header.set_value(EXTNAME_KEYWORD, String::from("SCI"))?;
+            data(&mut hdu);
+            Ok(hdu)
+        };
+        let int_data: Vec<i16> = (0..600).map(|i| ((i * 7) % 50 + i / 100) as i16).collect();
+        let float_data: Vec<f32> = (0..600)
+            .map(|i| (i as f32 * 0.1).sin() * 100.0 + ((i * 37) % 11) as f32 * 0.3)
+            .collect();
+
+        for algorithm in [
+            CompressionAlgorithm::Rice1,
+            CompressionAlgorithm::Gzip1,
+            CompressionAlgorithm::Gzip2,
+            CompressionAlgorithm::Plio1,
+        ] {
+            let options = CompressionOptions {
+                algorithm,
+                tile: Some(vec![10, 6]),
+                ..Default::default()
+            };
+            let mut hdu_list = HduList::default();
+            hdu_list.push(primary_hdu::default());
+            hdu_list.push(new_image(Bitpix::I16, &|hdu| hdu.set_data(&int_data))?);
+            hdu_list.push(new_image(Bitpix::F32, &|hdu| hdu.set_data(&float_data))?);
+            hdu_list.set_write_compression(Some(options));
+            let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+            hdu_list.write(&mut writer)?;
+            let fits_bytes = writer.into_inner()?.into_inner();
+
+            let in_cursor = Cursor::new(fits_bytes.clone());
+            let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+            hdu_list.set_parse_mode(ParseMode::Strict);
+            assert_eq!(hdu_list.try_iter_mut()?.count(), 3);
+            let compressed = hdu_list.get_by_index(1).unwrap();
+            assert!(compressed.is_compressed_image());
+            assert_eq!(compressed.get_dimensions()[1], 12);
+            let descriptors =
+                binary_table_hdu::column_by_name::<i32>(compressed, "COMPRESSED_DATA").unwrap();
+            assert_eq!(descriptors.len(), 24);
+            assert_eq!(descriptors[1], 0);
+            assert!(descriptors[0] > 0 && descriptors[3] >= descriptors[0]);
+
+            let in_cursor = Cursor::new(fits_bytes);
+            let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+            hdu_list.set_decompress_images(true);
+            let int_hdu = hdu_list.try_get_by_index(1)?.unwrap();
+            assert!(!int_hdu.is_compressed_image());
+            assert_eq!(int_hdu.get_name(), "SCI");
+            assert_eq!(int_hdu.get_dimensions(), vec![30, 20]);
+            assert_eq!(int_hdu.get_data::<Vec<i16>>()?[0..600], int_data);
+
+            let float_hdu = hdu_list.try_get_by_index(2)?.unwrap();
+            let decompressed = float_hdu.get_data::<Vec<f32>>()?;
+            for (expected, actual) in float_data.iter().zip(decompressed.iter()) {
+                assert!((expected - actual).abs() < 1.0, "{:?}", algorithm);
+            }
+        }
+
+        // 64 bit integers are compressed losslessly by GZIP_1
+        let long_data: Vec<i64> = (0..600).map(|i| (i as i64) << 40).collect();
+        let mut hdu = new_image(Bitpix::I64, &|hdu| hdu.set_data(&long_data))?;
+        let options = CompressionOptions {
+            algorithm: CompressionAlgorithm::Gzip1,
+            ..Default::default()
+        };
+        let mut decompressed = hdu.compress(&options)?.decompress()?;
+        assert_eq!(decompressed.header.bitpix()?, Bitpix::I64);
+        assert_eq!(decompressed.get_data::<Vec<i64>>()?[0..600], long_data);
+
+        // images that have not been read are compressed as well
+        let mut hdu_list = HduList::default();
+        hdu_list.push(primary_hdu::default());
+        hdu_list.push(new_image(Bitpix::I16, &|hdu| hdu.set_data(&int_data))?);
+        let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut writer)?;
+        let in_cursor = Cursor::new(writer.into_inner()?.into_inner());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        hdu_list.set_write_compression(Some(CompressionOptions::default()));
+        let mut writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut writer)?;
+        let in_cursor = Cursor::new(writer.into_inner()?.into_inner());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        assert!(hdu_list.try_get_by_index(1)?.unwrap().is_compressed_image());
+
+        // a 4x4 tile of 7s, in which every coefficient of the H-transform other than the sum is zero
+        let mut heap = vec![0xdd, 0x99];
+        for value in [4i32, 4, 0] {
+            heap.extend_from_slice(&value.to_be_bytes());
+        }
+        heap.extend_from_slice(&56i64.to_be_bytes());
+        heap.extend_from_slice(&[0, 0, 0, 0]);
+        let mut hdu = binary_table_hdu::default();
+        hdu.header.set_value(*b"NAXIS1  ", 8u32)?;
+        hdu.header.set_value(*b"NAXIS2  ", 1u32)?;
+        hdu.header.set_value(*b"PCOUNT  ", heap.len() as u32)?;
+        hdu.header.set_value(*b"TFIELDS ", 1u16)?;
+        hdu.header
+            .set_value(*b"TTYPE1  ", String::from("COMPRESSED_DATA"))?;
+        let tform = TForm {
+            r: 1,
+            t: TFormType::ArrayDescriptor,
+            a: String::from("B(26)"),
+        };
+        hdu.header.set_value(*b"TFORM1  ", tform)?;
+        hdu.header.set_value(ZIMAGE_KEYWORD, true)?;
+        hdu.header.set_value(*b"ZBITPIX ", Bitpix::I32)?;
+        hdu.header.set_value(*b"ZNAXIS  ", 2u16)?;
+        for keyword in [*b"ZNAXIS1 ", *b"ZNAXIS2 ", *b"ZTILE1  ", *b"ZTILE2  "] {
+            hdu.header.set_value(keyword, 4u32)?;
+        }
+        hdu.header
+            .set_value(ZCMPTYPE_KEYWORD, String::from("HCOMPRESS_1"))?;
+        let mut data = vec![0, 0, 0, heap.len() as u8, 0, 0, 0, 0];
+        data.append(&mut heap);
+        hdu.set_data_raw(data);
+        let mut decompressed = hdu.decompress()?;
+        assert_eq!(decompressed.get_dimensions(), vec![4, 4]);
+        assert_eq!(decompressed.get_data::<Vec<i32>>()?, vec![7; 16]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_hdu_list() -> Result<(), Box<dyn Error>> {
+        let mut fits_bytes = Vec::new();
+        for i in 0..3 {
+            let mut hdu = if i == 0 {
+                primary_hdu::default()
+            } else {
+                image_hdu::default()
+            };
+            hdu.header.set_value(BITPIX_KEYWORD, Bitpix::F64)?;
+            hdu.header.set_value(NAXIS_KEYWORD, 2u16)?;
+            hdu.header.set_value(*b"NAXIS1  ", 40u32)?;
+            hdu.header.set_value(*b"NAXIS2  ", 30u32)?;
+            let data: Vec<f64> = (0..1200).map(|x| (x * (i + 1)) as f64).collect();
+            hdu.set_data(&data);
+            fits_bytes.append(&mut hdu.to_bytes());
+        }
+        let path = std::env::temp_dir().join("astro_rs_test_mmap_hdu_list.fits");
+        std::fs::write(&path, &fits_bytes)?;
+
+        let mut mmap_list = unsafe { MmapHduList::open(&path)? };
+        assert_eq!(mmap_list.len(), 3);
+        let view = mmap_list.data_view::<f64>(2).unwrap();
+        assert_eq!(view.len(), 1200);
+        assert_eq!(view.get(1199), Some(3597.0));
+        assert_eq!(view.get(1200), None);
+
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(fits_bytes)));
+        let (report, mmap_report) = (hdu_list.verify()?, mmap_list.verify());
+        assert_eq!(mmap_report.errors().count(), report.errors().count());
+        assert_eq!(mmap_report.warnings().count(), report.warnings().count());
+        for (i, hdu) in hdu_list.iter_mut().enumerate() {
+            let expected = hdu.get_data::<Vec<f64>>()?[0..1200].to_vec();
+            assert_eq!(mmap_list.get_data::<Vec<f64>>(i).unwrap()?, expected);
+            assert_eq!(mmap_list.to_native::<f64>(i).unwrap(), expected.as_slice());
+            assert_eq!(mmap_list.to_hdu(i).unwrap().data_raw(), hdu.data_raw());
+        }
+
+        // an unpadded final header is accepted, and a missing data section is reported
+        let mut header_bytes = primary_hdu::default().header.to_bytes();
+        header_bytes.truncate(320);
+        std::fs::write(&path, &header_bytes)?;
+        let mut mmap_list = unsafe { MmapHduList::open(&path)? };
+        assert_eq!(mmap_list.len(), 1);
+        assert_eq!(mmap_list.data_raw(0), Some(&[][..]));
+        let report = mmap_list.verify();
+        assert_eq!(report.errors().count(), 0);
+        assert!(
+            report
+                .warnings()
+                .any(|issue| issue.message.contains("header padding are missing"))
+        );
+        let mut hdu = primary_hdu::default();
+        hdu.header.set_naxis(1)?;
+        hdu.header.set_naxisn(1, 10)?;
+        let mut header_bytes = hdu.header.to_bytes();
+        header_bytes.truncate(400);
+        std::fs::write(&path, &header_bytes)?;
+        assert!(matches!(
+            unsafe { MmapHduList::open(&path) },
+            Err(FitsError::Truncated {
+                expected: 10,
+                found: 0,
+                ..
+            })
+        ));
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_kind() -> Result<(), Box<dyn Error>> {
+        let mut table = binary_table_hdu::default();
+        table.header.set_value(*b"NAXIS1  ", 4u32)?;
+        table.header.set_value(*b"NAXIS2  ", 3u32)?;
+        table.header.set_value(PCOUNT_KEYWORD, 5u32)?;
+        table.header.set_value(*b"TFIELDS ", 1u16)?;
+        table
+            .header
+            .set_value(*b"TTYPE1  ", String::from("COUNT"))?;
+        let tform = TForm {
+            r: 1,
+            t: TFormType::I32,
+            a: String::new(),
+        };
+        table.header.set_value(*b"TFORM1  ", tform)?;
+        let mut data: Vec<u8> = [10i32, 20, 30]
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect();
+        data.extend_from_slice(b"HEAP!");
+        table.set_data_raw(data);
+
+        let mut hdu_list = HduList::default();
+        hdu_list.push(primary_hdu::default());
+        hdu_list.push(table);
+        let kinds: Vec<HduKind> = hdu_list.iter_mut().map(|hdu| hdu.kind()).collect();
+        assert_eq!(kinds, [HduKind::Primary, HduKind::BinTable]);
+
+        let hdu = hdu_list.get_by_index(1).unwrap().clone();
+        let error = PrimaryHdu::try_from(hdu).unwrap_err();
+        assert_eq!(error.expected, HduKind::Primary);
+        assert_eq!(error.found, HduKind::BinTable);
+        let mut table = BinTableHdu::try_from(*error.hdu)?;
+        assert_eq!(table.num_rows(), 3);
+        assert_eq!(table.row_len(), 4);
+        assert_eq!(table.num_fields(), 1);
+        assert_eq!(table.heap(), b"HEAP!");
+        assert_eq!(table.column_by_name::<i32>("COUNT"), Some(vec![10, 20, 30]));
+
+        // an image moves between the primary HDU and an extension with the required cards
+        let mut primary = PrimaryHdu::default();
+        primary.header.set_value(*b"EXTEND  ", true)?;
+        primary.set_pixels(&[3, 2], &[1.5f32, 2.5, 3.5, 4.5, 5.5, 6.5])?;
+        assert_eq!(primary.get_dimensions(), [3, 2]);
+        let mut image = ImageHdu::from(primary);
+        assert_eq!(image.kind(), HduKind::Image);
+        assert!(image.header.verify(1).is_empty());
+        assert!(image.header.get_card(*b"EXTEND  ").is_none());
+        assert_eq!(image.bitpix()?, Bitpix::F32);
+        assert_eq!(image.pixels::<f32>()?, [1.5, 2.5, 3.5, 4.5, 5.5, 6.5]);
+        assert!(matches!(
+            image.pixels::<i16>(),
+            Err(FitsHeaderError::InvalidType)
+        ));
+
+        image.set_pixels(&[2], &[7i16, 8])?;
+        assert!(image.header.verify(1).is_empty());
+        assert!(image.header.get_card(*b"NAXIS2  ").is_none());
+        assert!(matches!(
+            image.set_pixels(&[2, 2], &[7i16, 8]),
+            Err(FitsHeaderError::InvalidLength { .. })
+        ));
+
+        let mut hdu_list = HduList::default();
+        hdu_list.push(PrimaryHdu::default().into());
+        hdu_list.push(image.clone().into());
+        hdu_list.push(image.into_primary().into_image().into());
+        let out_cursor = Cursor::new(Vec::new());
+        let mut out_writer = BufWriter::new(out_cursor);
+        hdu_list.write(&mut out_writer)?;
+        let in_cursor = Cursor::new(out_writer.into_inner()?.into_inner());
+        let mut hdu_list = HduList::new(BufReader::new(in_cursor));
+        assert!(hdu_list.verify()?.is_valid());
+        let mut image = ImageHdu::try_from(hdu_list.get_by_index(2).unwrap().clone())?;
+        assert_eq!(image.pixels::<i16>()?, [7, 8]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_management() -> Result<(), Box<dyn Error>> {
+        let new_image = |value: i16| -> Result<Hdu, Box<dyn Error>> {
+            let mut image = ImageHdu::default();
+            image.set_pixels(&[2], &[value, value])?;
+            Ok(image.into())
+        };
+        let write = |hdu_list: &mut HduList<Cursor<Vec<u8>>>| -> Result<Vec<u8>, Box<dyn Error>> {
+            let mut out_writer = BufWriter::new(Cursor::new(Vec::new()));
+            hdu_list.write(&mut out_writer)?;
+            Ok(out_writer.into_inner()?.into_inner())
+        };
+
+        // an image at the start of the list becomes the primary HDU, and a later primary HDU becomes an image
+        let mut hdu_list = HduList::default();
+        hdu_list.push(new_image(1)?);
+        hdu_list.push(binary_table_hdu::default());
+        let mut primary = PrimaryHdu::default();
+        primary.set_pixels(&[2], &[3i16, 3])?;
+        hdu_list.push(primary.into());
+        let fits_bytes = write(&mut hdu_list)?;
+        // the list itself is not modified
+        let kinds: Vec<HduKind> = hdu_list.iter_mut().map(|hdu| hdu.kind()).collect();
+        assert_eq!(kinds, [HduKind::Image, HduKind::BinTable, HduKind::Primary]);
+        let mut hdu_list = HduList::new_lazy(BufReader::new(Cursor::new(fits_bytes.clone())));
+        let report = hdu_list.verify()?;
+        assert!(report.issues.is_empty(), "{}", report);
+        let kinds: Vec<HduKind> = hdu_list.iter_mut().map(|hdu| hdu.kind()).collect();
+        assert_eq!(kinds, [HduKind::Primary, HduKind::BinTable, HduKind::Image]);
+        let primary = hdu_list.first_mut().unwrap();
+        assert!(
+            *primary
+                .header
+                .get_card(*b"EXTEND  ")
+                .unwrap()
+                .get_value::<bool>()?
+        );
+        assert_eq!(
+            PrimaryHdu::try_from(primary.clone())?.pixels::<i16>()?,
+            [1, 1]
+        );
+
+        // HDUs are rearranged without reading the rest of the list
+        let mut hdu_list = HduList::new_lazy(BufReader::new(Cursor::new(fits_bytes.clone())));
+        hdu_list.swap(0, 2);
+        assert_eq!(hdu_list.get_by_index(0).unwrap().kind(), HduKind::Image);
+        assert_eq!(hdu_list.get_by_index(2).unwrap().kind(), HduKind::Primary);
+        let table = hdu_list.replace(1, new_image(4)?);
+        assert_eq!(table.kind(), HduKind::BinTable);
+        let removed = hdu_list.remove(2);
+        assert_eq!(removed.kind(), HduKind::Primary);
+        let mut hdu_list = HduList::new_lazy(BufReader::new(Cursor::new(write(&mut hdu_list)?)));
+        let pixels = hdu_list
+            .iter_mut()
+            .map(|hdu| {
+                let hdu = hdu.clone();
+                match hdu.clone().kind() {
+                    HduKind::Primary => PrimaryHdu::try_from(hdu)?.pixels::<i16>(),
+                    _ => ImageHdu::try_from(hdu)?.pixels::<i16>(),
+                }
+                .map_err(Box::<dyn Error>::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(pixels, [vec![3, 3], vec![4, 4]]);
+        assert!(hdu_list.verify()?.issues.is_empty());
+
+        // a primary HDU is inserted before a table at the start of the list
+        hdu_list.retain(|hdu| hdu.kind() == HduKind::Image);
+        hdu_list.insert(0, binary_table_hdu::default());
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(write(&mut hdu_list)?)));
+        let kinds: Vec<HduKind> = hdu_list.iter_mut().map(|hdu| hdu.kind()).collect();
+        assert_eq!(kinds, [HduKind::Primary, HduKind::BinTable, HduKind::Image]);
+        assert!(hdu_list.verify()?.issues.is_empty());
+
+        // HDUs that have not been read are arranged in the same way
+        let mut unordered_bytes = new_image(5)?.to_bytes();
+        unordered_bytes.append(&mut primary_hdu::default().to_bytes());
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(unordered_bytes)));
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(write(&mut hdu_list)?)));
+        let kinds: Vec<HduKind> = hdu_list.iter_mut().map(|hdu| hdu.kind()).collect();
+        assert_eq!(kinds, [HduKind::Primary, HduKind::Image]);
+        assert!(hdu_list.verify()?.issues.is_empty());
+
+        // bytes that cannot be read as an HDU are kept after the HDUs before them
+        let mut trailing_bytes = new_image(5)?.to_bytes();
+        trailing_bytes.extend_from_slice(b"XTENSION= 'IMAGE   '");
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(trailing_bytes)));
+        let written = write(&mut hdu_list)?;
+        assert_eq!(written[..8], SIMPLE_KEYWORD);
+        assert!(written.ends_with(b"XTENSION= 'IMAGE   '"));
+
+        // removed HDUs are dropped from files updated in place
+        let path = std::env::temp_dir().join("astro_rs_test_management.fits");
+        std::fs::write(&path, &fits_bytes)?;
+        let mut hdu_list = HduList::open_update(&path)?;
+        hdu_list.remove(1);
+        hdu_list.flush()?;
+        assert_eq!(std::fs::metadata(&path)?.len(), 4 * 2880);
+        let mut hdu_list = HduList::open(&path)?;
+        let kinds: Vec<HduKind> = hdu_list.iter_mut().map(|hdu| hdu.kind()).collect();
+        assert_eq!(kinds, [HduKind::Primary, HduKind::Image]);
+        std::fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_text() -> Result<(), Box<dyn Error>> {
+        let card_text = |header: &FitsHeader, index: usize| -> String {
+            let card: [u8; 80] = header.cards[index].clone().into();
+            String::from_utf8_lossy(&card).into_owned()
+        };
+
+        // fixed-format and free-format lines are normalized into the same cards
+        let text = "\
+XTENSION= 'IMAGE   '           / Image extension
+BITPIX  =                  -64
+naxis=2
+NAXIS1  = 10
+NAXIS2\t=\t20
+EXTNAME = 'O''Brien'  / the name contains a quote
+CRVAL1  = 1.5d2
+CPLX    = (1, -2.5E3)
+UNDEF   =                      / no value
+        blank keyword commentary
+COMMENT   indented comment
+LONGCOM = 1 / this comment is far too long to fit within a single card image so it is cut off
+END
+IGNORED = 'after the end'
+";
+        let mut header = FitsHeader::from_text(text)?;
+        assert_eq!(header.cards.len(), 13);
+        assert_eq!(header.naxis()?, 2);
+        assert_eq!(header.dimensions()?, [10, 20]);
+        assert_eq!(
+            card_text(&header, 5).trim_end(),
+            "EXTNAME = 'O''Brien'           / the name contains a quote"
+        );
+        assert_eq!(header.extname()?.as_deref(), Some("O'Brien"));
+        assert_eq!(
+            card_text(&header, 0),
+            format!("{:<80}", "XTENSION= 'IMAGE   '           / Image extension")
+        );
+        assert_eq!(
+            card_text(&header, 2),
+            card_text(&header, 1)
+                .replace("BITPIX  ", "NAXIS   ")
+                .replace("-64", "  2")
+        );
+        assert_eq!(
+            card_text(&header, 4).trim_end(),
+            "NAXIS2  =                   20"
+        );
+        assert_eq!(
+            card_text(&header, 6).trim_end(),
+            "CRVAL1  =                1.5D2"
+        );
+        assert_eq!(
+            card_text(&header, 7).trim_end(),
+            "CPLX    =          (1, -2.5E3)"
+        );
+        assert_eq!(
+            card_text(&header, 8).trim_end(),
+            "UNDEF   =                      / no value"
+        );
+        assert_eq!(
+            card_text(&header, 9).trim_end(),
+            "        blank keyword commentary"
+        );
+        assert_eq!(header.comments(), ["  indented comment"]);
+        assert_eq!(card_text(&header, 11).len(), 80);
+        assert!(card_text(&header, 11).ends_with("within a si"));
+        assert_eq!(*header.cards[12].keyword(), "END");
+
+        // serialized text reads back into identical cards
+        let header = FitsHeader::from_text(text)?;
+        let round_trip = FitsHeader::from_text(&header.to_text())?;
+        assert_eq!(round_trip.to_text(), header.to_text());
+        assert_eq!(round_trip.to_bytes(), header.to_bytes());
+
+        // headers written to a file are read back from their text
+        let mut hdu_list = HduList::default();
+        let mut hdu = primary_hdu::default();
+        hdu.header = FitsHeader::from_text("SIMPLE = T\nBITPIX = 8\nNAXIS = 0\nOBJECT = M31")?;
+        hdu_list.push(hdu);
+        let mut out_writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut out_writer)?;
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(
+            out_writer.into_inner()?.into_inner(),
+        )));
+        let hdu = hdu_list.first_mut().unwrap();
+        assert_eq!(hdu.header.object()?.as_deref(), Some("M31"));
+        assert!(
+            hdu.header
+                .to_text()
+                .ends_with("OBJECT  = 'M31     '\nEND\n")
+        );
+
+        // errors report the offending line
+        let errors = [
+            (
+                "SIMPLE = T\nEXP.TIME = 1",
+                "line 2: invalid keyword \"EXP.TIME\"",
+            ),
+            ("OBJECT = 'M31", "line 1: unterminated string"),
+            (
+                "OBJECT = 'M31' trailing",
+                "line 1: invalid value \"'M31' trailing\"",
+            ),
+            (
+                "\n\nCPLX = (1, 2, 3)",
+                "line 3: invalid value \"(1, 2, 3)\"",
+            ),
+            ("OBJECT = '\u{e9}'", "line 1: invalid character '\u{e9}'"),
+        ];
+        for (text, message) in errors {
+            assert_eq!(
+                FitsHeader::from_text(text).unwrap_err().to_string(),
+                message
+            );
+        }
+        let long_value = format!("OBJECT = '{}'", "x".repeat(70));
+        assert!(matches!(
+            FitsHeader::from_text(&long_value),
+            Err(HeaderTextError::CardTooLong {
+                line: 1,
+                length: 82
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_list_extension_lookup() -> Result<(), Box<dyn Error>> {
+        let mut hdu_list = HduList::default();
+        hdu_list.push(primary_hdu::default());
+        for ver in 1..=2u32 {
+            for name in ["SCI", "ERR", "DQ"] {
+                let mut image = ImageHdu::default();
+                image.set_pixels(&[2, 3], &[ver as i16; 6])?;
+                let mut hdu: Hdu = image.into();
+                hdu.header.set_extname(name.to_owned())?;
+                hdu.header.set_extver(ver)?;
+                hdu_list.push(hdu);
+            }
+        }
+        let mut table = binary_table_hdu::default();
+        table.header.set_extname(String::from("  events "))?;
+        hdu_list.push(table);
+        let mut out_writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut out_writer)?;
+        let fits_bytes = out_writer.into_inner()?.into_inner();
+
+        // lookups read only as far as the matching HDU
+        let mut hdu_list = HduList::new_lazy(BufReader::new(Cursor::new(fits_bytes.clone())));
+        let hdu = hdu_list.try_get_by_name_ver("err", 2)?.unwrap();
+        assert_eq!(hdu.get_name(), "ERR");
+        assert_eq!(hdu.header.extver()?, 2);
+        assert_eq!(ImageHdu::try_from(hdu.clone())?.pixels::<i16>()?, [2; 6]);
+        assert_eq!(hdu_list.get_by_name("sci").unwrap().header.extver()?, 1);
+        assert!(hdu_list.get_by_name("EVENTS").is_some());
+        assert!(hdu_list.try_get_by_name_ver("SCI", 3)?.is_none());
+        assert!(hdu_list.get_by_name_ver("EVENTS", 2).is_none());
+
+        // the index covers the HDUs read so far, and is kept as they are read
+        let mut hdu_list = HduList::new_lazy(BufReader::new(Cursor::new(fits_bytes)));
+        assert!(hdu_list.extension_index().is_empty());
+        assert!(hdu_list.try_get_by_name_ver("sci", 2)?.is_some());
+        assert_eq!(hdu_list.extension_index().len(), 4);
+        assert_eq!(hdu_list.info().entries.len(), 5);
+        assert_eq!(hdu_list.try_iter_mut()?.count(), 8);
+        let index = hdu_list.extension_index();
+        assert_eq!(index.len(), 7);
+        assert_eq!(index[&(String::from("SCI"), 1)], 1);
+        assert_eq!(index[&(String::from("DQ"), 2)], 6);
+        assert_eq!(index[&(String::from("EVENTS"), 1)], 7);
+
+        // the index follows the HDUs when the list is rearranged or their names are changed
+        hdu_list.swap(1, 7);
+        assert_eq!(hdu_list.extension_index()[&(String::from("EVENTS"), 1)], 1);
+        assert_eq!(
+            hdu_list
+                .get_by_name_ver("SCI", 1)
+                .unwrap()
+                .header
+                .extver()?,
+            1
+        );
+        hdu_list
+            .get_by_index(2)
+            .unwrap()
+            .header
+            .set_extname(String::from("VAR"))?;
+        assert!(hdu_list.get_by_name("var").is_some());
+        assert_eq!(
+            hdu_list
+                .try_get_by_name_ver("ERR", 1)?
+                .map(|hdu| hdu.get_name()),
+            None
+        );
+        hdu_list.swap(1, 7);
+
+        // the table of contents is built from the headers, without loading the data
+        let toc = hdu_list.info();
+        assert_eq!(toc.entries.len(), 8);
+        assert_eq!(toc.entries[0].kind, HduKind::Primary);
+        assert_eq!(toc.entries[4].name, "SCI");
+        assert_eq!(toc.entries[4].version, 2);
+        assert_eq!(toc.entries[4].dimensions, [2, 3]);
+        assert_eq!(toc.entries[4].bitpix, Some(Bitpix::I16));
+        assert_eq!(toc.entries[7].kind, HduKind::BinTable);
+        assert_eq!(toc.entries[7].num_fields, Some(0));
+        let lines: Vec<String> = toc.to_string().lines().map(str::to_owned).collect();
+        assert_eq!(lines.len(), 9);
+        assert!(lines[1].contains("PRIMARY"));
+        assert!(lines[5].contains("(2, 3)") && lines[5].contains("int16"));
+        assert!(lines[8].contains("0R x 0C"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdu_diff() -> Result<(), Box<dyn Error>> {
+        let table =
+            |flux: [f32; 3], names: [&str; 3], extra: bool| -> Result<Hdu, Box<dyn Error>> {
+                let mut hdu = binary_table_hdu::default();
+                let tfields = if extra { 3 } else { 2 };
+                hdu.header.set_dimensions(&[if extra { 9 } else { 7 }, 3])?;
+                hdu.header.set_tfields(tfields)?;
+                hdu.header.set_ttype(1, String::from("FLUX"))?;
+                hdu.header.set_tform(
+                    1,
+                    TForm {
+                        r: 1,
+                        t: TFormType::F32,
+                        a: String::new(),
+                    },
+                )?;
+                hdu.header.set_ttype(2, String::from("NAME"))?;
+                hdu.header.set_tform(
+                    2,
+                    TForm {
+                        r: 3,
+                        t: TFormType::Character,
+                        a: String::new(),
+                    },
+                )?;
+                if extra {
+                    hdu.header.set_ttype(3, String::from("QUALITY"))?;
+                    hdu.header.set_tform(
+                        3,
+                        TForm {
+                            r: 1,
+                            t: TFormType::I16,
+                            a: String::new(),
+                        },
+                    )?;
+                }
+                let mut data = Vec::new();
+                for (flux, name) in flux.iter().zip(names) {
+                    data.extend_from_slice(&flux.to_be_bytes());
+                    data.extend_from_slice(format!("{:<3}", name).as_bytes());
+                    if extra {
+                        data.extend_from_slice(&1i16.to_be_bytes());
+                    }
+                }
+                hdu.set_data_raw(data);
+                Ok(hdu)
+            };
+        let options = DiffOptions {
+            ignore_keywords: vec![String::from("DATE*"), String::from("checksum")],
+            rtol: 1e-6,
+            ..Default::default()
+        };
+
+        // headers are compared by keyword, pairing repeated keywords in order
+        let first = FitsHeader::from_text(
+            "SIMPLE = T\nBITPIX = 8\nNAXIS = 0\nDATE = '2024-01-01'\nCHECKSUM = 'abc'\n\
+             EXPTIME = 30.0 / seconds\nOBSERVER = 'Hubble'\nHISTORY first\nHISTORY second",
+        )?;
+        let second = FitsHeader::from_text(
+            "SIMPLE = T\nBITPIX = 8\nNAXIS = 0\nDATE-OBS = '2024-01-02'\nCHECKSUM = 'def'\n\
+             EXPTIME = 30.00000001 / s\nHISTORY first\nHISTORY changed\nHISTORY third",
+        )?;
+        let diff = first.diff(&second, &options);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].keyword, "OBSERVER");
+        assert_eq!(diff.removed[0].value, "'Hubble'");
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].occurrence, 2);
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].0.comment, "seconds");
+        assert_eq!(diff.changed[1].1.value, "changed");
+        assert_eq!(
+            diff.to_string(),
+            "- OBSERVER = 'Hubble'\n+ HISTORY[2] third\n! EXPTIME = 30.0 / seconds -> 30.00000001 / s\n! HISTORY[1] second -> changed\n"
+        );
+        let diff = first.diff(
+            &second,
+            &DiffOptions {
+                ignore_comments: true,
+                ignore_keywords: vec![
+                    String::from("DATE*"),
+                    String::from("CHECKSUM"),
+                    String::from("HISTORY"),
+                    String::from("OBSERVER"),
+                ],
+                ..options.clone()
+            },
+        );
+        assert!(diff.is_identical(), "{}", diff);
+
+        // images are compared by physical value, within the tolerances
+        let mut first = ImageHdu::default();
+        first.set_pixels(&[2, 2], &[1i16, 2, 3, 4])?;
+        let mut second = ImageHdu::default();
+        second.set_pixels(&[2, 2], &[2i16, 4, 6, -1])?;
+        second.header.set_bscale(0.5)?;
+        second.header.set_blank(-1)?;
+        let mut first: Hdu = first.into();
+        let mut second: Hdu = second.into();
+        let diff = first.diff(&mut second, &options);
+        assert_eq!(diff.header.added.len(), 2);
+        let DataDiff::Image(image_diff) = &diff.data else {
+            panic!("expected an image diff");
+        };
+        assert_eq!(image_diff.num_differences, 1);
+        assert_eq!(image_diff.differences[0].index, 3);
+        assert!(image_diff.differences[0].second.is_nan());
+        let mut reshaped = ImageHdu::default();
+        reshaped.set_pixels(&[4], &[1i16, 2, 3, 4])?;
+        let diff = first.diff(&mut reshaped.into(), &options);
+        assert_eq!(
+            diff.data,
+            DataDiff::Image(ImageDiff {
+                dimensions: Some((vec![2, 2], vec![4])),
+                ..Default::default()
+            })
+        );
+
+        // tables are compared column by column
+        let mut first = table([1.0, 2.0, 3.0], ["a", "b", "c"], false)?;
+        let mut second = table([1.0, 2.5, 3.000001], ["a", "b", "d"], true)?;
+        let diff = first.diff(&mut second, &options);
+        let DataDiff::Table(table_diff) = &diff.data else {
+            panic!("expected a table diff");
+        };
+        assert_eq!(table_diff.num_rows, None);
+        assert_eq!(table_diff.added_columns, ["QUALITY"]);
+        assert_eq!(table_diff.columns.len(), 2);
+        assert_eq!(table_diff.columns[0].name, "FLUX");
+        assert_eq!(table_diff.columns[0].num_differences, 1);
+        assert_eq!(
+            table_diff.columns[0].differences[0],
+            ValueDifference {
+                index: 1,
+                first: FieldValue::Numbers(vec![2.0]),
+                second: FieldValue::Numbers(vec![2.5]),
+            }
+        );
+        assert_eq!(
+            table_diff.to_string(),
+            "+ column QUALITY\n! column FLUX: 1 rows differ\n  row 1: 2 != 2.5\n! column NAME: 1 rows differ\n  row 2: \"c\" != \"d\"\n"
+        );
+        let options = DiffOptions {
+            ignore_columns: vec![
+                String::from("flux"),
+                String::from("NAME"),
+                String::from("Q*"),
+            ],
+            ..options
+        };
+        assert!(first.diff(&mut second, &options).data.is_identical());
+
+        // fields of ASCII tables are compared as numbers or text, as given by TFORMn
+        let ascii_table = |rows: &[u8]| -> Result<Hdu, Box<dyn Error>> {
+            let mut hdu = ascii_table_hdu::default();
+            hdu.header.set_dimensions(&[8, 2])?;
+            hdu.header.set_tfields(1)?;
+            hdu.header.set_ttype(1, String::from("FLUX"))?;
+            hdu.header.set_value(*b"TFORM1  ", String::from("F8.3"))?;
+            hdu.header.set_tbcol(1, 1u32)?;
+            hdu.set_data_raw(rows.to_vec());
+            Ok(hdu)
+        };
+        let mut first_ascii = ascii_table(b"   1.500   2.000")?;
+        let mut second_ascii = ascii_table(b"   1.5     2.001")?;
+        let diff = first_ascii.diff(
+            &mut second_ascii,
+            &DiffOptions {
+                rtol: 1e-6,
+                ..Default::default()
+            },
+        );
+        let DataDiff::Table(table_diff) = &diff.data else {
+            panic!("expected a table diff");
+        };
+        assert_eq!(table_diff.columns[0].num_differences, 1);
+        assert_eq!(table_diff.columns[0].differences[0].index, 1);
+
+        // tables with invalid array descriptors are compared byte by byte
+        let descriptor_table =
+            |t: TFormType, a: &str, descriptor: Vec<u8>| -> Result<Hdu, Box<dyn Error>> {
+                let mut hdu = binary_table_hdu::default();
+                hdu.header.set_dimensions(&[descriptor.len(), 1])?;
+                hdu.header.set_tfields(1)?;
+                hdu.header.set_ttype(1, String::from("SPECTRUM"))?;
+                hdu.header.set_tform(
+                    1,
+                    TForm {
+                        r: 1,
+                        t,
+                        a: String::from(a),
+                    },
+                )?;
+                hdu.set_data_raw(descriptor);
+                Ok(hdu)
+            };
+        let negative_offset = [1i32.to_be_bytes(), (-1i32).to_be_bytes()].concat();
+        let negative_count = [(-1i64).to_be_bytes(), 0i64.to_be_bytes()].concat();
+        let overflowing_count = [i64::MAX.to_be_bytes(), 0i64.to_be_bytes()].concat();
+        for (t, a, descriptor) in [
+            (TFormType::ArrayDescriptor, "B(3)", negative_offset),
+            (TFormType::LongArrayDescriptor, "B(3)", negative_count),
+            (TFormType::LongArrayDescriptor, "D(3)", overflowing_count),
+        ] {
+            let mut first = descriptor_table(t, a, descriptor.clone())?;
+            let mut second = descriptor_table(t, a, descriptor)?;
+            let diff = first.diff(&mut second, &DiffOptions::default());
+            assert_eq!(diff.data, DataDiff::Raw(RawDiff::default()));
+        }
+
+        // lists are compared HDU by HDU, including deferred data sections
+        let write = |hdus: Vec<Hdu>| -> Result<Vec<u8>, Box<dyn Error>> {
+            let mut hdu_list = HduList::default();
+            for hdu in hdus {
+                hdu_list.push(hdu);
+            }
+            let mut out_writer = BufWriter::new(Cursor::new(Vec::new()));
+            hdu_list.write(&mut out_writer)?;
+            Ok(out_writer.into_inner()?.into_inner())
+        };
+        let first_bytes = write(vec![primary_hdu::default(), first.clone()])?;
+        let second_bytes = write(vec![
+            primary_hdu::default(),
+            second.clone(),
+            binary_table_hdu::default(),
+        ])?;
+        let mut first_list = HduList::new_lazy(BufReader::new(Cursor::new(first_bytes.clone())));
+        let mut same_list = HduList::new_lazy(BufReader::new(Cursor::new(first_bytes)));
+        assert!(
+            first_list
+                .diff(&mut same_list, &DiffOptions::default())?
+                .is_identical()
+        );
+        let mut second_list = HduList::new_lazy(BufReader::new(Cursor::new(second_bytes)));
+        let diff = first_list.diff(&mut second_list, &DiffOptions::default())?;
+        assert_eq!(diff.num_hdus, Some((2, 3)));
+        assert_eq!(diff.hdus.len(), 1);
+        assert_eq!(diff.hdus[0].0, 1);
+        assert_eq!(
+            diff.to_string(),
+            "HDU counts differ: 2 != 3\nHDU 1:\n  + TTYPE3 = 'QUALITY'\n  + TFORM3 = '1I'\n  ! NAXIS1 = 7 -> 9\n  ! TFIELDS = 2 -> 3\n  + column QUALITY\n  ! column FLUX: 2 rows differ\n    row 1: 2 != 2.5\n    row 2: 3 != 3.0000009536743164\n  ! column NAME: 1 rows differ\n    row 2: \"c\" != \"d\"\n"
+        );
+
+        // compressed images
+        #[cfg(feature = "compression")]
+        {
+            let compressed = |pixels: &[i16]| -> Result<Hdu, Box<dyn Error>> {
+                let mut hdu = image_hdu::default();
+                hdu.header.set_bitpix(Bitpix::I16)?;
+                hdu.header.set_dimensions(&[4, 3])?;
+                hdu.set_data(&pixels.to_vec());
+                Ok(hdu.compress(&CompressionOptions::default())?)
+            };
+            let mut pixels: Vec<i16> = (0..12).collect();
+            let mut first = compressed(&pixels)?;
+            pixels[5] = 50;
+            let mut second = compressed(&pixels)?;
+            let diff = first.diff(&mut second, &DiffOptions::default());
+            let DataDiff::Image(image_diff) = &diff.data else {
+                panic!("expected an image diff");
+            };
+            assert_eq!(image_diff.num_differences, 1);
+            assert_eq!(image_diff.differences[0].index, 5);
+            assert_eq!(image_diff.differences[0].second, 50.0);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_header_keywords() -> Result<(), Box<dyn Error>> {
+        let mut header = FitsHeader::from_bytes(
+            [
+                *b"XTENSION= 'BINTABLE'                                                            ",
+                *b"BITPIX  =                    8                                                  ",
+                *b"NAXIS   =                    2                                                  ",
+                *b"NAXIS1  =                   12                                                  ",
+                *b"NAXIS2  =                    3                                                  ",
+                *b"PCOUNT  =                    0                                                  ",
+                *b"GCOUNT  =                    1                                                  ",
+                *b"TFIELDS =                    2                                                  ",
+                *b"TTYPE1  = 'FLUX    '                                                            ",
+                *b"TFORM1  = '2D      '                                                            ",
+                *b"TDIM1   = '(2,1)   '                                                            ",
+                *b"TTYPE2  = 'FLAG    '                                                            ",
+                *b"TFORM2  = '4L      '                                                            ",
+                *b"TSCAL2  =                  2.5                                                  ",
+                *b"EXTNAME = 'EVENTS  '                                                            ",
+                *b"EXTVER  =                    2                                                  ",
+                *b"DATE-OBS= '2024-03-01T12:00:00'                                                 ",
+                *b"EXPTIME =                  300                                                  ",
+                *b"PC1_2   =                  0.5                                                  ",
+                *b"END                                                                             ",
+            ]
+            .concat(),
+        );
+
+        assert_eq!(header.bitpix()?, Bitpix::U8);
+        assert_eq!(header.dimensions()?, [12, 3]);
+        assert_eq!(header.pcount()?, 0);
+        assert_eq!(header.gcount()?, 1);
+        assert_eq!(header.extname()?.as_deref(), Some("EVENTS"));
+        assert_eq!(header.extver()?, 2);
+        assert_eq!(header.extlevel()?, 1);
+        assert_eq!(header.date_obs()?.as_deref(), Some("2024-03-01T12:00:00"));
+        assert_eq!(header.exptime()?, Some(300.0));
+        assert_eq!(header.telescop()?, None);
+        assert_eq!(header.tfields()?, 2);
+        assert_eq!(header.ttype(2)?.as_deref(), Some("FLAG"));
+        assert_eq!(header.tform(1)?.value(), 16);
+        assert_eq!(header.tdim(1)?, Some(vec![2, 1]));
+        assert_eq!(header.tscal(1)?, 1.0);
+        assert_eq!(header.tscal(2)?, 2.5);
+        assert_eq!(header.pc(1, 1)?, 1.0);
+        assert_eq!(header.pc(1, 2)?, 0.5);
+        assert_eq!(header.pc(2, 1)?, 0.0);
+        assert_eq!(header.cd(1, 1)?, None);
+        assert!(matches!(
+            header.naxisn(3),
+            Err(FitsHeaderError::MissingKeyword { .. })
+        ));
+        header.set_exptime(120.5)?;
+        assert_eq!(header.exptime()?, Some(120.5));
+
+        // removing a column removes all of its cards
+        header.set_tfields(1)?;
+        assert_eq!(header.tfields()?, 1);
+        assert_eq!(header.ttype(2)?, None);
+        assert!(header.get_card(*b"TFORM2  ").is_none());
+        assert!(header.get_card(*b"TSCAL2  ").is_none());
+        assert_eq!(header.ttype(1)?.as_deref(), Some("FLUX"));
+
+        // NAXIS and the NAXISn cards are kept in sync, with the NAXISn cards in order
+        header.set_naxisn(4, 5)?;
+        assert_eq!(header.dimensions()?, [12, 3, 1, 5]);
+        let keywords: Vec<FitsHeaderKeyword> =
+            header.cards.iter().map(|card| *card.keyword()).collect();
+        let naxis_index = keywords
+            .iter()
+            .position(|keyword| *keyword == NAXIS_KEYWORD);
+        assert_eq!(
+            keywords[naxis_index.unwrap()..naxis_index.unwrap() + 5],
+            [
+                FitsHeaderKeyword::from(*b"NAXIS   "),
+                FitsHeaderKeyword::from(*b"NAXIS1  "),
+                FitsHeaderKeyword::from(*b"NAXIS2  "),
+                FitsHeaderKeyword::from(*b"NAXIS3  "),
+                FitsHeaderKeyword::from(*b"NAXIS4  "),
+            ]
+        );
+        header.set_dimensions(&[7])?;
+        assert_eq!(header.naxis()?, 1);
+        assert_eq!(header.dimensions()?, [7]);
+        assert!(header.get_card(*b"NAXIS2  ").is_none());
+
+        // BLANK only applies to integer arrays
+        header.set_blank(-1)?;
+        assert_eq!(header.blank()?, Some(-1));
+        header.set_bitpix(Bitpix::F32)?;
+        assert_eq!(header.blank()?, None);
+
+        // the data section is sized from the cards set through the accessors
+        let mut hdu = image_hdu::default();
+        hdu.header.set_dimensions(&[4, 2])?;
+        hdu.header.set_bitpix(Bitpix::I16)?;
+        hdu.set_data(&vec![0i16; 8]);
+        hdu.header.add_history("filled with zeros");
+        let mut hdu_list = HduList::default();
+        hdu_list.push(primary_hdu::default());
+        hdu_list.push(hdu);
+        let mut out_writer = BufWriter::new(Cursor::new(Vec::new()));
+        hdu_list.write(&mut out_writer)?;
+        let mut hdu_list = HduList::new(BufReader::new(Cursor::new(
+            out_writer.into_inner()?.into_inner(),
+        )));
+        let hdu = hdu_list.get_by_index(1).unwrap();
+        assert_eq!(hdu.get_dimensions(), [4, 2]);
+        assert_eq!(hdu.header.history(), ["filled with zeros"]);
+        assert_eq!(hdu.get_data::<Vec<i16>>()?[..8], [0; 8]);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time() -> Result<(), Box<dyn Error>> {
+        use hifitime::{Epoch, TimeScale, Unit};
+
+        let mut hdu = binary_table_hdu::default();
+        hdu.header.set_dimensions(&[6, 3])?;
+        hdu.header.set_tfields(2)?;
+        hdu.header.set_ttype(1, String::from("FLAG"))?;
+        hdu.header.set_tform(
+            1,
+            TForm {
+                r: 2,
+                t: TFormType::UnsignedByte,
+                a: String::new(),
+            },
+        )?;
+        hdu.header.set_ttype(2, String::from("TIME"))?;
+        hdu.header.set_tform(
+            2,
+            TForm {
+                r: 1,
+                t: TFormType::I32,
+                a: String::new(),
+            },
+        )?;
+        hdu.header.set_tzero(2, 10.0)?;
+        hdu.header.set_tunit(2, String::from("d"))?;
+        hdu.header.set_timesys(String::from("TT"))?;
+        hdu.header.set_timeunit(String::from("s"))?;
+        hdu.header.set_timezero(0.5)?;
+        hdu.header.set_mjdrefi(50000)?;
+        hdu.header.set_mjdreff(0.25)?;
+        hdu.header.set_tstart(3600.0)?;
+        hdu.header
+            .set_date_obs(String::from("1995-10-10T06:00:00"))?;
+        hdu.header.set_mjd_obs(50000.25)?;
+        let mut data = Vec::new();
+        for time in [0i32, 1, 2] {
+            data.extend_from_slice(&[0, 1]);
+            data.extend_from_slice(&time.to_be_bytes());
+        }
+        hdu.set_data_raw(data);
+
+        // the reference time keeps the precision of MJDREFF
+        let reference = Epoch::from_mjd_in_time_scale(50000.0, TimeScale::TT) + Unit::Hour * 6;
+        assert_eq!(hdu.header.time_reference()?, reference);
+        assert_eq!(
+            hdu.header.tstart_epoch()?,
+            Some(reference + Unit::Second * 3600.5)
+        );
+        assert_eq!(hdu.header.tstop_epoch()?, None);
+        assert_eq!(hdu.header.mjd_obs_epoch()?, Some(reference));
+        assert_eq!(
+            hdu.header.date_obs_epoch()?,
+            Some(Epoch::from_gregorian_hms(
+                1995,
+                10,
+                10,
+                6,
+                0,
+                0,
+                TimeScale::TT
+            ))
+        );
+
+        // TUNITn takes precedence over TIMEUNIT, which still applies to TIMEZERO
+        let times = hdu.time_column("time")?;
+        assert_eq!(
+            times,
+            [10.0, 11.0, 12.0].map(|days| reference + Unit::Second * 0.5 + Unit::Day * days)
+        );
+        assert!(matches!(
+            hdu.time_column("FLAG").map(|times| times.len()),
+            Ok(6)
+        ));
+        assert!(matches!(
+            hdu.time_column("MISSING"),
+            Err(TimeError::InvalidColumn { .. })
+        ));
+
+        hdu.header.set_timesys(String::from("LOCAL"))?;
+        assert!(matches!(
+            hdu.header.time_scale(),
+            Err(TimeError::UnsupportedTimeScale { .. })
+        ));
+        hdu.header.set_timesys(String::from("UTC"))?;
+        hdu.header.set_timeunit(String::from("fortnight"))?;
+        assert!(matches!(
+            hdu.header.tstart_epoch(),
+            Err(TimeError::UnsupportedTimeUnit { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "units")]
+    #[test]
+    fn test_units() -> Result<(), Box<dyn Error>> {
+        use uom::si::f64::{Frequency, Velocity};
+        use uom::si::frequency::hertz;
+        use uom::si::velocity::kilometer_per_second;
+
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-12 * a.abs().max(b.abs());
+        let si = |unit: &str| -> Result<f64, UnitError> { Ok(FitsUnit::parse(unit)?.scale) };
+
+        // equivalent spellings of the same unit
+        for unit in [
+            "erg/s/cm**2/Angstrom",
+            "erg/(s cm2 Angstrom)",
+            "erg.s-1.cm-2.Angstrom-1",
+            "erg s^-1 cm^(-2) Angstrom**(-1)",
+            "10**-7 J s-1 cm-2 Angstrom-1",
+            "10-7 J/s/cm2/Angstrom",
+            "1e-7 J / s / cm2 / Angstrom",
+        ] {
+            let parsed = FitsUnit::parse(unit)?;
+            assert!(close(parsed.scale, 1e7), "{}", unit);
+            assert_eq!(parsed.dimension.mass, 1.0, "{}", unit);
+            assert_eq!(parsed.dimension.length, -1.0, "{}", unit);
+            assert_eq!(parsed.dimension.time, -3.0, "{}", unit);
+        }
+
+        // prefixes only apply where the symbol alone is not a unit
+        assert!(close(si("mas")?, std::f64::consts::PI / 648e6));
+        assert!(close(si("min")?, 60.0));
+        assert!(close(si("Pa")?, 1.0));
+        assert!(close(si("kpc")?, 3.085677581491367e19));
+        assert!(close(si("mJy")?, 1e-29));
+        assert!(close(si("Gyr")?, 365.25 * 86400.0 * 1e9));
+        assert!(close(si("dam")?, 10.0));
+        assert!(close(si("kbyte")?, 8000.0));
+        assert!(close(si("sqrt(Hz)")?, 1.0));
+        assert_eq!(FitsUnit::parse("sqrt(Hz)")?.dimension.time, -0.5);
+        assert_eq!(FitsUnit::parse("m**(3/2)")?.dimension.length, 1.5);
+        assert_eq!(FitsUnit::parse("")?, FitsUnit::DIMENSIONLESS);
+        assert_eq!(FitsUnit::parse("count/s")?.dimension.count, 1.0);
+        assert!(!FitsUnit::parse("deg")?.is_compatible(&FitsUnit::DIMENSIONLESS));
+        assert!(FitsUnit::parse("deg2")?.is_compatible(&FitsUnit::parse("sr")?));
+        assert_eq!(FitsUnit::parse("km/s")?.to_string(), "1000 m s-1");
+
+        assert!(matches!(
+            FitsUnit::parse("furlong"),
+            Err(UnitError::UnknownUnit { .. })
+        ));
+        assert!(matches!(
+            FitsUnit::parse("kdeg"),
+            Err(UnitError::UnknownUnit { .. })
+        ));
+        assert!(matches!(
+            FitsUnit::parse("mag"),
+            Err(UnitError::UnsupportedUnit { .. })
+        ));
+        assert!(matches!(
+            FitsUnit::parse("log(Hz)"),
+            Err(UnitError::UnsupportedUnit { .. })
+        ));
+        for invalid in ["m**", "m/", "(m s", "m s)", "m**(1/0)", "10** m"] {
+            assert!(
+                matches!(FitsUnit::parse(invalid), Err(UnitError::InvalidUnit { .. })),
+                "{}",
+                invalid
+            );
+        }
+
+        // values of columns and axes with their units attached
+        let mut hdu = binary_table_hdu::default();
+        hdu.header.set_dimensions(&[4, 2])?;
+        hdu.header.set_tfields(2)?;
+        hdu.header.set_ttype(1, String::from("VELOCITY"))?;
+        hdu.header.set_tform(
+            1,
+            TForm {
+                r: 1,
+                t: TFormType::I16,
+                a: String::new(),
+            },
+        )?;
+        hdu.header.set_tunit(1, String::from("km/s"))?;
+        hdu.header.set_tscal(1, 0.5)?;
+        hdu.header.set_ttype(2, String::from("NAME"))?;
+        hdu.header.set_tform(
+            2,
+            TForm {
+                r: 2,
+                t: TFormType::Character,
+                a: String::new(),
+            },
+        )?;
+        hdu.header.set_cunit(3, String::from("GHz"))?;
+        hdu.set_data_raw(vec![0, 2, b'a', b'b', 0, 4, b'c', b'd']);
+
+        let velocity = hdu.column_quantities("velocity")?;
+        assert_eq!(velocity.values, [1.0, 2.0]);
+        assert_eq!(
+            velocity.convert_to(&FitsUnit::parse("m/s")?)?,
+            [1000.0, 2000.0]
+        );
+        let velocities = velocity.to_quantities::<Velocity>()?;
+        assert!(close(velocities[1].get::<kilometer_per_second>(), 2.0));
+        assert!(velocity.to_quantities::<Frequency>().is_err());
+        assert!(matches!(
+            hdu.column_quantities("NAME"),
+            Err(UnitError::InvalidColumn { .. })
+        ));
+
+        let frequency = hdu.header.axis_unit(3)?.unwrap();
+        assert!(close(
+            frequency.quantity::<Frequency>(1.4)?.get::<hertz>(),
+            1.4e9
+        ));
+        assert_eq!(hdu.header.axis_unit(1)?, None);
+
+        Ok(())
+    }
+
     #[test]
     fn test_hdu_list_new() -> Result<(), Box<dyn Error>> {
         let mut hdu_list = HduList::default();